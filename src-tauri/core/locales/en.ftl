@@ -0,0 +1,22 @@
+# Messages shown to users for errors returned by backend commands.
+# $detail is the technical cause, usually ffmpeg output, and stays untranslated.
+
+error-ffmpeg = The media tools couldn't process this file.
+    Details: { $detail }
+error-parse = The file's media information couldn't be read.
+    Details: { $detail }
+error-io = The file couldn't be read or written.
+    Details: { $detail }
+error-shell = The bundled media tools couldn't be started.
+    Details: { $detail }
+error-insufficient-space = Not enough free disk space: { $required } needed, { $available } available.
+error-not-media = This doesn't look like a video or audio file ({ $detail }).
+error-cancelled = The inspection was cancelled.
+error-job-in-use = Another inspection is already running as { $job }.
+
+# Notes shown in place of empty fields, about the streams a file carries.
+
+note-no-video = No video stream
+note-cover-art-only = No video stream, only cover art
+note-no-audio = No audio stream
+note-several-videos = { $count } video streams, the first marked default is described
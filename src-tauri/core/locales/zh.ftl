@@ -0,0 +1,22 @@
+# 后端命令返回错误时向用户显示的消息。
+# $detail 是技术原因（通常是 ffmpeg 输出），保持原文不翻译。
+
+error-ffmpeg = 媒体工具无法处理此文件。
+    详情：{ $detail }
+error-parse = 无法读取文件的媒体信息。
+    详情：{ $detail }
+error-io = 无法读取或写入文件。
+    详情：{ $detail }
+error-shell = 无法启动内置的媒体工具。
+    详情：{ $detail }
+error-insufficient-space = 磁盘空间不足：需要 { $required }，可用 { $available }。
+error-not-media = 这似乎不是视频或音频文件（{ $detail }）。
+error-cancelled = 检查已取消。
+error-job-in-use = 已有检查以 { $job } 运行中。
+
+# 代替空白字段显示的说明，关于文件包含的流。
+
+note-no-video = 没有视频流
+note-cover-art-only = 没有视频流，只有封面图
+note-no-audio = 没有音频流
+note-several-videos = 共 { $count } 个视频流，显示的是第一个标记为默认的流
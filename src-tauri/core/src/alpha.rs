@@ -0,0 +1,58 @@
+/// Side length of a checkerboard square, in pixels of the scaled frame
+const CHECKER_SIZE: u32 = 12;
+
+/// Detect transparency in a video stream
+///
+/// Covers pixel formats with an alpha plane (ProRes 4444, HEVC with alpha on
+/// recent ffmpeg, PNG/QuickTime Animation, ...) and VP8/VP9 in WebM, whose
+/// alpha travels separately and is only flagged by the `alpha_mode` tag.
+pub fn has_alpha(stream: &serde_json::Value) -> bool {
+    let pix_fmt_alpha = stream["pix_fmt"].as_str().is_some_and(pix_fmt_has_alpha);
+    let alpha_mode = stream["tags"].as_object().is_some_and(|tags| {
+        tags.iter().any(|(key, value)| {
+            key.eq_ignore_ascii_case("alpha_mode") && value.as_str() == Some("1")
+        })
+    });
+    pix_fmt_alpha || alpha_mode
+}
+
+/// Decoder that has to be forced to get the alpha plane of a codec
+///
+/// ffmpeg's native VP8/VP9 decoders ignore the WebM alpha channel.
+pub fn alpha_decoder(codec_name: &str) -> Option<&'static str> {
+    match codec_name {
+        "vp9" => Some("libvpx-vp9"),
+        "vp8" => Some("libvpx"),
+        _ => None,
+    }
+}
+
+/// Filter chain compositing a frame onto a light checkerboard, so transparent
+/// areas are visible instead of rendering black
+pub fn checkerboard_filter() -> String {
+    let checker = format!(
+        "if(mod(floor(X/{size})+floor(Y/{size}),2),204,255)",
+        size = CHECKER_SIZE
+    );
+    let blend = |channel: &str| {
+        format!(
+            "{channel}(X,Y)*alpha(X,Y)/255+(255-alpha(X,Y))*{checker}/255",
+            channel = channel,
+            checker = checker
+        )
+    };
+    format!(
+        "format=rgba,geq=r='{}':g='{}':b='{}':a=255,format=rgb24",
+        blend("r"),
+        blend("g"),
+        blend("b")
+    )
+}
+
+fn pix_fmt_has_alpha(pix_fmt: &str) -> bool {
+    [
+        "yuva", "rgba", "bgra", "argb", "abgr", "gbrap", "ya8", "ya16",
+    ]
+    .iter()
+    .any(|prefix| pix_fmt.starts_with(prefix))
+}
@@ -0,0 +1,199 @@
+use crate::bitrate::declared_bit_rate;
+use crate::codec::codec_tag;
+use crate::gapless::{gapless_info, GaplessInfo};
+use crate::loudness::LoudnessTags;
+use crate::tags::tag;
+
+/// Audio stream details parsed from ffprobe `-show_streams` output
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct AudioStreamInfo {
+    index: u64,
+    codec_name: String,
+    /// FourCC as stored in the container, e.g. "mp4a", "ac-3"
+    codec_tag: Option<String>,
+    channels: u32,
+    /// ffmpeg layout name, e.g. "stereo", "5.1(side)", "7.1"
+    channel_layout: String,
+    /// Human readable layout, e.g. "5.1 surround (side speakers)"
+    layout_description: String,
+    /// Hz
+    sample_rate: Option<u32>,
+    /// Bits per second, declared by the stream or measured from packets
+    bit_rate: Option<f64>,
+    bit_rate_measured: bool,
+    /// Language tag as stored, e.g. "eng"; `None` when untagged or "und"
+    language: Option<String>,
+    /// ReplayGain / R128 / iTunNORM values claimed by tags
+    loudness_tags: Option<LoudnessTags>,
+    /// Encoder delay/padding; `None` for codecs that don't need it
+    gapless: Option<GaplessInfo>,
+}
+
+impl AudioStreamInfo {
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn codec_name(&self) -> &str {
+        &self.codec_name
+    }
+
+    pub fn bit_rate(&self) -> Option<f64> {
+        self.bit_rate
+    }
+
+    /// Use a bit rate measured from packet sizes when none was declared
+    pub fn set_measured_bit_rate(&mut self, bit_rate: f64) {
+        self.bit_rate = Some(bit_rate);
+        self.bit_rate_measured = true;
+    }
+
+    /// Whether this is a two-channel track, which stereo equipment plays
+    /// without downmixing; mono tracks don't count
+    pub fn is_stereo(&self) -> bool {
+        self.channels == 2 || matches!(self.channel_layout.as_str(), "stereo" | "downmix")
+    }
+}
+
+/// Collect every audio stream from the ffprobe JSON
+pub fn parse_audio_streams(json: &serde_json::Value) -> Vec<AudioStreamInfo> {
+    let Some(streams) = json["streams"].as_array() else {
+        return Vec::new();
+    };
+
+    streams
+        .iter()
+        .filter(|stream| stream["codec_type"].as_str() == Some("audio"))
+        .enumerate()
+        .map(|(audio_index, stream)| {
+            let channels = stream["channels"].as_u64().unwrap_or(0) as u32;
+            // Some containers don't record a layout, so infer the usual one
+            let channel_layout = stream["channel_layout"]
+                .as_str()
+                .filter(|layout| !layout.is_empty() && *layout != "unknown")
+                .map(str::to_string)
+                .unwrap_or_else(|| default_layout(channels).to_string());
+
+            // Container-level tags describe the main (first) audio track
+            let format_tags = if audio_index == 0 {
+                &json["format"]["tags"]
+            } else {
+                &serde_json::Value::Null
+            };
+
+            AudioStreamInfo {
+                index: stream["index"].as_u64().unwrap_or(0),
+                codec_name: stream["codec_name"]
+                    .as_str()
+                    .unwrap_or("unknown")
+                    .to_string(),
+                codec_tag: codec_tag(stream),
+                channels,
+                layout_description: describe_layout(&channel_layout, channels),
+                channel_layout,
+                // ffprobe reports the rate as a string
+                sample_rate: stream["sample_rate"]
+                    .as_str()
+                    .and_then(|rate| rate.parse().ok()),
+                bit_rate: declared_bit_rate(stream),
+                bit_rate_measured: false,
+                language: tag(stream, "language").filter(|language| language != "und"),
+                loudness_tags: LoudnessTags::from_tags(&stream["tags"], format_tags),
+                gapless: gapless_info(stream, format_tags),
+            }
+        })
+        .collect()
+}
+
+/// Whether at least one track is stereo, so stereo equipment doesn't have to
+/// downmix a surround track
+///
+/// Returns `None` when there is no audio at all.
+pub fn has_stereo_downmix(audio_streams: &[AudioStreamInfo]) -> Option<bool> {
+    if audio_streams.is_empty() {
+        return None;
+    }
+    Some(audio_streams.iter().any(AudioStreamInfo::is_stereo))
+}
+
+/// Most common layout for a channel count
+fn default_layout(channels: u32) -> &'static str {
+    match channels {
+        0 => "unknown",
+        1 => "mono",
+        2 => "stereo",
+        3 => "2.1",
+        4 => "quad",
+        5 => "5.0",
+        6 => "5.1",
+        7 => "6.1",
+        8 => "7.1",
+        _ => "unknown",
+    }
+}
+
+/// Turn an ffmpeg layout name into a description suitable for display
+fn describe_layout(layout: &str, channels: u32) -> String {
+    let (base, variant) = match layout.split_once('(') {
+        Some((base, variant)) => (base, Some(variant.trim_end_matches(')'))),
+        None => (layout, None),
+    };
+
+    let description = match base {
+        "mono" => "Mono".to_string(),
+        "stereo" => "Stereo".to_string(),
+        "downmix" => "Stereo downmix".to_string(),
+        "2.1" => "2.1 (stereo + LFE)".to_string(),
+        "quad" => "Quadraphonic".to_string(),
+        "5.0" | "5.1" | "6.0" | "6.1" | "7.0" | "7.1" => format!("{} surround", base),
+        "5.1.2" | "5.1.4" | "7.1.2" | "7.1.4" => format!("{} immersive", base),
+        "unknown" => format!("{} channels", channels),
+        other => other.to_string(),
+    };
+
+    match variant {
+        Some("side") => format!("{} (side speakers)", description),
+        Some("back") => format!("{} (back speakers)", description),
+        Some("wide") => format!("{} (wide speakers)", description),
+        Some(other) => format!("{} ({})", description, other),
+        None => description,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn streams(tracks: &[(u64, &str)]) -> Vec<AudioStreamInfo> {
+        let streams: Vec<_> = tracks
+            .iter()
+            .map(|(channels, layout)| {
+                serde_json::json!({
+                    "codec_type": "audio",
+                    "channels": channels,
+                    "channel_layout": layout,
+                })
+            })
+            .collect();
+        parse_audio_streams(&serde_json::json!({ "streams": streams }))
+    }
+
+    #[test]
+    fn mono_is_not_a_stereo_downmix() {
+        assert_eq!(has_stereo_downmix(&streams(&[(1, "mono")])), Some(false));
+        assert_eq!(
+            has_stereo_downmix(&streams(&[(6, "5.1(side)"), (1, "mono")])),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn finds_stereo_next_to_surround() {
+        assert_eq!(
+            has_stereo_downmix(&streams(&[(6, "5.1"), (2, "stereo")])),
+            Some(true)
+        );
+        assert_eq!(has_stereo_downmix(&streams(&[(2, "")])), Some(true));
+        assert_eq!(has_stereo_downmix(&[]), None);
+    }
+}
@@ -0,0 +1,365 @@
+use crate::probe::run_ffprobe_json;
+use crate::runner::MediaToolRunner;
+use crate::Error;
+
+/// OBU type of an AV1 sequence header
+const OBU_SEQUENCE_HEADER: u8 = 1;
+
+/// seq_force_screen_content_tools value meaning "decided per frame"
+const SELECT_SCREEN_CONTENT_TOOLS: u8 = 2;
+
+/// AV1 features that affect hardware decode support
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Av1Info {
+    /// 0 = Main, 1 = High, 2 = Professional
+    seq_profile: u8,
+    seq_level_idx: u8,
+    /// Level as usually written, e.g. "5.1"
+    level: String,
+    /// "Main" or "High"
+    tier: String,
+    bit_depth: u8,
+    monochrome: bool,
+    /// e.g. "4:2:0"
+    chroma_subsampling: String,
+    /// Whether film grain synthesis parameters may be present; `None` when
+    /// the sequence header wasn't available
+    film_grain: Option<bool>,
+    /// "off", "on" or "per_frame"; `None` when the sequence header wasn't
+    /// available
+    screen_content_tools: Option<String>,
+}
+
+impl Av1Info {
+    /// Parse an AV1CodecConfigurationRecord (`av1C` box or Matroska
+    /// CodecPrivate)
+    pub fn from_av1c(data: &[u8]) -> Option<Self> {
+        let header = data.get(0..4)?;
+        // marker bit and version 1
+        if header[0] != 0x81 {
+            return None;
+        }
+        let seq_profile = header[1] >> 5;
+        let seq_level_idx = header[1] & 0x1F;
+        let high_tier = header[2] & 0x80 != 0;
+        let high_bitdepth = header[2] & 0x40 != 0;
+        let twelve_bit = header[2] & 0x20 != 0;
+        let monochrome = header[2] & 0x10 != 0;
+        let subsampling_x = header[2] & 0x08 != 0;
+        let subsampling_y = header[2] & 0x04 != 0;
+
+        let mut info = Av1Info {
+            seq_profile,
+            seq_level_idx,
+            level: level_name(seq_level_idx),
+            tier: if high_tier { "High" } else { "Main" }.to_string(),
+            bit_depth: match (high_bitdepth, twelve_bit) {
+                (true, true) => 12,
+                (true, false) => 10,
+                _ => 8,
+            },
+            monochrome,
+            chroma_subsampling: subsampling_name(monochrome, subsampling_x, subsampling_y),
+            film_grain: None,
+            screen_content_tools: None,
+        };
+
+        if let Some(sequence_header) = find_sequence_header(&data[4..]) {
+            if let Some(tools) = parse_sequence_header_tools(sequence_header) {
+                info.film_grain = Some(tools.film_grain);
+                info.screen_content_tools = Some(tools.screen_content_tools.to_string());
+            }
+        }
+
+        Some(info)
+    }
+
+    /// Read the codec configuration from ffprobe's extradata dump
+    ///
+    /// Used for containers other than MP4, whose boxes are read directly.
+    pub async fn from_extradata(
+        runner: &dyn MediaToolRunner,
+        path: &str,
+        stream_index: u64,
+    ) -> Result<Option<Self>, Error> {
+        let json = run_ffprobe_json(
+            runner,
+            path,
+            &[
+                "-select_streams",
+                &stream_index.to_string(),
+                "-show_data",
+                "-show_entries",
+                "stream=extradata",
+            ],
+        )
+        .await?;
+
+        let extradata = json["streams"][0]["extradata"]
+            .as_str()
+            .map(parse_hexdump)
+            .unwrap_or_default();
+        Ok(Self::from_av1c(&extradata))
+    }
+}
+
+/// Level as major.minor from seq_level_idx
+fn level_name(seq_level_idx: u8) -> String {
+    if seq_level_idx == 31 {
+        return "max".to_string();
+    }
+    format!("{}.{}", 2 + (seq_level_idx >> 2), seq_level_idx & 3)
+}
+
+fn subsampling_name(monochrome: bool, subsampling_x: bool, subsampling_y: bool) -> String {
+    match (monochrome, subsampling_x, subsampling_y) {
+        (true, _, _) => "4:0:0",
+        (false, true, true) => "4:2:0",
+        (false, true, false) => "4:2:2",
+        _ => "4:4:4",
+    }
+    .to_string()
+}
+
+/// Parse the hexdump ffprobe prints for `-show_data`, e.g.
+/// `00000000: 8101 0c00 0a0b 0000  ........`
+fn parse_hexdump(dump: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for line in dump.lines() {
+        let Some((_, rest)) = line.split_once(": ") else {
+            continue;
+        };
+        // Hex groups end at the double space before the ASCII column
+        let hex = rest.split("  ").next().unwrap_or_default();
+        for group in hex.split_whitespace() {
+            for pair in group.as_bytes().chunks(2) {
+                if let Some(byte) = std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                {
+                    bytes.push(byte);
+                }
+            }
+        }
+    }
+    bytes
+}
+
+/// Find the payload of the sequence header OBU among configOBUs
+fn find_sequence_header(mut obus: &[u8]) -> Option<&[u8]> {
+    while let Some(&header) = obus.first() {
+        let obu_type = (header >> 3) & 0x0F;
+        let has_extension = header & 0x04 != 0;
+        let has_size = header & 0x02 != 0;
+        let mut pos = 1 + has_extension as usize;
+
+        let size = if has_size {
+            let (size, len) = read_leb128(obus.get(pos..)?)?;
+            pos += len;
+            usize::try_from(size).ok()?
+        } else {
+            obus.len().checked_sub(pos)?
+        };
+        let payload = obus.get(pos..pos.checked_add(size)?)?;
+
+        if obu_type == OBU_SEQUENCE_HEADER {
+            return Some(payload);
+        }
+        obus = &obus[pos + size..];
+    }
+    None
+}
+
+fn read_leb128(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().take(8).enumerate() {
+        value |= ((byte & 0x7F) as u64) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+struct SequenceTools {
+    film_grain: bool,
+    screen_content_tools: &'static str,
+}
+
+/// Walk a sequence header far enough to read the coding tool flags
+///
+/// Follows the syntax in section 5.5 of the AV1 specification.
+fn parse_sequence_header_tools(data: &[u8]) -> Option<SequenceTools> {
+    let mut r = BitReader::new(data);
+
+    let seq_profile = r.bits(3)? as u8;
+    let _still_picture = r.flag()?;
+    let reduced_still_picture_header = r.flag()?;
+
+    let mut screen_content_tools = SELECT_SCREEN_CONTENT_TOOLS;
+    if reduced_still_picture_header {
+        r.bits(5)?; // seq_level_idx[0]
+    } else {
+        let mut decoder_model_info_present = false;
+        let mut buffer_delay_length = 0;
+        if r.flag()? {
+            // timing_info
+            r.bits(32)?;
+            r.bits(32)?;
+            if r.flag()? {
+                r.uvlc()?;
+            }
+            decoder_model_info_present = r.flag()?;
+            if decoder_model_info_present {
+                buffer_delay_length = r.bits(5)? + 1;
+                r.bits(32)?;
+                r.bits(5)?;
+                r.bits(5)?;
+            }
+        }
+        let initial_display_delay_present = r.flag()?;
+        let operating_points = r.bits(5)? + 1;
+        for _ in 0..operating_points {
+            r.bits(12)?; // operating_point_idc
+            if r.bits(5)? > 7 {
+                r.flag()?; // seq_tier
+            }
+            if decoder_model_info_present && r.flag()? {
+                r.bits(buffer_delay_length)?;
+                r.bits(buffer_delay_length)?;
+                r.flag()?;
+            }
+            if initial_display_delay_present && r.flag()? {
+                r.bits(4)?;
+            }
+        }
+    }
+
+    let frame_width_bits = r.bits(4)? + 1;
+    let frame_height_bits = r.bits(4)? + 1;
+    r.bits(frame_width_bits)?;
+    r.bits(frame_height_bits)?;
+    if !reduced_still_picture_header && r.flag()? {
+        // frame_id_numbers_present
+        r.bits(4)?;
+        r.bits(3)?;
+    }
+    r.flag()?; // use_128x128_superblock
+    r.flag()?; // enable_filter_intra
+    r.flag()?; // enable_intra_edge_filter
+
+    if !reduced_still_picture_header {
+        r.flag()?; // enable_interintra_compound
+        r.flag()?; // enable_masked_compound
+        r.flag()?; // enable_warped_motion
+        r.flag()?; // enable_dual_filter
+        let enable_order_hint = r.flag()?;
+        if enable_order_hint {
+            r.flag()?; // enable_jnt_comp
+            r.flag()?; // enable_ref_frame_mvs
+        }
+        screen_content_tools = if r.flag()? {
+            SELECT_SCREEN_CONTENT_TOOLS
+        } else {
+            r.bits(1)? as u8
+        };
+        if screen_content_tools > 0 && !r.flag()? {
+            r.flag()?; // seq_force_integer_mv
+        }
+        if enable_order_hint {
+            r.bits(3)?;
+        }
+    }
+
+    r.flag()?; // enable_superres
+    r.flag()?; // enable_cdef
+    r.flag()?; // enable_restoration
+    skip_color_config(&mut r, seq_profile)?;
+    let film_grain = r.flag()?;
+
+    Some(SequenceTools {
+        film_grain,
+        screen_content_tools: match screen_content_tools {
+            0 => "off",
+            1 => "on",
+            _ => "per_frame",
+        },
+    })
+}
+
+fn skip_color_config(r: &mut BitReader, seq_profile: u8) -> Option<()> {
+    let high_bitdepth = r.flag()?;
+    let twelve_bit = seq_profile == 2 && high_bitdepth && r.flag()?;
+    let monochrome = seq_profile != 1 && r.flag()?;
+
+    let (mut primaries, mut transfer, mut matrix) = (2, 2, 2);
+    if r.flag()? {
+        primaries = r.bits(8)?;
+        transfer = r.bits(8)?;
+        matrix = r.bits(8)?;
+    }
+
+    if monochrome {
+        r.flag()?; // color_range
+        return Some(());
+    }
+    // BT.709 primaries + sRGB transfer + identity matrix implies 4:4:4 RGB
+    if primaries == 1 && transfer == 13 && matrix == 0 {
+        r.flag()?; // separate_uv_delta_q
+        return Some(());
+    }
+
+    r.flag()?; // color_range
+    let (subsampling_x, subsampling_y) = match seq_profile {
+        0 => (true, true),
+        1 => (false, false),
+        _ if twelve_bit => {
+            let x = r.flag()?;
+            (x, x && r.flag()?)
+        }
+        _ => (true, false),
+    };
+    if subsampling_x && subsampling_y {
+        r.bits(2)?; // chroma_sample_position
+    }
+    r.flag()?; // separate_uv_delta_q
+    Some(())
+}
+
+/// MSB-first bit reader
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bits(&mut self, count: u32) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..count {
+            let byte = self.data.get(self.pos / 8)?;
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        self.bits(1).map(|bit| bit == 1)
+    }
+
+    fn uvlc(&mut self) -> Option<u32> {
+        let mut leading_zeros = 0;
+        while !self.flag()? {
+            leading_zeros += 1;
+            if leading_zeros >= 32 {
+                return Some(u32::MAX);
+            }
+        }
+        Some(self.bits(leading_zeros)? + ((1u64 << leading_zeros) - 1) as u32)
+    }
+}
@@ -0,0 +1,119 @@
+use std::{collections::HashMap, time::Instant};
+
+use crate::runner::{MediaTool, MediaToolRunner};
+use crate::Error;
+
+/// Bit rate declared by the stream itself, in bits per second
+///
+/// Falls back to the `BPS` statistics tag that mkvmerge writes, since Matroska
+/// streams usually have no `bit_rate` field.
+pub fn declared_bit_rate(stream: &serde_json::Value) -> Option<f64> {
+    let from_field = stream["bit_rate"]
+        .as_str()
+        .and_then(|value| value.parse::<f64>().ok());
+
+    let from_tags = || {
+        let tags = stream["tags"].as_object()?;
+        tags.iter()
+            .find(|(key, _)| {
+                key.eq_ignore_ascii_case("BPS") || key.to_ascii_uppercase().starts_with("BPS-")
+            })
+            .and_then(|(_, value)| value.as_str())
+            .and_then(|value| value.parse::<f64>().ok())
+    };
+
+    from_field.or_else(from_tags).filter(|&rate| rate > 0.0)
+}
+
+/// Overall bit rate in bits per second from ffprobe's `format` section
+///
+/// Containers that don't declare one get the file size over the duration; 0
+/// when neither is known.
+pub fn overall_bit_rate(format: &serde_json::Value, duration: f64) -> f64 {
+    let number = |key: &str| {
+        format[key]
+            .as_str()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| *value > 0.0)
+    };
+    number("bit_rate")
+        .or_else(|| {
+            number("size")
+                .filter(|_| duration > 0.0)
+                .map(|size| size * 8.0 / duration)
+        })
+        .unwrap_or(0.0)
+}
+
+/// Measure the average bit rate of every stream by summing packet sizes
+///
+/// This reads the whole file (without decoding), so it's only worth running
+/// when some stream has no declared bit rate. Returns bits per second keyed by
+/// stream index.
+pub async fn measure_stream_bit_rates(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    duration: f64,
+) -> Result<HashMap<u64, f64>, Error> {
+    if duration <= 0.0 {
+        return Ok(HashMap::new());
+    }
+
+    let start = Instant::now();
+    let output = runner
+        .run(
+            MediaTool::Ffprobe,
+            &[
+                "-v",
+                "quiet",
+                "-show_entries",
+                "packet=stream_index,size",
+                "-of",
+                "compact=p=0",
+                path,
+            ],
+        )
+        .await?;
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+
+    let totals = sum_packet_sizes(&String::from_utf8_lossy(&output.stdout));
+
+    tracing::debug!(
+        video_path = %path,
+        streams = totals.len(),
+        elapsed = ?start.elapsed(),
+        "Measured per-stream bit rates from packets"
+    );
+
+    Ok(totals
+        .into_iter()
+        .map(|(index, bytes)| (index, bytes as f64 * 8.0 / duration))
+        .collect())
+}
+
+/// Sum packet sizes per stream from `compact` output lines like
+/// `stream_index=0|size=1234`
+fn sum_packet_sizes(output: &str) -> HashMap<u64, u64> {
+    let mut totals = HashMap::new();
+
+    for line in output.lines() {
+        let mut stream_index = None;
+        let mut size = None;
+        for field in line.split('|') {
+            match field.split_once('=') {
+                Some(("stream_index", value)) => stream_index = value.parse::<u64>().ok(),
+                Some(("size", value)) => size = value.parse::<u64>().ok(),
+                _ => {}
+            }
+        }
+        if let (Some(stream_index), Some(size)) = (stream_index, size) {
+            *totals.entry(stream_index).or_insert(0) += size;
+        }
+    }
+
+    totals
+}
@@ -0,0 +1,160 @@
+/// FourCC / codec tag of a stream, e.g. "avc1", "hvc1", "XVID"
+///
+/// Returns `None` when the container stores no tag (ffprobe reports those as
+/// `[0][0][0][0]` with tag `0x0000`), as is usual for Matroska and MPEG-TS.
+pub fn codec_tag(stream: &serde_json::Value) -> Option<String> {
+    if stream["codec_tag"].as_str() == Some("0x0000") {
+        return None;
+    }
+    stream["codec_tag_string"]
+        .as_str()
+        .filter(|tag| !tag.is_empty() && !tag.starts_with("[0]"))
+        .map(str::to_string)
+}
+
+/// Codec level as usually written, e.g. 4.1
+///
+/// ffprobe reports H.264 levels times 10 and HEVC levels times 30.
+pub fn video_level(stream: &serde_json::Value) -> Option<f64> {
+    let level = stream["level"].as_i64().filter(|&level| level > 0)? as f64;
+    match stream["codec_name"].as_str()? {
+        "h264" => Some(level / 10.0),
+        "hevc" => Some(level / 30.0),
+        _ => None,
+    }
+}
+
+/// Bits per sample of the decoded video
+pub fn bit_depth(stream: &serde_json::Value) -> u32 {
+    stream["bits_per_raw_sample"]
+        .as_str()
+        .and_then(|bits| bits.parse::<u32>().ok())
+        .filter(|&bits| bits > 0)
+        .unwrap_or_else(|| pix_fmt_bit_depth(stream["pix_fmt"].as_str().unwrap_or("")))
+}
+
+/// Bits per sample of a pixel format, from the depth in its name
+///
+/// Only the depth suffix counts, as in `yuv420p10le` or `gray12be`, and the
+/// `p010`/`y210` style names of semi-planar and packed formats; the digits in
+/// `nv12` or `yuv410p` describe the layout, and those are 8-bit.
+pub fn pix_fmt_bit_depth(pix_fmt: &str) -> u32 {
+    let name = pix_fmt
+        .strip_suffix("le")
+        .or_else(|| pix_fmt.strip_suffix("be"))
+        .unwrap_or(pix_fmt);
+    let number = |digits: &str| {
+        (!digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()))
+            .then(|| digits.parse::<u32>().ok())
+            .flatten()
+    };
+
+    // p010, p216, y210, y410: layout digit then the depth
+    let semi_planar = ["p", "y"]
+        .iter()
+        .filter_map(|prefix| name.strip_prefix(prefix))
+        .find(|digits| digits.len() == 3)
+        .and_then(|digits| number(&digits[1..]));
+    // yuv420p10, gbrap12, gray16, xv30 has none
+    let suffix = name
+        .rsplit_once('p')
+        .and_then(|(_, depth)| number(depth))
+        .or_else(|| name.strip_prefix("gray").and_then(number));
+    // Packed RGB named after the bits of a whole pixel
+    let packed = match name {
+        "rgb48" | "bgr48" | "rgba64" | "bgra64" => Some(16),
+        "x2rgb10" | "x2bgr10" | "xv30" => Some(10),
+        _ => None,
+    };
+    semi_planar.or(suffix).or(packed).unwrap_or(8)
+}
+
+/// Chroma subsampling of a pixel format, e.g. "4:2:0" for `yuv420p10le`
+///
+/// `None` for RGB and other formats without subsampled chroma planes.
+pub fn chroma_subsampling(pix_fmt: &str) -> Option<&'static str> {
+    // Planar YUV, with alpha or full range: yuv420p, yuva444p, yuvj422p
+    let planar = ["yuvj", "yuva", "yuv"]
+        .iter()
+        .find_map(|prefix| pix_fmt.strip_prefix(prefix));
+    if let Some(layout) = planar {
+        return [
+            ("420", "4:2:0"),
+            ("422", "4:2:2"),
+            ("444", "4:4:4"),
+            ("440", "4:4:0"),
+            ("411", "4:1:1"),
+            ("410", "4:1:0"),
+        ]
+        .iter()
+        .find(|(prefix, _)| layout.starts_with(prefix))
+        .map(|(_, subsampling)| *subsampling);
+    }
+
+    // Semi-planar and packed formats hardware decoders produce
+    let formats: [(&[&str], &str); 4] = [
+        (&["nv12", "nv21", "p010", "p012", "p016"], "4:2:0"),
+        (
+            &[
+                "nv16", "nv20", "p210", "p212", "p216", "y210", "y212", "yuyv422", "uyvy422",
+            ],
+            "4:2:2",
+        ),
+        (
+            &[
+                "nv24", "nv42", "p410", "p412", "p416", "y410", "y412", "vuya", "xv30",
+            ],
+            "4:4:4",
+        ),
+        (&["gray"], "4:0:0"),
+    ];
+    formats
+        .iter()
+        .find(|(prefixes, _)| prefixes.iter().any(|prefix| pix_fmt.starts_with(prefix)))
+        .map(|(_, subsampling)| *subsampling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_digits_are_not_a_depth() {
+        assert_eq!(pix_fmt_bit_depth("nv12"), 8);
+        assert_eq!(pix_fmt_bit_depth("nv16"), 8);
+        assert_eq!(pix_fmt_bit_depth("yuv410p"), 8);
+        assert_eq!(pix_fmt_bit_depth("yuv411p"), 8);
+        assert_eq!(pix_fmt_bit_depth("yuvj420p"), 8);
+    }
+
+    #[test]
+    fn depth_suffixes() {
+        assert_eq!(pix_fmt_bit_depth("yuv420p10le"), 10);
+        assert_eq!(pix_fmt_bit_depth("yuv444p12be"), 12);
+        assert_eq!(pix_fmt_bit_depth("gbrap16le"), 16);
+        assert_eq!(pix_fmt_bit_depth("gray12le"), 12);
+    }
+
+    #[test]
+    fn semi_planar_depths() {
+        assert_eq!(pix_fmt_bit_depth("p010le"), 10);
+        assert_eq!(pix_fmt_bit_depth("p216le"), 16);
+        assert_eq!(pix_fmt_bit_depth("y210le"), 10);
+    }
+
+    #[test]
+    fn raw_sample_bits_win_over_pix_fmt() {
+        let stream = serde_json::json!({ "bits_per_raw_sample": "10", "pix_fmt": "yuv420p" });
+        assert_eq!(bit_depth(&stream), 10);
+        let stream = serde_json::json!({ "pix_fmt": "nv12" });
+        assert_eq!(bit_depth(&stream), 8);
+    }
+
+    #[test]
+    fn chroma_subsampling_of_common_formats() {
+        assert_eq!(chroma_subsampling("yuv420p10le"), Some("4:2:0"));
+        assert_eq!(chroma_subsampling("p010le"), Some("4:2:0"));
+        assert_eq!(chroma_subsampling("yuvj422p"), Some("4:2:2"));
+        assert_eq!(chroma_subsampling("rgb24"), None);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fs::File, io::Read};
+
+use crate::sniff::FileKind;
+use crate::Error;
+
+/// Bytes read from the start of the file to find the ftyp box or EBML header
+const HEADER_READ_LEN: u64 = 4096;
+
+/// EBML element IDs (with their length marker bits, as stored)
+const EBML_HEADER_ID: u32 = 0x1A45_DFA3;
+const EBML_DOCTYPE_ID: u32 = 0x4282;
+const EBML_DOCTYPE_VERSION_ID: u32 = 0x4287;
+const EBML_DOCTYPE_READ_VERSION_ID: u32 = 0x4285;
+
+/// Container identification useful for diagnosing playback compatibility
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct ContainerInfo {
+    mime_type: Option<String>,
+    /// MP4/MOV `ftyp` major brand, e.g. "isom", "qt  "
+    major_brand: Option<String>,
+    minor_version: Option<u32>,
+    compatible_brands: Vec<String>,
+    /// Matroska EBML DocType, "matroska" or "webm"
+    doctype: Option<String>,
+    doctype_version: Option<u64>,
+    doctype_read_version: Option<u64>,
+}
+
+impl ContainerInfo {
+    pub fn mime_type(&self) -> Option<&str> {
+        self.mime_type.as_deref()
+    }
+}
+
+/// Read brand or DocType information from the file header
+pub fn read_container_info(path: &str, kind: FileKind) -> Result<ContainerInfo, Error> {
+    let mut header = Vec::new();
+    File::open(path)?
+        .take(HEADER_READ_LEN)
+        .read_to_end(&mut header)?;
+
+    let mut info = ContainerInfo::default();
+    match kind {
+        FileKind::Media("mp4") => {
+            if let Some((major, minor, compatible)) = parse_ftyp(&header) {
+                info.major_brand = Some(major);
+                info.minor_version = Some(minor);
+                info.compatible_brands = compatible;
+            }
+        }
+        FileKind::Media("matroska") => {
+            let (doctype, version, read_version) = parse_ebml_header(&header);
+            info.doctype = doctype;
+            info.doctype_version = version;
+            info.doctype_read_version = read_version;
+        }
+        _ => {}
+    }
+    info.mime_type = mime_type(kind, &info).map(str::to_string);
+
+    Ok(info)
+}
+
+/// MIME type for a sniffed container, refined by its brand or DocType
+fn mime_type(kind: FileKind, info: &ContainerInfo) -> Option<&'static str> {
+    let FileKind::Media(container) = kind else {
+        return None;
+    };
+
+    let mime = match container {
+        "mp4" => {
+            let brand = info.major_brand.as_deref().unwrap_or("");
+            match brand {
+                "qt  " => "video/quicktime",
+                "M4A " | "M4B " => "audio/mp4",
+                _ if brand.starts_with("3g2") => "video/3gpp2",
+                _ if brand.starts_with("3gp") => "video/3gpp",
+                _ => "video/mp4",
+            }
+        }
+        "matroska" => match info.doctype.as_deref() {
+            Some("webm") => "video/webm",
+            _ => "video/x-matroska",
+        },
+        "avi" => "video/x-msvideo",
+        "wav" => "audio/wav",
+        "mpegts" | "m2ts" => "video/mp2t",
+        "mpeg" => "video/mpeg",
+        "flv" => "video/x-flv",
+        "asf" => "video/x-ms-asf",
+        "ogg" => "video/ogg",
+        "rm" => "application/vnd.rn-realmedia",
+        "flac" => "audio/flac",
+        "mp3" => "audio/mpeg",
+        "hls" => "application/vnd.apple.mpegurl",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Parse a leading `ftyp` box into (major brand, minor version, compatible brands)
+fn parse_ftyp(header: &[u8]) -> Option<(String, u32, Vec<String>)> {
+    if header.get(4..8)? != b"ftyp" {
+        return None;
+    }
+    let size = u32::from_be_bytes(header.get(0..4)?.try_into().ok()?) as usize;
+    let body = header.get(8..size.min(header.len()))?;
+
+    let major = brand(body.get(0..4)?);
+    let minor = u32::from_be_bytes(body.get(4..8)?.try_into().ok()?);
+    let compatible = body
+        .get(8..)?
+        .chunks_exact(4)
+        .map(brand)
+        .filter(|brand| !brand.trim_matches(['\0', ' ']).is_empty())
+        .collect();
+
+    Some((major, minor, compatible))
+}
+
+fn brand(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// Read DocType, DocTypeVersion and DocTypeReadVersion from the EBML header
+fn parse_ebml_header(header: &[u8]) -> (Option<String>, Option<u64>, Option<u64>) {
+    let mut doctype = None;
+    let mut version = None;
+    let mut read_version = None;
+
+    let Some((EBML_HEADER_ID, id_len)) = read_element_id(header) else {
+        return (None, None, None);
+    };
+    let Some((size, size_len)) = read_vint(&header[id_len..]) else {
+        return (None, None, None);
+    };
+    let start = id_len + size_len;
+    let end = (start as u64).saturating_add(size).min(header.len() as u64) as usize;
+
+    let mut pos = start;
+    while pos < end {
+        let Some((id, id_len)) = read_element_id(&header[pos..end]) else {
+            break;
+        };
+        let Some((size, size_len)) = read_vint(&header[pos + id_len..end]) else {
+            break;
+        };
+        let data_start = pos + id_len + size_len;
+        let Some(data) = usize::try_from(size)
+            .ok()
+            .and_then(|size| header.get(data_start..data_start.checked_add(size)?))
+        else {
+            break;
+        };
+
+        match id {
+            EBML_DOCTYPE_ID => {
+                doctype = Some(
+                    String::from_utf8_lossy(data)
+                        .trim_end_matches('\0')
+                        .to_string(),
+                )
+            }
+            EBML_DOCTYPE_VERSION_ID => version = Some(read_uint(data)),
+            EBML_DOCTYPE_READ_VERSION_ID => read_version = Some(read_uint(data)),
+            _ => {}
+        }
+        pos = data_start + data.len();
+    }
+
+    (doctype, version, read_version)
+}
+
+/// Read an EBML element ID, keeping its length marker bits
+fn read_element_id(data: &[u8]) -> Option<(u32, usize)> {
+    let first = *data.first()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 4 {
+        return None;
+    }
+    let bytes = data.get(..len)?;
+    let id = bytes.iter().fold(0u32, |id, &b| (id << 8) | b as u32);
+    Some((id, len))
+}
+
+/// Read an EBML variable-length size, without its length marker
+fn read_vint(data: &[u8]) -> Option<(u64, usize)> {
+    let first = *data.first()?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 {
+        return None;
+    }
+    let bytes = data.get(..len)?;
+    let mask = if len == 8 { 0 } else { 0xFFu8 >> len };
+    let value = bytes[1..]
+        .iter()
+        .fold((first & mask) as u64, |value, &b| (value << 8) | b as u64);
+    Some((value, len))
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .take(8)
+        .fold(0, |value, &b| (value << 8) | b as u64)
+}
@@ -0,0 +1,114 @@
+use crate::mp4::Mp4Track;
+
+/// MP4 boxes carrying a DOVIDecoderConfigurationRecord
+const DOVI_CONFIG_BOXES: [&str; 3] = ["dvcC", "dvvC", "dvwC"];
+
+/// ffprobe side data type for the same record (Matroska, MPEG-TS)
+const DOVI_SIDE_DATA: &str = "DOVI configuration record";
+
+/// Dolby Vision configuration of a video stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct DolbyVisionInfo {
+    version: String,
+    profile: u8,
+    level: u8,
+    /// Profile as usually written, e.g. "5", "7", "8.1"
+    profile_name: String,
+    rpu_present: bool,
+    enhancement_layer_present: bool,
+    base_layer_present: bool,
+    /// Base-layer signal compatibility ID (0 = none, 1 = HDR10, 2 = SDR,
+    /// 4 = HLG, 6 = Ultra HD Blu-ray HDR10)
+    base_layer_compatibility_id: u8,
+    /// What non-Dolby Vision displays get from the base layer
+    fallback: String,
+    /// Where the record was found: an MP4 box type or "side_data"
+    source: String,
+}
+
+/// Read the Dolby Vision configuration box of an MP4 video track
+pub fn from_mp4_track(track: &Mp4Track) -> Option<DolbyVisionInfo> {
+    track
+        .sample_entry_boxes
+        .iter()
+        .find(|(kind, _)| DOVI_CONFIG_BOXES.contains(&kind.as_str()))
+        .and_then(|(kind, body)| parse_dovi_record(body, kind))
+}
+
+/// Read the Dolby Vision configuration ffprobe reports as stream side data
+pub fn from_side_data(stream: &serde_json::Value) -> Option<DolbyVisionInfo> {
+    let entry = stream["side_data_list"]
+        .as_array()?
+        .iter()
+        .find(|entry| entry["side_data_type"].as_str() == Some(DOVI_SIDE_DATA))?;
+    let field = |key: &str| entry[key].as_u64().map(|value| value as u8);
+    Some(build_info(
+        field("dv_version_major")?,
+        field("dv_version_minor")?,
+        field("dv_profile")?,
+        field("dv_level")?,
+        field("rpu_present_flag")? != 0,
+        field("el_present_flag")? != 0,
+        field("bl_present_flag")? != 0,
+        field("dv_bl_signal_compatibility_id").unwrap_or(0),
+        "side_data",
+    ))
+}
+
+/// Parse the bit-packed DOVIDecoderConfigurationRecord
+fn parse_dovi_record(data: &[u8], source: &str) -> Option<DolbyVisionInfo> {
+    let bytes = data.get(0..5)?;
+    Some(build_info(
+        bytes[0],
+        bytes[1],
+        bytes[2] >> 1,
+        ((bytes[2] & 0x01) << 5) | (bytes[3] >> 3),
+        bytes[3] & 0x04 != 0,
+        bytes[3] & 0x02 != 0,
+        bytes[3] & 0x01 != 0,
+        bytes[4] >> 4,
+        source,
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_info(
+    version_major: u8,
+    version_minor: u8,
+    profile: u8,
+    level: u8,
+    rpu_present: bool,
+    enhancement_layer_present: bool,
+    base_layer_present: bool,
+    compatibility_id: u8,
+    source: &str,
+) -> DolbyVisionInfo {
+    // Profiles 8 and 10 are split by compatibility ID, e.g. 8.1 vs 8.4
+    let profile_name = if matches!(profile, 8 | 10) && compatibility_id != 0 {
+        format!("{}.{}", profile, compatibility_id)
+    } else {
+        profile.to_string()
+    };
+
+    let fallback = match compatibility_id {
+        0 => "None: requires a Dolby Vision decoder",
+        1 => "HDR10",
+        2 => "SDR",
+        4 => "HLG",
+        6 => "HDR10 (Ultra HD Blu-ray)",
+        _ => "Unknown",
+    };
+
+    DolbyVisionInfo {
+        version: format!("{}.{}", version_major, version_minor),
+        profile,
+        level,
+        profile_name,
+        rpu_present,
+        enhancement_layer_present,
+        base_layer_present,
+        base_layer_compatibility_id: compatibility_id,
+        fallback: fallback.to_string(),
+        source: source.to_string(),
+    }
+}
@@ -0,0 +1,158 @@
+use std::time::Instant;
+
+use crate::runner::{MediaTool, MediaToolRunner};
+use crate::Error;
+
+/// Where the duration of a file came from
+///
+/// Old AVI, OGM and RealMedia files often leave the container duration out,
+/// so it's derived from the streams, then from their packets.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationSource {
+    /// The container's `format.duration`
+    Format,
+    /// The longest stream duration
+    Stream,
+    /// A `DURATION` tag, as mkvmerge writes
+    Tag,
+    /// Frame count of the video stream over its frame rate
+    FrameCount,
+    /// First to last packet timestamp, read from the whole file
+    Packets,
+}
+
+/// Duration in seconds, from the first source that has one
+///
+/// The packet scan reads the whole file, so it only runs when nothing
+/// declared a duration.
+pub async fn resolve_duration(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    json: &serde_json::Value,
+    video_stream: &serde_json::Value,
+    frame_rate: f64,
+) -> Result<(f64, DurationSource), Error> {
+    if let Some(declared) = declared_duration(json, video_stream, frame_rate) {
+        return Ok(declared);
+    }
+
+    tracing::debug!(video_path = %path, "No declared duration, measuring from packets");
+    measure_packet_duration(runner, path)
+        .await?
+        .map(|duration| (duration, DurationSource::Packets))
+        .ok_or_else(|| Error::ParseError("Duration not found".to_string()))
+}
+
+/// Duration declared by the container or its streams
+fn declared_duration(
+    json: &serde_json::Value,
+    video_stream: &serde_json::Value,
+    frame_rate: f64,
+) -> Option<(f64, DurationSource)> {
+    let seconds = |value: &serde_json::Value| {
+        value
+            .as_str()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|duration| duration.is_finite() && *duration > 0.0)
+    };
+    let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+
+    if let Some(duration) = seconds(&json["format"]["duration"]) {
+        return Some((duration, DurationSource::Format));
+    }
+    let longest_stream = streams
+        .iter()
+        .filter_map(|stream| seconds(&stream["duration"]))
+        .reduce(f64::max);
+    if let Some(duration) = longest_stream {
+        return Some((duration, DurationSource::Stream));
+    }
+    let longest_tag = streams
+        .iter()
+        .filter_map(|stream| {
+            let tags = stream["tags"].as_object()?;
+            tags.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case("DURATION"))
+                .and_then(|(_, value)| parse_clock(value.as_str()?))
+                .filter(|duration| *duration > 0.0)
+        })
+        .reduce(f64::max);
+    if let Some(duration) = longest_tag {
+        return Some((duration, DurationSource::Tag));
+    }
+    let frames = video_stream["nb_frames"]
+        .as_str()
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|frames| *frames > 0);
+    match frames {
+        Some(frames) if frame_rate > 0.0 => {
+            Some((frames as f64 / frame_rate, DurationSource::FrameCount))
+        }
+        _ => None,
+    }
+}
+
+/// Time from the first packet to the end of the last, over all streams
+async fn measure_packet_duration(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+) -> Result<Option<f64>, Error> {
+    let start = Instant::now();
+    let output = runner
+        .run(
+            MediaTool::Ffprobe,
+            &[
+                "-v",
+                "quiet",
+                "-show_entries",
+                "packet=pts_time,dts_time,duration_time",
+                "-of",
+                "compact=p=0",
+                path,
+            ],
+        )
+        .await?;
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+
+    let mut first: Option<f64> = None;
+    let mut end: Option<f64> = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut time = None;
+        let mut duration = 0.0;
+        for field in line.split('|') {
+            match field.split_once('=') {
+                // Packets without a PTS, common in AVI, still have a DTS
+                Some(("pts_time", value)) => time = time.or(value.parse::<f64>().ok()),
+                Some(("dts_time", value)) => time = time.or(value.parse::<f64>().ok()),
+                Some(("duration_time", value)) => duration = value.parse().unwrap_or(0.0),
+                _ => {}
+            }
+        }
+        if let Some(time) = time {
+            first = Some(first.map_or(time, |first| first.min(time)));
+            end = Some(end.map_or(time + duration, |end| end.max(time + duration)));
+        }
+    }
+
+    let duration = first.zip(end).map(|(first, end)| end - first);
+    tracing::debug!(
+        video_path = %path,
+        duration = ?duration,
+        elapsed = ?start.elapsed(),
+        "Measured duration from packets"
+    );
+    Ok(duration.filter(|duration| *duration > 0.0))
+}
+
+/// Parse an `HH:MM:SS.micro` clock value into seconds
+pub fn parse_clock(value: &str) -> Option<f64> {
+    let mut parts = value.split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
@@ -0,0 +1,197 @@
+// Cheap content heuristics for decoded frames; good enough to rank
+// thumbnails, not a replacement for real face detection or OCR
+
+/// Side of the square cells the skin mask is pooled into, in pixels
+const SKIN_CELL_SIZE: u32 = 8;
+
+/// Share of skin-toned pixels that makes a cell count as skin
+const SKIN_CELL_RATIO: f64 = 0.6;
+
+/// Smallest skin region taken as a face, in cells
+const MIN_FACE_CELLS: usize = 4;
+
+/// Largest skin region taken as a face, as a share of the frame; bigger
+/// regions are walls, sand or close-ups of arms
+const MAX_FACE_SHARE: f64 = 0.2;
+
+/// Width over height of a face's bounding box
+const FACE_ASPECT: std::ops::RangeInclusive<f64> = 0.5..=1.3;
+
+/// Share of a face's bounding box the skin region must fill
+const MIN_FACE_FILL: f64 = 0.5;
+
+/// Luma spread inside a face's bounding box; eyes and mouth make faces
+/// less uniform than skin-coloured surfaces
+const MIN_FACE_LUMA_STDDEV: f64 = 18.0;
+
+/// Luma step between neighbouring pixels that counts as a glyph edge
+const TEXT_EDGE_STEP: i16 = 60;
+
+/// Share of a row's pixels that must be glyph edges for it to carry text
+const TEXT_ROW_DENSITY: f64 = 0.06;
+
+/// Height of a line of text, as a share of the frame height
+const TEXT_BAND_HEIGHT: std::ops::RangeInclusive<f64> = 0.02..=0.12;
+
+/// Content found in a frame, for picking poster frames
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ContentHints {
+    /// Skin-toned regions shaped like a face
+    pub faces: u32,
+    /// The frame has lines of high-contrast text such as captions, titles
+    /// or credits
+    pub text_overlay: bool,
+}
+
+impl ContentHints {
+    /// Look for faces and text in an encoded image (PNG, JPEG, ...)
+    pub fn from_image_data(data: &[u8]) -> Option<Self> {
+        let image = image::load_from_memory(data).ok()?.to_rgb8();
+        Some(Self::from_rgb(&image))
+    }
+
+    pub fn from_rgb(image: &image::RgbImage) -> Self {
+        let luma = image::imageops::grayscale(image);
+        ContentHints {
+            faces: count_faces(image, &luma),
+            text_overlay: !text_bands(&luma, luma.height()).is_empty(),
+        }
+    }
+}
+
+/// Rows of an image that look like a line of text, as `(top, bottom)` pixel
+/// rows, bottom exclusive
+///
+/// Text is a band of rows crossed by many sharp luma steps, with calmer rows
+/// above and below. Busy textures such as foliage fail the height limits,
+/// which are relative to `frame_height` so crops of a frame can be checked.
+pub fn text_bands(image: &image::GrayImage, frame_height: u32) -> Vec<(u32, u32)> {
+    let (width, height) = image.dimensions();
+    if width < 2 || frame_height == 0 {
+        return Vec::new();
+    }
+
+    let is_text_row = |y: u32| {
+        let edges = (1..width)
+            .filter(|&x| {
+                let step =
+                    image.get_pixel(x, y).0[0] as i16 - image.get_pixel(x - 1, y).0[0] as i16;
+                step.abs() >= TEXT_EDGE_STEP
+            })
+            .count();
+        edges as f64 / width as f64 >= TEXT_ROW_DENSITY
+    };
+
+    let mut bands = Vec::new();
+    let mut band_start = None;
+    for y in 0..=height {
+        match (band_start, y < height && is_text_row(y)) {
+            (None, true) => band_start = Some(y),
+            (Some(top), false) => {
+                let share = (y - top) as f64 / frame_height as f64;
+                if TEXT_BAND_HEIGHT.contains(&share) {
+                    bands.push((top, y));
+                }
+                band_start = None;
+            }
+            _ => {}
+        }
+    }
+    bands
+}
+
+/// Skin colour test in YCbCr, after Chai and Ngan
+fn is_skin(pixel: &image::Rgb<u8>) -> bool {
+    let [r, g, b] = pixel.0.map(f64::from);
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    let cb = 128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b;
+    let cr = 128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b;
+    y > 40.0 && (77.0..=127.0).contains(&cb) && (133.0..=173.0).contains(&cr)
+}
+
+/// Count connected skin regions whose size, shape and detail fit a face
+fn count_faces(image: &image::RgbImage, luma: &image::GrayImage) -> u32 {
+    let (width, height) = image.dimensions();
+    let (columns, rows) = (width / SKIN_CELL_SIZE, height / SKIN_CELL_SIZE);
+    if columns == 0 || rows == 0 {
+        return 0;
+    }
+
+    let cell_pixels = (SKIN_CELL_SIZE * SKIN_CELL_SIZE) as f64;
+    let mut skin = vec![false; (columns * rows) as usize];
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut count = 0;
+            for y in row * SKIN_CELL_SIZE..(row + 1) * SKIN_CELL_SIZE {
+                for x in column * SKIN_CELL_SIZE..(column + 1) * SKIN_CELL_SIZE {
+                    if is_skin(image.get_pixel(x, y)) {
+                        count += 1;
+                    }
+                }
+            }
+            skin[(row * columns + column) as usize] = count as f64 / cell_pixels >= SKIN_CELL_RATIO;
+        }
+    }
+
+    let max_cells = (skin.len() as f64 * MAX_FACE_SHARE) as usize;
+    let mut visited = vec![false; skin.len()];
+    let mut faces = 0;
+    for start in 0..skin.len() {
+        if !skin[start] || visited[start] {
+            continue;
+        }
+
+        // Flood fill the region, tracking its bounding box in cells
+        let mut stack = vec![start];
+        visited[start] = true;
+        let mut cells = 0;
+        let (mut left, mut top, mut right, mut bottom) = (columns, rows, 0, 0);
+        while let Some(cell) = stack.pop() {
+            cells += 1;
+            let (column, row) = (cell as u32 % columns, cell as u32 / columns);
+            left = left.min(column);
+            right = right.max(column);
+            top = top.min(row);
+            bottom = bottom.max(row);
+
+            let neighbours = [
+                (column > 0).then(|| cell - 1),
+                (column + 1 < columns).then(|| cell + 1),
+                (row > 0).then(|| cell - columns as usize),
+                (row + 1 < rows).then(|| cell + columns as usize),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if skin[neighbour] && !visited[neighbour] {
+                    visited[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        let (box_width, box_height) = (right - left + 1, bottom - top + 1);
+        let aspect = box_width as f64 / box_height as f64;
+        let fill = cells as f64 / (box_width * box_height) as f64;
+        if cells < MIN_FACE_CELLS
+            || cells > max_cells
+            || !FACE_ASPECT.contains(&aspect)
+            || fill < MIN_FACE_FILL
+        {
+            continue;
+        }
+
+        let region = image::imageops::crop_imm(
+            luma,
+            left * SKIN_CELL_SIZE,
+            top * SKIN_CELL_SIZE,
+            box_width * SKIN_CELL_SIZE,
+            box_height * SKIN_CELL_SIZE,
+        )
+        .to_image();
+        let detailed = crate::frame_stats::LumaStats::from_luma(&region)
+            .is_some_and(|stats| stats.stddev >= MIN_FACE_LUMA_STDDEV);
+        if detailed {
+            faces += 1;
+        }
+    }
+    faces
+}
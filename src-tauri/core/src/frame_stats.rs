@@ -0,0 +1,233 @@
+/// Frames darker than this average luma are treated as fades / black frames
+const MIN_MEAN_LUMA: f64 = 24.0;
+
+/// Frames brighter than this average luma are treated as white flashes
+const MAX_MEAN_LUMA: f64 = 235.0;
+
+/// Frames with less luma spread than this are treated as flat (title cards,
+/// solid backgrounds, studio logos on black)
+const MIN_LUMA_STDDEV: f64 = 12.0;
+
+/// Pixels with a larger luma gradient are edges or texture and are left out
+/// of noise estimation
+const NOISE_EDGE_LIMIT: f64 = 24.0;
+
+/// Least share of flat pixels for a meaningful noise estimate
+const MIN_FLAT_SHARE: f64 = 0.05;
+
+/// Size of the DCT blocks whose edges blockiness looks for
+const CODEC_BLOCK_SIZE: u32 = 8;
+
+/// Side of the squares banding is judged in
+const BANDING_TILE_SIZE: u32 = 16;
+
+/// Largest luma range of a smooth tile, where banding can show
+const BANDING_MAX_STEP: u8 = 2;
+
+/// Value changes between neighbours a band edge causes, per tile side; a
+/// contour crossing the tile changes value once per row or column, up to
+/// twice for a diagonal one
+const BANDING_CONTOUR_CROSSINGS: u32 = 2;
+
+/// Brightness and detail statistics of a decoded frame
+#[derive(Debug, Clone, Copy)]
+pub struct LumaStats {
+    pub mean: f64,
+    pub stddev: f64,
+    /// Variance of the Laplacian; higher means more in-focus detail
+    pub sharpness: f64,
+}
+
+impl LumaStats {
+    /// Compute luma statistics from an encoded image (PNG, JPEG, ...)
+    pub fn from_image_data(data: &[u8]) -> Option<Self> {
+        let image = image::load_from_memory(data).ok()?.to_luma8();
+        Self::from_luma(&image)
+    }
+
+    /// Compute luma statistics from a grayscale image
+    pub fn from_luma(image: &image::GrayImage) -> Option<Self> {
+        let (width, height) = image.dimensions();
+        let pixel_count = (width * height) as f64;
+        if pixel_count == 0.0 {
+            return None;
+        }
+
+        let (sum, sum_sq) = image.pixels().fold((0.0, 0.0), |(sum, sum_sq), pixel| {
+            let value = pixel.0[0] as f64;
+            (sum + value, sum_sq + value * value)
+        });
+        let mean = sum / pixel_count;
+        let variance = (sum_sq / pixel_count - mean * mean).max(0.0);
+
+        Some(LumaStats {
+            mean,
+            stddev: variance.sqrt(),
+            sharpness: laplacian_variance(image),
+        })
+    }
+
+    /// Whether the frame is too dark, too bright or too flat to be a useful thumbnail
+    pub fn is_dull(&self) -> bool {
+        self.mean < MIN_MEAN_LUMA || self.mean > MAX_MEAN_LUMA || self.stddev < MIN_LUMA_STDDEV
+    }
+
+    /// Overall frame appeal combining sharpness and exposure
+    ///
+    /// Well-exposed, detailed frames score highest; dull frames are heavily
+    /// penalized rather than excluded so there is always a best candidate.
+    pub fn score(&self) -> f64 {
+        let exposure = 1.0 - ((self.mean - 128.0).abs() / 128.0).powi(2);
+        let penalty = if self.is_dull() { 0.25 } else { 1.0 };
+        self.sharpness.sqrt() * exposure.max(0.0) * penalty
+    }
+}
+
+/// Variance of the 4-neighbour Laplacian over the interior pixels
+fn laplacian_variance(image: &image::GrayImage) -> f64 {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return 0.0;
+    }
+
+    let at = |x: u32, y: u32| image.get_pixel(x, y).0[0] as f64;
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let laplacian =
+                4.0 * at(x, y) - at(x - 1, y) - at(x + 1, y) - at(x, y - 1) - at(x, y + 1);
+            sum += laplacian;
+            sum_sq += laplacian * laplacian;
+        }
+    }
+
+    let count = ((width - 2) * (height - 2)) as f64;
+    let mean = sum / count;
+    (sum_sq / count - mean * mean).max(0.0)
+}
+
+/// Standard deviation of the noise (or film grain) in a frame, in luma steps
+///
+/// Immerkær's estimator: a mask that cancels smooth image content leaves
+/// mostly noise, which is averaged over flat areas only so detail isn't
+/// mistaken for grain. `None` when the frame has too few flat areas.
+pub fn noise_sigma(image: &image::GrayImage) -> Option<f64> {
+    let (width, height) = image.dimensions();
+    if width < 3 || height < 3 {
+        return None;
+    }
+
+    let at = |x: u32, y: u32| image.get_pixel(x, y).0[0] as f64;
+    let mut sum = 0.0;
+    let mut count = 0u64;
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let gradient =
+                (at(x + 1, y) - at(x - 1, y)).abs() + (at(x, y + 1) - at(x, y - 1)).abs();
+            if gradient > NOISE_EDGE_LIMIT {
+                continue;
+            }
+            let response = 4.0 * at(x, y)
+                - 2.0 * (at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1))
+                + at(x - 1, y - 1)
+                + at(x + 1, y - 1)
+                + at(x - 1, y + 1)
+                + at(x + 1, y + 1);
+            sum += response.abs();
+            count += 1;
+        }
+    }
+
+    let interior = ((width - 2) * (height - 2)) as f64;
+    if (count as f64) < interior * MIN_FLAT_SHARE {
+        return None;
+    }
+    Some((std::f64::consts::PI / 2.0).sqrt() * sum / (6.0 * count as f64))
+}
+
+/// How much stronger luma steps are across 8x8 block edges than inside
+/// blocks; around 1 for clean video, well above for over-compressed video
+///
+/// Only meaningful on frames at their coded size, since scaling moves the
+/// block grid.
+pub fn blockiness(image: &image::GrayImage) -> Option<f64> {
+    let (width, height) = image.dimensions();
+    if width < CODEC_BLOCK_SIZE * 2 || height < CODEC_BLOCK_SIZE * 2 {
+        return None;
+    }
+
+    let at = |x: u32, y: u32| image.get_pixel(x, y).0[0] as f64;
+    let (mut edge_sum, mut edge_count) = (0.0, 0u64);
+    let (mut inner_sum, mut inner_count) = (0.0, 0u64);
+    for y in 0..height {
+        for x in 1..width {
+            let step = (at(x, y) - at(x - 1, y)).abs();
+            if x % CODEC_BLOCK_SIZE == 0 {
+                edge_sum += step;
+                edge_count += 1;
+            } else {
+                inner_sum += step;
+                inner_count += 1;
+            }
+        }
+    }
+    for y in 1..height {
+        for x in 0..width {
+            let step = (at(x, y) - at(x, y - 1)).abs();
+            if y % CODEC_BLOCK_SIZE == 0 {
+                edge_sum += step;
+                edge_count += 1;
+            } else {
+                inner_sum += step;
+                inner_count += 1;
+            }
+        }
+    }
+
+    let inner = inner_sum / inner_count.max(1) as f64;
+    if inner <= f64::EPSILON {
+        return None;
+    }
+    Some(edge_sum / edge_count.max(1) as f64 / inner)
+}
+
+/// Share of smooth tiles crossed by a band edge, i.e. a clean step between
+/// two flat plateaus rather than dither or grain; `None` without smooth areas
+///
+/// Undithered gradients in skies and shadows turn into plateaus a level or
+/// two apart, so their tiles hold a single straight contour. Grain makes
+/// values flicker on every other pixel instead.
+pub fn banding(image: &image::GrayImage) -> Option<f64> {
+    let (width, height) = image.dimensions();
+    let tile = BANDING_TILE_SIZE;
+    let (mut smooth, mut contours) = (0u64, 0u64);
+    for top in (0..height.saturating_sub(tile - 1)).step_by(tile as usize) {
+        for left in (0..width.saturating_sub(tile - 1)).step_by(tile as usize) {
+            let at = |x: u32, y: u32| image.get_pixel(left + x, top + y).0[0];
+            let (mut min, mut max) = (u8::MAX, u8::MIN);
+            let mut changes = 0;
+            for y in 0..tile {
+                for x in 0..tile {
+                    let value = at(x, y);
+                    min = min.min(value);
+                    max = max.max(value);
+                    if x > 0 && value != at(x - 1, y) {
+                        changes += 1;
+                    }
+                    if y > 0 && value != at(x, y - 1) {
+                        changes += 1;
+                    }
+                }
+            }
+            if max - min > BANDING_MAX_STEP {
+                continue;
+            }
+            smooth += 1;
+            if max > min && changes <= tile * BANDING_CONTOUR_CROSSINGS {
+                contours += 1;
+            }
+        }
+    }
+    (smooth > 0).then(|| contours as f64 / smooth as f64)
+}
@@ -0,0 +1,127 @@
+use crate::tags::tag_value;
+
+/// Lossy codecs that add encoder priming and padding, and so need gapless
+/// metadata to play back sample-accurately
+const PRIMING_CODECS: [&str; 5] = ["aac", "mp3", "opus", "vorbis", "ac3"];
+
+/// Sample counts may be off by up to one codec frame due to rounding
+const SAMPLE_TOLERANCE: f64 = 2048.0;
+
+/// Whether the gapless information is usable
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GaplessStatus {
+    /// Encoder delay and the end trim are both known and agree with the stream
+    Complete,
+    /// Only the encoder delay is known, so the end of the track will have
+    /// padding silence
+    DelayOnly,
+    /// Values are present but contradict the stream duration
+    Inconsistent,
+    /// A codec that needs gapless metadata, but none was found
+    Missing,
+}
+
+/// Encoder delay and padding for one audio stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct GaplessInfo {
+    /// Where the values came from: "iTunSMPB", "opus_pre_skip" or
+    /// "initial_padding"
+    source: Option<String>,
+    encoder_delay: Option<u64>,
+    padding: Option<u64>,
+    /// Number of real samples, excluding delay and padding
+    valid_samples: Option<u64>,
+    status: GaplessStatus,
+}
+
+/// Read gapless information from an ffprobe audio stream
+///
+/// `format_tags` should only be passed for the main audio track, since
+/// container-level iTunSMPB describes that one. Returns `None` for codecs that
+/// don't need gapless metadata, like PCM and FLAC.
+pub fn gapless_info(
+    stream: &serde_json::Value,
+    format_tags: &serde_json::Value,
+) -> Option<GaplessInfo> {
+    let codec_name = stream["codec_name"].as_str().unwrap_or_default();
+    let initial_padding = stream["initial_padding"]
+        .as_u64()
+        .filter(|&padding| padding > 0);
+
+    let itunsmpb = tag_value(&stream["tags"], "iTunSMPB")
+        .or_else(|| tag_value(format_tags, "iTunSMPB"))
+        .and_then(parse_itunsmpb);
+
+    if let Some((delay, padding, valid_samples)) = itunsmpb {
+        let consistent = stream_samples(stream).is_none_or(|samples| {
+            ((delay + valid_samples + padding) as f64 - samples).abs() <= SAMPLE_TOLERANCE
+                || (valid_samples as f64 - samples).abs() <= SAMPLE_TOLERANCE
+        });
+        return Some(GaplessInfo {
+            source: Some("iTunSMPB".to_string()),
+            encoder_delay: Some(delay),
+            padding: Some(padding),
+            valid_samples: Some(valid_samples),
+            status: if consistent {
+                GaplessStatus::Complete
+            } else {
+                GaplessStatus::Inconsistent
+            },
+        });
+    }
+
+    if codec_name == "opus" {
+        // Opus carries its pre-skip in the header and trims the end using the
+        // container's final granule position, so pre-skip alone is enough
+        return Some(GaplessInfo {
+            source: initial_padding.map(|_| "opus_pre_skip".to_string()),
+            encoder_delay: initial_padding,
+            padding: None,
+            valid_samples: None,
+            status: if initial_padding.is_some() {
+                GaplessStatus::Complete
+            } else {
+                GaplessStatus::Missing
+            },
+        });
+    }
+
+    if let Some(delay) = initial_padding {
+        return Some(GaplessInfo {
+            source: Some("initial_padding".to_string()),
+            encoder_delay: Some(delay),
+            padding: None,
+            valid_samples: None,
+            status: GaplessStatus::DelayOnly,
+        });
+    }
+
+    PRIMING_CODECS.contains(&codec_name).then_some(GaplessInfo {
+        source: None,
+        encoder_delay: None,
+        padding: None,
+        valid_samples: None,
+        status: GaplessStatus::Missing,
+    })
+}
+
+/// Decode an iTunSMPB tag into (encoder delay, padding, valid samples)
+///
+/// The tag is a list of hex words: a reserved word, the delay, the padding and
+/// the original sample count, followed by fields players ignore.
+fn parse_itunsmpb(value: &str) -> Option<(u64, u64, u64)> {
+    let words: Vec<u64> = value
+        .split_whitespace()
+        .map(|word| u64::from_str_radix(word, 16).ok())
+        .collect::<Option<_>>()?;
+    let (delay, padding, valid_samples) = (*words.get(1)?, *words.get(2)?, *words.get(3)?);
+    (valid_samples > 0).then_some((delay, padding, valid_samples))
+}
+
+/// Total samples in the stream according to its duration
+fn stream_samples(stream: &serde_json::Value) -> Option<f64> {
+    let sample_rate: f64 = stream["sample_rate"].as_str()?.parse().ok()?;
+    let duration: f64 = stream["duration"].as_str()?.parse().ok()?;
+    Some(duration * sample_rate)
+}
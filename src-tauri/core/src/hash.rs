@@ -0,0 +1,128 @@
+use sha2::{Digest, Sha256};
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::Error;
+
+/// Size of each read while hashing
+const HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Minimum time between two progress reports
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes read from the start, middle and end of the file by the quick hash
+const QUICK_HASH_SAMPLE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Which hash a hash value is; values of different kinds never match
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HashKind {
+    /// SHA-256 of the whole file
+    Sha256,
+    /// SHA-256 of the file size and samples of its start, middle and end
+    Quick,
+}
+
+/// Hashing progress snapshot
+#[derive(serde::Serialize, Clone, Copy, Debug)]
+pub struct HashProgress {
+    pub bytes_processed: u64,
+    pub total_bytes: u64,
+    /// Average read+hash speed so far, in bytes per second
+    pub throughput_bps: f64,
+}
+
+/// Calculate SHA256 hash of the file
+///
+/// The file is read in chunks; `on_progress` is called periodically and once
+/// more when hashing completes. Setting `cancelled` stops hashing at the next
+/// chunk with an `Interrupted` error.
+pub fn calculate_file_hash(
+    path: &str,
+    cancelled: &AtomicBool,
+    on_progress: impl FnMut(HashProgress),
+) -> Result<String, Error> {
+    calculate_file_hash_with_chunk_size(path, HASH_CHUNK_SIZE, cancelled, on_progress)
+}
+
+/// Hash the file size and samples from the start, middle and end of the file
+///
+/// Reads the same few megabytes whatever the file size, so large files on
+/// slow disks are identified in a blink. Small files are hashed whole.
+pub fn calculate_quick_hash(path: &str) -> Result<String, Error> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let samples = if size <= QUICK_HASH_SAMPLE_SIZE * 3 {
+        vec![(0, size)]
+    } else {
+        vec![
+            (0, QUICK_HASH_SAMPLE_SIZE),
+            (
+                size / 2 - QUICK_HASH_SAMPLE_SIZE / 2,
+                QUICK_HASH_SAMPLE_SIZE,
+            ),
+            (size - QUICK_HASH_SAMPLE_SIZE, QUICK_HASH_SAMPLE_SIZE),
+        ]
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    let mut buffer = Vec::new();
+    for (offset, length) in samples {
+        file.seek(SeekFrom::Start(offset))?;
+        buffer.clear();
+        file.by_ref().take(length).read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Calculate SHA256 hash of the file, reading `chunk_size` bytes at a time
+///
+/// Batch scans pick the chunk size from the measured disk speed.
+pub fn calculate_file_hash_with_chunk_size(
+    path: &str,
+    chunk_size: usize,
+    cancelled: &AtomicBool,
+    mut on_progress: impl FnMut(HashProgress),
+) -> Result<String, Error> {
+    let mut file = File::open(path)?;
+    let total_bytes = file.metadata()?.len();
+
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; chunk_size.max(1)];
+    let mut bytes_processed = 0u64;
+    let start = Instant::now();
+    let mut last_report = start;
+
+    let progress = |bytes_processed: u64| HashProgress {
+        bytes_processed,
+        total_bytes,
+        throughput_bps: bytes_processed as f64 / start.elapsed().as_secs_f64().max(1e-3),
+    };
+
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "Hashing cancelled").into());
+        }
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes_processed += read as u64;
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            on_progress(progress(bytes_processed));
+        }
+    }
+    on_progress(progress(bytes_processed));
+
+    let result = hasher.finalize();
+    Ok(format!("{:x}", result))
+}
@@ -0,0 +1,155 @@
+use crate::probe::run_ffprobe_json;
+use crate::runner::MediaToolRunner;
+use crate::Error;
+
+/// Transfer characteristics that indicate HDR
+const HDR_TRANSFERS: [&str; 2] = ["smpte2084", "arib-std-b67"];
+
+const MASTERING_DISPLAY_SIDE_DATA: &str = "Mastering display metadata";
+const CONTENT_LIGHT_LEVEL_SIDE_DATA: &str = "Content light level metadata";
+
+/// SMPTE ST 2086 mastering display color volume
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct MasteringDisplay {
+    /// CIE 1931 xy chromaticity of each primary and the white point
+    red: [f64; 2],
+    green: [f64; 2],
+    blue: [f64; 2],
+    white_point: [f64; 2],
+    /// Luminance in cd/m²
+    min_luminance: f64,
+    max_luminance: f64,
+}
+
+/// CTA-861.3 content light level, in cd/m²
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ContentLightLevel {
+    max_cll: u64,
+    max_fall: u64,
+}
+
+/// HDR signalling and HDR10 static metadata of a video stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct HdrInfo {
+    color_transfer: Option<String>,
+    color_primaries: Option<String>,
+    color_space: Option<String>,
+    mastering_display: Option<MasteringDisplay>,
+    content_light_level: Option<ContentLightLevel>,
+}
+
+impl HdrInfo {
+    /// Read HDR information from an ffprobe stream
+    ///
+    /// Returns `None` for SDR streams without any HDR side data.
+    pub fn from_stream(stream: &serde_json::Value) -> Option<Self> {
+        let color_transfer = stream["color_transfer"].as_str().map(str::to_string);
+        let side_data = &stream["side_data_list"];
+        let info = HdrInfo {
+            color_primaries: stream["color_primaries"].as_str().map(str::to_string),
+            color_space: stream["color_space"].as_str().map(str::to_string),
+            mastering_display: find_side_data(side_data, MASTERING_DISPLAY_SIDE_DATA)
+                .and_then(parse_mastering_display),
+            content_light_level: find_side_data(side_data, CONTENT_LIGHT_LEVEL_SIDE_DATA)
+                .and_then(parse_content_light_level),
+            color_transfer,
+        };
+
+        let is_hdr = info
+            .color_transfer
+            .as_deref()
+            .is_some_and(|transfer| HDR_TRANSFERS.contains(&transfer));
+        (is_hdr || info.has_static_metadata()).then_some(info)
+    }
+
+    fn has_static_metadata(&self) -> bool {
+        self.mastering_display.is_some() || self.content_light_level.is_some()
+    }
+
+    /// Whether the stream uses the PQ curve but the static metadata wasn't in
+    /// the stream parameters
+    pub fn needs_frame_metadata(&self) -> bool {
+        self.color_transfer.as_deref() == Some("smpte2084") && !self.has_static_metadata()
+    }
+
+    /// Fill in static metadata carried in the first frame's side data
+    ///
+    /// HEVC in MPEG-TS and raw bitstreams only signal it in SEI messages, which
+    /// ffprobe reports per frame rather than per stream.
+    pub async fn read_frame_metadata(
+        &mut self,
+        runner: &dyn MediaToolRunner,
+        path: &str,
+        stream_index: u64,
+    ) -> Result<(), Error> {
+        let json = run_ffprobe_json(
+            runner,
+            path,
+            &[
+                "-select_streams",
+                &stream_index.to_string(),
+                "-read_intervals",
+                "%+#1",
+                "-show_frames",
+                "-show_entries",
+                "frame=side_data_list",
+            ],
+        )
+        .await?;
+
+        let side_data = &json["frames"][0]["side_data_list"];
+        if self.mastering_display.is_none() {
+            self.mastering_display = find_side_data(side_data, MASTERING_DISPLAY_SIDE_DATA)
+                .and_then(parse_mastering_display);
+        }
+        if self.content_light_level.is_none() {
+            self.content_light_level = find_side_data(side_data, CONTENT_LIGHT_LEVEL_SIDE_DATA)
+                .and_then(parse_content_light_level);
+        }
+        Ok(())
+    }
+}
+
+fn find_side_data<'a>(
+    side_data: &'a serde_json::Value,
+    side_data_type: &str,
+) -> Option<&'a serde_json::Value> {
+    side_data
+        .as_array()?
+        .iter()
+        .find(|entry| entry["side_data_type"].as_str() == Some(side_data_type))
+}
+
+fn parse_mastering_display(entry: &serde_json::Value) -> Option<MasteringDisplay> {
+    let xy = |x: &str, y: &str| Some([rational(&entry[x])?, rational(&entry[y])?]);
+    Some(MasteringDisplay {
+        red: xy("red_x", "red_y")?,
+        green: xy("green_x", "green_y")?,
+        blue: xy("blue_x", "blue_y")?,
+        white_point: xy("white_point_x", "white_point_y")?,
+        min_luminance: rational(&entry["min_luminance"])?,
+        max_luminance: rational(&entry["max_luminance"])?,
+    })
+}
+
+fn parse_content_light_level(entry: &serde_json::Value) -> Option<ContentLightLevel> {
+    Some(ContentLightLevel {
+        max_cll: entry["max_content"].as_u64()?,
+        max_fall: entry["max_average"].as_u64()?,
+    })
+}
+
+/// Parse a value ffprobe prints as a "num/den" string or a plain number
+fn rational(value: &serde_json::Value) -> Option<f64> {
+    if let Some(number) = value.as_f64() {
+        return Some(number);
+    }
+    let text = value.as_str()?;
+    match text.split_once('/') {
+        Some((num, den)) => {
+            let den: f64 = den.parse().ok()?;
+            (den != 0.0).then_some(num.parse::<f64>().ok()? / den)
+        }
+        None => text.parse().ok(),
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::Error;
+
+/// Logical sector size of ISO 9660 and UDF images
+pub const SECTOR_SIZE: u64 = 2048;
+
+/// Volume descriptors start after the 32 KiB system area
+const DESCRIPTORS_START: u64 = 16;
+
+/// Descriptors looked at before giving up
+const MAX_DESCRIPTORS: u64 = 32;
+
+/// File systems found in a disc image
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct ImageInfo {
+    /// ISO 9660, which DVDs carry next to UDF
+    pub iso9660: bool,
+    /// UDF, the only file system of most Blu-rays
+    pub udf: bool,
+    pub volume_id: Option<String>,
+}
+
+/// A file or folder of an ISO 9660 directory
+#[derive(Clone, Debug)]
+pub struct IsoEntry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Byte offset of the data in the image
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Read the volume descriptors to tell which file systems an image has
+pub fn detect(path: &Path) -> Result<ImageInfo, Error> {
+    let mut file = File::open(path)?;
+    let mut info = ImageInfo::default();
+    let mut sector = [0u8; SECTOR_SIZE as usize];
+    for index in DESCRIPTORS_START..DESCRIPTORS_START + MAX_DESCRIPTORS {
+        file.seek(SeekFrom::Start(index * SECTOR_SIZE))?;
+        if file.read_exact(&mut sector).is_err() {
+            break;
+        }
+        match &sector[1..6] {
+            b"CD001" if sector[0] == 1 => {
+                info.iso9660 = true;
+                info.volume_id = Some(
+                    String::from_utf8_lossy(&sector[40..72])
+                        .trim_end()
+                        .to_string(),
+                )
+                .filter(|id| !id.is_empty());
+            }
+            // Terminator of the ISO 9660 descriptors; UDF ones may follow
+            b"CD001" if sector[0] == 255 => {}
+            b"NSR02" | b"NSR03" => info.udf = true,
+            b"BEA01" | b"TEA01" | b"BOOT2" | b"CDW02" => {}
+            _ => break,
+        }
+    }
+    Ok(info)
+}
+
+/// List the ISO 9660 directory at `dir_path` (e.g. `["VIDEO_TS"]`)
+///
+/// Names are matched ignoring case and returned without the `;1` version.
+pub fn list_dir(path: &Path, dir_path: &[&str]) -> Result<Vec<IsoEntry>, Error> {
+    let mut file = File::open(path)?;
+    let mut descriptor = [0u8; SECTOR_SIZE as usize];
+    file.seek(SeekFrom::Start(DESCRIPTORS_START * SECTOR_SIZE))?;
+    file.read_exact(&mut descriptor)?;
+    if &descriptor[1..6] != b"CD001" || descriptor[0] != 1 {
+        return Err(Error::ParseError("No ISO 9660 file system".to_string()));
+    }
+
+    // The root directory record is embedded in the primary volume descriptor
+    let mut dir = parse_record(&descriptor[156..190])
+        .ok_or_else(|| Error::ParseError("Invalid ISO 9660 root directory".to_string()))?;
+    for name in dir_path {
+        dir = read_dir(&mut file, &dir)?
+            .into_iter()
+            .find(|entry| entry.is_dir && entry.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::NotMediaFile(format!("No {} folder in the image", name)))?;
+    }
+    read_dir(&mut file, &dir)
+}
+
+fn read_dir(file: &mut File, dir: &IsoEntry) -> Result<Vec<IsoEntry>, Error> {
+    let mut data = vec![0u8; dir.size as usize];
+    file.seek(SeekFrom::Start(dir.offset))?;
+    file.read_exact(&mut data)?;
+
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let length = data[offset] as usize;
+        if length == 0 {
+            // Records don't cross sectors; the rest of this one is padding
+            offset = (offset / SECTOR_SIZE as usize + 1) * SECTOR_SIZE as usize;
+            continue;
+        }
+        let Some(record) = data.get(offset..offset + length) else {
+            break;
+        };
+        // Skip the "." and ".." entries, named \0 and \1
+        if let Some(entry) =
+            parse_record(record).filter(|entry| !entry.name.starts_with(['\0', '\u{1}']))
+        {
+            entries.push(entry);
+        }
+        offset += length;
+    }
+    Ok(entries)
+}
+
+/// Parse a directory record; multi-byte fields are stored both-endian
+fn parse_record(record: &[u8]) -> Option<IsoEntry> {
+    let extent = u32::from_le_bytes(record.get(2..6)?.try_into().ok()?) as u64;
+    let size = u32::from_le_bytes(record.get(10..14)?.try_into().ok()?) as u64;
+    let flags = *record.get(25)?;
+    let name_length = *record.get(32)? as usize;
+    let name = record.get(33..33 + name_length)?;
+    let name = String::from_utf8_lossy(name);
+    let name = name.split(';').next().unwrap_or_default();
+    Some(IsoEntry {
+        name: name.trim_end_matches('.').to_string(),
+        is_dir: flags & 0x02 != 0,
+        offset: extent * SECTOR_SIZE,
+        size,
+    })
+}
@@ -0,0 +1,126 @@
+use fluent_bundle::FluentArgs;
+
+use crate::locale::tr;
+
+/// Which kinds of streams a file carries
+///
+/// Timelapses without audio and music files without video are normal inputs,
+/// so a missing kind is reported here rather than as an error or as fields
+/// left blank.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct StreamLayout {
+    /// Video streams, not counting attached pictures
+    video_streams: usize,
+    audio_streams: usize,
+    subtitle_streams: usize,
+    /// Cover art and other pictures stored as video streams
+    attached_pictures: usize,
+    /// Data, attachment and unknown streams
+    other_streams: usize,
+    /// Facts to show in place of empty fields, e.g. "No audio stream"
+    notes: Vec<String>,
+}
+
+impl StreamLayout {
+    pub fn has_video(&self) -> bool {
+        self.video_streams > 0
+    }
+
+    pub fn has_audio(&self) -> bool {
+        self.audio_streams > 0
+    }
+
+    pub fn has_attached_pictures(&self) -> bool {
+        self.attached_pictures > 0
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+}
+
+/// Count the streams of an ffprobe output by kind
+pub fn stream_layout(json: &serde_json::Value) -> StreamLayout {
+    let mut layout = StreamLayout::default();
+    for stream in json["streams"].as_array().into_iter().flatten() {
+        match stream["codec_type"].as_str() {
+            Some("video") if is_attached_picture(stream) => layout.attached_pictures += 1,
+            Some("video") => layout.video_streams += 1,
+            Some("audio") => layout.audio_streams += 1,
+            Some("subtitle") => layout.subtitle_streams += 1,
+            _ => layout.other_streams += 1,
+        }
+    }
+
+    let no_args = FluentArgs::new();
+    if !layout.has_video() {
+        layout.notes.push(if layout.has_attached_pictures() {
+            tr("note-cover-art-only", &no_args)
+        } else {
+            tr("note-no-video", &no_args)
+        });
+    }
+    if !layout.has_audio() {
+        layout.notes.push(tr("note-no-audio", &no_args));
+    }
+    if layout.video_streams > 1 {
+        let mut args = FluentArgs::new();
+        args.set("count", layout.video_streams);
+        layout.notes.push(tr("note-several-videos", &args));
+    }
+    layout
+}
+
+/// The video stream the metadata describes
+///
+/// Cover art may come before the real video, and some muxers put the
+/// default track after others, so the first default stream that isn't an
+/// attached picture is preferred, then the first that isn't one. `None`
+/// for audio-only files.
+pub fn main_video_stream(json: &serde_json::Value) -> Option<&serde_json::Value> {
+    let mut videos = json["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|stream| {
+            stream["codec_type"].as_str() == Some("video") && !is_attached_picture(stream)
+        })
+        .peekable();
+    let first = videos.peek().copied();
+    videos
+        .find(|stream| stream["disposition"]["default"].as_u64() == Some(1))
+        .or(first)
+}
+
+fn is_attached_picture(stream: &serde_json::Value) -> bool {
+    stream["disposition"]["attached_pic"].as_u64() == Some(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_missing_and_extra_streams() {
+        let probe = serde_json::json!({"streams": [
+            {"codec_type": "video", "disposition": {"attached_pic": 1}},
+            {"codec_type": "audio"},
+        ]});
+        assert_eq!(
+            stream_layout(&probe).notes(),
+            ["No video stream, only cover art"]
+        );
+
+        let probe = serde_json::json!({"streams": [
+            {"codec_type": "video"},
+            {"codec_type": "video"},
+        ]});
+        assert_eq!(
+            stream_layout(&probe).notes(),
+            [
+                "No audio stream",
+                "2 video streams, the first marked default is described"
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+// Inspection logic with no Tauri dependency, shared by the desktop app and
+// usable from tests and other tools; ffprobe and ffmpeg are run through
+// `runner::MediaToolRunner`, which the app implements with its sidecars
+pub mod alpha;
+pub mod audio;
+pub mod av1;
+pub mod bitrate;
+pub mod codec;
+pub mod container;
+pub mod dolby_vision;
+pub mod duration;
+pub mod frame_content;
+pub mod frame_stats;
+pub mod gapless;
+pub mod hash;
+pub mod hdr;
+pub mod iso9660;
+pub mod layout;
+pub mod locale;
+pub mod loudness;
+pub mod motion_photo;
+pub mod mp4;
+pub mod mpegts;
+pub mod mpls;
+pub mod offsets;
+pub mod probe;
+pub mod programs;
+pub mod rar;
+pub mod runner;
+pub mod scene;
+pub mod sniff;
+pub mod stereo3d;
+pub mod tags;
+pub mod thumbnail;
+pub mod video;
+
+use thiserror::Error;
+
+/// Errors from the core; the app wraps them in its own error type
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    #[error("Failed to execute ffmpeg: {0}")]
+    FFmpegError(String),
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Parse error: {0}")]
+    ParseError(String),
+    #[error("Not a media file: {0}")]
+    NotMediaFile(String),
+}
@@ -0,0 +1,84 @@
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use std::sync::{OnceLock, RwLock};
+use unic_langid::LanguageIdentifier;
+
+/// Locale used when the requested one has no catalog
+const DEFAULT_LOCALE: &str = "en";
+
+/// Message catalogs compiled into the binary, matching the frontend languages
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.ftl")),
+    ("zh", include_str!("../locales/zh.ftl")),
+];
+
+/// Locale chosen by the app
+static LOCALE: RwLock<&'static str> = RwLock::new(DEFAULT_LOCALE);
+
+static BUNDLES: OnceLock<Vec<(&'static str, FluentBundle<FluentResource>)>> = OnceLock::new();
+
+/// Select the language of user-facing messages, returning the locale used
+///
+/// Accepts language tags like "zh-CN"; unsupported languages fall back to
+/// English.
+pub fn select_locale(locale: &str) -> &'static str {
+    let primary = locale.split(['-', '_']).next().unwrap_or_default();
+    let selected = CATALOGS
+        .iter()
+        .map(|(id, _)| *id)
+        .find(|id| id.eq_ignore_ascii_case(primary))
+        .unwrap_or(DEFAULT_LOCALE);
+
+    *LOCALE.write().unwrap_or_else(|e| e.into_inner()) = selected;
+    selected
+}
+
+/// Locale messages are currently formatted in, e.g. "zh"
+pub fn current_locale() -> &'static str {
+    *LOCALE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Format a message from the current locale's catalog
+///
+/// Falls back to English, then to the message id, so a missing translation
+/// never hides an error.
+pub fn tr(id: &str, args: &FluentArgs) -> String {
+    format_message(current_locale(), id, args)
+        .or_else(|| format_message(DEFAULT_LOCALE, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn format_message(locale: &str, id: &str, args: &FluentArgs) -> Option<String> {
+    let (_, bundle) = bundles().iter().find(|(name, _)| *name == locale)?;
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let message = bundle.format_pattern(pattern, Some(args), &mut errors);
+    if !errors.is_empty() {
+        tracing::warn!(locale, id, errors = ?errors, "Failed to format message");
+    }
+    Some(message.into_owned())
+}
+
+fn bundles() -> &'static [(&'static str, FluentBundle<FluentResource>)] {
+    BUNDLES.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .filter_map(|(locale, source)| {
+                let bundle = build_bundle(locale, source);
+                if bundle.is_none() {
+                    tracing::error!(locale, "Invalid message catalog");
+                }
+                Some((*locale, bundle?))
+            })
+            .collect()
+    })
+}
+
+fn build_bundle(locale: &str, source: &str) -> Option<FluentBundle<FluentResource>> {
+    let language: LanguageIdentifier = locale.parse().ok()?;
+    let resource = FluentResource::try_new(source.to_string()).ok()?;
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Unicode isolation marks show up as boxes in some dialogs
+    bundle.set_use_isolating(false);
+    bundle.add_resource(resource).ok()?;
+    Some(bundle)
+}
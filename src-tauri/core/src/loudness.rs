@@ -0,0 +1,136 @@
+use crate::tags::tag_value;
+
+/// ReplayGain 2.0 reference loudness
+const REPLAYGAIN_REFERENCE_LUFS: f64 = -18.0;
+
+/// EBU R128 reference loudness used by Opus `R128_*` gain tags
+const R128_REFERENCE_LUFS: f64 = -23.0;
+
+/// Loudness Apple Sound Check normalizes to
+const SOUND_CHECK_REFERENCE_LUFS: f64 = -16.0;
+
+/// Loudness information claimed by tags in the stream or container
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct LoudnessTags {
+    replaygain_track_gain_db: Option<f64>,
+    replaygain_track_peak: Option<f64>,
+    replaygain_album_gain_db: Option<f64>,
+    replaygain_album_peak: Option<f64>,
+    /// Opus output gain relative to -23 LUFS, in dB
+    r128_track_gain_db: Option<f64>,
+    /// Apple Sound Check adjustment decoded from iTunNORM, in dB
+    itunnorm_gain_db: Option<f64>,
+}
+
+impl LoudnessTags {
+    /// Read loudness tags from a stream, falling back to container tags
+    pub fn from_tags(
+        stream_tags: &serde_json::Value,
+        format_tags: &serde_json::Value,
+    ) -> Option<Self> {
+        let lookup =
+            |key: &str| tag_value(stream_tags, key).or_else(|| tag_value(format_tags, key));
+
+        let tags = LoudnessTags {
+            replaygain_track_gain_db: lookup("REPLAYGAIN_TRACK_GAIN").and_then(parse_db),
+            replaygain_track_peak: lookup("REPLAYGAIN_TRACK_PEAK")
+                .and_then(|v| v.trim().parse().ok()),
+            replaygain_album_gain_db: lookup("REPLAYGAIN_ALBUM_GAIN").and_then(parse_db),
+            replaygain_album_peak: lookup("REPLAYGAIN_ALBUM_PEAK")
+                .and_then(|v| v.trim().parse().ok()),
+            // Stored as a Q7.8 fixed point integer
+            r128_track_gain_db: lookup("R128_TRACK_GAIN")
+                .and_then(|v| v.trim().parse::<i32>().ok())
+                .map(|q78| q78 as f64 / 256.0),
+            itunnorm_gain_db: lookup("iTunNORM").and_then(parse_itunnorm),
+        };
+
+        tags.has_any().then_some(tags)
+    }
+
+    fn has_any(&self) -> bool {
+        self.replaygain_track_gain_db.is_some()
+            || self.replaygain_track_peak.is_some()
+            || self.replaygain_album_gain_db.is_some()
+            || self.replaygain_album_peak.is_some()
+            || self.r128_track_gain_db.is_some()
+            || self.itunnorm_gain_db.is_some()
+    }
+
+    /// Integrated loudness implied by the tags, in LUFS
+    ///
+    /// A gain tag says how much to adjust playback to hit the reference, so the
+    /// original loudness is the reference minus that gain. ReplayGain is
+    /// preferred, then the Opus gain, then Sound Check.
+    pub fn claimed_lufs(&self) -> Option<f64> {
+        self.replaygain_track_gain_db
+            .map(|gain| REPLAYGAIN_REFERENCE_LUFS - gain)
+            .or_else(|| {
+                self.r128_track_gain_db
+                    .map(|gain| R128_REFERENCE_LUFS - gain)
+            })
+            .or_else(|| {
+                self.itunnorm_gain_db
+                    .map(|gain| SOUND_CHECK_REFERENCE_LUFS - gain)
+            })
+    }
+}
+
+/// Parse gain values like "-6.54 dB"
+fn parse_db(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .trim_end_matches("dB")
+        .trim_end_matches("db")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Decode the Sound Check gain from an iTunNORM tag
+///
+/// The first two hex words are the left/right adjustment relative to a
+/// 1/1000 W reference; the louder channel determines the applied gain.
+fn parse_itunnorm(value: &str) -> Option<f64> {
+    let words: Vec<u32> = value
+        .split_whitespace()
+        .filter_map(|word| u32::from_str_radix(word, 16).ok())
+        .collect();
+    let loudest = (*words.first()?).max(*words.get(1)?);
+    if loudest == 0 {
+        return None;
+    }
+    Some(-10.0 * (loudest as f64 / 1000.0).log10())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_loudness_from_sound_check_alone() {
+        let tags = LoudnessTags::from_tags(
+            &serde_json::json!({"iTunNORM": " 00000FA0 00000FA0 00000000 00000000"}),
+            &serde_json::Value::Null,
+        )
+        .unwrap();
+
+        // 0xFA0 is 4000, a gain of -6.02 dB for a file at -9.98 LUFS
+        let claimed = tags.claimed_lufs().unwrap();
+        assert!((claimed + 9.98).abs() < 0.01, "{claimed}");
+    }
+
+    #[test]
+    fn prefers_replaygain_over_sound_check() {
+        let tags = LoudnessTags::from_tags(
+            &serde_json::json!({
+                "REPLAYGAIN_TRACK_GAIN": "-4.00 dB",
+                "iTunNORM": "00000FA0 00000FA0",
+            }),
+            &serde_json::Value::Null,
+        )
+        .unwrap();
+
+        assert_eq!(tags.claimed_lufs(), Some(-14.0));
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::Error;
+
+/// Photos larger than this aren't read; real motion photos are a few MB
+const MAX_PHOTO_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Start of the Apple maker note in the EXIF data of iPhone photos
+const APPLE_MAKER_NOTE: &[u8] = b"Apple iOS\0";
+
+/// Maker note tag of the identifier shared by the halves of a Live Photo
+const CONTENT_IDENTIFIER_TAG: u16 = 0x0011;
+
+/// Samsung writes the embedded video right after this marker
+const SAMSUNG_MARKER: &[u8] = b"MotionPhoto_Data";
+
+/// Major brands of an embedded MP4 or QuickTime video
+const VIDEO_BRANDS: [&[u8; 4]; 9] = [
+    b"isom", b"iso2", b"iso4", b"iso5", b"iso6", b"mp41", b"mp42", b"avc1", b"qt  ",
+];
+
+/// Major brands of HEIF still images
+const HEIF_BRANDS: [&[u8; 4]; 6] = [b"heic", b"heix", b"hevc", b"heim", b"mif1", b"msf1"];
+
+/// A video embedded at the end of a still photo
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MotionPhoto {
+    /// "jpeg" or "heic"
+    pub image_format: String,
+    /// How the video was found: "xmp_container" for Google motion photos,
+    /// "micro_video" for older Google ones, "samsung" for the Samsung
+    /// trailer, or "scan" when a video was found without any metadata
+    pub source: String,
+    /// Byte offset of the video in the file
+    pub video_offset: u64,
+    pub video_length: u64,
+    /// Where the still sits in the video, in microseconds, when recorded
+    pub presentation_timestamp_us: Option<i64>,
+}
+
+/// Find the video embedded in a JPEG or HEIC motion photo
+///
+/// `None` for files that aren't still photos or carry no video.
+pub fn find_motion_photo(path: &str) -> Result<Option<MotionPhoto>, Error> {
+    Ok(read_photo(path)?.and_then(|(data, image_format)| locate_video(&data, image_format)))
+}
+
+/// Apple's `ContentIdentifier` of a HEIC or JPEG still, which the video of
+/// the same Live Photo repeats in its QuickTime metadata
+///
+/// Read from the Apple maker note, or from XMP for edited exports. `None`
+/// for files that aren't still photos or don't carry one.
+pub fn apple_content_identifier(path: &str) -> Result<Option<String>, Error> {
+    Ok(read_photo(path)?.and_then(|(data, _)| {
+        maker_note_content_identifier(&data)
+            .or_else(|| find_xmp(&data).and_then(xmp_content_identifier))
+    }))
+}
+
+/// Contents and format of a still photo; `None` without reading the rest of
+/// the file when its magic bytes aren't a JPEG or HEIC, or it's too large to
+/// be a photo
+fn read_photo(path: &str) -> Result<Option<(Vec<u8>, &'static str)>, Error> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() > MAX_PHOTO_BYTES {
+        return Ok(None);
+    }
+    let mut header = Vec::with_capacity(12);
+    file.by_ref().take(12).read_to_end(&mut header)?;
+    let Some(image_format) = image_format(&header) else {
+        return Ok(None);
+    };
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(Some((data, image_format)))
+}
+
+/// The embedded video of a photo's contents, trying the metadata written by
+/// each kind of camera before scanning
+fn locate_video(data: &[u8], image_format: &str) -> Option<MotionPhoto> {
+    let xmp = find_xmp(data);
+    let presentation_timestamp_us = xmp.and_then(|xmp| {
+        [
+            "GCamera:MotionPhotoPresentationTimestampUs",
+            "GCamera:MicroVideoPresentationTimestampUs",
+        ]
+        .iter()
+        .find_map(|name| xmp_value(xmp, name)?.parse().ok())
+    });
+
+    // Tried in order and only as far as needed, the scan being the slowest
+    let candidates: [(&str, &dyn Fn() -> Option<usize>); 4] = [
+        ("xmp_container", &|| {
+            container_video_offset(xmp?, data.len())
+        }),
+        ("micro_video", &|| {
+            xmp_value(xmp?, "GCamera:MicroVideoOffset")?
+                .parse::<usize>()
+                .ok()
+                .and_then(|offset| data.len().checked_sub(offset))
+        }),
+        ("samsung", &|| {
+            rfind(data, SAMSUNG_MARKER).map(|marker| marker + SAMSUNG_MARKER.len())
+        }),
+        ("scan", &|| scan_for_video(data)),
+    ];
+    let (source, offset) = candidates.iter().find_map(|(source, offset)| {
+        offset()
+            .filter(|&offset| is_video_start(data, offset))
+            .map(|offset| (*source, offset))
+    })?;
+
+    Some(MotionPhoto {
+        image_format: image_format.to_string(),
+        source: source.to_string(),
+        video_offset: offset as u64,
+        video_length: (data.len() - offset) as u64,
+        presentation_timestamp_us,
+    })
+}
+
+/// "jpeg" or "heic" from the magic bytes, `None` for anything else
+fn image_format(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("jpeg");
+    }
+    let brand = data.get(8..12)?;
+    (data.get(4..8) == Some(b"ftyp") && HEIF_BRANDS.iter().any(|heif| brand == *heif))
+        .then_some("heic")
+}
+
+/// The XMP packet, which both JPEG and HEIC store as plain text
+fn find_xmp(data: &[u8]) -> Option<&str> {
+    let start = find(data, b"<x:xmpmeta")?;
+    let end = find(&data[start..], b"</x:xmpmeta>")? + start;
+    std::str::from_utf8(&data[start..end]).ok()
+}
+
+/// Value of an XMP property, written either as `name="value"` or as
+/// `<name>value</name>`
+fn xmp_value<'a>(xmp: &'a str, name: &str) -> Option<&'a str> {
+    let attribute = format!("{}=\"", name);
+    if let Some(start) = xmp.find(&attribute) {
+        let value = &xmp[start + attribute.len()..];
+        return value.split('"').next();
+    }
+    let element = format!("<{}>", name);
+    let start = xmp.find(&element)? + element.len();
+    xmp[start..].split('<').next().map(str::trim)
+}
+
+/// Offset of the motion photo item of a Google `Container:Directory`
+///
+/// Items are appended in order after the primary image and only their
+/// lengths are listed, so the video starts where the lengths of it and the
+/// items after it, counted back from the end, say.
+fn container_video_offset(xmp: &str, file_len: usize) -> Option<usize> {
+    let items: Vec<(&str, usize)> = xmp
+        .split('<')
+        .filter_map(|element| {
+            let element = element.split('>').next()?;
+            let semantic = xmp_value(element, "Item:Semantic")?;
+            let length = xmp_value(element, "Item:Length")
+                .and_then(|length| length.parse().ok())
+                .unwrap_or(0);
+            Some((semantic, length))
+        })
+        .collect();
+    let video = items
+        .iter()
+        .position(|(semantic, _)| *semantic == "MotionPhoto")?;
+    let trailing: usize = items[video..].iter().map(|(_, length)| length).sum();
+    file_len.checked_sub(trailing)
+}
+
+/// `ContentIdentifier` of the Apple maker note
+///
+/// The note starts with "Apple iOS", a version and the byte order, and its
+/// IFD follows at offset 14 with value offsets counted from the note's start.
+fn maker_note_content_identifier(data: &[u8]) -> Option<String> {
+    let note = &data[find(data, APPLE_MAKER_NOTE)?..];
+    let big_endian = match note.get(12..14)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        let bytes: [u8; 2] = note.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| {
+        let bytes: [u8; 4] = note.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let entries = u16_at(14)? as usize;
+    let entry = (0..entries)
+        .map(|index| 16 + index * 12)
+        .find(|&entry| u16_at(entry) == Some(CONTENT_IDENTIFIER_TAG))?;
+    let length = u32_at(entry + 4)? as usize;
+    // Values of up to four bytes are stored in place of the offset
+    let value_offset = if length <= 4 {
+        entry + 8
+    } else {
+        u32_at(entry + 8)? as usize
+    };
+    let value = note.get(value_offset..value_offset.checked_add(length)?)?;
+    let identifier = std::str::from_utf8(value)
+        .ok()?
+        .trim_end_matches('\0')
+        .trim();
+    (!identifier.is_empty()).then(|| identifier.to_string())
+}
+
+/// A `ContentIdentifier` XMP property under any namespace prefix
+fn xmp_content_identifier(xmp: &str) -> Option<String> {
+    let (_, rest) = xmp.split_once(":ContentIdentifier")?;
+    let value = match rest.strip_prefix("=\"") {
+        Some(attribute) => attribute.split('"').next()?,
+        None => rest.strip_prefix('>')?.split('<').next()?,
+    };
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// First `ftyp` box of a video after the image's own header
+fn scan_for_video(data: &[u8]) -> Option<usize> {
+    let mut from = 12;
+    while let Some(found) = find(data.get(from..)?, b"ftyp") {
+        let offset = from + found - 4;
+        if is_video_start(data, offset) {
+            return Some(offset);
+        }
+        from += found + 4;
+    }
+    None
+}
+
+/// Whether an MP4 or QuickTime `ftyp` box with a video brand starts at `offset`
+fn is_video_start(data: &[u8], offset: usize) -> bool {
+    let Some(header) = data.get(offset..offset + 12) else {
+        return false;
+    };
+    let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    offset > 0
+        && &header[4..8] == b"ftyp"
+        && (16..=256).contains(&size)
+        && VIDEO_BRANDS.iter().any(|brand| &header[8..12] == *brand)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+        let mut ftyp = vec![0, 0, 0, 16];
+        ftyp.extend_from_slice(b"ftyp");
+        ftyp.extend_from_slice(brand);
+        ftyp.extend_from_slice(&[0, 0, 0, 0]);
+        ftyp
+    }
+
+    #[test]
+    fn truncated_jpeg_has_no_video() {
+        assert!(locate_video(&[0xFF, 0xD8, 0xFF], "jpeg").is_none());
+        assert!(locate_video(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0], "jpeg").is_none());
+    }
+
+    #[test]
+    fn finds_video_after_samsung_marker() {
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 16, 0, 0, 0, 0, 0, 0, 0xFF, 0xD9];
+        data.extend_from_slice(SAMSUNG_MARKER);
+        let offset = data.len();
+        data.extend(ftyp(b"mp42"));
+        data.extend_from_slice(&[0; 32]);
+
+        let photo = locate_video(&data, "jpeg").unwrap();
+        assert_eq!(photo.source, "samsung");
+        assert_eq!(photo.video_offset, offset as u64);
+        assert_eq!(photo.video_length, (data.len() - offset) as u64);
+    }
+
+    #[test]
+    fn finds_video_from_xmp_container() {
+        let video = [ftyp(b"isom"), vec![0; 24]].concat();
+        let xmp = format!(
+            "<x:xmpmeta><Container:Item Item:Semantic=\"Primary\" Item:Length=\"0\"/>\
+             <Container:Item Item:Semantic=\"MotionPhoto\" Item:Length=\"{}\"/></x:xmpmeta>",
+            video.len()
+        );
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend_from_slice(xmp.as_bytes());
+        let offset = data.len();
+        data.extend(&video);
+
+        let photo = locate_video(&data, "jpeg").unwrap();
+        assert_eq!(photo.source, "xmp_container");
+        assert_eq!(photo.video_offset, offset as u64);
+    }
+
+    #[test]
+    fn reads_content_identifier_from_maker_note() {
+        let identifier = b"2A4C5E1B-0000-4000-8000-000000000001\0";
+        let mut note = APPLE_MAKER_NOTE.to_vec();
+        note.extend_from_slice(&[0, 1]);
+        note.extend_from_slice(b"MM");
+        note.extend_from_slice(&1u16.to_be_bytes());
+        let value_offset = note.len() + 12 + 4;
+        note.extend_from_slice(&CONTENT_IDENTIFIER_TAG.to_be_bytes());
+        note.extend_from_slice(&2u16.to_be_bytes());
+        note.extend_from_slice(&(identifier.len() as u32).to_be_bytes());
+        note.extend_from_slice(&(value_offset as u32).to_be_bytes());
+        note.extend_from_slice(&[0; 4]);
+        note.extend_from_slice(identifier);
+
+        let data = [vec![0xFF, 0xD8, 0xFF, 0xE1, 0, 0], note].concat();
+        assert_eq!(
+            maker_note_content_identifier(&data).as_deref(),
+            Some("2A4C5E1B-0000-4000-8000-000000000001")
+        );
+        assert_eq!(maker_note_content_identifier(&data[..20]), None);
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+};
+
+use crate::Error;
+
+/// Largest `moov` box read into memory; bigger ones are skipped
+const MAX_MOOV_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Size of the fixed fields of a VisualSampleEntry before its child boxes
+const VISUAL_SAMPLE_ENTRY_LEN: usize = 78;
+
+/// One entry of an MP4 edit list
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct EditListEntry {
+    /// Duration of this segment of the presentation, in seconds
+    pub segment_duration: f64,
+    /// Where in the track's media the segment starts, in seconds; `None` for
+    /// an empty edit, which delays the track instead of showing media
+    pub media_time: Option<f64>,
+    pub rate: f64,
+}
+
+/// Track-level information from the `moov` box
+#[derive(Debug, Clone)]
+pub struct Mp4Track {
+    pub track_id: u32,
+    pub edit_list: Vec<EditListEntry>,
+    /// Child boxes of a video track's first sample entry (e.g. `avcC`,
+    /// `dvcC`, `colr`), as (type, body)
+    pub sample_entry_boxes: Vec<(String, Vec<u8>)>,
+}
+
+/// Track ID of an ffprobe stream from an MP4/MOV file
+///
+/// The mov demuxer reports the track ID as the stream id, e.g. "0x1".
+pub fn stream_track_id(stream: &serde_json::Value) -> Option<u32> {
+    let id = stream["id"].as_str()?;
+    u32::from_str_radix(id.trim_start_matches("0x"), 16).ok()
+}
+
+/// Find the track an ffprobe stream came from
+pub fn track_for_stream<'a>(
+    tracks: &'a [Mp4Track],
+    stream: &serde_json::Value,
+) -> Option<&'a Mp4Track> {
+    let track_id = stream_track_id(stream)?;
+    tracks.iter().find(|track| track.track_id == track_id)
+}
+
+/// Read the tracks of an MP4/MOV file
+///
+/// Failures are logged and yield no tracks, since the box data only adds
+/// detail on top of what ffprobe reports.
+pub fn read_tracks(path: &str) -> Vec<Mp4Track> {
+    match read_moov(path) {
+        Ok(Some(moov)) => tracks(&moov),
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            tracing::warn!(video_path = %path, error = %e, "Failed to read moov box");
+            Vec::new()
+        }
+    }
+}
+
+/// Iterate over sibling boxes, yielding (type, body)
+pub fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let header = data.get(pos..pos + 8)?;
+        let kind = &header[4..8];
+        let (size, header_len) = match u32::from_be_bytes(header[0..4].try_into().ok()?) {
+            0 => (data.len() - pos, 8),
+            1 => {
+                let large = u64::from_be_bytes(data.get(pos + 8..pos + 16)?.try_into().ok()?);
+                (usize::try_from(large).ok()?, 16)
+            }
+            size => (size as usize, 8),
+        };
+        if size < header_len {
+            return None;
+        }
+        let body = data.get(pos + header_len..pos.checked_add(size)?)?;
+        pos += size;
+        Some((kind, body))
+    })
+}
+
+/// Find a nested box by its type path, e.g. `[b"mdia", b"mdhd"]`
+pub fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    let (first, rest) = path.split_first()?;
+    let (_, body) = boxes(data).find(|(kind, _)| kind == first)?;
+    if rest.is_empty() {
+        Some(body)
+    } else {
+        find_box(body, rest)
+    }
+}
+
+/// Read the `moov` box body, wherever it is in the file
+///
+/// Returns `None` when the file has no `moov` (fragmented files may still
+/// have one; raw streams don't) or it's implausibly large.
+fn read_moov(path: &str) -> Result<Option<Vec<u8>>, Error> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut pos = 0u64;
+    while pos + 8 <= file_len {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 16];
+        file.read_exact(&mut header[..8])?;
+        let (size, header_len) = match u32::from_be_bytes(header[0..4].try_into().unwrap()) {
+            0 => (file_len - pos, 8),
+            1 => {
+                file.read_exact(&mut header[8..16])?;
+                (u64::from_be_bytes(header[8..16].try_into().unwrap()), 16)
+            }
+            size => (size as u64, 8),
+        };
+        if size < header_len {
+            break;
+        }
+
+        if &header[4..8] == b"moov" {
+            let body_len = size - header_len;
+            if body_len > MAX_MOOV_SIZE {
+                return Ok(None);
+            }
+            let mut body = vec![0u8; body_len as usize];
+            file.read_exact(&mut body)?;
+            return Ok(Some(body));
+        }
+        pos += size;
+    }
+
+    Ok(None)
+}
+
+/// Parse every `trak` in a `moov` body
+pub fn tracks(moov: &[u8]) -> Vec<Mp4Track> {
+    let movie_timescale = find_box(moov, &[b"mvhd"])
+        .and_then(timescale)
+        .unwrap_or(1000);
+
+    boxes(moov)
+        .filter(|(kind, _)| *kind == b"trak")
+        .filter_map(|(_, trak)| {
+            let track_id = track_id(find_box(trak, &[b"tkhd"])?)?;
+            let media_timescale = find_box(trak, &[b"mdia", b"mdhd"])
+                .and_then(timescale)
+                .unwrap_or(movie_timescale);
+            let edit_list = find_box(trak, &[b"edts", b"elst"])
+                .map(|elst| parse_elst(elst, movie_timescale, media_timescale))
+                .unwrap_or_default();
+
+            let handler = find_box(trak, &[b"mdia", b"hdlr"]).and_then(|hdlr| hdlr.get(8..12));
+            let sample_entry_boxes = if handler == Some(b"vide") {
+                video_sample_entry_boxes(trak)
+            } else {
+                Vec::new()
+            };
+
+            Some(Mp4Track {
+                track_id,
+                edit_list,
+                sample_entry_boxes,
+            })
+        })
+        .collect()
+}
+
+/// Child boxes of the first visual sample entry in `stsd`
+fn video_sample_entry_boxes(trak: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let Some(stsd) = find_box(trak, &[b"mdia", b"minf", b"stbl", b"stsd"]) else {
+        return Vec::new();
+    };
+    // Skip version/flags and the entry count
+    let Some((_, entry)) = stsd.get(8..).and_then(|entries| boxes(entries).next()) else {
+        return Vec::new();
+    };
+    let Some(children) = entry.get(VISUAL_SAMPLE_ENTRY_LEN..) else {
+        return Vec::new();
+    };
+
+    boxes(children)
+        .map(|(kind, body)| (String::from_utf8_lossy(kind).to_string(), body.to_vec()))
+        .collect()
+}
+
+/// Timescale of an `mvhd` or `mdhd` full box
+fn timescale(body: &[u8]) -> Option<u32> {
+    // version 1 uses 64-bit creation/modification times
+    let offset = if *body.first()? == 1 { 20 } else { 12 };
+    let timescale = u32::from_be_bytes(body.get(offset..offset + 4)?.try_into().ok()?);
+    (timescale > 0).then_some(timescale)
+}
+
+fn track_id(tkhd: &[u8]) -> Option<u32> {
+    let offset = if *tkhd.first()? == 1 { 20 } else { 12 };
+    Some(u32::from_be_bytes(
+        tkhd.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn parse_elst(elst: &[u8], movie_timescale: u32, media_timescale: u32) -> Vec<EditListEntry> {
+    let Some(&version) = elst.first() else {
+        return Vec::new();
+    };
+    let Some(count) = elst
+        .get(4..8)
+        .and_then(|b| b.try_into().ok())
+        .map(u32::from_be_bytes)
+    else {
+        return Vec::new();
+    };
+    let entry_len = if version == 1 { 20 } else { 12 };
+
+    (0..count as usize)
+        .map_while(|i| {
+            let entry = elst.get(8 + i * entry_len..8 + (i + 1) * entry_len)?;
+            let (duration, media_time, rate) = if version == 1 {
+                (
+                    u64::from_be_bytes(entry[0..8].try_into().ok()?),
+                    i64::from_be_bytes(entry[8..16].try_into().ok()?),
+                    &entry[16..20],
+                )
+            } else {
+                (
+                    u32::from_be_bytes(entry[0..4].try_into().ok()?) as u64,
+                    i32::from_be_bytes(entry[4..8].try_into().ok()?) as i64,
+                    &entry[8..12],
+                )
+            };
+            // 16.16 fixed point
+            let rate = i32::from_be_bytes(rate.try_into().ok()?) as f64 / 65536.0;
+
+            Some(EditListEntry {
+                segment_duration: duration as f64 / movie_timescale as f64,
+                media_time: (media_time >= 0).then(|| media_time as f64 / media_timescale as f64),
+                rate,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,1078 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::Read,
+    path::Path,
+};
+
+use crate::Error;
+
+/// Size of an MPEG-TS packet; M2TS adds a 4-byte timecode in front
+pub const TS_PACKET_SIZE: usize = 188;
+
+const SYNC_BYTE: u8 = 0x47;
+
+/// Packets read from the file at a time
+const READ_CHUNK_PACKETS: usize = 8192;
+
+const PAT_PID: u16 = 0x0000;
+/// Service description table of DVB streams
+const SDT_PID: u16 = 0x0011;
+const NULL_PID: u16 = 0x1FFF;
+
+const PAT_TABLE_ID: u8 = 0x00;
+const PMT_TABLE_ID: u8 = 0x02;
+/// SDT describing the stream itself rather than another one of the network
+const SDT_ACTUAL_TABLE_ID: u8 = 0x42;
+const SCTE35_TABLE_ID: u8 = 0xFC;
+
+/// Stream type of SCTE-35 splice information
+const SCTE35_STREAM_TYPE: u8 = 0x86;
+
+const ISO_639_LANGUAGE_DESCRIPTOR: u8 = 0x0A;
+const VBI_TELETEXT_DESCRIPTOR: u8 = 0x46;
+const SERVICE_DESCRIPTOR: u8 = 0x48;
+const TELETEXT_DESCRIPTOR: u8 = 0x56;
+const SUBTITLING_DESCRIPTOR: u8 = 0x59;
+const AC3_DESCRIPTOR: u8 = 0x6A;
+const EAC3_DESCRIPTOR: u8 = 0x7A;
+
+/// SCTE-35 markers kept; splice messages repeat, and long captures hold many
+const MAX_SCTE35_MARKERS: usize = 1000;
+
+/// PTS values count ticks of a 90 kHz clock and wrap at 33 bits
+const PTS_CLOCK: f64 = 90_000.0;
+const PTS_MASK: u64 = (1 << 33) - 1;
+
+/// A descriptor of a PMT stream entry
+#[derive(Clone, Debug)]
+pub struct Descriptor {
+    pub tag: u8,
+    pub data: Vec<u8>,
+}
+
+/// An elementary stream listed in a PMT
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TsStream {
+    pub pid: u16,
+    pub stream_type: u8,
+    /// e.g. "H.264", from the stream type
+    pub kind: &'static str,
+    pub language: Option<String>,
+    /// DVB subtitle services carried by the stream
+    pub dvb_subtitles: Vec<DvbSubtitle>,
+    /// Teletext pages carried by the stream
+    pub teletext_pages: Vec<TeletextPage>,
+    #[serde(skip)]
+    pub descriptors: Vec<Descriptor>,
+}
+
+/// A DVB subtitle service announced by a subtitling descriptor
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DvbSubtitle {
+    pub language: String,
+    /// 0x10-0x14 for normal subtitles, 0x20-0x24 for the hard of hearing
+    pub subtitling_type: u8,
+    pub hard_of_hearing: bool,
+    pub composition_page: u16,
+    pub ancillary_page: u16,
+}
+
+/// A teletext page announced by a teletext descriptor
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TeletextPage {
+    pub language: String,
+    /// "initial", "subtitles", "additional_info", "schedule" or
+    /// "hearing_impaired_subtitles"
+    pub page_type: &'static str,
+    /// Page number as shown on a TV, e.g. 888
+    pub page: u16,
+}
+
+/// A splice message found on an SCTE-35 PID, marking ad breaks and program
+/// boundaries
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Scte35Marker {
+    pub pid: u16,
+    /// "splice_insert", "time_signal", "bandwidth_reservation", ...
+    pub command: &'static str,
+    /// Seconds on the stream's PTS clock; unset for immediate splices
+    pub pts_time: Option<f64>,
+    pub event_id: Option<u32>,
+    /// Whether a splice_insert leaves (true) or returns to the network
+    pub out_of_network: Option<bool>,
+    pub cancelled: bool,
+    /// Length of the break, in seconds
+    pub break_duration: Option<f64>,
+    /// The splice details are encrypted
+    pub encrypted: bool,
+}
+
+/// A program of the PAT with its PMT and, on DVB streams, its SDT service
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TsProgram {
+    pub program_number: u16,
+    pub pmt_pid: u16,
+    /// Unset until the PMT was seen
+    pub pcr_pid: Option<u16>,
+    pub service_name: Option<String>,
+    pub provider_name: Option<String>,
+    /// DVB service type, e.g. 0x01 for digital TV and 0x19 for HD TV
+    pub service_type: Option<u8>,
+    pub streams: Vec<TsStream>,
+}
+
+/// Packet counts and errors of one PID
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PidStats {
+    pub pid: u16,
+    /// "PAT", "PMT", a stream kind, ... ; unset for PIDs no table mentions
+    pub role: Option<String>,
+    pub packets: u64,
+    /// Packets whose continuity counter skipped, which means lost packets
+    pub continuity_errors: u64,
+    /// Packets flagged by the demodulator as uncorrectable
+    pub transport_errors: u64,
+    /// Payload encrypted, so it can't be decoded without a CAM
+    pub scrambled: bool,
+}
+
+/// What the transport stream layer of a `.ts`/`.m2ts` file holds
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TransportStreamInfo {
+    /// 188, or 192 for M2TS
+    pub packet_size: usize,
+    pub packets: u64,
+    pub transport_stream_id: Option<u16>,
+    pub programs: Vec<TsProgram>,
+    /// By PID
+    pub pids: Vec<PidStats>,
+    pub continuity_errors: u64,
+    pub transport_errors: u64,
+    /// Times the sync byte was missing and the reader had to search for it
+    pub sync_losses: u64,
+    /// In stream order, without back to back repeats
+    pub scte35_markers: Vec<Scte35Marker>,
+}
+
+/// Read a whole transport stream and collect its tables and packet errors
+pub fn scan_file(path: &Path) -> Result<TransportStreamInfo, Error> {
+    let mut file = File::open(path)?;
+    let mut header = Vec::new();
+    file.by_ref()
+        .take((TS_PACKET_SIZE * 4) as u64 * 3)
+        .read_to_end(&mut header)?;
+    let (packet_size, sync_offset) = detect_packet_size(&header).ok_or_else(|| {
+        Error::NotMediaFile(format!("{} is not a transport stream", path.display()))
+    })?;
+
+    let mut scanner = TsScanner::new(packet_size, sync_offset);
+    scanner.push(&header);
+    let mut chunk = vec![0u8; packet_size * READ_CHUNK_PACKETS];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        scanner.push(&chunk[..read]);
+    }
+    Ok(scanner.finish())
+}
+
+/// Packet size and sync byte offset of a transport stream, from its first
+/// packets
+pub fn detect_packet_size(header: &[u8]) -> Option<(usize, usize)> {
+    // Plain TS, M2TS with its timecode prefix, and TS with Reed-Solomon bytes
+    [
+        (TS_PACKET_SIZE, 0),
+        (TS_PACKET_SIZE + 4, 4),
+        (TS_PACKET_SIZE + 16, 0),
+    ]
+    .into_iter()
+    .find(|&(size, offset)| (0..3).all(|n| header.get(offset + n * size) == Some(&SYNC_BYTE)))
+}
+
+#[derive(Default)]
+struct PidState {
+    packets: u64,
+    last_counter: Option<u8>,
+    continuity_errors: u64,
+    transport_errors: u64,
+    scrambled: bool,
+    /// Section being reassembled, for PIDs carrying tables
+    section: Option<Vec<u8>>,
+}
+
+/// Incremental transport stream parser, fed bytes in any chunk size
+pub struct TsScanner {
+    packet_size: usize,
+    sync_offset: usize,
+    pending: Vec<u8>,
+    packets: u64,
+    sync_losses: u64,
+    pids: BTreeMap<u16, PidState>,
+    transport_stream_id: Option<u16>,
+    programs: BTreeMap<u16, TsProgram>,
+    /// PMT PID to program number
+    pmt_pids: HashMap<u16, u16>,
+    scte35_pids: HashSet<u16>,
+    scte35_markers: Vec<Scte35Marker>,
+}
+
+impl TsScanner {
+    pub fn new(packet_size: usize, sync_offset: usize) -> Self {
+        TsScanner {
+            packet_size,
+            sync_offset,
+            pending: Vec::new(),
+            packets: 0,
+            sync_losses: 0,
+            pids: BTreeMap::new(),
+            transport_stream_id: None,
+            programs: BTreeMap::new(),
+            pmt_pids: HashMap::new(),
+            scte35_pids: HashSet::new(),
+            scte35_markers: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.pending.extend_from_slice(data);
+        let mut start = 0;
+        while self.pending.len() - start >= self.packet_size {
+            let sync = start + self.sync_offset;
+            if self.pending[sync] != SYNC_BYTE {
+                // Skip to the next sync byte and continue from there
+                self.sync_losses += 1;
+                match self.pending[sync + 1..]
+                    .iter()
+                    .position(|&b| b == SYNC_BYTE)
+                {
+                    Some(skip) => start = (sync + 1 + skip).saturating_sub(self.sync_offset),
+                    None => start = self.pending.len(),
+                }
+                continue;
+            }
+            let packet: [u8; TS_PACKET_SIZE] = self.pending[sync..sync + TS_PACKET_SIZE]
+                .try_into()
+                .unwrap_or([0; TS_PACKET_SIZE]);
+            self.packet(&packet);
+            start += self.packet_size;
+        }
+        self.pending.drain(..start.min(self.pending.len()));
+    }
+
+    fn packet(&mut self, packet: &[u8; TS_PACKET_SIZE]) {
+        self.packets += 1;
+        let transport_error = packet[1] & 0x80 != 0;
+        let unit_start = packet[1] & 0x40 != 0;
+        let pid = u16::from_be_bytes([packet[1] & 0x1F, packet[2]]);
+        let scrambled = packet[3] & 0xC0 != 0;
+        let adaptation = packet[3] & 0x20 != 0;
+        let has_payload = packet[3] & 0x10 != 0;
+        let counter = packet[3] & 0x0F;
+
+        let state = self.pids.entry(pid).or_default();
+        state.packets += 1;
+        if transport_error {
+            state.transport_errors += 1;
+            return;
+        }
+        state.scrambled |= scrambled;
+
+        let mut payload_start = 4;
+        let mut discontinuity = false;
+        if adaptation {
+            let length = packet[4] as usize;
+            discontinuity = length > 0 && packet[5] & 0x80 != 0;
+            payload_start = 5 + length;
+        }
+        // Counters only advance on packets with payload; one repeat is allowed
+        if has_payload && pid != NULL_PID {
+            if let Some(last) = state.last_counter {
+                if !discontinuity && counter != last && counter != (last + 1) & 0x0F {
+                    state.continuity_errors += 1;
+                }
+            }
+            state.last_counter = Some(counter);
+        }
+
+        let is_table = pid == PAT_PID
+            || pid == SDT_PID
+            || self.pmt_pids.contains_key(&pid)
+            || self.scte35_pids.contains(&pid);
+        if !has_payload || !is_table || scrambled || payload_start >= TS_PACKET_SIZE {
+            return;
+        }
+        for section in collect_sections(state, &packet[payload_start..], unit_start) {
+            self.section(pid, &section);
+        }
+    }
+
+    fn section(&mut self, pid: u16, section: &[u8]) {
+        if section.len() < 12 || crc32_mpeg2(section) != 0 {
+            return;
+        }
+        let body = &section[..section.len() - 4];
+        match (pid, section[0]) {
+            (PAT_PID, PAT_TABLE_ID) => self.pat(body),
+            (SDT_PID, SDT_ACTUAL_TABLE_ID) => self.sdt(body),
+            (_, SCTE35_TABLE_ID) if self.scte35_pids.contains(&pid) => self.scte35(pid, body),
+            (_, PMT_TABLE_ID) => self.pmt(pid, body),
+            _ => {}
+        }
+    }
+
+    fn pat(&mut self, body: &[u8]) {
+        self.transport_stream_id = Some(u16::from_be_bytes([body[3], body[4]]));
+        for entry in body[8..].chunks_exact(4) {
+            let program_number = u16::from_be_bytes([entry[0], entry[1]]);
+            let pmt_pid = u16::from_be_bytes([entry[2] & 0x1F, entry[3]]);
+            // Program 0 points at the network information table
+            if program_number == 0 {
+                continue;
+            }
+            self.pmt_pids.insert(pmt_pid, program_number);
+            self.programs
+                .entry(program_number)
+                .or_insert_with(|| TsProgram {
+                    program_number,
+                    pmt_pid,
+                    pcr_pid: None,
+                    service_name: None,
+                    provider_name: None,
+                    service_type: None,
+                    streams: Vec::new(),
+                })
+                .pmt_pid = pmt_pid;
+        }
+    }
+
+    fn pmt(&mut self, pid: u16, body: &[u8]) {
+        if body.len() < 12 {
+            return;
+        }
+        let program_number = u16::from_be_bytes([body[3], body[4]]);
+        if self.pmt_pids.get(&pid) != Some(&program_number) {
+            return;
+        }
+        let Some(program) = self.programs.get_mut(&program_number) else {
+            return;
+        };
+        program.pcr_pid = Some(u16::from_be_bytes([body[8] & 0x1F, body[9]]));
+        let info_length = u16::from_be_bytes([body[10] & 0x0F, body[11]]) as usize;
+
+        let mut streams = Vec::new();
+        let mut offset = 12 + info_length;
+        while offset + 5 <= body.len() {
+            let stream_type = body[offset];
+            let stream_pid = u16::from_be_bytes([body[offset + 1] & 0x1F, body[offset + 2]]);
+            let es_length =
+                u16::from_be_bytes([body[offset + 3] & 0x0F, body[offset + 4]]) as usize;
+            let end = (offset + 5 + es_length).min(body.len());
+            let descriptors = parse_descriptors(&body[offset + 5..end]);
+            let language = descriptors
+                .iter()
+                .find(|d| d.tag == ISO_639_LANGUAGE_DESCRIPTOR && d.data.len() >= 3)
+                .map(|d| String::from_utf8_lossy(&d.data[..3]).to_string());
+            let dvb_subtitles: Vec<DvbSubtitle> = descriptors
+                .iter()
+                .filter(|d| d.tag == SUBTITLING_DESCRIPTOR)
+                .flat_map(|d| d.data.chunks_exact(8).map(parse_dvb_subtitle))
+                .collect();
+            let teletext_pages: Vec<TeletextPage> = descriptors
+                .iter()
+                .filter(|d| matches!(d.tag, TELETEXT_DESCRIPTOR | VBI_TELETEXT_DESCRIPTOR))
+                .flat_map(|d| d.data.chunks_exact(5).map(parse_teletext_page))
+                .collect();
+            let has_descriptor = |tag: u8| descriptors.iter().any(|d| d.tag == tag);
+            // Private data streams are told apart by their descriptors
+            let kind = match stream_type {
+                0x06 if !dvb_subtitles.is_empty() => "DVB subtitles",
+                0x06 if !teletext_pages.is_empty() => "teletext",
+                0x06 if has_descriptor(AC3_DESCRIPTOR) => "AC-3",
+                0x06 if has_descriptor(EAC3_DESCRIPTOR) => "E-AC-3",
+                _ => stream_type_name(stream_type),
+            };
+            if stream_type == SCTE35_STREAM_TYPE {
+                self.scte35_pids.insert(stream_pid);
+            }
+            streams.push(TsStream {
+                pid: stream_pid,
+                stream_type,
+                kind,
+                language,
+                dvb_subtitles,
+                teletext_pages,
+                descriptors,
+            });
+            offset = end;
+        }
+        // A new PMT version replaces the stream list
+        program.streams = streams;
+    }
+
+    fn scte35(&mut self, pid: u16, body: &[u8]) {
+        let Some(marker) = parse_splice_info(pid, body) else {
+            return;
+        };
+        // Encoders repeat each message a few times in a row
+        let repeat = self.scte35_markers.last().is_some_and(|last| {
+            last.pid == pid
+                && last.command == marker.command
+                && last.event_id == marker.event_id
+                && last.pts_time == marker.pts_time
+        });
+        if !repeat && self.scte35_markers.len() < MAX_SCTE35_MARKERS {
+            self.scte35_markers.push(marker);
+        }
+    }
+
+    fn sdt(&mut self, body: &[u8]) {
+        let mut offset = 11;
+        while offset + 5 <= body.len() {
+            let service_id = u16::from_be_bytes([body[offset], body[offset + 1]]);
+            let loop_length =
+                u16::from_be_bytes([body[offset + 3] & 0x0F, body[offset + 4]]) as usize;
+            let end = (offset + 5 + loop_length).min(body.len());
+            let service = parse_descriptors(&body[offset + 5..end])
+                .into_iter()
+                .find(|d| d.tag == SERVICE_DESCRIPTOR);
+            if let (Some(service), Some(program)) = (service, self.programs.get_mut(&service_id)) {
+                let data = &service.data;
+                let provider_end = 2 + *data.get(1).unwrap_or(&0) as usize;
+                let name_length = *data.get(provider_end).unwrap_or(&0) as usize;
+                program.service_type = data.first().copied();
+                program.provider_name = data.get(2..provider_end).and_then(dvb_text);
+                program.service_name = data
+                    .get(provider_end + 1..provider_end + 1 + name_length)
+                    .and_then(dvb_text);
+            }
+            offset = end;
+        }
+    }
+
+    pub fn finish(self) -> TransportStreamInfo {
+        let roles: HashMap<u16, String> = self
+            .programs
+            .values()
+            .flat_map(|program| {
+                let streams = program
+                    .streams
+                    .iter()
+                    .map(|stream| (stream.pid, stream.kind.to_string()));
+                std::iter::once((program.pmt_pid, "PMT".to_string())).chain(streams)
+            })
+            .chain([
+                (PAT_PID, "PAT".to_string()),
+                (SDT_PID, "SDT".to_string()),
+                (NULL_PID, "null".to_string()),
+            ])
+            .collect();
+
+        let pids: Vec<PidStats> = self
+            .pids
+            .into_iter()
+            .map(|(pid, state)| PidStats {
+                pid,
+                role: roles.get(&pid).cloned(),
+                packets: state.packets,
+                continuity_errors: state.continuity_errors,
+                transport_errors: state.transport_errors,
+                scrambled: state.scrambled,
+            })
+            .collect();
+        TransportStreamInfo {
+            packet_size: self.packet_size,
+            packets: self.packets,
+            transport_stream_id: self.transport_stream_id,
+            programs: self.programs.into_values().collect(),
+            continuity_errors: pids.iter().map(|pid| pid.continuity_errors).sum(),
+            transport_errors: pids.iter().map(|pid| pid.transport_errors).sum(),
+            pids,
+            sync_losses: self.sync_losses,
+            scte35_markers: self.scte35_markers,
+        }
+    }
+}
+
+/// Add a packet payload to the PID's section buffer and take out every
+/// section it completes
+fn collect_sections(state: &mut PidState, payload: &[u8], unit_start: bool) -> Vec<Vec<u8>> {
+    let mut sections = Vec::new();
+    if unit_start {
+        // The pointer field says where the new section starts; the bytes
+        // before it finish the previous one
+        let pointer = payload[0] as usize;
+        let Some(rest) = payload.get(1..) else {
+            return sections;
+        };
+        let split = pointer.min(rest.len());
+        if let Some(buffer) = state.section.as_mut() {
+            buffer.extend_from_slice(&rest[..split]);
+            take_sections(buffer, &mut sections);
+        }
+        state.section = Some(rest[split..].to_vec());
+    } else if let Some(buffer) = state.section.as_mut() {
+        buffer.extend_from_slice(payload);
+    }
+    if let Some(buffer) = state.section.as_mut() {
+        take_sections(buffer, &mut sections);
+    }
+    sections
+}
+
+fn take_sections(buffer: &mut Vec<u8>, sections: &mut Vec<Vec<u8>>) {
+    while buffer.len() >= 3 {
+        // Stuffing fills the rest of the packet
+        if buffer[0] == 0xFF {
+            buffer.clear();
+            break;
+        }
+        let length = 3 + u16::from_be_bytes([buffer[1] & 0x0F, buffer[2]]) as usize;
+        if buffer.len() < length {
+            break;
+        }
+        sections.push(buffer.drain(..length).collect());
+    }
+}
+
+fn parse_descriptors(mut data: &[u8]) -> Vec<Descriptor> {
+    let mut descriptors = Vec::new();
+    while data.len() >= 2 {
+        let end = (2 + data[1] as usize).min(data.len());
+        descriptors.push(Descriptor {
+            tag: data[0],
+            data: data[2..end].to_vec(),
+        });
+        data = &data[end..];
+    }
+    descriptors
+}
+
+/// One 8-byte entry of a subtitling descriptor
+fn parse_dvb_subtitle(entry: &[u8]) -> DvbSubtitle {
+    let subtitling_type = entry[3];
+    DvbSubtitle {
+        language: String::from_utf8_lossy(&entry[..3]).to_string(),
+        subtitling_type,
+        hard_of_hearing: (0x20..=0x24).contains(&subtitling_type),
+        composition_page: u16::from_be_bytes([entry[4], entry[5]]),
+        ancillary_page: u16::from_be_bytes([entry[6], entry[7]]),
+    }
+}
+
+/// One 5-byte entry of a teletext descriptor
+fn parse_teletext_page(entry: &[u8]) -> TeletextPage {
+    let page_type = match entry[3] >> 3 {
+        0x01 => "initial",
+        0x02 => "subtitles",
+        0x03 => "additional_info",
+        0x04 => "schedule",
+        0x05 => "hearing_impaired_subtitles",
+        _ => "reserved",
+    };
+    // Magazine 0 is shown as 8; the page number within it is BCD
+    let magazine = match entry[3] & 0x07 {
+        0 => 8,
+        magazine => magazine as u16,
+    };
+    let page = (entry[4] >> 4) as u16 * 10 + (entry[4] & 0x0F) as u16;
+    TeletextPage {
+        language: String::from_utf8_lossy(&entry[..3]).to_string(),
+        page_type,
+        page: magazine * 100 + page,
+    }
+}
+
+/// Parse an SCTE-35 splice_info_section, without its CRC
+///
+/// Splice null heartbeats are left out; they carry nothing to report.
+fn parse_splice_info(pid: u16, body: &[u8]) -> Option<Scte35Marker> {
+    let encrypted = *body.get(4)? & 0x80 != 0;
+    let pts_adjustment = read_pts(body.get(4..9)?);
+    let command_type = *body.get(13)?;
+    let command = body.get(14..)?;
+    let mut marker = Scte35Marker {
+        pid,
+        command: match command_type {
+            0x00 => return None,
+            0x04 => "splice_schedule",
+            0x05 => "splice_insert",
+            0x06 => "time_signal",
+            0x07 => "bandwidth_reservation",
+            0xFF => "private_command",
+            _ => "reserved",
+        },
+        pts_time: None,
+        event_id: None,
+        out_of_network: None,
+        cancelled: false,
+        break_duration: None,
+        encrypted,
+    };
+    if encrypted {
+        return Some(marker);
+    }
+    let pts_time = |pts: u64| ((pts + pts_adjustment) & PTS_MASK) as f64 / PTS_CLOCK;
+
+    match command_type {
+        0x05 => {
+            marker.event_id = Some(u32::from_be_bytes(command.get(..4)?.try_into().ok()?));
+            marker.cancelled = *command.get(4)? & 0x80 != 0;
+            if marker.cancelled {
+                return Some(marker);
+            }
+            let flags = *command.get(5)?;
+            let program_splice = flags & 0x40 != 0;
+            let has_duration = flags & 0x20 != 0;
+            let immediate = flags & 0x10 != 0;
+            marker.out_of_network = Some(flags & 0x80 != 0);
+            let mut offset = 6;
+            if program_splice && !immediate {
+                let (pts, length) = splice_time(command.get(offset..)?)?;
+                marker.pts_time = pts.map(pts_time);
+                offset += length;
+            } else if !program_splice {
+                // Component splices list a time per component; report the first
+                let components = *command.get(offset)? as usize;
+                offset += 1;
+                for component in 0..components {
+                    offset += 1;
+                    if !immediate {
+                        let (pts, length) = splice_time(command.get(offset..)?)?;
+                        if component == 0 {
+                            marker.pts_time = pts.map(pts_time);
+                        }
+                        offset += length;
+                    }
+                }
+            }
+            if has_duration {
+                let duration = read_pts(command.get(offset..offset + 5)?);
+                marker.break_duration = Some(duration as f64 / PTS_CLOCK);
+            }
+        }
+        0x06 => {
+            let (pts, _) = splice_time(command)?;
+            marker.pts_time = pts.map(pts_time);
+        }
+        _ => {}
+    }
+    Some(marker)
+}
+
+/// A splice_time structure: the PTS when one is specified, and its length
+fn splice_time(data: &[u8]) -> Option<(Option<u64>, usize)> {
+    if *data.first()? & 0x80 == 0 {
+        return Some((None, 1));
+    }
+    Some((Some(read_pts(data.get(..5)?)), 5))
+}
+
+/// A 33-bit timestamp stored in the low bit of a byte and the 4 bytes after it
+fn read_pts(bytes: &[u8]) -> u64 {
+    ((bytes[0] as u64 & 0x01) << 32)
+        | u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as u64
+}
+
+/// Decode a DVB string: an optional character table selector, then text
+///
+/// Only UTF-8 is told apart; other tables are read as Latin-1, which gets
+/// the ASCII range of every one of them right.
+pub fn dvb_text(bytes: &[u8]) -> Option<String> {
+    let (utf8, text) = match bytes.first()? {
+        0x15 => (true, &bytes[1..]),
+        0x10 => (false, bytes.get(3..)?),
+        0x01..=0x1F => (false, &bytes[1..]),
+        _ => (false, bytes),
+    };
+    let text = if utf8 {
+        String::from_utf8_lossy(text).to_string()
+    } else {
+        text.iter().map(|&b| b as char).collect()
+    };
+    let text: String = text.chars().filter(|c| !c.is_control()).collect();
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// Name of an MPEG-TS stream type
+pub fn stream_type_name(stream_type: u8) -> &'static str {
+    match stream_type {
+        0x01 => "MPEG-1 video",
+        0x02 => "MPEG-2 video",
+        0x03 => "MPEG-1 audio",
+        0x04 => "MPEG-2 audio",
+        0x06 => "private data",
+        0x0F => "AAC",
+        0x11 => "AAC LATM",
+        0x15 => "metadata",
+        0x1B => "H.264",
+        0x24 => "HEVC",
+        0x80 => "LPCM",
+        0x81 => "AC-3",
+        0x82 => "DTS",
+        0x83 => "TrueHD",
+        0x84 | 0x87 => "E-AC-3",
+        0x86 => "SCTE-35",
+        0x90 => "PGS subtitles",
+        _ => "unknown",
+    }
+}
+
+/// CRC-32/MPEG-2 of a table section; zero over a section including its CRC
+fn crc32_mpeg2(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PMT_PID: u16 = 0x0100;
+
+    /// A long-form table section with a valid CRC
+    fn section(table_id: u8, table_id_extension: u16, data: &[u8]) -> Vec<u8> {
+        let length = 5 + data.len() + 4;
+        let mut section = vec![table_id, 0xB0 | (length >> 8) as u8, length as u8];
+        section.extend_from_slice(&table_id_extension.to_be_bytes());
+        // Version 0, current, section 0 of 0
+        section.extend_from_slice(&[0xC1, 0x00, 0x00]);
+        section.extend_from_slice(data);
+        with_crc(section)
+    }
+
+    fn with_crc(mut section: Vec<u8>) -> Vec<u8> {
+        let crc = crc32_mpeg2(&section);
+        section.extend_from_slice(&crc.to_be_bytes());
+        section
+    }
+
+    /// One packet with `payload`, padded with stuffing
+    fn packet(pid: u16, unit_start: bool, counter: u8, payload: &[u8]) -> Vec<u8> {
+        let unit_start = if unit_start { 0x40 } else { 0x00 };
+        let mut packet = vec![
+            SYNC_BYTE,
+            unit_start | (pid >> 8) as u8 & 0x1F,
+            pid as u8,
+            0x10 | counter & 0x0F,
+        ];
+        packet.extend_from_slice(payload);
+        packet.resize(TS_PACKET_SIZE, 0xFF);
+        packet
+    }
+
+    /// The packets carrying `section`, with counters from `counter` on
+    fn section_packets(pid: u16, counter: u8, section: &[u8]) -> Vec<u8> {
+        let payload = [&[0x00][..], section].concat();
+        payload
+            .chunks(TS_PACKET_SIZE - 4)
+            .enumerate()
+            .flat_map(|(n, chunk)| packet(pid, n == 0, counter.wrapping_add(n as u8), chunk))
+            .collect()
+    }
+
+    /// Program 1 on PMT PID 0x100, next to the network PID every PAT lists
+    fn pat() -> Vec<u8> {
+        section(
+            PAT_TABLE_ID,
+            0x0042,
+            &[0x00, 0x00, 0xE0, 0x10, 0x00, 0x01, 0xE1, 0x00],
+        )
+    }
+
+    /// PMT of program 1 with PCR PID 0x101 and `(stream_type, pid,
+    /// descriptors)` streams
+    fn pmt(streams: &[(u8, u16, &[u8])]) -> Vec<u8> {
+        let mut data = vec![0xE1, 0x01, 0xF0, 0x00];
+        for (stream_type, pid, descriptors) in streams {
+            data.push(*stream_type);
+            data.extend_from_slice(&(0xE000 | pid).to_be_bytes());
+            data.extend_from_slice(&(0xF000 | descriptors.len() as u16).to_be_bytes());
+            data.extend_from_slice(descriptors);
+        }
+        section(PMT_TABLE_ID, 1, &data)
+    }
+
+    fn scan(data: &[u8]) -> TransportStreamInfo {
+        let mut scanner = TsScanner::new(TS_PACKET_SIZE, 0);
+        scanner.push(data);
+        scanner.finish()
+    }
+
+    /// Bytes that look random but are the same on every run
+    fn noise(length: usize) -> Vec<u8> {
+        let mut state = 0x2545_F491u32;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_programs_from_pat_and_pmt() {
+        let language = [ISO_639_LANGUAGE_DESCRIPTOR, 4, b'e', b'n', b'g', 0];
+        let data = [
+            section_packets(PAT_PID, 0, &pat()),
+            section_packets(
+                PMT_PID,
+                0,
+                &pmt(&[(0x1B, 0x101, &[]), (0x0F, 0x102, &language)]),
+            ),
+        ]
+        .concat();
+
+        // Fed in pieces that don't line up with packets
+        let mut scanner = TsScanner::new(TS_PACKET_SIZE, 0);
+        for piece in data.chunks(7) {
+            scanner.push(piece);
+        }
+        let info = scanner.finish();
+
+        assert_eq!(info.packets, 2);
+        assert_eq!(info.transport_stream_id, Some(0x0042));
+        assert_eq!(info.programs.len(), 1);
+        let program = &info.programs[0];
+        assert_eq!(program.program_number, 1);
+        assert_eq!(program.pmt_pid, PMT_PID);
+        assert_eq!(program.pcr_pid, Some(0x101));
+        let streams: Vec<_> = program
+            .streams
+            .iter()
+            .map(|stream| (stream.pid, stream.kind, stream.language.as_deref()))
+            .collect();
+        assert_eq!(
+            streams,
+            [(0x101, "H.264", None), (0x102, "AAC", Some("eng"))]
+        );
+        let pmt = info.pids.iter().find(|pid| pid.pid == PMT_PID).unwrap();
+        assert_eq!(pmt.role.as_deref(), Some("PMT"));
+        assert_eq!(info.continuity_errors, 0);
+    }
+
+    #[test]
+    fn counts_continuity_counter_gaps() {
+        // A repeated counter is allowed, skipping 2 means a packet was lost
+        let data: Vec<u8> = [0, 1, 1, 3, 4]
+            .into_iter()
+            .flat_map(|counter| section_packets(PAT_PID, counter, &pat()))
+            .collect();
+        let info = scan(&data);
+
+        assert_eq!(info.packets, 5);
+        assert_eq!(info.continuity_errors, 1);
+        assert_eq!(info.pids[0].continuity_errors, 1);
+        assert_eq!(info.programs.len(), 1);
+    }
+
+    #[test]
+    fn reassembles_a_section_split_across_packets() {
+        let streams: Vec<(u8, u16, &[u8])> = (0..40).map(|n| (0x1B, 0x200 + n, &[][..])).collect();
+        let pmt_packets = section_packets(PMT_PID, 0, &pmt(&streams));
+        assert_eq!(pmt_packets.len(), 2 * TS_PACKET_SIZE);
+
+        let pat_packets = section_packets(PAT_PID, 0, &pat());
+        let info = scan(&[pat_packets.clone(), pmt_packets.clone()].concat());
+        assert_eq!(info.programs[0].streams.len(), 40);
+        assert_eq!(info.programs[0].streams[39].pid, 0x227);
+
+        // Without its second half the section is never parsed
+        let info = scan(&[pat_packets, pmt_packets[..TS_PACKET_SIZE].to_vec()].concat());
+        assert!(info.programs[0].streams.is_empty());
+        assert_eq!(info.programs[0].pcr_pid, None);
+    }
+
+    #[test]
+    fn survives_garbage_and_truncated_input() {
+        assert_eq!(scan(&[]).packets, 0);
+        assert_eq!(detect_packet_size(&[SYNC_BYTE; 10]), None);
+
+        let mut garbage = noise(TS_PACKET_SIZE * 64 + 100);
+        let info = scan(&garbage);
+        assert!(info.sync_losses > 0);
+        // With sync bytes in place every packet header is random instead
+        for packet in garbage.chunks_mut(TS_PACKET_SIZE) {
+            packet[0] = SYNC_BYTE;
+        }
+        assert_eq!(scan(&garbage).packets, 64);
+        let mut m2ts = TsScanner::new(TS_PACKET_SIZE + 4, 4);
+        m2ts.push(&garbage);
+        m2ts.finish();
+
+        // Tables whose lengths point past their end, and a pointer field
+        // past the packet
+        let bogus_pmt = section(PMT_TABLE_ID, 1, &[0xE1, 0x01, 0xFF, 0xFF, 0x1B, 0xE1]);
+        let bogus_stream = section(
+            PMT_TABLE_ID,
+            1,
+            &[0xE1, 0x01, 0xF0, 0x00, 0x1B, 0xE1, 0x01, 0xFF, 0xFF],
+        );
+        let bogus_sdt = section(
+            SDT_ACTUAL_TABLE_ID,
+            0x42,
+            &[0x00, 0x00, 0xFF, 0x00, 0x01, 0xFF, 0xFF],
+        );
+        let data = [
+            section_packets(PAT_PID, 0, &pat()),
+            section_packets(PMT_PID, 0, &bogus_pmt),
+            section_packets(PMT_PID, 1, &bogus_stream),
+            section_packets(SDT_PID, 0, &bogus_sdt),
+            packet(PMT_PID, true, 2, &[0xFF, PMT_TABLE_ID, 0xB3, 0xFF]),
+            packet(PMT_PID, true, 3, &[]),
+            section_packets(PMT_PID, 4, &pmt(&[(0x1B, 0x101, &[])]))[..100].to_vec(),
+        ]
+        .concat();
+        let info = scan(&data);
+        assert_eq!(info.programs.len(), 1);
+        assert_eq!(info.programs[0].streams.len(), 1);
+    }
+
+    /// A splice_info_section without PTS adjustment or descriptors
+    fn splice_info(command_type: u8, command: &[u8]) -> Vec<u8> {
+        let length = 11 + command.len() + 2 + 4;
+        let mut section = vec![SCTE35_TABLE_ID, 0x30 | (length >> 8) as u8, length as u8];
+        // Protocol version, not encrypted, no PTS adjustment, CW index
+        section.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        // Tier and command length
+        section.extend_from_slice(&[0xFF, 0xF0 | (command.len() >> 8) as u8, command.len() as u8]);
+        section.push(command_type);
+        section.extend_from_slice(command);
+        section.extend_from_slice(&[0x00, 0x00]);
+        with_crc(section)
+    }
+
+    /// A splice_insert of event `event_id` with the given flags byte and the
+    /// splice time and break duration that follow it
+    fn splice_insert(event_id: u32, flags: u8, times: &[u8]) -> Vec<u8> {
+        let mut command = event_id.to_be_bytes().to_vec();
+        command.extend_from_slice(&[0x7F, flags]);
+        command.extend_from_slice(times);
+        // Unique program ID, avail number and avails expected
+        command.extend_from_slice(&[0x00, 0x01, 0x00, 0x00]);
+        splice_info(0x05, &command)
+    }
+
+    #[test]
+    fn reads_scte35_splice_inserts() {
+        const SCTE35_PID: u16 = 0x0104;
+        // Out of network at 10 s for 30 s: program splice with a duration,
+        // then a 33-bit time and an auto-return break duration
+        let mut times = vec![0xFE];
+        times.extend_from_slice(&900_000u32.to_be_bytes());
+        times.push(0xFE);
+        times.extend_from_slice(&2_700_000u32.to_be_bytes());
+        let leave = splice_insert(0x1234, 0xEF, &times);
+        // Back to the network right away, so without a time
+        let immediate = splice_insert(0x1235, 0x5F, &[]);
+        // A program splice whose time isn't specified
+        let unspecified = splice_insert(0x1236, 0xCF, &[0x7F]);
+
+        let data = [
+            section_packets(PAT_PID, 0, &pat()),
+            section_packets(PMT_PID, 0, &pmt(&[(SCTE35_STREAM_TYPE, SCTE35_PID, &[])])),
+            section_packets(SCTE35_PID, 0, &leave),
+            // Encoders repeat messages, which are reported once
+            section_packets(SCTE35_PID, 1, &leave),
+            section_packets(SCTE35_PID, 2, &immediate),
+            section_packets(SCTE35_PID, 3, &unspecified),
+            section_packets(SCTE35_PID, 4, &splice_info(0x00, &[])),
+        ]
+        .concat();
+        let info = scan(&data);
+
+        assert_eq!(info.programs[0].streams[0].kind, "SCTE-35");
+        let markers: Vec<_> = info
+            .scte35_markers
+            .iter()
+            .map(|marker| {
+                (
+                    marker.command,
+                    marker.event_id,
+                    marker.out_of_network,
+                    marker.pts_time,
+                    marker.break_duration,
+                )
+            })
+            .collect();
+        assert_eq!(
+            markers,
+            [
+                (
+                    "splice_insert",
+                    Some(0x1234),
+                    Some(true),
+                    Some(10.0),
+                    Some(30.0)
+                ),
+                ("splice_insert", Some(0x1235), Some(false), None, None),
+                ("splice_insert", Some(0x1236), Some(true), None, None),
+            ]
+        );
+        assert!(info
+            .scte35_markers
+            .iter()
+            .all(|marker| marker.pid == SCTE35_PID));
+        assert!(!info.scte35_markers[0].cancelled);
+    }
+
+    #[test]
+    fn reads_teletext_pages_and_dvb_subtitles() {
+        // Language, then page type and magazine, then the BCD page number
+        let teletext = [
+            &[TELETEXT_DESCRIPTOR, 15][..],
+            b"eng\x10\x88",
+            b"deu\x09\x00",
+            b"fra\x2F\x77",
+        ]
+        .concat();
+        // Language, subtitling type, composition and ancillary pages
+        let subtitles = [&[SUBTITLING_DESCRIPTOR, 8][..], b"eng\x20\x00\x01\x00\x02"].concat();
+        let data = [
+            section_packets(PAT_PID, 0, &pat()),
+            section_packets(
+                PMT_PID,
+                0,
+                &pmt(&[(0x06, 0x103, &teletext), (0x06, 0x105, &subtitles)]),
+            ),
+        ]
+        .concat();
+        let info = scan(&data);
+
+        let streams = &info.programs[0].streams;
+        assert_eq!(streams[0].kind, "teletext");
+        let pages: Vec<_> = streams[0]
+            .teletext_pages
+            .iter()
+            .map(|page| (page.language.as_str(), page.page_type, page.page))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                ("eng", "subtitles", 888),
+                ("deu", "initial", 100),
+                ("fra", "hearing_impaired_subtitles", 777),
+            ]
+        );
+
+        assert_eq!(streams[1].kind, "DVB subtitles");
+        let subtitle = &streams[1].dvb_subtitles[0];
+        assert_eq!(subtitle.language, "eng");
+        assert!(subtitle.hard_of_hearing);
+        assert_eq!((subtitle.composition_page, subtitle.ancillary_page), (1, 2));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::Error;
+
+/// Blu-ray timestamps count ticks of a 45 kHz clock
+const TICKS_PER_SECOND: f64 = 45_000.0;
+
+/// Mark type of chapter entry points; the other type marks link points
+const ENTRY_MARK: u8 = 1;
+
+/// Connection conditions of play items following the previous one seamlessly
+const SEAMLESS_CONNECTIONS: [u8; 2] = [5, 6];
+
+/// One clip of a playlist, played from `in_time` to `out_time`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct PlayItem {
+    /// Five digit clip name; the video is `BDMV/STREAM/<clip>.m2ts`
+    pub clip: String,
+    /// Seconds into the clip
+    pub in_time: f64,
+    pub out_time: f64,
+    /// Continues the previous item without a break, as when a camera splits
+    /// a long recording across files
+    pub seamless: bool,
+}
+
+/// A parsed `.mpls` movie playlist
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Playlist {
+    pub items: Vec<PlayItem>,
+    /// Chapter starts, in seconds from the start of the playlist
+    pub chapters: Vec<f64>,
+}
+
+impl Playlist {
+    /// Total playback time in seconds
+    pub fn duration(&self) -> f64 {
+        self.items
+            .iter()
+            .map(|item| item.out_time - item.in_time)
+            .sum()
+    }
+}
+
+/// Parse a Blu-ray movie playlist (`BDMV/PLAYLIST/*.mpls`) or its AVCHD
+/// equivalent (`*.MPL`)
+pub fn parse_mpls(data: &[u8]) -> Result<Playlist, Error> {
+    if data.get(..4) != Some(b"MPLS") {
+        return Err(Error::ParseError("Not an MPLS playlist".to_string()));
+    }
+    let playlist_start = read_u32(data, 8)? as usize;
+    let mark_start = read_u32(data, 12)? as usize;
+
+    // PlayList: length, reserved, item count, sub-path count, then the items
+    let item_count = read_u16(data, playlist_start + 6)?;
+    let mut offset = playlist_start + 10;
+    let mut items = Vec::with_capacity(item_count as usize);
+    for _ in 0..item_count {
+        let length = read_u16(data, offset)? as usize;
+        let clip = data
+            .get(offset + 2..offset + 7)
+            .map(|name| String::from_utf8_lossy(name).to_string())
+            .ok_or_else(|| truncated("play item"))?;
+        // Clip codec (4 bytes), flags (2) and STC id (1) come before the times
+        let connection = *data
+            .get(offset + 12)
+            .ok_or_else(|| truncated("play item"))?
+            & 0x0F;
+        let in_time = read_u32(data, offset + 14)?;
+        let out_time = read_u32(data, offset + 18)?;
+        items.push(PlayItem {
+            clip,
+            in_time: in_time as f64 / TICKS_PER_SECOND,
+            out_time: out_time as f64 / TICKS_PER_SECOND,
+            seamless: SEAMLESS_CONNECTIONS.contains(&connection),
+        });
+        offset += 2 + length;
+    }
+
+    // PlayListMark: length, mark count, then 14 byte marks
+    let mark_count = read_u16(data, mark_start + 4)?;
+    let mut chapters = Vec::new();
+    for index in 0..mark_count as usize {
+        let mark = mark_start + 6 + index * 14;
+        let mark_type = *data.get(mark + 1).ok_or_else(|| truncated("mark"))?;
+        if mark_type != ENTRY_MARK {
+            continue;
+        }
+        let item_index = read_u16(data, mark + 2)? as usize;
+        let Some(item) = items.get(item_index) else {
+            continue;
+        };
+        let time = read_u32(data, mark + 4)? as f64 / TICKS_PER_SECOND;
+        let item_start: f64 = items[..item_index]
+            .iter()
+            .map(|item| item.out_time - item.in_time)
+            .sum();
+        chapters.push(item_start + (time - item.in_time).max(0.0));
+    }
+
+    Ok(Playlist { items, chapters })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, Error> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| truncated("playlist"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, Error> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| truncated("playlist"))
+}
+
+fn truncated(what: &str) -> Error {
+    Error::ParseError(format!("Truncated MPLS {}", what))
+}
@@ -0,0 +1,124 @@
+use crate::mp4::{track_for_stream, EditListEntry, Mp4Track};
+
+/// Streams starting further apart than this are reported, in seconds
+const START_SKEW_TOLERANCE: f64 = 0.01;
+
+/// Start offsets and edits of one stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct StreamOffsets {
+    index: u64,
+    codec_type: String,
+    /// Stream start time in seconds, as reported by the demuxer
+    start_time: Option<f64>,
+    /// Encoder delay (priming samples) declared by the stream
+    initial_padding: Option<u64>,
+    /// MP4 edit list of the matching track
+    edit_list: Vec<EditListEntry>,
+}
+
+/// Edit lists, start times and encoder delay, with anything likely to cause
+/// sync or trimming problems called out
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct StartOffsetReport {
+    streams: Vec<StreamOffsets>,
+    issues: Vec<String>,
+}
+
+/// Collect start offsets for every audio and video stream
+///
+/// MP4 edit lists aren't exposed by ffprobe, so they come from `tracks`, read
+/// from the `moov` box directly.
+pub fn analyze_start_offsets(json: &serde_json::Value, tracks: &[Mp4Track]) -> StartOffsetReport {
+    let Some(streams) = json["streams"].as_array() else {
+        return StartOffsetReport::default();
+    };
+
+    let streams: Vec<StreamOffsets> = streams
+        .iter()
+        .filter(|stream| matches!(stream["codec_type"].as_str(), Some("video" | "audio")))
+        .map(|stream| {
+            let edit_list = track_for_stream(tracks, stream)
+                .map(|track| track.edit_list.clone())
+                .unwrap_or_default();
+
+            StreamOffsets {
+                index: stream["index"].as_u64().unwrap_or(0),
+                codec_type: stream["codec_type"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                start_time: stream["start_time"]
+                    .as_str()
+                    .and_then(|time| time.parse().ok()),
+                initial_padding: stream["initial_padding"]
+                    .as_u64()
+                    .filter(|&padding| padding > 0),
+                edit_list,
+            }
+        })
+        .collect();
+
+    let issues = find_issues(&streams);
+    StartOffsetReport { streams, issues }
+}
+
+fn find_issues(streams: &[StreamOffsets]) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    let starts: Vec<f64> = streams.iter().filter_map(|s| s.start_time).collect();
+    if let (Some(min), Some(max)) = (
+        starts.iter().copied().reduce(f64::min),
+        starts.iter().copied().reduce(f64::max),
+    ) {
+        if max - min > START_SKEW_TOLERANCE {
+            issues.push(format!(
+                "Streams start {:.0} ms apart, which can show up as A/V offset in editors",
+                (max - min) * 1000.0
+            ));
+        }
+    }
+
+    for stream in streams {
+        let label = format!("Stream #{} ({})", stream.index, stream.codec_type);
+
+        if let Some(first) = stream.edit_list.first() {
+            match first.media_time {
+                None => issues.push(format!(
+                    "{} begins with an empty edit of {:.3}s, delaying it relative to other streams",
+                    label, first.segment_duration
+                )),
+                Some(media_time) if media_time > 0.0 => issues.push(format!(
+                    "{} edit list skips the first {:.3}s of media (encoder priming or a trim)",
+                    label, media_time
+                )),
+                _ => {}
+            }
+        }
+        let segments = stream
+            .edit_list
+            .iter()
+            .filter(|edit| edit.media_time.is_some())
+            .count();
+        if segments > 1 {
+            issues.push(format!(
+                "{} has {} edit segments; editors that ignore edit lists show the untrimmed media",
+                label, segments
+            ));
+        }
+        if stream.edit_list.iter().any(|edit| edit.rate != 1.0) {
+            issues.push(format!(
+                "{} has an edit with a non-standard playback rate",
+                label
+            ));
+        }
+
+        if let Some(padding) = stream.initial_padding {
+            issues.push(format!(
+                "{} declares {} samples of encoder delay",
+                label, padding
+            ));
+        }
+    }
+
+    issues
+}
@@ -0,0 +1,538 @@
+use std::time::Instant;
+
+use crate::audio::{parse_audio_streams, AudioStreamInfo};
+use crate::bitrate::{declared_bit_rate, measure_stream_bit_rates, overall_bit_rate};
+use crate::duration::{resolve_duration, DurationSource};
+use crate::layout::{main_video_stream, stream_layout, StreamLayout};
+use crate::programs::{keep_program_streams, parse_programs, select_program, ProgramInfo};
+use crate::runner::{MediaTool, MediaToolRunner};
+use crate::video::VideoStreamInfo;
+use crate::Error;
+
+/// Characters of unparseable ffprobe output kept in the log
+const LOG_PREVIEW_CHARS: usize = 512;
+
+/// Streams listed individually in the ffprobe log summary
+const LOG_SUMMARY_STREAMS: usize = 16;
+
+#[derive(Debug)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    pub duration: f64,
+    /// Where `duration` came from; anything but the container is a fallback
+    pub duration_source: DurationSource,
+    pub frame_rate: f64,
+    /// The frame rate as ffprobe declares it, e.g. 30000/1001
+    pub frame_rate_fraction: Option<Rational>,
+    pub bit_rate: f64,
+    /// `None` for audio-only files, whose video fields are left at zero
+    pub video_stream_index: Option<u64>,
+    pub video_stream: VideoStreamInfo,
+    pub video_bit_rate: Option<f64>,
+    pub video_bit_rate_measured: bool,
+    pub audio_streams: Vec<AudioStreamInfo>,
+    /// Which kinds of streams the file has
+    pub layout: StreamLayout,
+    /// Programs of a transport stream carrying more than one
+    pub programs: Vec<ProgramInfo>,
+    /// Program the fields above describe, when there was a choice
+    pub program: Option<u64>,
+    /// Raw ffprobe output, for analyses that need more than the fields above;
+    /// when a program was chosen, other programs' streams are left out
+    pub probe_json: serde_json::Value,
+}
+
+impl VideoInfo {
+    /// The ffprobe stream object of the main video stream
+    pub fn video_stream_json(&self) -> Option<&serde_json::Value> {
+        self.probe_json["streams"]
+            .as_array()?
+            .iter()
+            .find(|stream| {
+                self.video_stream_index
+                    .is_some_and(|index| stream["index"].as_u64() == Some(index))
+            })
+    }
+}
+
+/// Populate per-stream bit rates that the container doesn't declare (common
+/// in MKV) by summing packet sizes
+///
+/// Failures only lose the measured values, so they're logged rather than
+/// failing the whole inspection.
+pub async fn fill_missing_bit_rates(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    video_info: &mut VideoInfo,
+) {
+    let missing = (video_info.video_stream_index.is_some() && video_info.video_bit_rate.is_none())
+        || video_info
+            .audio_streams
+            .iter()
+            .any(|stream| stream.bit_rate().is_none());
+    if !missing {
+        return;
+    }
+
+    let measured = match measure_stream_bit_rates(runner, path, video_info.duration).await {
+        Ok(measured) => measured,
+        Err(e) => {
+            tracing::warn!(video_path = %path, error = %e, "Failed to measure stream bit rates");
+            return;
+        }
+    };
+
+    if video_info.video_bit_rate.is_none() {
+        if let Some(&rate) = video_info
+            .video_stream_index
+            .and_then(|index| measured.get(&index))
+        {
+            video_info.video_bit_rate = Some(rate);
+            video_info.video_bit_rate_measured = true;
+        }
+    }
+    for stream in &mut video_info.audio_streams {
+        if stream.bit_rate().is_none() {
+            if let Some(&rate) = measured.get(&stream.index()) {
+                stream.set_measured_bit_rate(rate);
+            }
+        }
+    }
+}
+
+/// Run ffprobe with JSON output and parse the result
+///
+/// `args` are inserted before the input path; `-v quiet -print_format json`
+/// are always added.
+pub async fn run_ffprobe_json(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    args: &[&str],
+) -> Result<serde_json::Value, Error> {
+    let mut ffprobe_args = vec!["-v", "quiet", "-print_format", "json"];
+    ffprobe_args.extend_from_slice(args);
+    ffprobe_args.push(path);
+    let output = runner.run(MediaTool::Ffprobe, &ffprobe_args).await?;
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| Error::ParseError(format!("Failed to parse ffprobe JSON: {}", e)))
+}
+
+/// How a file is probed beyond the defaults
+#[derive(Default)]
+pub struct ProbeOptions {
+    /// Already validated; passed to ffprobe after the runner's own
+    pub input_args: Vec<String>,
+    /// Program number of the program to describe, in a transport stream with
+    /// several; the first with video is used otherwise
+    pub program: Option<u64>,
+    /// Log the whole ffprobe output, which can be megabytes for files with
+    /// many streams
+    pub trace_output: bool,
+}
+
+/// Get video information with the given probe options
+pub async fn probe_video_info(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    options: &ProbeOptions,
+) -> Result<VideoInfo, Error> {
+    tracing::debug!(video_path = %path, "Getting video info with ffprobe");
+
+    let start = Instant::now();
+    // Use ffprobe to get video metadata in JSON format
+    let mut args: Vec<&str> = options.input_args.iter().map(String::as_str).collect();
+    args.extend([
+        "-v",
+        "quiet",
+        "-print_format",
+        "json",
+        "-show_format",
+        "-show_streams",
+        "-show_programs",
+        path,
+    ]);
+    let output = runner.run(MediaTool::Ffprobe, &args).await?;
+
+    let elapsed = start.elapsed();
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if options.trace_output {
+        tracing::debug!(video_path = %path, ffprobe_output = %stdout, "FFprobe raw output");
+    }
+
+    // Parse the JSON output
+    let mut json: serde_json::Value = serde_json::from_str(&stdout).map_err(|e| {
+        tracing::debug!(
+            video_path = %path,
+            output_preview = %truncate_for_log(&stdout, LOG_PREVIEW_CHARS),
+            "Unparseable ffprobe output"
+        );
+        Error::ParseError(format!("Failed to parse ffprobe JSON: {}", e))
+    })?;
+
+    tracing::debug!(
+        video_path = %path,
+        output_bytes = stdout.len(),
+        format = json["format"]["format_name"].as_str().unwrap_or("unknown"),
+        streams = %stream_summary(&json),
+        elapsed = ?elapsed,
+        "FFprobe finished"
+    );
+
+    // Narrow multi-program streams down to one program, so its video is used
+    let programs = parse_programs(&json);
+    let selected = if programs.len() > 1 || options.program.is_some() {
+        select_program(&programs, options.program)?.cloned()
+    } else {
+        None
+    };
+    if let Some(selected) = &selected {
+        tracing::debug!(
+            video_path = %path,
+            program = selected.program_number(),
+            programs = programs.len(),
+            "Selected program"
+        );
+        keep_program_streams(&mut json, selected);
+    }
+    let programs = if programs.len() > 1 {
+        programs
+    } else {
+        Vec::new()
+    };
+
+    if json["streams"].as_array().is_none() {
+        return Err(Error::ParseError(
+            "No streams found in ffprobe output".to_string(),
+        ));
+    }
+    // Audio-only and video-only files are fine, only files with neither fail
+    let layout = stream_layout(&json);
+    if !layout.has_video() && !layout.has_audio() {
+        return Err(Error::ParseError(
+            "No video or audio stream found".to_string(),
+        ));
+    }
+
+    // Extract video stream information; left empty for audio-only files
+    let video_stream = main_video_stream(&json);
+    let (width, height, frame_rate, frame_rate_fraction) = match video_stream {
+        Some(video_stream) => {
+            let width = video_stream["width"]
+                .as_u64()
+                .ok_or_else(|| Error::ParseError("Width not found".to_string()))?
+                as u32;
+            let height = video_stream["height"]
+                .as_u64()
+                .ok_or_else(|| Error::ParseError("Height not found".to_string()))?
+                as u32;
+
+            // Parse frame rate (can be a fraction like "30/1"); legacy
+            // containers may leave the real base rate at "0/0" and only have
+            // the average
+            let (frame_rate, fraction) = ["r_frame_rate", "avg_frame_rate"]
+                .iter()
+                .filter_map(|key| video_stream[*key].as_str())
+                .filter_map(|rate| Some((parse_fraction(rate).ok()?, rate)))
+                .find(|(rate, _)| rate.is_finite() && *rate > 0.0)
+                .ok_or_else(|| Error::ParseError("Frame rate not found".to_string()))?;
+            (width, height, frame_rate, Rational::parse(fraction))
+        }
+        None => (0, 0, 0.0, None),
+    };
+    let empty_stream = serde_json::Value::Null;
+    let video_stream_json = video_stream.unwrap_or(&empty_stream);
+
+    // Old AVI, OGM and RealMedia files may not declare a duration or bit rate
+    let (duration, duration_source) =
+        resolve_duration(runner, path, &json, video_stream_json, frame_rate).await?;
+    let bit_rate = overall_bit_rate(&json["format"], duration);
+
+    tracing::debug!(
+        video_path = %path,
+        width = width,
+        height = height,
+        duration = duration,
+        frame_rate = frame_rate,
+        bit_rate = bit_rate,
+        ?duration_source,
+        notes = ?layout.notes(),
+        "Successfully extracted video metadata"
+    );
+
+    let audio_streams = parse_audio_streams(&json);
+
+    Ok(VideoInfo {
+        width,
+        height,
+        duration,
+        duration_source,
+        frame_rate,
+        frame_rate_fraction,
+        bit_rate,
+        video_stream_index: video_stream.map(|stream| stream["index"].as_u64().unwrap_or(0)),
+        video_stream: VideoStreamInfo::from_stream(video_stream_json),
+        video_bit_rate: video_stream.and_then(declared_bit_rate),
+        video_bit_rate_measured: false,
+        audio_streams,
+        layout,
+        programs,
+        program: selected.map(|selected| selected.program_number()),
+        probe_json: json,
+    })
+}
+
+/// A frame rate kept exact, since 29.97 stands for 30000/1001
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rational {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl Rational {
+    /// Parse "30000/1001"; `None` for a zero denominator or anything else
+    pub fn parse(fraction: &str) -> Option<Self> {
+        let (numerator, denominator) = fraction.split_once('/')?;
+        let rational = Rational {
+            numerator: numerator.trim().parse().ok()?,
+            denominator: denominator.trim().parse().ok()?,
+        };
+        (rational.denominator != 0).then_some(rational)
+    }
+}
+
+/// Parse a fraction string like "30/1" to a float
+pub fn parse_fraction(fraction_str: &str) -> Result<f64, Error> {
+    let parts: Vec<&str> = fraction_str.split('/').collect();
+    if parts.len() != 2 {
+        return Err(Error::ParseError(format!(
+            "Invalid fraction format: {}",
+            fraction_str
+        )));
+    }
+
+    let numerator: f64 = parts[0]
+        .parse()
+        .map_err(|_| Error::ParseError(format!("Invalid numerator: {}", parts[0])))?;
+    let denominator: f64 = parts[1]
+        .parse()
+        .map_err(|_| Error::ParseError(format!("Invalid denominator: {}", parts[1])))?;
+
+    if denominator == 0.0 {
+        return Err(Error::ParseError(
+            "Division by zero in fraction".to_string(),
+        ));
+    }
+
+    Ok(numerator / denominator)
+}
+
+/// Compact `index:type/codec` list of the streams in a probe, for logs
+fn stream_summary(json: &serde_json::Value) -> String {
+    let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let mut summary: Vec<String> = streams
+        .iter()
+        .take(LOG_SUMMARY_STREAMS)
+        .map(|stream| {
+            format!(
+                "{}:{}/{}",
+                stream["index"],
+                stream["codec_type"].as_str().unwrap_or("?"),
+                stream["codec_name"].as_str().unwrap_or("?")
+            )
+        })
+        .collect();
+    if streams.len() > LOG_SUMMARY_STREAMS {
+        summary.push(format!("+{} more", streams.len() - LOG_SUMMARY_STREAMS));
+    }
+    summary.join(", ")
+}
+
+/// Shorten text for a log field, marking where it was cut
+fn truncate_for_log(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}… ({} bytes total)", &text[..end], text.len()),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::mock::MockRunner;
+    use crate::runner::ToolOutput;
+
+    const PROBE: &str = r#"{
+        "streams": [
+            {
+                "index": 0,
+                "codec_type": "video",
+                "codec_name": "h264",
+                "width": 1920,
+                "height": 1080,
+                "r_frame_rate": "30000/1001",
+                "avg_frame_rate": "30000/1001",
+                "disposition": {"default": 1}
+            },
+            {
+                "index": 1,
+                "codec_type": "audio",
+                "codec_name": "aac",
+                "channels": 2,
+                "bit_rate": "128000",
+                "disposition": {"default": 1}
+            }
+        ],
+        "format": {"format_name": "mov,mp4", "duration": "12.5", "bit_rate": "5000000"}
+    }"#;
+
+    #[tokio::test]
+    async fn probe_video_info_parses_ffprobe_output() {
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffprobe, PROBE);
+        let info = probe_video_info(&runner, "clip.mp4", &ProbeOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!((info.width, info.height), (1920, 1080));
+        assert_eq!(info.duration, 12.5);
+        assert_eq!(info.duration_source, DurationSource::Format);
+        assert_eq!(info.frame_rate_fraction, Rational::parse("30000/1001"));
+        assert_eq!(info.video_stream_index, Some(0));
+        assert_eq!(info.audio_streams.len(), 1);
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].1.last().map(String::as_str), Some("clip.mp4"));
+    }
+
+    #[tokio::test]
+    async fn probe_video_info_passes_input_args_first() {
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffprobe, PROBE);
+        let options = ProbeOptions {
+            input_args: vec!["-probesize".to_string(), "50M".to_string()],
+            ..ProbeOptions::default()
+        };
+        probe_video_info(&runner, "clip.mp4", &options)
+            .await
+            .unwrap();
+
+        assert_eq!(runner.calls()[0].1[..2], ["-probesize", "50M"]);
+    }
+
+    #[tokio::test]
+    async fn probe_video_info_measures_packets_without_declared_duration() {
+        let probe = PROBE.replace(r#", "duration": "12.5""#, "");
+        let runner = MockRunner::new()
+            .with_output(
+                MediaTool::Ffprobe,
+                Some("-show_programs"),
+                ToolOutput::success(&probe),
+            )
+            .with_output(
+                MediaTool::Ffprobe,
+                Some("packet=pts_time,dts_time,duration_time"),
+                ToolOutput::success(
+                    "pts_time=0.0|duration_time=0.5\npts_time=9.5|duration_time=0.5\n",
+                ),
+            );
+        let info = probe_video_info(&runner, "clip.avi", &ProbeOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(info.duration, 10.0);
+        assert_eq!(info.duration_source, DurationSource::Packets);
+    }
+
+    #[tokio::test]
+    async fn probe_video_info_reports_ffprobe_failure() {
+        let runner = MockRunner::new().with_output(
+            MediaTool::Ffprobe,
+            None,
+            ToolOutput::failure("clip.mp4: Invalid data found when processing input"),
+        );
+        let error = probe_video_info(&runner, "clip.mp4", &ProbeOptions::default())
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(&error, Error::FFmpegError(detail) if detail.contains("Invalid data")),
+            "{error:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_video_info_rejects_bad_json() {
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffprobe, "{\"streams\": [");
+        let error = probe_video_info(&runner, "clip.mp4", &ProbeOptions::default())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::ParseError(_)), "{error:?}");
+    }
+
+    #[tokio::test]
+    async fn probe_video_info_rejects_files_without_streams() {
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffprobe, r#"{"streams": []}"#);
+        let error = probe_video_info(&runner, "notes.txt", &ProbeOptions::default())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, Error::ParseError(_)), "{error:?}");
+    }
+
+    #[tokio::test]
+    async fn run_ffprobe_json_adds_output_options() {
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffprobe, r#"{"format": {}}"#);
+        let json = run_ffprobe_json(&runner, "clip.mp4", &["-show_format"])
+            .await
+            .unwrap();
+
+        assert!(json["format"].is_object());
+        assert_eq!(
+            runner.calls()[0].1,
+            [
+                "-v",
+                "quiet",
+                "-print_format",
+                "json",
+                "-show_format",
+                "clip.mp4"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn run_ffprobe_json_reports_failure_and_bad_json() {
+        let failing = MockRunner::new().with_output(
+            MediaTool::Ffprobe,
+            None,
+            ToolOutput::failure("No such file or directory"),
+        );
+        let error = run_ffprobe_json(&failing, "missing.mp4", &[])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::FFmpegError(detail) if detail.contains("No such file")),
+            "{error:?}"
+        );
+
+        let garbled = MockRunner::new().with_stdout(MediaTool::Ffprobe, "not json");
+        let error = run_ffprobe_json(&garbled, "clip.mp4", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::ParseError(_)), "{error:?}");
+    }
+}
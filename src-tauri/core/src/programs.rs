@@ -0,0 +1,105 @@
+use crate::tags::tag;
+use crate::Error;
+
+/// One program of a multi-program transport stream, as ffprobe lists it
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ProgramInfo {
+    /// Program number from the PAT, which `get_video_metadata` takes to pick
+    /// the program
+    program_number: u64,
+    pmt_pid: Option<u64>,
+    /// Channel name from the DVB service description, e.g. "BBC ONE HD"
+    service_name: Option<String>,
+    service_provider: Option<String>,
+    /// Indexes of the program's streams in the file
+    stream_indexes: Vec<u64>,
+    /// First video stream of the program; `None` for radio services
+    video_stream_index: Option<u64>,
+    /// e.g. "1920x1080 h264"
+    video_summary: Option<String>,
+}
+
+impl ProgramInfo {
+    pub fn program_number(&self) -> u64 {
+        self.program_number
+    }
+}
+
+/// Programs from ffprobe `-show_programs` output; empty for files that
+/// aren't transport streams
+pub fn parse_programs(json: &serde_json::Value) -> Vec<ProgramInfo> {
+    json["programs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|program| {
+            let streams = program["streams"].as_array()?;
+            let video = streams
+                .iter()
+                .find(|stream| stream["codec_type"].as_str() == Some("video"));
+            Some(ProgramInfo {
+                program_number: program["program_num"].as_u64()?,
+                pmt_pid: program["pmt_pid"].as_u64(),
+                service_name: tag(program, "service_name"),
+                service_provider: tag(program, "service_provider"),
+                stream_indexes: streams
+                    .iter()
+                    .filter_map(|stream| stream["index"].as_u64())
+                    .collect(),
+                video_stream_index: video.and_then(|stream| stream["index"].as_u64()),
+                video_summary: video.map(|stream| {
+                    format!(
+                        "{}x{} {}",
+                        stream["width"].as_u64().unwrap_or(0),
+                        stream["height"].as_u64().unwrap_or(0),
+                        stream["codec_name"].as_str().unwrap_or("unknown")
+                    )
+                }),
+            })
+        })
+        .collect()
+}
+
+/// The program an inspection describes: `requested`, or else the first
+/// program with video
+///
+/// `None` when the file has no programs or none of them has video.
+pub fn select_program(
+    programs: &[ProgramInfo],
+    requested: Option<u64>,
+) -> Result<Option<&ProgramInfo>, Error> {
+    match requested {
+        Some(number) => programs
+            .iter()
+            .find(|program| program.program_number == number)
+            .map(Some)
+            .ok_or_else(|| {
+                let available: Vec<String> = programs
+                    .iter()
+                    .map(|program| program.program_number.to_string())
+                    .collect();
+                Error::ParseError(format!(
+                    "Program {} not found, the file has programs [{}]",
+                    number,
+                    available.join(", ")
+                ))
+            }),
+        None => Ok(programs
+            .iter()
+            .find(|program| program.video_stream_index.is_some())),
+    }
+}
+
+/// Drop the streams of other programs from the ffprobe output
+///
+/// Stream indexes are kept, so they still address the same streams in
+/// ffmpeg `-map` arguments; the `programs` list stays complete.
+pub fn keep_program_streams(json: &mut serde_json::Value, program: &ProgramInfo) {
+    if let Some(streams) = json["streams"].as_array_mut() {
+        streams.retain(|stream| {
+            stream["index"]
+                .as_u64()
+                .is_some_and(|index| program.stream_indexes.contains(&index))
+        });
+    }
+}
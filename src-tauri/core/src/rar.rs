@@ -0,0 +1,211 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::Error;
+
+const RAR4_SIGNATURE: &[u8] = b"Rar!\x1A\x07\x00";
+const RAR5_SIGNATURE: &[u8] = b"Rar!\x1A\x07\x01\x00";
+
+/// Headers read before giving up, so a damaged archive can't loop forever
+const MAX_HEADERS: usize = 100_000;
+
+/// A file stored in a RAR archive
+#[derive(Clone, Debug)]
+pub struct RarEntry {
+    /// Path inside the archive, with `/` separators
+    pub name: String,
+    pub size: u64,
+    /// Byte offset of the entry's data in the archive
+    pub data_offset: u64,
+    /// Stored without compression in this volume, so the data can be copied out
+    pub stored: bool,
+}
+
+/// List the files of a RAR 4 or RAR 5 archive from its headers
+///
+/// Only headers are read; archives with encrypted headers can't be listed.
+pub fn list_rar(path: &Path) -> Result<Vec<RarEntry>, Error> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut signature = [0u8; 8];
+    file.read_exact(&mut signature)?;
+    if signature.starts_with(RAR5_SIGNATURE) {
+        list_rar5(&mut file)
+    } else if signature.starts_with(RAR4_SIGNATURE) {
+        file.seek(SeekFrom::Start(RAR4_SIGNATURE.len() as u64))?;
+        list_rar4(&mut file)
+    } else {
+        Err(Error::ParseError("Not a RAR archive".to_string()))
+    }
+}
+
+fn list_rar5(file: &mut BufReader<File>) -> Result<Vec<RarEntry>, Error> {
+    let mut entries = Vec::new();
+    let mut offset = RAR5_SIGNATURE.len() as u64;
+    for _ in 0..MAX_HEADERS {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut crc = [0u8; 4];
+        if file.read_exact(&mut crc).is_err() {
+            break;
+        }
+        let (header_size, size_length) = read_vint(file)?;
+        let mut header = vec![0u8; header_size as usize];
+        file.read_exact(&mut header)?;
+        let data_offset = offset + 4 + size_length + header_size;
+
+        let mut fields = header.as_slice();
+        let header_type = take_vint(&mut fields)?;
+        let flags = take_vint(&mut fields)?;
+        if flags & 0x01 != 0 {
+            take_vint(&mut fields)?;
+        }
+        let data_size = if flags & 0x02 != 0 {
+            take_vint(&mut fields)?
+        } else {
+            0
+        };
+
+        match header_type {
+            // File
+            2 => {
+                let file_flags = take_vint(&mut fields)?;
+                let size = take_vint(&mut fields)?;
+                take_vint(&mut fields)?; // attributes
+                if file_flags & 0x02 != 0 {
+                    take_bytes(&mut fields, 4)?; // modification time
+                }
+                if file_flags & 0x04 != 0 {
+                    take_bytes(&mut fields, 4)?; // data CRC
+                }
+                let compression = take_vint(&mut fields)?;
+                take_vint(&mut fields)?; // host OS
+                let name_length = take_vint(&mut fields)? as usize;
+                let name = String::from_utf8_lossy(take_bytes(&mut fields, name_length)?);
+                // Data continuing from or into another volume can't be copied
+                let split = flags & 0x18 != 0;
+                if file_flags & 0x01 == 0 {
+                    entries.push(RarEntry {
+                        name: name.replace('\\', "/"),
+                        size,
+                        data_offset,
+                        stored: (compression >> 7) & 0x07 == 0 && !split,
+                    });
+                }
+            }
+            // Archive encryption: everything after is unreadable
+            4 => {
+                return Err(Error::ParseError(
+                    "RAR archive headers are encrypted".to_string(),
+                ))
+            }
+            // End of archive
+            5 => break,
+            _ => {}
+        }
+        offset = data_offset + data_size;
+    }
+    Ok(entries)
+}
+
+fn list_rar4(file: &mut BufReader<File>) -> Result<Vec<RarEntry>, Error> {
+    let mut entries = Vec::new();
+    let mut offset = RAR4_SIGNATURE.len() as u64;
+    for _ in 0..MAX_HEADERS {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut block = [0u8; 7];
+        if file.read_exact(&mut block).is_err() {
+            break;
+        }
+        let block_type = block[2];
+        let flags = u16::from_le_bytes([block[3], block[4]]);
+        let header_size = u16::from_le_bytes([block[5], block[6]]) as u64;
+        if header_size < 7 {
+            return Err(Error::ParseError("Invalid RAR block header".to_string()));
+        }
+        let mut header = vec![0u8; header_size as usize - 7];
+        file.read_exact(&mut header)?;
+        let mut fields = header.as_slice();
+        let mut data_size = if flags & 0x8000 != 0 {
+            take_u32(&mut fields)? as u64
+        } else {
+            0
+        };
+
+        match block_type {
+            // Main header; 0x80 means the headers are encrypted
+            0x73 if flags & 0x80 != 0 => {
+                return Err(Error::ParseError(
+                    "RAR archive headers are encrypted".to_string(),
+                ))
+            }
+            // File
+            0x74 => {
+                let mut size = take_u32(&mut fields)? as u64;
+                take_bytes(&mut fields, 10)?; // host OS, CRC, time, version
+                let method = take_bytes(&mut fields, 1)?[0];
+                let name_length =
+                    u16::from_le_bytes(take_bytes(&mut fields, 2)?.try_into().unwrap());
+                take_bytes(&mut fields, 4)?; // attributes
+                if flags & 0x100 != 0 {
+                    data_size |= (take_u32(&mut fields)? as u64) << 32;
+                    size |= (take_u32(&mut fields)? as u64) << 32;
+                }
+                let name = take_bytes(&mut fields, name_length as usize)?;
+                // Unicode names follow the legacy name after a NUL; the legacy one will do
+                let name = name.split(|&byte| byte == 0).next().unwrap_or_default();
+                let is_dir = flags & 0xE0 == 0xE0;
+                let split = flags & 0x03 != 0;
+                if !is_dir {
+                    entries.push(RarEntry {
+                        name: String::from_utf8_lossy(name).replace('\\', "/"),
+                        size,
+                        data_offset: offset + header_size,
+                        stored: method == 0x30 && !split,
+                    });
+                }
+            }
+            // End of archive
+            0x7B => break,
+            _ => {}
+        }
+        offset += header_size + data_size;
+    }
+    Ok(entries)
+}
+
+/// Read a RAR 5 variable-length integer, returning it and its length in bytes
+fn read_vint(reader: &mut impl Read) -> Result<(u64, u64), Error> {
+    let mut value = 0u64;
+    for index in 0..10 {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7F) as u64) << (7 * index);
+        if byte[0] & 0x80 == 0 {
+            return Ok((value, index + 1));
+        }
+    }
+    Err(Error::ParseError(
+        "Invalid RAR variable-length integer".to_string(),
+    ))
+}
+
+fn take_vint(fields: &mut &[u8]) -> Result<u64, Error> {
+    Ok(read_vint(fields)?.0)
+}
+
+fn take_u32(fields: &mut &[u8]) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(
+        take_bytes(fields, 4)?.try_into().unwrap(),
+    ))
+}
+
+fn take_bytes<'a>(fields: &mut &'a [u8], length: usize) -> Result<&'a [u8], Error> {
+    if fields.len() < length {
+        return Err(Error::ParseError("Truncated RAR header".to_string()));
+    }
+    let (bytes, rest) = fields.split_at(length);
+    *fields = rest;
+    Ok(bytes)
+}
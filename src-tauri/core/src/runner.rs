@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+
+use crate::Error;
+
+/// The bundled command line tools
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaTool {
+    Ffprobe,
+    Ffmpeg,
+}
+
+impl MediaTool {
+    /// Executable name, which is also the sidecar name in the app
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaTool::Ffprobe => "ffprobe",
+            MediaTool::Ffmpeg => "ffmpeg",
+        }
+    }
+}
+
+/// What a finished tool run printed
+#[derive(Clone, Debug, Default)]
+pub struct ToolOutput {
+    pub success: bool,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// Runs ffprobe and ffmpeg to completion
+///
+/// The app runs the bundled sidecars; code taking a `&dyn MediaToolRunner`
+/// can be given canned output instead, so parsing doesn't need a running app
+/// or the binaries.
+#[async_trait]
+pub trait MediaToolRunner: Send + Sync {
+    async fn run(&self, tool: MediaTool, args: &[&str]) -> Result<ToolOutput, Error>;
+}
+
+/// Runner replaying canned output, for unit tests
+#[cfg(test)]
+pub mod mock {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Answers each call with the first canned output registered for the tool
+    /// whose marker argument is present; records every call
+    #[derive(Default)]
+    pub struct MockRunner {
+        outputs: Vec<(MediaTool, Option<String>, ToolOutput)>,
+        calls: Mutex<Vec<(MediaTool, Vec<String>)>>,
+    }
+
+    impl MockRunner {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Answer every call of `tool` with `stdout`
+        pub fn with_stdout(self, tool: MediaTool, stdout: &str) -> Self {
+            self.with_output(tool, None, ToolOutput::success(stdout))
+        }
+
+        /// Answer calls of `tool` that pass `marker` as an argument
+        pub fn with_output(
+            mut self,
+            tool: MediaTool,
+            marker: Option<&str>,
+            output: ToolOutput,
+        ) -> Self {
+            self.outputs
+                .push((tool, marker.map(str::to_string), output));
+            self
+        }
+
+        /// Arguments of every call so far
+        pub fn calls(&self) -> Vec<(MediaTool, Vec<String>)> {
+            self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+        }
+    }
+
+    impl ToolOutput {
+        pub fn success(stdout: &str) -> Self {
+            Self {
+                success: true,
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            }
+        }
+
+        pub fn failure(stderr: &str) -> Self {
+            Self {
+                success: false,
+                stdout: Vec::new(),
+                stderr: stderr.as_bytes().to_vec(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MediaToolRunner for MockRunner {
+        async fn run(&self, tool: MediaTool, args: &[&str]) -> Result<ToolOutput, Error> {
+            self.calls
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((tool, args.iter().map(|arg| arg.to_string()).collect()));
+            self.outputs
+                .iter()
+                .find(|(output_tool, marker, _)| {
+                    *output_tool == tool
+                        && marker
+                            .as_deref()
+                            .is_none_or(|marker| args.contains(&marker))
+                })
+                .map(|(_, _, output)| output.clone())
+                .ok_or_else(|| {
+                    Error::FFmpegError(format!("No canned {} output for {:?}", tool.name(), args))
+                })
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::Instant;
+
+use crate::runner::{MediaTool, MediaToolRunner};
+use crate::Error;
+
+/// Scene score above which ffmpeg's `scene` metric is treated as a cut
+const SCENE_THRESHOLD: f64 = 0.3;
+
+/// Scenes shorter than this are ignored when placing thumbnails (flashes,
+/// quick cuts in trailers)
+const MIN_SCENE_SECS: f64 = 1.0;
+
+/// A detected cut between two scenes
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct SceneChange {
+    /// Timestamp of the first frame of the new scene, in seconds
+    pub time: f64,
+    /// ffmpeg scene score (0.0 - 1.0)
+    pub score: f64,
+}
+
+/// Run ffmpeg scene detection over the whole file
+///
+/// Frames are downscaled before scoring since the scene metric doesn't need
+/// full resolution, which keeps this pass reasonably fast on large files.
+pub async fn detect_scenes(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+) -> Result<Vec<SceneChange>, Error> {
+    tracing::debug!(video_path = %path, "Running scene detection");
+
+    let start = Instant::now();
+    let filter = format!(
+        "scale=320:-2,select='gt(scene,{})',metadata=print",
+        SCENE_THRESHOLD
+    );
+    let output = runner
+        .run(
+            MediaTool::Ffmpeg,
+            &[
+                "-hide_banner",
+                "-i",
+                path,
+                "-an",
+                "-sn",
+                "-vf",
+                &filter,
+                "-f",
+                "null",
+                "-",
+            ],
+        )
+        .await?;
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg scene detection failed: {}",
+            stderr
+        )));
+    }
+
+    let scene_changes = parse_scene_changes(&String::from_utf8_lossy(&output.stderr));
+
+    tracing::debug!(
+        video_path = %path,
+        scene_changes = scene_changes.len(),
+        elapsed = ?start.elapsed(),
+        "Scene detection finished"
+    );
+
+    Ok(scene_changes)
+}
+
+/// Parse `metadata=print` output, which logs a `pts_time:` line for every
+/// selected frame followed by its `lavfi.scene_score=` value
+fn parse_scene_changes(stderr: &str) -> Vec<SceneChange> {
+    let mut scene_changes = Vec::new();
+    let mut pending_time: Option<f64> = None;
+
+    for line in stderr.lines() {
+        if let Some(rest) = line.split("pts_time:").nth(1) {
+            pending_time = rest
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse().ok());
+        } else if let Some(rest) = line.split("lavfi.scene_score=").nth(1) {
+            if let (Some(time), Ok(score)) = (pending_time.take(), rest.trim().parse()) {
+                scene_changes.push(SceneChange { time, score });
+            }
+        }
+    }
+
+    scene_changes
+}
+
+/// Choose `count` thumbnail time points from the most representative scenes
+///
+/// The longest scenes are taken as the most representative and a thumbnail is
+/// placed in the middle of each. Returns `None` when there aren't enough usable
+/// scenes, so callers can fall back to fixed positions.
+pub fn representative_time_points(
+    scene_changes: &[SceneChange],
+    duration: f64,
+    count: usize,
+) -> Option<Vec<f64>> {
+    let mut boundaries = vec![0.0];
+    boundaries.extend(
+        scene_changes
+            .iter()
+            .map(|change| change.time)
+            .filter(|&time| time > 0.0 && time < duration),
+    );
+    boundaries.push(duration);
+
+    let mut scenes: Vec<(f64, f64)> = boundaries
+        .windows(2)
+        .map(|bounds| (bounds[0], bounds[1]))
+        .filter(|(start, end)| end - start >= MIN_SCENE_SECS)
+        .collect();
+
+    if scenes.len() < count {
+        return None;
+    }
+
+    scenes.sort_by(|a, b| (b.1 - b.0).total_cmp(&(a.1 - a.0)));
+
+    let mut time_points: Vec<f64> = scenes
+        .iter()
+        .take(count)
+        .map(|(start, end)| (start + end) / 2.0)
+        .collect();
+    time_points.sort_by(f64::total_cmp);
+
+    Some(time_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::mock::MockRunner;
+    use crate::runner::ToolOutput;
+
+    const SCENE_LOG: &str = "\
+frame:0    pts:1200   pts_time:4.8
+lavfi.scene_score=0.412000
+frame:1    pts:4000   pts_time:16
+lavfi.scene_score=0.873100
+frame:2    pts:4010   pts_time:16.04
+lavfi.scene_score=garbled
+[out#0/null @ 0x0] video:0kB audio:0kB
+";
+
+    #[test]
+    fn parses_scene_changes() {
+        let changes = parse_scene_changes(SCENE_LOG);
+        let parsed: Vec<(f64, f64)> = changes
+            .iter()
+            .map(|change| (change.time, change.score))
+            .collect();
+        assert_eq!(parsed, [(4.8, 0.412), (16.0, 0.8731)]);
+        assert!(parse_scene_changes("lavfi.scene_score=0.5\n").is_empty());
+    }
+
+    #[tokio::test]
+    async fn detect_scenes_reads_ffmpeg_log() {
+        let runner = MockRunner::new().with_output(
+            MediaTool::Ffmpeg,
+            None,
+            ToolOutput {
+                success: true,
+                stdout: Vec::new(),
+                stderr: SCENE_LOG.as_bytes().to_vec(),
+            },
+        );
+        let changes = detect_scenes(&runner, "clip.mp4").await.unwrap();
+
+        assert_eq!(changes.len(), 2);
+        let (tool, args) = &runner.calls()[0];
+        assert_eq!(*tool, MediaTool::Ffmpeg);
+        assert!(args
+            .iter()
+            .any(|arg| arg.contains("select='gt(scene,0.3)'")));
+    }
+
+    #[tokio::test]
+    async fn detect_scenes_reports_ffmpeg_failure() {
+        let runner = MockRunner::new().with_output(
+            MediaTool::Ffmpeg,
+            None,
+            ToolOutput::failure("Invalid data found when processing input"),
+        );
+        let error = detect_scenes(&runner, "clip.mp4").await.unwrap_err();
+
+        assert!(
+            matches!(&error, Error::FFmpegError(detail) if detail.contains("Invalid data")),
+            "{error:?}"
+        );
+    }
+
+    #[test]
+    fn places_thumbnails_in_longest_scenes() {
+        let changes = [4.0, 4.5, 20.0, 26.0, 40.0].map(|time| SceneChange { time, score: 0.5 });
+        let time_points = representative_time_points(&changes, 60.0, 3).unwrap();
+
+        // 0-4, 4.5-20, 20-26, 26-40 and 40-60 are long enough; 4-4.5 isn't
+        assert_eq!(time_points, [12.25, 33.0, 50.0]);
+    }
+
+    #[test]
+    fn falls_back_without_enough_scenes() {
+        let changes = [SceneChange {
+            time: 30.0,
+            score: 0.9,
+        }];
+        assert!(representative_time_points(&changes, 60.0, 4).is_none());
+        assert!(representative_time_points(&[], 0.5, 1).is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use std::{fs::File, io::Read};
+
+use crate::Error;
+
+/// Number of leading bytes read for classification
+///
+/// Large enough to see three MPEG-TS packets, which is what distinguishes a
+/// transport stream from a file that happens to start with 0x47.
+const SNIFF_LEN: usize = 512;
+
+/// Size of an MPEG-TS packet
+const TS_PACKET_SIZE: usize = 188;
+
+/// What the first bytes of a file say about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// A media container ffprobe should be able to read
+    Media(&'static str),
+    /// A well-known non-media format
+    NotMedia(&'static str),
+    /// No known signature; left for ffprobe to decide
+    Unknown,
+}
+
+/// Classify a file by its magic bytes
+pub fn sniff_file(path: &str) -> Result<FileKind, Error> {
+    let mut file = File::open(path)?;
+    let mut header = Vec::with_capacity(SNIFF_LEN);
+    file.by_ref()
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut header)?;
+
+    if header.is_empty() {
+        return Ok(FileKind::NotMedia("an empty file"));
+    }
+    Ok(classify(&header))
+}
+
+/// Fail early for inputs that clearly aren't media files
+pub fn ensure_media_file(path: &str) -> Result<FileKind, Error> {
+    let kind = sniff_file(path)?;
+    tracing::debug!(video_path = %path, kind = ?kind, "Sniffed file type");
+
+    if let FileKind::NotMedia(description) = kind {
+        return Err(Error::NotMediaFile(format!(
+            "{} looks like {}",
+            path, description
+        )));
+    }
+    Ok(kind)
+}
+
+fn classify(header: &[u8]) -> FileKind {
+    let starts_with = |magic: &[u8]| header.starts_with(magic);
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+
+    // Media containers
+    if starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return FileKind::Media("matroska");
+    }
+    if [b"ftyp", b"moov", b"mdat", b"free", b"wide", b"skip"]
+        .iter()
+        .any(|atom| at(4, *atom))
+    {
+        return FileKind::Media("mp4");
+    }
+    if starts_with(b"RIFF") {
+        if at(8, b"AVI ") {
+            return FileKind::Media("avi");
+        }
+        if at(8, b"WAVE") {
+            return FileKind::Media("wav");
+        }
+        if at(8, b"WEBP") {
+            return FileKind::NotMedia("a WebP image");
+        }
+    }
+    if is_transport_stream(header, 0) {
+        return FileKind::Media("mpegts");
+    }
+    if is_transport_stream(header, 4) {
+        return FileKind::Media("m2ts");
+    }
+    if starts_with(&[0x00, 0x00, 0x01, 0xBA]) {
+        return FileKind::Media("mpeg");
+    }
+    if starts_with(b"FLV") {
+        return FileKind::Media("flv");
+    }
+    if starts_with(&[0x30, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11]) {
+        return FileKind::Media("asf");
+    }
+    if starts_with(b"OggS") {
+        return FileKind::Media("ogg");
+    }
+    if starts_with(b".RMF") {
+        return FileKind::Media("rm");
+    }
+    if starts_with(b"fLaC") {
+        return FileKind::Media("flac");
+    }
+    if starts_with(b"ID3") || (header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0) {
+        return FileKind::Media("mp3");
+    }
+    if starts_with(b"#EXTM3U") {
+        return FileKind::Media("hls");
+    }
+    // Text formats ffprobe opens as inputs, which the text check below would
+    // otherwise reject
+    if starts_with(b"ffconcat version") {
+        return FileKind::Media("ffconcat");
+    }
+    if starts_with(b"v=0") {
+        return FileKind::Media("sdp");
+    }
+    if is_dash_manifest(header) {
+        return FileKind::Media("dash");
+    }
+
+    // Common things people drop by accident
+    if starts_with(b"PK\x03\x04") || starts_with(b"PK\x05\x06") {
+        return FileKind::NotMedia("a ZIP archive");
+    }
+    if starts_with(b"Rar!\x1A\x07") {
+        return FileKind::NotMedia("a RAR archive");
+    }
+    if starts_with(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) {
+        return FileKind::NotMedia("a 7z archive");
+    }
+    if starts_with(&[0x1F, 0x8B]) {
+        return FileKind::NotMedia("a gzip archive");
+    }
+    if starts_with(b"%PDF") {
+        return FileKind::NotMedia("a PDF document");
+    }
+    if starts_with(b"MZ") {
+        return FileKind::NotMedia("a Windows executable");
+    }
+    if starts_with(b"\x7FELF") {
+        return FileKind::NotMedia("an ELF executable");
+    }
+    if [
+        [0xFE, 0xED, 0xFA, 0xCE],
+        [0xFE, 0xED, 0xFA, 0xCF],
+        [0xCE, 0xFA, 0xED, 0xFE],
+        [0xCF, 0xFA, 0xED, 0xFE],
+    ]
+    .iter()
+    .any(|magic| starts_with(magic))
+    {
+        return FileKind::NotMedia("a macOS executable");
+    }
+    if starts_with(b"\x89PNG") {
+        return FileKind::NotMedia("a PNG image");
+    }
+    if starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return FileKind::NotMedia("a JPEG image");
+    }
+    if starts_with(b"GIF8") {
+        return FileKind::NotMedia("a GIF image");
+    }
+    if is_text(header) {
+        return FileKind::NotMedia("a text file");
+    }
+
+    FileKind::Unknown
+}
+
+/// Sync bytes at the start of the first three packets
+///
+/// M2TS prefixes every packet with a 4-byte timecode, so its sync bytes start
+/// at `offset` 4 and repeat every 192 bytes.
+fn is_transport_stream(header: &[u8], offset: usize) -> bool {
+    let packet_size = TS_PACKET_SIZE + offset;
+    (0..3).all(|n| header.get(offset + n * packet_size) == Some(&0x47))
+}
+
+/// An MPEG-DASH manifest, whose `<MPD` root may follow an XML declaration,
+/// a byte order mark or comments
+fn is_dash_manifest(header: &[u8]) -> bool {
+    let header = header.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(header);
+    let text = header.trim_ascii_start();
+    (text.starts_with(b"<?xml") || text.starts_with(b"<!--") || text.starts_with(b"<MPD"))
+        && header.windows(4).any(|window| window == b"<MPD")
+}
+
+/// Whether the header is printable UTF-8 without control characters
+fn is_text(header: &[u8]) -> bool {
+    // A multi-byte character may be cut off at the end of the sample
+    let text = match std::str::from_utf8(header) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&header[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+}
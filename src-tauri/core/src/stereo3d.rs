@@ -0,0 +1,175 @@
+use crate::frame_stats::LumaStats;
+
+/// Width over height of full-resolution side-by-side frames, e.g. 3840x1080
+const FULL_SIDE_BY_SIDE_ASPECT: std::ops::RangeInclusive<f64> = 3.4..=3.7;
+
+/// Width over height of full-resolution top-and-bottom frames, e.g.
+/// 1920x2160; narrow enough to leave portrait phone video out
+const FULL_TOP_BOTTOM_ASPECT: std::ops::RangeInclusive<f64> = 0.85..=0.92;
+
+/// Luma correlation between the two halves of a frame above which they are
+/// taken to be the two eyes of the same picture
+const EYE_CORRELATION: f64 = 0.85;
+
+/// Halves flatter than this correlate for any content, e.g. black frames
+const MIN_EYE_STDDEV: f64 = 12.0;
+
+/// How the two views of stereoscopic video are stored
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoLayout {
+    SideBySide,
+    TopBottom,
+    /// H.264 MVC; the base view decodes as plain 2D
+    Mvc,
+    /// Frame-sequential, checkerboard, interleaved lines or anaglyph; shown
+    /// as is
+    Other,
+}
+
+/// Where the layout was found
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StereoSource {
+    /// Stereo 3D side data, the Matroska `stereo_mode` or the codec profile
+    Metadata,
+    /// Frame dimensions only frame-packed video has
+    Aspect,
+}
+
+/// Stereoscopic 3D layout of a video stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Stereo3d {
+    pub layout: StereoLayout,
+    /// Each view is squeezed to half the width or height ("half SBS/OU")
+    pub half: bool,
+    pub source: StereoSource,
+}
+
+impl Stereo3d {
+    /// Detect 3D from an ffprobe stream object; `None` for 2D video
+    pub fn from_stream(stream: &serde_json::Value) -> Option<Self> {
+        let width = stream["width"].as_f64().unwrap_or(0.0);
+        let height = stream["height"].as_f64().unwrap_or(0.0);
+        let aspect = if height > 0.0 { width / height } else { 0.0 };
+
+        let from_metadata = side_data_layout(stream)
+            .or_else(|| stereo_mode_layout(stream))
+            .or_else(|| {
+                let profile = stream["profile"].as_str().unwrap_or_default();
+                (profile.contains("Stereo High") || profile.contains("Multiview High"))
+                    .then_some(StereoLayout::Mvc)
+            });
+        if let Some(layout) = from_metadata {
+            return Some(Stereo3d {
+                layout,
+                half: match layout {
+                    StereoLayout::SideBySide => !FULL_SIDE_BY_SIDE_ASPECT.contains(&aspect),
+                    StereoLayout::TopBottom => !FULL_TOP_BOTTOM_ASPECT.contains(&aspect),
+                    _ => false,
+                },
+                source: StereoSource::Metadata,
+            });
+        }
+
+        let layout = if FULL_SIDE_BY_SIDE_ASPECT.contains(&aspect) {
+            StereoLayout::SideBySide
+        } else if FULL_TOP_BOTTOM_ASPECT.contains(&aspect) {
+            StereoLayout::TopBottom
+        } else {
+            return None;
+        };
+        Some(Stereo3d {
+            layout,
+            half: false,
+            source: StereoSource::Aspect,
+        })
+    }
+
+    /// ffmpeg filter keeping the left (or top) view at its display shape,
+    /// so previews don't show doubled images
+    pub fn single_eye_filter(&self) -> Option<&'static str> {
+        match (self.layout, self.half) {
+            (StereoLayout::SideBySide, false) => Some("crop=iw/2:ih:0:0"),
+            (StereoLayout::SideBySide, true) => Some("crop=iw/2:ih:0:0,scale=iw*2:ih,setsar=1"),
+            (StereoLayout::TopBottom, false) => Some("crop=iw:ih/2:0:0"),
+            (StereoLayout::TopBottom, true) => Some("crop=iw:ih/2:0:0,scale=iw:ih*2,setsar=1"),
+            _ => None,
+        }
+    }
+}
+
+/// Frame-packed layout of a frame whose halves show nearly the same
+/// picture, as in half side-by-side video without any 3D flag
+pub fn frame_packing(image: &image::GrayImage) -> Option<StereoLayout> {
+    let (width, height) = image.dimensions();
+    let (half_width, half_height) = (width / 2, height / 2);
+    if half_width == 0 || half_height == 0 {
+        return None;
+    }
+    let crop = |x, y, w, h| image::imageops::crop_imm(image, x, y, w, h).to_image();
+
+    let side_by_side = eye_correlation(
+        &crop(0, 0, half_width, height),
+        &crop(half_width, 0, half_width, height),
+    );
+    let top_bottom = eye_correlation(
+        &crop(0, 0, width, half_height),
+        &crop(0, half_height, width, half_height),
+    );
+    match (side_by_side, top_bottom) {
+        (Some(sbs), tb) if sbs >= EYE_CORRELATION && sbs >= tb.unwrap_or(0.0) => {
+            Some(StereoLayout::SideBySide)
+        }
+        (_, Some(tb)) if tb >= EYE_CORRELATION => Some(StereoLayout::TopBottom),
+        _ => None,
+    }
+}
+
+/// Pearson correlation of two equally sized images; `None` when either is
+/// too flat to tell
+fn eye_correlation(a: &image::GrayImage, b: &image::GrayImage) -> Option<f64> {
+    let (stats_a, stats_b) = (LumaStats::from_luma(a)?, LumaStats::from_luma(b)?);
+    if stats_a.stddev < MIN_EYE_STDDEV || stats_b.stddev < MIN_EYE_STDDEV {
+        return None;
+    }
+    let count = a.pixels().len() as f64;
+    let covariance = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|(pa, pb)| (pa.0[0] as f64 - stats_a.mean) * (pb.0[0] as f64 - stats_b.mean))
+        .sum::<f64>()
+        / count;
+    Some(covariance / (stats_a.stddev * stats_b.stddev))
+}
+
+/// Layout from ffprobe's `Stereo 3D` side data, e.g. `"type": "side by side"`
+fn side_data_layout(stream: &serde_json::Value) -> Option<StereoLayout> {
+    let side_data = stream["side_data_list"]
+        .as_array()?
+        .iter()
+        .find(|side_data| side_data["side_data_type"].as_str() == Some("Stereo 3D"))?;
+    match side_data["type"].as_str()? {
+        "2D" => None,
+        kind if kind.starts_with("side by side") => Some(StereoLayout::SideBySide),
+        "top and bottom" => Some(StereoLayout::TopBottom),
+        _ => Some(StereoLayout::Other),
+    }
+}
+
+/// Layout from the Matroska `stereo_mode` tag, e.g. `left_right`
+fn stereo_mode_layout(stream: &serde_json::Value) -> Option<StereoLayout> {
+    let mode = stream["tags"]
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("stereo_mode"))?
+        .1
+        .as_str()?;
+    match mode {
+        "mono" => None,
+        "left_right" | "right_left" => Some(StereoLayout::SideBySide),
+        "top_bottom" | "bottom_top" => Some(StereoLayout::TopBottom),
+        "block_lr" | "block_rl" => Some(StereoLayout::Mvc),
+        _ => Some(StereoLayout::Other),
+    }
+}
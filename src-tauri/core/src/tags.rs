@@ -0,0 +1,19 @@
+/// Tag by name, ignoring case; Matroska files usually have upper case tag names
+pub fn tag(value: &serde_json::Value, name: &str) -> Option<String> {
+    value["tags"]
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Case-insensitive tag lookup (tag key casing varies between containers)
+pub fn tag_value<'a>(tags: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    tags.as_object()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .and_then(|(_, v)| v.as_str())
+}
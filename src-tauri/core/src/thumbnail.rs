@@ -0,0 +1,406 @@
+use base64::{engine::general_purpose, Engine};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::alpha::checkerboard_filter;
+use crate::frame_content::ContentHints;
+use crate::frame_stats::LumaStats;
+use crate::probe::VideoInfo;
+use crate::runner::{MediaTool, MediaToolRunner};
+use crate::Error;
+
+/// Number of frames the `thumbnail` filter looks at around each target
+/// timestamp before picking the most representative one
+const THUMBNAIL_FILTER_WINDOW: u32 = 30;
+
+/// Offsets (as a fraction of the total duration) tried around each target
+/// point when the picked frame is still black or washed out
+const CANDIDATE_OFFSETS: [f64; 3] = [0.0, 0.03, -0.03];
+
+/// A generated thumbnail and the timestamp it was taken at
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct Thumbnail {
+    pub timestamp: f64,
+    pub data_url: String,
+    /// Faces and text found in the frame, when asked for
+    #[serde(default)]
+    pub hints: Option<ContentHints>,
+    /// Time taken to produce it, cache lookup included; not cached
+    #[serde(skip)]
+    pub elapsed_ms: u64,
+}
+
+/// Burn the source timestamp, and optionally the frame number, into the
+/// corner of each thumbnail
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct TimestampOverlay {
+    #[serde(default)]
+    pub frame_number: bool,
+    /// Font for ffmpeg builds without fontconfig
+    #[serde(default)]
+    pub font_file: Option<PathBuf>,
+}
+
+impl TimestampOverlay {
+    /// drawtext filter for a frame extracted with `-ss time_point`, which
+    /// restarts timestamps at zero
+    fn filter(&self, time_point: f64, frame_rate: f64) -> String {
+        // Same rounding as the seek in `extract_frame`
+        let offset = format!("{:.2}", time_point);
+        let mut text = format!("%{{pts\\:hms\\:{}}}", offset);
+        if self.frame_number && frame_rate > 0.0 {
+            text.push_str(&format!("  #%{{eif\\:(t+{})*{}\\:d}}", offset, frame_rate));
+        }
+        let mut filter = format!(
+            "drawtext=text='{}':x=6:y=h-th-6:fontsize=14:fontcolor=white\
+             :box=1:boxcolor=black@0.6:boxborderw=3",
+            text
+        );
+        if let Some(font_file) = &self.font_file {
+            filter.push_str(&format!(":fontfile='{}'", filter_path(font_file)));
+        }
+        filter
+    }
+}
+
+/// Path usable inside a filtergraph: forward slashes, escaped drive colon
+fn filter_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "/")
+        .replace(':', "\\:")
+        .replace('\'', "")
+}
+
+/// The filter frames are extracted with, which may depend on their time
+pub struct FrameFilter {
+    base: String,
+    overlay: Option<TimestampOverlay>,
+    frame_rate: f64,
+    /// Index of the stream filtered, when it isn't left to ffmpeg
+    video_stream: Option<u64>,
+    /// Decoder keeping the alpha plane, for video that has one
+    decoder: Option<&'static str>,
+}
+
+impl FrameFilter {
+    /// Thumbnail filter for the main video stream of `video_info`
+    pub fn for_thumbnails(video_info: &VideoInfo, overlay: Option<TimestampOverlay>) -> Self {
+        // Let the thumbnail filter pick the most representative frame of the
+        // window, then scale down for speed
+        let mut base = format!(
+            "thumbnail={},scale=480:270:force_original_aspect_ratio=decrease",
+            THUMBNAIL_FILTER_WINDOW
+        );
+        if video_info.video_stream.has_alpha() {
+            base = format!("{},{}", base, checkerboard_filter());
+        }
+        if let Some(eye_filter) = video_info.video_stream.single_eye_filter() {
+            // One view of 3D video rather than both next to each other
+            base = format!("{},{}", eye_filter, base);
+        }
+        FrameFilter {
+            base,
+            overlay,
+            frame_rate: video_info.frame_rate,
+            // Only a chosen program or cover art needs it; ffmpeg's pick is
+            // right otherwise
+            video_stream: (video_info.program.is_some()
+                || video_info.layout.has_attached_pictures())
+            .then_some(video_info.video_stream_index)
+            .flatten(),
+            decoder: video_info.video_stream.alpha_decoder(),
+        }
+    }
+
+    /// Index of the stream frames are taken from, when it isn't left to ffmpeg
+    pub fn video_stream(&self) -> Option<u64> {
+        self.video_stream
+    }
+
+    /// The filter for a frame taken at `time_point`
+    pub fn at(&self, time_point: f64) -> String {
+        match &self.overlay {
+            Some(overlay) => format!(
+                "{},{}",
+                self.base,
+                overlay.filter(time_point, self.frame_rate)
+            ),
+            None => self.base.clone(),
+        }
+    }
+}
+
+/// Default thumbnail positions when no better placement is known
+pub fn default_time_points(duration: f64) -> Vec<f64> {
+    // 4 time points evenly distributed across the video duration
+    vec![
+        duration * 0.1, // 10% into the video
+        duration * 0.3, // 30% into the video
+        duration * 0.6, // 60% into the video
+        duration * 0.9, // 90% into the video
+    ]
+}
+
+/// Take the thumbnail for `time_point`, encoded as a PNG data URL
+///
+/// `temp_image_path` is where ffmpeg writes the frame; `detect_hints` looks
+/// for faces and text in it.
+pub async fn render_thumbnail(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    time_point: f64,
+    duration: f64,
+    thumbnail_filter: &FrameFilter,
+    detect_hints: bool,
+    temp_image_path: &Path,
+) -> Result<Thumbnail, Error> {
+    let decoder = thumbnail_filter.decoder;
+    let mut selected = select_thumbnail(
+        runner,
+        path,
+        time_point,
+        duration,
+        thumbnail_filter,
+        decoder,
+        temp_image_path,
+    )
+    .await;
+    if selected.is_err() && decoder.is_some() {
+        // The ffmpeg build may lack the alpha-capable decoder
+        selected = select_thumbnail(
+            runner,
+            path,
+            time_point,
+            duration,
+            thumbnail_filter,
+            None,
+            temp_image_path,
+        )
+        .await;
+    }
+    let (thumbnail_time, image_data) = selected?;
+    let thumbnail_base64 = general_purpose::STANDARD.encode(&image_data);
+    Ok(Thumbnail {
+        timestamp: thumbnail_time,
+        data_url: format!("data:image/png;base64,{}", thumbnail_base64),
+        hints: detect_hints
+            .then(|| ContentHints::from_image_data(&image_data))
+            .flatten(),
+        elapsed_ms: 0,
+    })
+}
+
+/// Pick a thumbnail near `time_point`, avoiding black fades, white flashes and
+/// flat frames such as studio logos.
+///
+/// Each candidate is chosen by ffmpeg's `thumbnail` filter over a short window;
+/// if the result is still dull, nearby offsets are tried and the candidate with
+/// the most luma detail wins. Returns the timestamp actually used along with
+/// the encoded image.
+async fn select_thumbnail(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    time_point: f64,
+    duration: f64,
+    thumbnail_filter: &FrameFilter,
+    decoder: Option<&str>,
+    temp_image_path: &Path,
+) -> Result<(f64, Vec<u8>), Error> {
+    let mut best: Option<(f64, Vec<u8>, LumaStats)> = None;
+
+    for offset in CANDIDATE_OFFSETS {
+        let candidate_time = (time_point + offset * duration).clamp(0.0, duration.max(0.0));
+        let image_data = match extract_frame(
+            runner,
+            path,
+            candidate_time,
+            &thumbnail_filter.at(candidate_time),
+            decoder,
+            thumbnail_filter.video_stream,
+            temp_image_path,
+        )
+        .await
+        {
+            Ok(image_data) => image_data,
+            Err(e) => {
+                tracing::debug!(
+                    video_path = %path,
+                    time_point = candidate_time,
+                    error = %e,
+                    "Thumbnail candidate extraction failed"
+                );
+                continue;
+            }
+        };
+
+        let Some(stats) = LumaStats::from_image_data(&image_data) else {
+            // Can't judge the frame, so take it as-is
+            return Ok((candidate_time, image_data));
+        };
+
+        if !stats.is_dull() {
+            return Ok((candidate_time, image_data));
+        }
+
+        tracing::debug!(
+            video_path = %path,
+            time_point = candidate_time,
+            mean_luma = stats.mean,
+            luma_stddev = stats.stddev,
+            "Thumbnail candidate looks dull, trying a nearby timestamp"
+        );
+
+        if best
+            .as_ref()
+            .is_none_or(|(_, _, best_stats)| stats.stddev > best_stats.stddev)
+        {
+            best = Some((candidate_time, image_data, stats));
+        }
+    }
+
+    best.map(|(time, image_data, _)| (time, image_data))
+        .ok_or_else(|| {
+            Error::FFmpegError(format!(
+                "ffmpeg thumbnail generation failed at time {:.2}s",
+                time_point
+            ))
+        })
+}
+
+/// Extract a single frame starting at `time_point`, passed through `video_filter`
+///
+/// `decoder` forces a specific video decoder, e.g. to get the alpha plane.
+/// `video_stream` picks the stream by index, such as the video of a chosen
+/// program; otherwise ffmpeg takes the largest video stream.
+pub async fn extract_frame(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    time_point: f64,
+    video_filter: &str,
+    decoder: Option<&str>,
+    video_stream: Option<u64>,
+    temp_image_path: &Path,
+) -> Result<Vec<u8>, Error> {
+    let temp_image_path_string = temp_image_path.to_string_lossy().to_string();
+    let seek = format!("{:.2}", time_point);
+    let mut args = vec!["-ss", &seek];
+    if let Some(decoder) = decoder {
+        args.extend(["-c:v", decoder]);
+    }
+    args.extend(["-i", path]);
+    let map = video_stream.map(|index| format!("0:{}", index));
+    if let Some(map) = &map {
+        args.extend(["-map", map]);
+    }
+    args.extend([
+        "-vf",
+        video_filter,
+        "-frames:v",
+        "1",
+        "-q:v",
+        "2",
+        "-f",
+        "image2",
+        "-y",
+        &temp_image_path_string,
+    ]);
+
+    let output = runner.run(MediaTool::Ffmpeg, &args).await?;
+
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg frame extraction failed at time {:.2}s: {}",
+            time_point, stderr
+        )));
+    }
+
+    Ok(fs::read(temp_image_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::mock::MockRunner;
+    use crate::runner::ToolOutput;
+
+    #[test]
+    fn default_time_points_spread_over_duration() {
+        assert_eq!(default_time_points(100.0), [10.0, 30.0, 60.0, 90.0]);
+    }
+
+    #[test]
+    fn timestamp_overlay_offsets_by_seek() {
+        let overlay = TimestampOverlay {
+            frame_number: true,
+            font_file: Some(PathBuf::from("C:\\Fonts\\it's.ttf")),
+        };
+        let filter = overlay.filter(12.5, 25.0);
+
+        assert!(filter.starts_with("drawtext=text='%{pts\\:hms\\:12.50}"));
+        assert!(filter.contains("#%{eif\\:(t+12.50)*25\\:d}"));
+        assert!(filter.ends_with(":fontfile='C\\:/Fonts/its.ttf'"));
+    }
+
+    #[tokio::test]
+    async fn extract_frame_seeks_before_the_input() {
+        let frame_path = std::env::temp_dir().join("extract_frame_seeks_before_the_input.png");
+        fs::write(&frame_path, b"png").unwrap();
+        let runner = MockRunner::new().with_stdout(MediaTool::Ffmpeg, "");
+        let image_data = extract_frame(
+            &runner,
+            "clip.webm",
+            12.5,
+            "scale=320:-2",
+            Some("libvpx-vp9"),
+            Some(2),
+            &frame_path,
+        )
+        .await
+        .unwrap();
+        let _ = fs::remove_file(&frame_path);
+
+        assert_eq!(image_data, b"png");
+        let args = &runner.calls()[0].1;
+        assert_eq!(
+            args[..8],
+            [
+                "-ss",
+                "12.50",
+                "-c:v",
+                "libvpx-vp9",
+                "-i",
+                "clip.webm",
+                "-map",
+                "0:2"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_frame_reports_ffmpeg_failure() {
+        let runner = MockRunner::new().with_output(
+            MediaTool::Ffmpeg,
+            None,
+            ToolOutput::failure("Invalid data found when processing input"),
+        );
+        let error = extract_frame(
+            &runner,
+            "clip.mp4",
+            1.0,
+            "scale=320:-2",
+            None,
+            None,
+            Path::new("unused.png"),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(
+            matches!(&error, Error::FFmpegError(detail) if detail.contains("at time 1.00s")),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::alpha::{self, alpha_decoder};
+use crate::av1::Av1Info;
+use crate::codec::{bit_depth, chroma_subsampling, codec_tag, video_level};
+use crate::dolby_vision::{self, DolbyVisionInfo};
+use crate::hdr::HdrInfo;
+use crate::mp4::Mp4Track;
+use crate::runner::MediaToolRunner;
+use crate::stereo3d::Stereo3d;
+
+/// Details of the main video stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct VideoStreamInfo {
+    index: u64,
+    codec_name: String,
+    /// FourCC as stored in the container, e.g. "avc1" vs "H264" vs "XVID"
+    codec_tag: Option<String>,
+    profile: Option<String>,
+    /// As usually written, e.g. "4.1"; only for H.264 and HEVC, AV1 levels
+    /// are under `av1`
+    level: Option<String>,
+    pix_fmt: Option<String>,
+    /// Bits per sample of the decoded video, e.g. 8 or 10
+    bit_depth: Option<u32>,
+    /// e.g. "4:2:0"; `None` for RGB formats
+    chroma_subsampling: Option<String>,
+    /// Codec, profile, level, depth and subsampling in one line, e.g.
+    /// "h264 High@4.1 10-bit 4:2:0"
+    summary: String,
+    /// Whether the stream carries an alpha channel
+    has_alpha: bool,
+    /// HDR signalling and static metadata; `None` for SDR
+    hdr: Option<HdrInfo>,
+    dolby_vision: Option<DolbyVisionInfo>,
+    /// Profile, level, tier and coding tools of AV1 streams
+    av1: Option<Av1Info>,
+    /// Stereoscopic layout; `None` for 2D
+    stereo_3d: Option<Stereo3d>,
+}
+
+impl VideoStreamInfo {
+    /// Read codec details from an ffprobe stream object
+    pub fn from_stream(stream: &serde_json::Value) -> Self {
+        let codec_name = stream["codec_name"]
+            .as_str()
+            .unwrap_or("unknown")
+            .to_string();
+        let profile = stream["profile"].as_str().map(str::to_string);
+        let level = video_level(stream).map(|level| level.to_string());
+        let pix_fmt = stream["pix_fmt"].as_str().map(str::to_string);
+        let bit_depth = pix_fmt.as_ref().map(|_| bit_depth(stream));
+        let chroma_subsampling = pix_fmt
+            .as_deref()
+            .and_then(chroma_subsampling)
+            .map(str::to_string);
+
+        let mut summary = codec_name.clone();
+        match (&profile, &level) {
+            (Some(profile), Some(level)) => summary.push_str(&format!(" {}@{}", profile, level)),
+            (Some(profile), None) => summary.push_str(&format!(" {}", profile)),
+            (None, Some(level)) => summary.push_str(&format!(" @{}", level)),
+            (None, None) => {}
+        }
+        if let Some(bit_depth) = bit_depth {
+            summary.push_str(&format!(" {}-bit", bit_depth));
+        }
+        if let Some(chroma_subsampling) = &chroma_subsampling {
+            summary.push_str(&format!(" {}", chroma_subsampling));
+        }
+
+        VideoStreamInfo {
+            index: stream["index"].as_u64().unwrap_or(0),
+            codec_name,
+            codec_tag: codec_tag(stream),
+            profile,
+            level,
+            pix_fmt,
+            bit_depth,
+            chroma_subsampling,
+            summary,
+            has_alpha: alpha::has_alpha(stream),
+            hdr: HdrInfo::from_stream(stream),
+            dolby_vision: dolby_vision::from_side_data(stream),
+            av1: None,
+            stereo_3d: Stereo3d::from_stream(stream),
+        }
+    }
+
+    pub fn codec_name(&self) -> &str {
+        &self.codec_name
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.has_alpha
+    }
+
+    /// Decoder to force so that the alpha channel is decoded
+    pub fn alpha_decoder(&self) -> Option<&'static str> {
+        if !self.has_alpha {
+            return None;
+        }
+        alpha_decoder(&self.codec_name)
+    }
+
+    /// Filter keeping one view of 3D video, for previews
+    pub fn single_eye_filter(&self) -> Option<&'static str> {
+        self.stereo_3d.as_ref()?.single_eye_filter()
+    }
+
+    /// Add details only available from the MP4 sample entry boxes
+    pub fn apply_mp4_track(&mut self, track: &Mp4Track) {
+        if let Some(dolby_vision) = dolby_vision::from_mp4_track(track) {
+            self.dolby_vision = Some(dolby_vision);
+        }
+        if self.codec_name == "av1" {
+            self.av1 = track
+                .sample_entry_boxes
+                .iter()
+                .find(|(kind, _)| kind == "av1C")
+                .and_then(|(_, av1c)| Av1Info::from_av1c(av1c));
+        }
+    }
+
+    /// Read AV1 configuration from the codec extradata when no MP4 `av1C` box
+    /// provided it
+    pub async fn complete_av1_info(&mut self, runner: &dyn MediaToolRunner, path: &str) {
+        if self.codec_name != "av1" || self.av1.is_some() {
+            return;
+        }
+        match Av1Info::from_extradata(runner, path, self.index).await {
+            Ok(av1) => self.av1 = av1,
+            Err(e) => {
+                tracing::warn!(video_path = %path, error = %e, "Failed to read AV1 configuration");
+            }
+        }
+    }
+
+    /// Look for HDR10 static metadata in the first frame when the stream
+    /// parameters didn't carry it
+    ///
+    /// Failures are logged; the stream is still reported as HDR without values.
+    pub async fn complete_hdr_metadata(&mut self, runner: &dyn MediaToolRunner, path: &str) {
+        let Some(hdr) = self.hdr.as_mut().filter(|hdr| hdr.needs_frame_metadata()) else {
+            return;
+        };
+        if let Err(e) = hdr.read_frame_metadata(runner, path, self.index).await {
+            tracing::warn!(video_path = %path, error = %e, "Failed to read HDR frame metadata");
+        }
+    }
+}
@@ -0,0 +1,660 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::{
+    process::Stdio,
+    sync::{Arc, OnceLock, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::{io::AsyncWriteExt, process::Command};
+
+use crate::bitrate::declared_bit_rate;
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::glitch::{AudioDropoutAnalyzer, FreezeAnalyzer, TimestampAnalyzer};
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::loudness::measure_loudness_async;
+use crate::overlay::{sample_frames, HardsubAnalyzer, WatermarkAnalyzer};
+use crate::quality::{ArtifactAnalyzer, NoiseAnalyzer, SavingsAnalyzer};
+use crate::runner::{sidecar, KillOnDrop, MediaTool};
+use crate::settings;
+use crate::stereo3d::{frame_packing, Stereo3d, StereoLayout};
+use crate::whisper::{AudioLanguageAnalyzer, TranscriptAnalyzer};
+
+/// External analyzers still running after this long are killed
+const EXTERNAL_ANALYZER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Frames read by the interlace analyzer; enough for a stable verdict
+const IDET_FRAMES: &str = "500";
+
+/// Share of frames that must be interlaced to call a stream interlaced
+const INTERLACED_RATIO: f64 = 0.5;
+
+/// idet with each frame's own verdict printed, for the pulldown cadence
+const IDET_FILTER: &str = "idet,metadata=mode=print:key=lavfi.idet.single.current_frame";
+
+/// Share of frames with a repeated field above which the stream is soft
+/// telecined; 3:2 pulldown flags two frames in five
+const SOFT_TELECINE_RATIO: f64 = 0.3;
+
+/// In hard telecined video, the two combed frames of each five sit at the
+/// same positions of the cycle; those positions must be combed this often,
+/// and the other three this rarely
+const PULLDOWN_COMBED_SHARE: f64 = 0.6;
+const PULLDOWN_CLEAN_SHARE: f64 = 0.2;
+
+/// Frames needed to see the pulldown cycle repeat reliably
+const MIN_CADENCE_FRAMES: usize = 50;
+
+/// Seconds of each audio stream decoded and hashed to find duplicates
+const AUDIO_HASH_SECS: &str = "120";
+
+/// Frames compared half to half by the 3D analyzer
+const STEREO_SAMPLES: usize = 8;
+
+/// Small frames for the 3D analyzer; the halves only need to line up
+const STEREO_FILTER: &str = "scale=256:256,setsar=1";
+
+/// An analysis that can run on any inspected file
+///
+/// Analyzers get the file path and its `-show_format -show_streams` probe,
+/// and return free-form JSON. Register new ones with [`register`].
+#[async_trait]
+pub trait Analyzer: Send + Sync {
+    /// Unique name, used to select the analyzer
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Whether the analyzer has anything to look at, e.g. an audio stream
+    fn applies_to(&self, _probe: &serde_json::Value) -> bool {
+        true
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error>;
+}
+
+/// Command line analyzer configured in settings
+///
+/// The program receives `{"path": ..., "probe": ...}` on stdin and must print
+/// a single JSON value on stdout. A non-zero exit status is a failure, with
+/// stderr as the message.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ExternalAnalyzer {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[async_trait]
+impl Analyzer for ExternalAnalyzer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let input = serde_json::to_vec(&json!({ "path": path, "probe": probe }))
+            .map_err(|e| Error::ParseError(format!("Failed to serialize analyzer input: {}", e)))?;
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                Error::IoError(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to start analyzer '{}': {}", self.program, e),
+                ))
+            })?;
+
+        // Write the input while collecting the output, so neither side blocks
+        let mut stdin = child.stdin.take();
+        let write_input = async move {
+            if let Some(stdin) = stdin.as_mut() {
+                // An analyzer that exits without reading its input isn't an error
+                let _ = stdin.write_all(&input).await;
+            }
+            drop(stdin);
+        };
+        let run = async { tokio::join!(write_input, child.wait_with_output()).1 };
+        let output = tokio::time::timeout(EXTERNAL_ANALYZER_TIMEOUT, run)
+            .await
+            .map_err(|_| {
+                Error::FFmpegError(format!(
+                    "Analyzer '{}' timed out after {:?}",
+                    self.name, EXTERNAL_ANALYZER_TIMEOUT
+                ))
+            })??;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FFmpegError(format!(
+                "Analyzer '{}' failed: {}",
+                self.name,
+                stderr.trim()
+            )));
+        }
+        serde_json::from_slice(&output.stdout).map_err(|e| {
+            Error::ParseError(format!(
+                "Analyzer '{}' printed invalid JSON: {}",
+                self.name, e
+            ))
+        })
+    }
+}
+
+/// EBU R128 loudness of the first audio stream
+struct LoudnessAnalyzer;
+
+#[async_trait]
+impl Analyzer for LoudnessAnalyzer {
+    fn name(&self) -> &str {
+        "loudness"
+    }
+
+    fn description(&self) -> &str {
+        "Integrated loudness, range and true peak of the first audio stream"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        has_stream(probe, "audio")
+    }
+
+    async fn run(
+        &self,
+        path: &str,
+        _probe: &serde_json::Value,
+    ) -> Result<serde_json::Value, Error> {
+        to_value(measure_loudness_async(path, 0).await?)
+    }
+}
+
+/// Audio streams that decode to the same samples, found by hashing the
+/// decoded audio of each stream
+struct DuplicateAudioAnalyzer;
+
+#[async_trait]
+impl Analyzer for DuplicateAudioAnalyzer {
+    fn name(&self) -> &str {
+        "duplicate_audio"
+    }
+
+    fn description(&self) -> &str {
+        "Audio tracks that are identical copies of another track"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        audio_streams(probe).len() > 1
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let app_handle = get_app_handle()
+            .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+        // Decoding to one sample format makes the hash independent of the
+        // decoder's native output
+        let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-v",
+                "error",
+                "-t",
+                AUDIO_HASH_SECS,
+                "-i",
+                path,
+                "-map",
+                "0:a",
+                "-c:a",
+                "pcm_s16le",
+                "-f",
+                "streamhash",
+                "-hash",
+                "sha256",
+                "-",
+            ])
+            .output_or_kill()
+            .await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FFmpegError(format!(
+                "ffmpeg audio hashing failed: {}",
+                stderr
+            )));
+        }
+
+        // `0,a,SHA256=...`, numbered in the order the streams were mapped
+        let streams = audio_streams(probe);
+        let mut groups: Vec<(String, Vec<&serde_json::Value>)> = Vec::new();
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut fields = line.splitn(3, ',');
+            let (Some(position), Some(_), Some(hash)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Some(stream) = position.parse::<usize>().ok().and_then(|p| streams.get(p)) else {
+                continue;
+            };
+            match groups.iter_mut().find(|(existing, _)| existing == hash) {
+                Some((_, members)) => members.push(stream),
+                None => groups.push((hash.to_string(), vec![stream])),
+            }
+        }
+
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let duplicates: Vec<serde_json::Value> = groups
+            .iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(_, members)| {
+                // Every copy after the first is wasted space
+                let wasted_bytes: f64 = members[1..]
+                    .iter()
+                    .filter_map(|stream| declared_bit_rate(stream))
+                    .map(|bit_rate| bit_rate * duration / 8.0)
+                    .sum();
+                json!({
+                    "stream_indexes": members
+                        .iter()
+                        .map(|stream| stream["index"].as_u64())
+                        .collect::<Vec<_>>(),
+                    "wasted_bytes": wasted_bytes.round() as u64,
+                })
+            })
+            .collect();
+        tracing::debug!(video_path = %path, groups = duplicates.len(), "Compared audio streams");
+
+        Ok(json!({
+            "seconds_compared": AUDIO_HASH_SECS.parse::<f64>().ok(),
+            "duplicates": duplicates,
+        }))
+    }
+}
+
+/// Interlacing detected with ffmpeg's idet filter
+struct InterlaceAnalyzer;
+
+#[async_trait]
+impl Analyzer for InterlaceAnalyzer {
+    fn name(&self) -> &str {
+        "interlace"
+    }
+
+    fn description(&self) -> &str {
+        "Whether the video is progressive, telecined or interlaced, and in which field order"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        has_stream(probe, "video")
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let app_handle = get_app_handle()
+            .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+        let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-i",
+                path,
+                "-map",
+                "0:v:0",
+                "-frames:v",
+                IDET_FRAMES,
+                "-filter:v",
+                IDET_FILTER,
+                "-an",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output_or_kill()
+            .await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FFmpegError(format!(
+                "ffmpeg interlace detection failed: {}",
+                stderr
+            )));
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let counts = parse_idet_summary(&stderr).ok_or_else(|| {
+            Error::ParseError("Interlace summary not found in ffmpeg output".to_string())
+        })?;
+        let [tff, bff, progressive, undetermined] = counts;
+        let decided = (tff + bff + progressive).max(1) as f64;
+        let repeated = parse_repeated_fields(&stderr);
+        let repeated_ratio = repeated.map_or(0.0, |[neither, top, bottom]| {
+            (top + bottom) as f64 / (neither + top + bottom).max(1) as f64
+        });
+        let pulldown = has_pulldown_cadence(&parse_frame_verdicts(&stderr));
+        let verdict = if repeated_ratio >= SOFT_TELECINE_RATIO {
+            // Film flagged for 3:2 playback; the frames themselves are progressive
+            "soft_telecined"
+        } else if pulldown {
+            "telecined"
+        } else if (tff + bff) as f64 / decided < INTERLACED_RATIO {
+            "progressive"
+        } else if tff >= bff {
+            "interlaced_tff"
+        } else {
+            "interlaced_bff"
+        };
+
+        Ok(json!({
+            "tff": tff,
+            "bff": bff,
+            "progressive": progressive,
+            "undetermined": undetermined,
+            "repeated_fields_ratio": repeated_ratio,
+            "verdict": verdict,
+            // Inverse telecine restores the original progressive frames;
+            // deinterlacing telecined video only blurs it
+            "ivtc_recommended": matches!(verdict, "telecined" | "soft_telecined"),
+            // What the container claims, for comparison
+            "field_order": first_stream(probe, "video").and_then(|s| s["field_order"].as_str()),
+        }))
+    }
+}
+
+/// Parse idet's `Repeated Fields: Neither: 1 Top: 2 Bottom: 3`
+fn parse_repeated_fields(stderr: &str) -> Option<[u64; 3]> {
+    let line = stderr
+        .lines()
+        .rfind(|line| line.contains("Repeated Fields:"))?;
+    let mut counts = [0; 3];
+    for (count, label) in counts.iter_mut().zip(["Neither:", "Top:", "Bottom:"]) {
+        let rest = &line[line.find(label)? + label.len()..];
+        *count = rest.split_whitespace().next()?.parse().ok()?;
+    }
+    Some(counts)
+}
+
+/// Per-frame idet verdicts from `lavfi.idet.single.current_frame=tff`
+/// lines, `true` for combed frames
+fn parse_frame_verdicts(stderr: &str) -> Vec<bool> {
+    stderr
+        .lines()
+        .filter_map(|line| line.split_once("lavfi.idet.single.current_frame="))
+        .map(|(_, verdict)| matches!(verdict.trim(), "tff" | "bff"))
+        .collect()
+}
+
+/// Whether combed frames follow 3:2 pulldown's two-in-five cycle
+fn has_pulldown_cadence(combed: &[bool]) -> bool {
+    if combed.len() < MIN_CADENCE_FRAMES {
+        return false;
+    }
+    let mut shares: Vec<f64> = (0..5)
+        .map(|phase| {
+            let frames: Vec<bool> = combed.iter().skip(phase).step_by(5).copied().collect();
+            frames.iter().filter(|&&c| c).count() as f64 / frames.len().max(1) as f64
+        })
+        .collect();
+    shares.sort_by(|a, b| b.total_cmp(a));
+    shares[..2]
+        .iter()
+        .all(|&share| share >= PULLDOWN_COMBED_SHARE)
+        && shares[2..]
+            .iter()
+            .all(|&share| share <= PULLDOWN_CLEAN_SHARE)
+}
+
+/// Stereoscopic 3D layout from the stream metadata, confirmed or found by
+/// comparing the halves of sampled frames
+struct Stereo3dAnalyzer;
+
+#[async_trait]
+impl Analyzer for Stereo3dAnalyzer {
+    fn name(&self) -> &str {
+        "stereo_3d"
+    }
+
+    fn description(&self) -> &str {
+        "Side-by-side, top-and-bottom or MVC 3D layout"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        has_stream(probe, "video")
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let metadata = first_stream(probe, "video").and_then(Stereo3d::from_stream);
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let frames = sample_frames(path, duration, STEREO_SAMPLES, STEREO_FILTER).await?;
+        let layouts: Vec<_> = frames
+            .iter()
+            .map(|(_, image)| frame_packing(image))
+            .collect();
+        let count = |layout| layouts.iter().filter(|l| **l == Some(layout)).count();
+        let (side_by_side, top_bottom) = (
+            count(StereoLayout::SideBySide),
+            count(StereoLayout::TopBottom),
+        );
+        // Most frames have to agree, since symmetric shots fool single frames
+        let frame_layout = if side_by_side * 2 > frames.len() {
+            Some(StereoLayout::SideBySide)
+        } else if top_bottom * 2 > frames.len() {
+            Some(StereoLayout::TopBottom)
+        } else {
+            None
+        };
+
+        Ok(json!({
+            "metadata": metadata,
+            "frame_layout": frame_layout,
+            "frames_sampled": frames.len(),
+            "frames_side_by_side": side_by_side,
+            "frames_top_bottom": top_bottom,
+            "layout": metadata.map(|stereo| stereo.layout).or(frame_layout),
+        }))
+    }
+}
+
+/// Parse idet's `Multi frame detection: TFF: 1 BFF: 0 Progressive: 2 Undetermined: 3`
+fn parse_idet_summary(stderr: &str) -> Option<[u64; 4]> {
+    let line = stderr
+        .lines()
+        .rfind(|line| line.contains("Multi frame detection:"))?;
+    let mut counts = [0; 4];
+    for (count, label) in counts
+        .iter_mut()
+        .zip(["TFF:", "BFF:", "Progressive:", "Undetermined:"])
+    {
+        let rest = &line[line.find(label)? + label.len()..];
+        *count = rest.split_whitespace().next()?.parse().ok()?;
+    }
+    Some(counts)
+}
+
+/// Registered analyzers, built-in ones first
+static REGISTRY: OnceLock<RwLock<Vec<Arc<dyn Analyzer>>>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn Analyzer>>> {
+    REGISTRY.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Add an analyzer, replacing any registered under the same name
+pub fn register(analyzer: Arc<dyn Analyzer>) {
+    let mut analyzers = registry().write().unwrap_or_else(|e| e.into_inner());
+    analyzers.retain(|existing| existing.name() != analyzer.name());
+    analyzers.push(analyzer);
+}
+
+/// Register the analyzers shipped with the app; called once at startup
+pub fn register_builtin_analyzers() {
+    register(Arc::new(LoudnessAnalyzer));
+    register(Arc::new(DuplicateAudioAnalyzer));
+    register(Arc::new(InterlaceAnalyzer));
+    register(Arc::new(Stereo3dAnalyzer));
+    register(Arc::new(AudioLanguageAnalyzer));
+    register(Arc::new(TranscriptAnalyzer));
+    register(Arc::new(HardsubAnalyzer));
+    register(Arc::new(WatermarkAnalyzer));
+    register(Arc::new(FreezeAnalyzer));
+    register(Arc::new(AudioDropoutAnalyzer));
+    register(Arc::new(TimestampAnalyzer));
+    register(Arc::new(NoiseAnalyzer));
+    register(Arc::new(ArtifactAnalyzer));
+    register(Arc::new(SavingsAnalyzer));
+}
+
+/// Registered analyzers followed by the external ones from settings
+///
+/// External analyzers are read on every call so settings changes apply
+/// immediately; they can't shadow a registered name.
+fn all_analyzers() -> Vec<Arc<dyn Analyzer>> {
+    let mut analyzers = registry().read().unwrap_or_else(|e| e.into_inner()).clone();
+    for external in settings::current().external_analyzers {
+        if analyzers.iter().any(|a| a.name() == external.name) {
+            tracing::warn!(analyzer = %external.name, "External analyzer name already taken");
+            continue;
+        }
+        analyzers.push(Arc::new(external));
+    }
+    analyzers
+}
+
+/// An analyzer available to `run_analyzers`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct AnalyzerInfo {
+    name: String,
+    description: String,
+}
+
+/// Outcome of one analyzer
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct AnalyzerResult {
+    name: String,
+    /// Set when the file has nothing the analyzer can look at
+    skipped: bool,
+    value: Option<serde_json::Value>,
+    error: Option<String>,
+    elapsed_ms: u64,
+}
+
+/// List the registered and configured analyzers
+#[tauri::command]
+pub async fn list_analyzers() -> Result<Vec<AnalyzerInfo>, String> {
+    Ok(all_analyzers()
+        .iter()
+        .map(|analyzer| AnalyzerInfo {
+            name: analyzer.name().to_string(),
+            description: analyzer.description().to_string(),
+        })
+        .collect())
+}
+
+/// Run analyzers on a file, all of them when `names` is omitted
+///
+/// A failing analyzer doesn't stop the others; its error is reported in its
+/// result instead.
+#[tauri::command]
+pub async fn run_analyzers(
+    path: String,
+    names: Option<Vec<String>>,
+) -> Result<Vec<AnalyzerResult>, String> {
+    run_analyzers_async(&path, names.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Running analyzers failed");
+            e.localized()
+        })
+}
+
+async fn run_analyzers_async(
+    path: &str,
+    names: Option<&[String]>,
+) -> Result<Vec<AnalyzerResult>, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let analyzers = all_analyzers();
+    let selected: Vec<Arc<dyn Analyzer>> = match names {
+        None => analyzers,
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                analyzers
+                    .iter()
+                    .find(|analyzer| analyzer.name() == name)
+                    .cloned()
+                    .ok_or_else(|| Error::ParseError(format!("Unknown analyzer '{}'", name)))
+            })
+            .collect::<Result<_, _>>()?,
+    };
+
+    let probe = run_ffprobe_json(app_handle, path, &["-show_format", "-show_streams"]).await?;
+
+    let mut results = Vec::with_capacity(selected.len());
+    for analyzer in selected {
+        let start = Instant::now();
+        let skipped = !analyzer.applies_to(&probe);
+        let (value, error) = if skipped {
+            (None, None)
+        } else {
+            match analyzer.run(path, &probe).await {
+                Ok(value) => (Some(value), None),
+                Err(e) => {
+                    tracing::warn!(
+                        video_path = %path,
+                        analyzer = %analyzer.name(),
+                        error = %e,
+                        "Analyzer failed"
+                    );
+                    (None, Some(e.to_string()))
+                }
+            }
+        };
+        results.push(AnalyzerResult {
+            name: analyzer.name().to_string(),
+            skipped,
+            value,
+            error,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+        });
+    }
+
+    tracing::debug!(video_path = %path, analyzers = results.len(), "Ran analyzers");
+    Ok(results)
+}
+
+/// The first stream of a type in a `-show_streams` probe
+pub fn first_stream<'a>(
+    probe: &'a serde_json::Value,
+    codec_type: &str,
+) -> Option<&'a serde_json::Value> {
+    probe["streams"]
+        .as_array()?
+        .iter()
+        .find(|stream| stream["codec_type"].as_str() == Some(codec_type))
+}
+
+/// Audio streams in the order `-map 0:a` picks them
+fn audio_streams(probe: &serde_json::Value) -> Vec<&serde_json::Value> {
+    probe["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|stream| stream["codec_type"].as_str() == Some("audio"))
+        .collect()
+}
+
+fn has_stream(probe: &serde_json::Value, codec_type: &str) -> bool {
+    first_stream(probe, codec_type).is_some()
+}
+
+/// Analyzer result from any serializable value
+pub fn to_value(value: impl serde::Serialize) -> Result<serde_json::Value, Error> {
+    serde_json::to_value(value)
+        .map_err(|e| Error::ParseError(format!("Failed to serialize analyzer result: {}", e)))
+}
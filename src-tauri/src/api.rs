@@ -0,0 +1,373 @@
+#[cfg(feature = "rest-api")]
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "rest-api")]
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+#[cfg(feature = "rest-api")]
+use tauri::async_runtime::JoinHandle;
+#[cfg(feature = "rest-api")]
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::concat;
+#[cfg(feature = "rest-api")]
+use crate::events;
+use crate::motion_photo;
+use crate::settings;
+use crate::subtitle;
+use crate::transcode;
+use crate::transport_stream;
+use crate::{
+    analyzer, archive, batch, benchmark, bluray, cache, camera_card, chapters, compatibility,
+};
+use crate::{dvd, export, frames, inspector, integrity, iso, job, loudness, multipart, report};
+
+/// Inspection and QC commands callable over the automation API
+pub const API_COMMANDS: &[&str] = &[
+    "get_video_metadata",
+    "analyze_frame_types",
+    "frame_size_timeline",
+    "scan_integrity",
+    "measure_loudness",
+    "preview_subtitles",
+    "check_compatibility",
+    "suggest_transcode",
+    "check_concat",
+    "report_folder",
+    "get_cache_stats",
+    "list_analyzers",
+    "run_analyzers",
+    "benchmark_decode",
+    "inspect_dvd",
+    "inspect_bluray",
+    "inspect_iso",
+    "inspect_multipart",
+    "list_archive",
+    "inspect_camera_card",
+    "export_inspection",
+    "chapter_index",
+    "inspect_videos_batch",
+    "cancel_inspection",
+    "inspect_transport_stream",
+    "inspect_motion_photo",
+];
+
+/// The running server task, if any
+#[cfg(feature = "rest-api")]
+static SERVER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Start, restart or stop the automation API to match the current settings
+///
+/// The server only ever binds to localhost and rejects requests without the
+/// configured token.
+#[cfg(feature = "rest-api")]
+pub fn apply_settings() {
+    let settings = settings::current();
+    let mut server = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = server.take() {
+        handle.abort();
+        tracing::info!("Automation API stopped");
+    }
+
+    if !settings.api_enabled {
+        return;
+    }
+    let Some(token) = settings.api_token.filter(|token| !token.is_empty()) else {
+        tracing::warn!("Automation API enabled without a token, not starting it");
+        return;
+    };
+    *server = Some(tauri::async_runtime::spawn(serve(settings.api_port, token)));
+}
+
+/// Builds without the `rest-api` feature only warn when it's turned on
+#[cfg(not(feature = "rest-api"))]
+pub fn apply_settings() {
+    if settings::current().api_enabled {
+        tracing::warn!("Automation API enabled, but this build doesn't include it");
+    }
+}
+
+#[cfg(feature = "rest-api")]
+async fn serve(port: u16, token: String) {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let listener = match tokio::net::TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(address = %address, error = %e, "Failed to start automation API");
+            return;
+        }
+    };
+    tracing::info!(address = %address, "Automation API listening");
+
+    let router = Router::new()
+        .route("/api/commands", get(list_commands))
+        .route("/api/{command}", post(invoke))
+        .route("/ws", get(events_socket))
+        .with_state(Arc::new(token));
+    if let Err(e) = axum::serve(listener, router).await {
+        tracing::error!(address = %address, error = %e, "Automation API stopped");
+    }
+}
+
+#[cfg(feature = "rest-api")]
+type Token = State<Arc<String>>;
+
+#[cfg(feature = "rest-api")]
+async fn list_commands(
+    State(token): Token,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if !authorized(&token, &headers, &query) {
+        return unauthorized();
+    }
+    Json(API_COMMANDS).into_response()
+}
+
+/// `POST /api/<command>` with the command arguments as a JSON object
+#[cfg(feature = "rest-api")]
+async fn invoke(
+    State(token): Token,
+    Path(command): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    body: Bytes,
+) -> Response {
+    if !authorized(&token, &headers, &query) {
+        return unauthorized();
+    }
+    if !API_COMMANDS.contains(&command.as_str()) {
+        return error_response(
+            StatusCode::NOT_FOUND,
+            format!("Unknown command '{}'", command),
+        );
+    }
+
+    let params = if body.is_empty() {
+        serde_json::Value::Null
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(params) => params,
+            Err(e) => {
+                return error_response(StatusCode::BAD_REQUEST, format!("Invalid JSON: {}", e))
+            }
+        }
+    };
+
+    match dispatch(&command, params).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => error_response(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+/// Stream every progress and partial result event to the client
+///
+/// Browsers can't set headers on WebSocket requests, so the token may also be
+/// passed as `?token=`.
+#[cfg(feature = "rest-api")]
+async fn events_socket(
+    State(token): Token,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    if !authorized(&token, &headers, &query) {
+        return unauthorized();
+    }
+    upgrade.on_upgrade(stream_events)
+}
+
+#[cfg(feature = "rest-api")]
+async fn stream_events(mut socket: WebSocket) {
+    let mut events = events::subscribe();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if socket.send(Message::Text(event.into())).await.is_err() {
+                    // Client went away
+                    break;
+                }
+            }
+            Err(RecvError::Lagged(missed)) => {
+                tracing::warn!(missed, "Automation client too slow, events dropped");
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Call an exposed command with arguments taken from a JSON object
+///
+/// Missing arguments are passed as `None`, like when the frontend omits them.
+pub async fn dispatch(
+    command: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let p = &params;
+    match command {
+        "get_video_metadata" => to_json(
+            inspector::get_video_metadata(
+                param(p, "path")?,
+                param(p, "scene_detection")?,
+                param(p, "job_id")?,
+                param(p, "preset")?,
+                param(p, "skip_hash")?,
+                param(p, "skip_thumbnails")?,
+                param(p, "input_args")?,
+                param(p, "program")?,
+                param(p, "quick_hash")?,
+            )
+            .await,
+        ),
+        "analyze_frame_types" => {
+            to_json(frames::analyze_frame_types(param(p, "path")?, param(p, "window_secs")?).await)
+        }
+        "frame_size_timeline" => to_json(
+            frames::frame_size_timeline(
+                param(p, "path")?,
+                param(p, "max_points")?,
+                param(p, "granularity")?,
+                param(p, "output_path")?,
+            )
+            .await,
+        ),
+        "scan_integrity" => {
+            to_json(integrity::scan_integrity(param(p, "path")?, param(p, "job_id")?).await)
+        }
+        "measure_loudness" => {
+            to_json(loudness::measure_loudness(param(p, "path")?, param(p, "audio_stream")?).await)
+        }
+        "preview_subtitles" => to_json(
+            subtitle::preview_subtitles(
+                param(p, "path")?,
+                param(p, "subtitle_stream")?,
+                param(p, "cues")?,
+            )
+            .await,
+        ),
+        "check_compatibility" => to_json(
+            compatibility::check_compatibility(param(p, "path")?, param(p, "target")?).await,
+        ),
+        "suggest_transcode" => to_json(
+            transcode::suggest_transcode(
+                param(p, "path")?,
+                param(p, "target")?,
+                param(p, "output_path")?,
+            )
+            .await,
+        ),
+        "check_concat" => to_json(concat::check_concat(param(p, "paths")?).await),
+        "report_folder" => to_json(
+            report::report_folder(
+                param(p, "path")?,
+                param(p, "export_path")?,
+                param(p, "hash")?,
+                param(p, "job_id")?,
+                param(p, "quick_hash")?,
+            )
+            .await,
+        ),
+        "get_cache_stats" => to_json(cache::get_cache_stats().await),
+        "list_analyzers" => to_json(analyzer::list_analyzers().await),
+        "run_analyzers" => {
+            to_json(analyzer::run_analyzers(param(p, "path")?, param(p, "names")?).await)
+        }
+        "benchmark_decode" => to_json(
+            benchmark::benchmark_decode(
+                param(p, "path")?,
+                param(p, "hardware")?,
+                param(p, "seconds")?,
+                param(p, "job_id")?,
+            )
+            .await,
+        ),
+        "inspect_dvd" => to_json(dvd::inspect_dvd(param(p, "path")?, param(p, "job_id")?).await),
+        "inspect_bluray" => to_json(bluray::inspect_bluray(param(p, "path")?).await),
+        "inspect_iso" => to_json(iso::inspect_iso(param(p, "path")?, param(p, "job_id")?).await),
+        "inspect_multipart" => {
+            to_json(multipart::inspect_multipart(param(p, "path")?, param(p, "job_id")?).await)
+        }
+        "list_archive" => to_json(archive::list_archive(param(p, "path")?).await),
+        "inspect_camera_card" => to_json(camera_card::inspect_camera_card(param(p, "path")?).await),
+        "export_inspection" => to_json(
+            export::export_inspection(
+                param(p, "path")?,
+                param(p, "format")?,
+                param(p, "thumbnails")?,
+                param(p, "export_path")?,
+            )
+            .await,
+        ),
+        "chapter_index" => to_json(chapters::chapter_index(param(p, "path")?).await),
+        "inspect_videos_batch" => to_json(
+            batch::inspect_videos_batch(
+                param(p, "paths")?,
+                param(p, "concurrency")?,
+                param(p, "preset")?,
+                param(p, "job_id")?,
+            )
+            .await,
+        ),
+        "cancel_inspection" => to_json(job::cancel_inspection(param(p, "job_id")?).await),
+        "inspect_transport_stream" => {
+            to_json(transport_stream::inspect_transport_stream(param(p, "path")?).await)
+        }
+        "inspect_motion_photo" => to_json(
+            motion_photo::inspect_motion_photo(param(p, "path")?, param(p, "extract_to")?).await,
+        ),
+        other => Err(format!("Unknown command '{}'", other)),
+    }
+}
+
+/// Read one argument; absent arguments deserialize from `null`
+fn param<T: DeserializeOwned>(params: &serde_json::Value, name: &str) -> Result<T, String> {
+    let value = params.get(name).cloned().unwrap_or_default();
+    serde_json::from_value(value).map_err(|e| format!("Invalid argument '{}': {}", name, e))
+}
+
+fn to_json<T: Serialize>(result: Result<T, String>) -> Result<serde_json::Value, String> {
+    result.and_then(|value| serde_json::to_value(value).map_err(|e| e.to_string()))
+}
+
+/// Accept `Authorization: Bearer <token>` or a `token` query parameter
+#[cfg(feature = "rest-api")]
+fn authorized(token: &str, headers: &HeaderMap, query: &HashMap<String, String>) -> bool {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let provided = bearer.or(query.get("token").map(String::as_str));
+    provided.is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+}
+
+/// Compare without exiting early, so timing doesn't reveal the token
+#[cfg(feature = "rest-api")]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(feature = "rest-api")]
+fn unauthorized() -> Response {
+    error_response(
+        StatusCode::UNAUTHORIZED,
+        "Missing or invalid token".to_string(),
+    )
+}
+
+#[cfg(feature = "rest-api")]
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
@@ -0,0 +1,238 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::disk::ensure_free_space;
+use crate::inspector::Error;
+use crate::temp::temp_dir;
+use video_inspector_core::rar::list_rar;
+use video_inspector_core::Error as CoreError;
+
+/// Extensions listed as media; archived files can't be sniffed without
+/// decompressing them
+const MEDIA_EXTENSIONS: [&str; 22] = [
+    "3gp", "asf", "avi", "flv", "iso", "m2ts", "m4a", "m4v", "mka", "mkv", "mov", "mp4", "mpeg",
+    "mpg", "mts", "ogv", "rm", "rmvb", "ts", "vob", "webm", "wmv",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    Rar,
+    SevenZip,
+}
+
+/// A media file inside an archive
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ArchiveEntry {
+    /// Path inside the archive
+    name: String,
+    size: u64,
+    /// Whether `extract_archive_entry` can get it out; false for encrypted
+    /// entries and compressed or multi-volume RAR data
+    extractable: bool,
+}
+
+/// Media files found in an archive
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ArchiveListing {
+    path: String,
+    /// "zip", "rar" or "7z"
+    format: String,
+    entries: Vec<ArchiveEntry>,
+    /// Files left out because they don't look like media
+    other_files: usize,
+}
+
+/// List the media files of a `.zip`, `.rar` or `.7z` archive
+#[tauri::command]
+pub async fn list_archive(path: String) -> Result<ArchiveListing, String> {
+    read_listing(&path).map_err(|e| {
+        tracing::error!(file = %path, error = %e, "Archive listing failed");
+        e.localized()
+    })
+}
+
+/// Extract one file of an archive to the temp directory for inspection
+///
+/// Returns the path of the extracted copy, which is swept with the other
+/// temp files once stale.
+#[tauri::command]
+pub async fn extract_archive_entry(path: String, name: String) -> Result<String, String> {
+    // Decompressing a whole video takes a while; keep it off the async workers
+    let (archive_path, entry_name) = (path.clone(), name.clone());
+    tauri::async_runtime::spawn_blocking(move || extract_entry(&archive_path, &entry_name))
+        .await
+        .map_err(|e| Error::IoError(io::Error::other(e.to_string())))
+        .and_then(|result| result)
+        .map_err(|e| {
+            tracing::error!(file = %path, entry = %name, error = %e, "Archive extraction failed");
+            e.localized()
+        })
+}
+
+fn read_listing(path: &str) -> Result<ArchiveListing, Error> {
+    let format = archive_format(Path::new(path))?;
+    let all_entries = read_entries(Path::new(path), format)?;
+    let total = all_entries.len();
+    let entries: Vec<ArchiveEntry> = all_entries
+        .into_iter()
+        .filter(|entry| is_media_name(&entry.name))
+        .collect();
+
+    tracing::debug!(file = %path, entries = entries.len(), total, "Listed archive");
+    Ok(ArchiveListing {
+        path: path.to_string(),
+        format: match format {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::Rar => "rar",
+            ArchiveFormat::SevenZip => "7z",
+        }
+        .to_string(),
+        other_files: total - entries.len(),
+        entries,
+    })
+}
+
+fn extract_entry(path: &str, name: &str) -> Result<String, Error> {
+    let archive_path = Path::new(path);
+    let format = archive_format(archive_path)?;
+    let entry = read_entries(archive_path, format)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| Error::ParseError(format!("{} is not in the archive", name)))?;
+    if !entry.extractable {
+        return Err(Error::ParseError(format!(
+            "{} can't be extracted here; use an archiver",
+            name
+        )));
+    }
+
+    let dir = temp_dir()?;
+    ensure_free_space(&dir, entry.size)?;
+    let extension = Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut output = tempfile::Builder::new()
+        .prefix("archive_")
+        .suffix(&format!(".{}", extension))
+        .tempfile_in(&dir)?;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(archive_path)?).map_err(zip_error)?;
+            let mut file = archive.by_name(name).map_err(zip_error)?;
+            io::copy(&mut file, &mut output)?;
+        }
+        ArchiveFormat::Rar => {
+            let rar_entry = list_rar(archive_path)?
+                .into_iter()
+                .find(|rar_entry| rar_entry.name == name)
+                .ok_or_else(|| Error::ParseError(format!("{} is not in the archive", name)))?;
+            let mut file = File::open(archive_path)?;
+            file.seek(SeekFrom::Start(rar_entry.data_offset))?;
+            io::copy(&mut file.take(rar_entry.size), &mut output)?;
+        }
+        ArchiveFormat::SevenZip => {
+            let mut reader =
+                sevenz_rust::SevenZReader::open(archive_path, sevenz_rust::Password::empty())
+                    .map_err(sevenz_error)?;
+            reader
+                .for_each_entries(|file, data| {
+                    if file.name == name {
+                        io::copy(data, &mut output)?;
+                        return Ok(false);
+                    }
+                    // Solid blocks must be read through to reach later files
+                    io::copy(data, &mut io::sink())?;
+                    Ok(true)
+                })
+                .map_err(sevenz_error)?;
+        }
+    }
+
+    let (_, output_path) = output.keep().map_err(|e| Error::IoError(e.error))?;
+    tracing::info!(
+        file = %path,
+        entry = %name,
+        output_path = %output_path.display(),
+        "Extracted archive entry"
+    );
+    Ok(output_path.to_string_lossy().to_string())
+}
+
+/// Tell the archive format from the magic bytes
+fn archive_format(path: &Path) -> Result<ArchiveFormat, Error> {
+    let mut header = [0u8; 6];
+    File::open(path)?.read_exact(&mut header)?;
+    match header {
+        [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => Ok(ArchiveFormat::Zip),
+        [b'R', b'a', b'r', b'!', 0x1A, 0x07] => Ok(ArchiveFormat::Rar),
+        [0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C] => Ok(ArchiveFormat::SevenZip),
+        _ => Err(CoreError::NotMediaFile(format!(
+            "{} is not a ZIP, RAR or 7z archive",
+            path.display()
+        ))
+        .into()),
+    }
+}
+
+/// Every file of the archive, media or not
+fn read_entries(path: &Path, format: ArchiveFormat) -> Result<Vec<ArchiveEntry>, Error> {
+    let mut entries = Vec::new();
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+            for index in 0..archive.len() {
+                let file = archive.by_index_raw(index).map_err(zip_error)?;
+                if file.is_dir() {
+                    continue;
+                }
+                entries.push(ArchiveEntry {
+                    name: file.name().to_string(),
+                    size: file.size(),
+                    extractable: !file.encrypted(),
+                });
+            }
+        }
+        ArchiveFormat::Rar => {
+            entries.extend(list_rar(path)?.into_iter().map(|entry| ArchiveEntry {
+                name: entry.name,
+                size: entry.size,
+                extractable: entry.stored,
+            }));
+        }
+        ArchiveFormat::SevenZip => {
+            let archive = sevenz_rust::Archive::open(path).map_err(sevenz_error)?;
+            entries.extend(
+                archive
+                    .files
+                    .into_iter()
+                    .filter(|file| file.has_stream && !file.is_directory)
+                    .map(|file| ArchiveEntry {
+                        name: file.name,
+                        size: file.size,
+                        extractable: true,
+                    }),
+            );
+        }
+    }
+    Ok(entries)
+}
+
+fn is_media_name(name: &str) -> bool {
+    Path::new(name).extension().is_some_and(|extension| {
+        MEDIA_EXTENSIONS.contains(&extension.to_string_lossy().to_ascii_lowercase().as_str())
+    })
+}
+
+fn zip_error(e: zip::result::ZipError) -> Error {
+    Error::ParseError(format!("Invalid ZIP archive: {}", e))
+}
+
+fn sevenz_error(e: sevenz_rust::Error) -> Error {
+    Error::ParseError(format!("Invalid 7z archive: {}", e))
+}
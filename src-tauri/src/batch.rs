@@ -0,0 +1,123 @@
+use std::{sync::Arc, time::Instant};
+use tokio::sync::Semaphore;
+
+use crate::events::emit_batch_file;
+use crate::inspector::{get_video_metadata, VideoMetadata};
+use crate::job::new_job_id;
+use crate::preset::InspectionPreset;
+use crate::settings;
+
+/// Outcome of one file of a batch inspection
+#[derive(serde::Serialize, Clone)]
+pub struct BatchItem {
+    path: String,
+    /// Job ID of this file's own inspection, which its partial results and
+    /// progress events carry
+    file_job_id: String,
+    metadata: Option<VideoMetadata>,
+    /// Localized error when the inspection failed
+    error: Option<String>,
+}
+
+/// Results of a batch inspection, in the order the paths were given
+#[derive(serde::Serialize, Clone)]
+pub struct BatchResult {
+    job_id: String,
+    succeeded: usize,
+    failed: usize,
+    items: Vec<BatchItem>,
+}
+
+/// Inspect several videos through a queue that runs at most `concurrency`
+/// inspections at once
+///
+/// `concurrency` defaults to the batch concurrency in settings. Each file is
+/// inspected like `get_video_metadata` with `preset`, under its own job ID,
+/// and emits an `inspection://batch-file` event tagged with the batch
+/// `job_id` when it finishes, so results can be shown as they arrive.
+#[tauri::command]
+pub async fn inspect_videos_batch(
+    paths: Vec<String>,
+    concurrency: Option<usize>,
+    preset: Option<InspectionPreset>,
+    job_id: Option<String>,
+) -> Result<BatchResult, String> {
+    let start = Instant::now();
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    let concurrency = concurrency
+        .unwrap_or_else(|| settings::current().batch_concurrency)
+        .max(1);
+    tracing::info!(
+        job_id = %job_id,
+        files = paths.len(),
+        concurrency,
+        "Starting batch inspection"
+    );
+
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let tasks: Vec<_> = paths
+        .into_iter()
+        .enumerate()
+        .map(|(index, path)| {
+            let semaphore = semaphore.clone();
+            let batch_job_id = job_id.clone();
+            tauri::async_runtime::spawn(async move {
+                // Permits only fail once the semaphore is closed, which it never is
+                let _permit = semaphore.acquire_owned().await;
+                let file_job_id = new_job_id();
+                let result = get_video_metadata(
+                    path.clone(),
+                    None,
+                    Some(file_job_id.clone()),
+                    preset,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+                let item = match result {
+                    Ok(metadata) => BatchItem {
+                        path,
+                        file_job_id,
+                        metadata: Some(metadata),
+                        error: None,
+                    },
+                    Err(error) => BatchItem {
+                        path,
+                        file_job_id,
+                        metadata: None,
+                        error: Some(error),
+                    },
+                };
+                emit_batch_file(&batch_job_id, index, &item);
+                item
+            })
+        })
+        .collect();
+
+    let mut items = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let item = task.await.map_err(|e| {
+            tracing::error!(job_id = %job_id, error = %e, "Batch inspection task failed");
+            e.to_string()
+        })?;
+        items.push(item);
+    }
+
+    let failed = items.iter().filter(|item| item.error.is_some()).count();
+    tracing::info!(
+        job_id = %job_id,
+        files = items.len(),
+        failed,
+        elapsed = ?start.elapsed(),
+        "Batch inspection finished"
+    );
+    Ok(BatchResult {
+        job_id,
+        succeeded: items.len() - failed,
+        failed,
+        items,
+    })
+}
@@ -0,0 +1,188 @@
+use std::time::Instant;
+
+use crate::events::emit_ffmpeg_progress;
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::job::new_job_id;
+use crate::progress::{run_ffmpeg_with_progress, FfmpegProgress};
+
+/// Seconds of video decoded when the caller doesn't say
+const DEFAULT_BENCHMARK_SECONDS: f64 = 30.0;
+
+/// Longest span a benchmark decodes
+const MAX_BENCHMARK_SECONDS: f64 = 600.0;
+
+/// Hardware decoding API tried on this platform
+#[cfg(target_os = "macos")]
+const HWACCEL: &str = "videotoolbox";
+#[cfg(target_os = "windows")]
+const HWACCEL: &str = "d3d11va";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const HWACCEL: &str = "vaapi";
+
+/// Decode speed of one decoder
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct BenchmarkRun {
+    /// "software", or the hwaccel API used
+    decoder: String,
+    frames: u64,
+    /// Seconds of video decoded
+    decoded: f64,
+    /// Wall clock seconds the decode took
+    elapsed: f64,
+    fps: f64,
+    /// Decoded seconds per wall clock second; below 1.0 playback will stutter
+    realtime_factor: f64,
+}
+
+/// How fast this machine decodes a file
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DecodeBenchmark {
+    job_id: String,
+    codec_name: String,
+    resolution: String,
+    /// Frame rate of the source, for comparison with the decode fps
+    frame_rate: f64,
+    software: BenchmarkRun,
+    hardware: Option<BenchmarkRun>,
+    /// Why hardware decoding couldn't be measured, when it was requested
+    hardware_error: Option<String>,
+}
+
+/// Measure how fast the file's video decodes on this machine
+///
+/// Decodes the first `seconds` (default 30) of the main video stream to
+/// nowhere, in software and, when `hardware` is set, with the platform's
+/// hardware decoder. A real-time factor well above 1.0 means stutter comes
+/// from the file or player, not the machine. Progress is emitted as
+/// `inspection://ffmpeg-progress` events.
+#[tauri::command]
+pub async fn benchmark_decode(
+    path: String,
+    hardware: Option<bool>,
+    seconds: Option<f64>,
+    job_id: Option<String>,
+) -> Result<DecodeBenchmark, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    let seconds = seconds
+        .unwrap_or(DEFAULT_BENCHMARK_SECONDS)
+        .clamp(1.0, MAX_BENCHMARK_SECONDS);
+    benchmark_decode_async(&path, hardware.unwrap_or(false), seconds, job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Decode benchmark failed");
+            e.localized()
+        })
+}
+
+async fn benchmark_decode_async(
+    path: &str,
+    hardware: bool,
+    seconds: f64,
+    job_id: String,
+) -> Result<DecodeBenchmark, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let video_info = get_video_info_with_ffprobe(app_handle, path).await?;
+    let seconds = seconds.min(video_info.duration.max(0.0));
+
+    let software = run_decode(app_handle, path, None, seconds, &job_id).await?;
+    let (hardware, hardware_error) = if hardware {
+        match run_decode(app_handle, path, Some(HWACCEL), seconds, &job_id).await {
+            Ok(run) => (Some(run), None),
+            Err(e) => {
+                tracing::warn!(video_path = %path, error = %e, "Hardware decode benchmark failed");
+                (None, Some(e.localized()))
+            }
+        }
+    } else {
+        (None, None)
+    };
+
+    tracing::info!(
+        video_path = %path,
+        software_fps = software.fps,
+        hardware_fps = ?hardware.as_ref().map(|run| run.fps),
+        "Decode benchmark finished"
+    );
+
+    Ok(DecodeBenchmark {
+        job_id,
+        codec_name: video_info.video_stream.codec_name().to_string(),
+        resolution: format!("{}x{}", video_info.width, video_info.height),
+        frame_rate: video_info.frame_rate,
+        software,
+        hardware,
+        hardware_error,
+    })
+}
+
+/// Decode `seconds` of the main video stream to the null muxer
+async fn run_decode(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    hwaccel: Option<&str>,
+    seconds: f64,
+    job_id: &str,
+) -> Result<BenchmarkRun, Error> {
+    let mut args: Vec<String> = Vec::new();
+    if let Some(hwaccel) = hwaccel {
+        args.extend(["-hwaccel".to_string(), hwaccel.to_string()]);
+    }
+    args.extend(
+        [
+            "-i",
+            path,
+            "-map",
+            "0:v:0",
+            "-t",
+            &format!("{:.3}", seconds),
+            "-an",
+            "-f",
+            "null",
+            "-",
+        ]
+        .iter()
+        .map(|arg| arg.to_string()),
+    );
+
+    let mut last = FfmpegProgress::default();
+    let start = Instant::now();
+    let run = run_ffmpeg_with_progress(app_handle, &args, Some(seconds), |progress| {
+        last = progress.clone();
+        emit_ffmpeg_progress(job_id, path, "decode_benchmark", progress)
+    })
+    .await?;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    if !run.success() {
+        let tail = run.stderr[run.stderr.len().saturating_sub(5)..].join("\n");
+        return Err(Error::FFmpegError(format!("Decode failed: {}", tail)));
+    }
+    // ffmpeg quietly falls back to software decoding when the hwaccel fails
+    if let Some(hwaccel) = hwaccel {
+        let failed = run.stderr.iter().any(|line| {
+            let line = line.to_lowercase();
+            line.contains("hwaccel") && (line.contains("fail") || line.contains("error"))
+        });
+        if failed {
+            return Err(Error::FFmpegError(format!(
+                "{} hardware decoding isn't available for this file",
+                hwaccel
+            )));
+        }
+    }
+
+    let frames = last.frame().unwrap_or(0);
+    let decoded = last.out_time();
+    let elapsed = elapsed.max(f64::EPSILON);
+    Ok(BenchmarkRun {
+        decoder: hwaccel.unwrap_or("software").to_string(),
+        frames,
+        decoded,
+        elapsed,
+        fps: frames as f64 / elapsed,
+        realtime_factor: decoded / elapsed,
+    })
+}
@@ -0,0 +1,151 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::disc::{find_child, TitleStreams, DISC_PROBE_ARGS};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use video_inspector_core::mpls::{parse_mpls, Playlist};
+use video_inspector_core::Error as CoreError;
+
+/// Folder holding the Blu-ray movie structure
+const BDMV: &str = "BDMV";
+
+/// Playlists shorter than this are menus, trailers and warnings
+const MIN_TITLE_SECONDS: f64 = 60.0;
+
+/// One movie playlist of a Blu-ray
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct BlurayPlaylist {
+    /// File name, e.g. `00800.mpls`
+    name: String,
+    /// Seconds
+    duration: f64,
+    /// `.m2ts` files in playback order
+    segments: Vec<String>,
+    /// Chapter starts in seconds
+    chapters: Vec<f64>,
+}
+
+/// Titles of a Blu-ray folder, with the streams of the main title
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct BlurayReport {
+    bdmv: String,
+    /// Longest first; short playlists are left out
+    playlists: Vec<BlurayPlaylist>,
+    /// Name of the longest playlist, which is almost always the feature
+    main_playlist: String,
+    /// Streams of the main title's first segment
+    streams: TitleStreams,
+}
+
+/// Inspect a Blu-ray folder: the `BDMV` folder itself or a folder containing it
+#[tauri::command]
+pub async fn inspect_bluray(path: String) -> Result<BlurayReport, String> {
+    inspect_bluray_async(&path).await.map_err(|e| {
+        tracing::error!(folder = %path, error = %e, "Blu-ray inspection failed");
+        e.localized()
+    })
+}
+
+async fn inspect_bluray_async(path: &str) -> Result<BlurayReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let bdmv = bdmv_dir(Path::new(path))
+        .ok_or_else(|| CoreError::NotMediaFile(format!("{} has no {} folder", path, BDMV)))?;
+    let stream_dir = find_child(&bdmv, "STREAM").unwrap_or_else(|| bdmv.join("STREAM"));
+
+    let mut playlists = Vec::new();
+    for (name, playlist) in read_playlists(&bdmv)? {
+        let duration = playlist.duration();
+        if duration < MIN_TITLE_SECONDS {
+            continue;
+        }
+        let segments = playlist
+            .items
+            .iter()
+            .map(|item| {
+                let file = format!("{}.m2ts", item.clip);
+                find_child(&stream_dir, &file)
+                    .unwrap_or_else(|| stream_dir.join(file))
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        playlists.push(BlurayPlaylist {
+            name,
+            duration,
+            segments,
+            chapters: playlist.chapters,
+        });
+    }
+    if playlists.is_empty() {
+        return Err(CoreError::NotMediaFile(format!("{} has no movie playlists", path)).into());
+    }
+    playlists.sort_by(|a, b| b.duration.total_cmp(&a.duration));
+
+    // Segments share their streams, so probing the first is enough
+    let main = &playlists[0];
+    let streams = match run_ffprobe_json(app_handle, &main.segments[0], &DISC_PROBE_ARGS).await {
+        Ok(probe) => TitleStreams::from_probe(&probe),
+        Err(e) => {
+            tracing::warn!(folder = %path, error = %e, "Failed to probe Blu-ray main title");
+            TitleStreams::default()
+        }
+    };
+
+    tracing::debug!(
+        folder = %path,
+        playlists = playlists.len(),
+        main_playlist = %main.name,
+        "Inspected Blu-ray folder"
+    );
+
+    Ok(BlurayReport {
+        bdmv: bdmv.to_string_lossy().to_string(),
+        main_playlist: main.name.clone(),
+        playlists,
+        streams,
+    })
+}
+
+/// The `BDMV` folder at or directly under `path`
+pub fn bdmv_dir(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        return None;
+    }
+    let is_bdmv = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(BDMV));
+    if is_bdmv {
+        return Some(path.to_path_buf());
+    }
+    find_child(path, BDMV).filter(|dir| dir.is_dir())
+}
+
+/// Every readable playlist in `BDMV/PLAYLIST`, by file name
+///
+/// Damaged playlists are logged and skipped; discs sometimes ship decoys.
+fn read_playlists(bdmv: &Path) -> Result<Vec<(String, Playlist)>, Error> {
+    let playlist_dir = find_child(bdmv, "PLAYLIST").unwrap_or_else(|| bdmv.join("PLAYLIST"));
+    let mut playlists = Vec::new();
+    for entry in fs::read_dir(&playlist_dir)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !name.to_ascii_lowercase().ends_with(".mpls") {
+            continue;
+        }
+        let playlist = fs::read(&path)
+            .map_err(Error::from)
+            .and_then(|data| Ok(parse_mpls(&data)?));
+        match playlist {
+            Ok(playlist) => playlists.push((name, playlist)),
+            Err(e) => tracing::warn!(file = %path.display(), error = %e, "Skipping playlist"),
+        }
+    }
+    Ok(playlists)
+}
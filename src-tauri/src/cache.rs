@@ -0,0 +1,210 @@
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tauri::Manager;
+
+use crate::disk::ensure_free_space;
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::settings;
+
+/// Name of the cache subdirectory inside the app cache directory
+const CACHE_DIR_NAME: &str = "cache";
+
+/// Extension of cache entry files, so unrelated files in a user-chosen
+/// directory are never counted or evicted
+const ENTRY_EXTENSION: &str = "cache";
+
+/// Serializes writes and evictions across concurrent tasks
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Cache usage summary
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CacheStats {
+    location: String,
+    entries: usize,
+    total_bytes: u64,
+    max_bytes: u64,
+}
+
+/// Directory holding cache entries, from settings or the app cache directory
+pub fn cache_dir() -> Result<PathBuf, Error> {
+    let dir = match settings::current().cache_dir {
+        Some(dir) => dir,
+        None => {
+            let app_handle = get_app_handle()
+                .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+            app_handle
+                .path()
+                .app_cache_dir()
+                .map_err(|e| Error::IoError(std::io::Error::other(e.to_string())))?
+                .join(CACHE_DIR_NAME)
+        }
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Identify a file's current contents by path, size and modification time
+///
+/// Cheap compared to hashing; any edit to the file changes the fingerprint.
+pub fn file_fingerprint(path: &str) -> Result<String, Error> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Ok(format!("{}|{}|{}", path, metadata.len(), modified))
+}
+
+/// Build a cache key from its parts
+pub fn cache_key(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Look up a cache entry, marking it as recently used
+pub fn read(key: &str) -> Option<Vec<u8>> {
+    let path = entry_path(&cache_dir().ok()?, key);
+    let data = fs::read(&path).ok()?;
+
+    // The modification time doubles as the last access time for eviction
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(data)
+}
+
+/// Store a cache entry, evicting old entries beyond the size cap
+pub fn write(key: &str, data: &[u8]) -> Result<(), Error> {
+    let dir = cache_dir()?;
+    let max_bytes = settings::current().cache_max_bytes;
+    if data.len() as u64 > max_bytes {
+        return Ok(());
+    }
+
+    ensure_free_space(&dir, data.len() as u64)?;
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    fs::write(entry_path(&dir, key), data)?;
+    evict(&dir, max_bytes)?;
+    Ok(())
+}
+
+/// Evict entries until the cache fits the configured size
+///
+/// Failures are logged; a cache over its cap is not worth failing a command.
+pub fn enforce_size_limit() {
+    let result = cache_dir().and_then(|dir| {
+        let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        evict(&dir, settings::current().cache_max_bytes)
+    });
+    if let Err(e) = result {
+        tracing::warn!(error = %e, "Failed to enforce cache size limit");
+    }
+}
+
+/// Report cache location and usage
+#[tauri::command]
+pub async fn get_cache_stats() -> Result<CacheStats, String> {
+    cache_stats().map_err(|e| {
+        tracing::error!(error = %e, "Failed to read cache stats");
+        e.localized()
+    })
+}
+
+/// Delete every cache entry
+#[tauri::command]
+pub async fn clear_cache() -> Result<CacheStats, String> {
+    clear_cache_entries()
+        .and_then(|_| cache_stats())
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to clear cache");
+            e.localized()
+        })
+}
+
+fn cache_stats() -> Result<CacheStats, Error> {
+    let dir = cache_dir()?;
+    let entries = list_entries(&dir)?;
+    Ok(CacheStats {
+        location: dir.to_string_lossy().to_string(),
+        entries: entries.len(),
+        total_bytes: entries.iter().map(|entry| entry.size).sum(),
+        max_bytes: settings::current().cache_max_bytes,
+    })
+}
+
+fn clear_cache_entries() -> Result<(), Error> {
+    let dir = cache_dir()?;
+    let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let entries = list_entries(&dir)?;
+    for entry in &entries {
+        fs::remove_file(&entry.path)?;
+    }
+
+    tracing::info!(dir = %dir.display(), removed = entries.len(), "Cache cleared");
+    Ok(())
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.{}", key, ENTRY_EXTENSION))
+}
+
+fn list_entries(dir: &Path) -> Result<Vec<CacheEntry>, Error> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(ENTRY_EXTENSION) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        entries.push(CacheEntry {
+            path,
+            size: metadata.len(),
+            last_used: metadata.modified().unwrap_or(UNIX_EPOCH),
+        });
+    }
+    Ok(entries)
+}
+
+/// Remove least recently used entries until the total fits `max_bytes`
+fn evict(dir: &Path, max_bytes: u64) -> Result<(), Error> {
+    let mut entries = list_entries(dir)?;
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    if total <= max_bytes {
+        return Ok(());
+    }
+
+    entries.sort_by_key(|entry| entry.last_used);
+    let mut removed = 0;
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&entry.path).is_ok() {
+            total -= entry.size;
+            removed += 1;
+        }
+    }
+
+    tracing::debug!(removed, total_bytes = total, "Evicted cache entries");
+    Ok(())
+}
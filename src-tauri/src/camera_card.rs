@@ -0,0 +1,317 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::disc::{find_child, probe_duration, TitleStreams};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use video_inspector_core::mpls::parse_mpls;
+use video_inspector_core::Error as CoreError;
+
+/// Video files looked for in `DCIM` folders; stills and GoPro proxies are left out
+const DCIM_VIDEO_EXTENSIONS: [&str; 7] = ["3gp", "avi", "m2ts", "mov", "mp4", "mts", "mxf"];
+
+/// Card folder structures recognized by `inspect_camera_card`
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CardLayout {
+    /// `DCIM/100XXXXX`, used by action cams, phones and still cameras
+    Dcim,
+    /// Sony `XDROOT/Clip` or `PRIVATE/M4ROOT/CLIP`
+    Xdcam,
+    /// `PRIVATE/AVCHD/BDMV`, with recordings split into 2 GB `.MTS` files
+    Avchd,
+}
+
+/// One recording, which may span several files
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CardClip {
+    name: String,
+    /// Files in recording order
+    files: Vec<String>,
+    size: u64,
+    /// Seconds, summed over the files
+    duration: Option<f64>,
+    /// Streams of the first file
+    streams: TitleStreams,
+}
+
+/// Clips recorded to one folder of the card
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CardReel {
+    name: String,
+    layout: CardLayout,
+    clips: Vec<CardClip>,
+}
+
+/// Recordings found on a camera card
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CardReport {
+    root: String,
+    reels: Vec<CardReel>,
+    clip_count: usize,
+    total_size: u64,
+    /// Seconds
+    total_duration: f64,
+}
+
+/// Files of each clip of a reel, by clip name
+type ReelClips = Vec<(String, Vec<PathBuf>)>;
+
+/// Inspect a camera card (or a copy of one), grouping files into clips
+///
+/// Files a camera split at the FAT32 size limit are reported as one clip.
+#[tauri::command]
+pub async fn inspect_camera_card(path: String) -> Result<CardReport, String> {
+    inspect_camera_card_async(&path).await.map_err(|e| {
+        tracing::error!(folder = %path, error = %e, "Camera card inspection failed");
+        e.localized()
+    })
+}
+
+async fn inspect_camera_card_async(path: &str) -> Result<CardReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let root = Path::new(path);
+
+    let mut found: Vec<(String, CardLayout, ReelClips)> = Vec::new();
+    if let Some(dcim) = find_child(root, "DCIM") {
+        for (reel, clips) in dcim_reels(&dcim)? {
+            found.push((reel, CardLayout::Dcim, clips));
+        }
+    }
+    if let Some(clip_dir) = xdcam_clip_dir(root) {
+        found.push((
+            "XDCAM".to_string(),
+            CardLayout::Xdcam,
+            xdcam_clips(&clip_dir)?,
+        ));
+    }
+    if let Some(bdmv) = avchd_dir(root) {
+        found.push(("AVCHD".to_string(), CardLayout::Avchd, avchd_clips(&bdmv)?));
+    }
+    found.retain(|(_, _, clips)| !clips.is_empty());
+    if found.is_empty() {
+        return Err(CoreError::NotMediaFile(format!("{} has no camera clips", path)).into());
+    }
+
+    let mut reels = Vec::with_capacity(found.len());
+    for (name, layout, clips) in found {
+        let mut card_clips = Vec::with_capacity(clips.len());
+        for (clip_name, files) in clips {
+            card_clips.push(inspect_clip(app_handle, clip_name, &files).await);
+        }
+        reels.push(CardReel {
+            name,
+            layout,
+            clips: card_clips,
+        });
+    }
+
+    let clips = || reels.iter().flat_map(|reel| &reel.clips);
+    let report = CardReport {
+        root: path.to_string(),
+        clip_count: clips().count(),
+        total_size: clips().map(|clip| clip.size).sum(),
+        total_duration: clips().filter_map(|clip| clip.duration).sum(),
+        reels,
+    };
+    tracing::debug!(
+        folder = %path,
+        reels = report.reels.len(),
+        clips = report.clip_count,
+        "Inspected camera card"
+    );
+    Ok(report)
+}
+
+/// Combine the metadata of a clip's files
+///
+/// Files that can't be probed are logged and leave the duration incomplete.
+async fn inspect_clip(app_handle: &tauri::AppHandle, name: String, files: &[PathBuf]) -> CardClip {
+    let mut size = 0;
+    let mut duration = Some(0.0);
+    let mut streams = None;
+    for file in files {
+        size += fs::metadata(file)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let file_path = file.to_string_lossy();
+        match run_ffprobe_json(app_handle, &file_path, &["-show_format", "-show_streams"]).await {
+            Ok(probe) => {
+                duration = duration.zip(probe_duration(&probe)).map(|(a, b)| a + b);
+                streams.get_or_insert_with(|| TitleStreams::from_probe(&probe));
+            }
+            Err(e) => {
+                tracing::warn!(video_path = %file_path, error = %e, "Failed to probe clip file");
+                duration = None;
+            }
+        }
+    }
+    CardClip {
+        name,
+        files: files
+            .iter()
+            .map(|file| file.to_string_lossy().to_string())
+            .collect(),
+        size,
+        duration,
+        streams: streams.unwrap_or_default(),
+    }
+}
+
+/// Clips of each `DCIM` subfolder, with GoPro chapters joined
+fn dcim_reels(dcim: &Path) -> Result<Vec<(String, ReelClips)>, Error> {
+    let mut folders: Vec<PathBuf> = fs::read_dir(dcim)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    folders.sort();
+
+    let mut reels = Vec::new();
+    for folder in folders {
+        // Clip name -> chapter -> file
+        let mut clips: BTreeMap<String, BTreeMap<u32, PathBuf>> = BTreeMap::new();
+        for file in sorted_files(&folder)? {
+            let name = file_name(&file);
+            let is_video = Path::new(&name).extension().is_some_and(|extension| {
+                DCIM_VIDEO_EXTENSIONS
+                    .contains(&extension.to_string_lossy().to_ascii_lowercase().as_str())
+            });
+            if !is_video {
+                continue;
+            }
+            let (clip, chapter) = gopro_chapter(&name).unwrap_or((name, 0));
+            clips.entry(clip).or_default().insert(chapter, file);
+        }
+        reels.push((
+            file_name(&folder),
+            clips
+                .into_iter()
+                .map(|(clip, chapters)| (clip, chapters.into_values().collect()))
+                .collect(),
+        ));
+    }
+    Ok(reels)
+}
+
+/// Clip name and chapter of a GoPro file name
+///
+/// `GOPR0042.MP4` starts clip 42 and `GP010042.MP4` continues it on older
+/// models; newer ones name chapters `GX010042.MP4`, `GX020042.MP4`, ...
+fn gopro_chapter(name: &str) -> Option<(String, u32)> {
+    let upper = name.to_ascii_uppercase();
+    let (stem, extension) = upper.split_once('.')?;
+    let digits = |text: &str| text.len() == 4 && text.bytes().all(|byte| byte.is_ascii_digit());
+    if let Some(clip) = stem.strip_prefix("GOPR").filter(|clip| digits(clip)) {
+        return Some((format!("GOPR{}.{}", clip, extension), 0));
+    }
+    let (prefix, rest) = stem.split_at_checked(2)?;
+    let (chapter, clip) = rest.split_at_checked(2)?;
+    if !matches!(prefix, "GP" | "GH" | "GX") || !digits(clip) {
+        return None;
+    }
+    let chapter: u32 = chapter.parse().ok()?;
+    // The first chapter of older models is the GOPR file
+    let clip = if prefix == "GP" {
+        format!("GOPR{}.{}", clip, extension)
+    } else {
+        format!("{}01{}.{}", prefix, clip, extension)
+    };
+    Some((clip, chapter))
+}
+
+/// `XDROOT/Clip` or `PRIVATE/M4ROOT/CLIP` under `root`
+fn xdcam_clip_dir(root: &Path) -> Option<PathBuf> {
+    find_child(root, "XDROOT")
+        .and_then(|xdroot| find_child(&xdroot, "Clip"))
+        .or_else(|| {
+            find_child(root, "PRIVATE")
+                .and_then(|private| find_child(&private, "M4ROOT"))
+                .and_then(|m4root| find_child(&m4root, "CLIP"))
+        })
+}
+
+/// Each `.MXF` or `.MP4` is a clip; the `.XML` sidecars hold camera metadata
+fn xdcam_clips(clip_dir: &Path) -> Result<ReelClips, Error> {
+    Ok(sorted_files(clip_dir)?
+        .into_iter()
+        .filter(|file| {
+            file.extension().is_some_and(|extension| {
+                matches!(
+                    extension.to_string_lossy().to_ascii_lowercase().as_str(),
+                    "mxf" | "mp4"
+                )
+            })
+        })
+        .map(|file| (file_name(&file), vec![file]))
+        .collect())
+}
+
+/// `PRIVATE/AVCHD/BDMV` under `root`
+fn avchd_dir(root: &Path) -> Option<PathBuf> {
+    find_child(root, "PRIVATE")
+        .and_then(|private| find_child(&private, "AVCHD"))
+        .and_then(|avchd| find_child(&avchd, "BDMV"))
+}
+
+/// Recordings of an AVCHD card from its playlists
+///
+/// Play items connected seamlessly to the previous one are the continuation
+/// files of a split recording.
+fn avchd_clips(bdmv: &Path) -> Result<ReelClips, Error> {
+    let stream_dir = find_child(bdmv, "STREAM").unwrap_or_else(|| bdmv.join("STREAM"));
+    let Some(playlist_dir) = find_child(bdmv, "PLAYLIST") else {
+        return Ok(Vec::new());
+    };
+
+    let mut clips: ReelClips = Vec::new();
+    let mut seen = HashSet::new();
+    for file in sorted_files(&playlist_dir)? {
+        let playlist = match fs::read(&file)
+            .map_err(Error::from)
+            .and_then(|data| Ok(parse_mpls(&data)?))
+        {
+            Ok(playlist) => playlist,
+            Err(e) => {
+                tracing::warn!(file = %file.display(), error = %e, "Skipping playlist");
+                continue;
+            }
+        };
+        let mut recordings: ReelClips = Vec::new();
+        for item in playlist.items {
+            let name = format!("{}.MTS", item.clip);
+            let path = find_child(&stream_dir, &name).unwrap_or_else(|| stream_dir.join(&name));
+            match recordings.last_mut() {
+                Some((_, files)) if item.seamless => files.push(path),
+                _ => recordings.push((name, vec![path])),
+            }
+        }
+        // The same recordings may be listed by more than one playlist
+        clips.extend(
+            recordings
+                .into_iter()
+                .filter(|(name, _)| seen.insert(name.clone())),
+        );
+    }
+    Ok(clips)
+}
+
+fn sorted_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,109 @@
+use base64::{engine::general_purpose, Engine};
+use std::time::Instant;
+
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::runner::SidecarRunner;
+use crate::tags::tag;
+use crate::temp::temp_frame_path;
+use crate::thumbnail::{extract_frame, Thumbnail};
+
+/// Chapter thumbnails are taken this many seconds after the chapter start,
+/// past the fade-in most chapters open with
+const THUMBNAIL_OFFSET_SECS: f64 = 1.0;
+
+/// Filter used for chapter thumbnails
+const CHAPTER_THUMBNAIL_FILTER: &str = "scale=320:-2";
+
+/// One row of a chapter index
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ChapterEntry {
+    index: usize,
+    title: Option<String>,
+    /// Seconds
+    start: f64,
+    /// Seconds until the next chapter, or the end of the file
+    duration: f64,
+    /// `None` when no frame could be extracted
+    thumbnail: Option<Thumbnail>,
+}
+
+/// List the chapters of a file with their start, duration and a thumbnail
+/// near each start, as a visual index; empty when there are no chapters
+#[tauri::command]
+pub async fn chapter_index(path: String) -> Result<Vec<ChapterEntry>, String> {
+    chapter_index_async(&path).await.map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Chapter index failed");
+        e.localized()
+    })
+}
+
+async fn chapter_index_async(path: &str) -> Result<Vec<ChapterEntry>, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let json = run_ffprobe_json(app_handle, path, &["-show_format", "-show_chapters"]).await?;
+    let runner = SidecarRunner::new(app_handle);
+    let file_duration = json_f64(&json["format"]["duration"]);
+
+    let mut entries = Vec::new();
+    for (index, chapter) in json["chapters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let Some(start) = json_f64(&chapter["start_time"]) else {
+            continue;
+        };
+        let end = json_f64(&chapter["end_time"])
+            .or(file_duration)
+            .unwrap_or(start);
+        let duration = (end - start).max(0.0);
+
+        let time_point = start + THUMBNAIL_OFFSET_SECS.min(duration / 2.0);
+        let temp_image_path = temp_frame_path("chapter", "png")?;
+        let start_time = Instant::now();
+        let thumbnail = match extract_frame(
+            &runner,
+            path,
+            time_point,
+            CHAPTER_THUMBNAIL_FILTER,
+            None,
+            None,
+            &temp_image_path,
+        )
+        .await
+        {
+            Ok(image_data) => Some(Thumbnail {
+                timestamp: time_point,
+                data_url: format!(
+                    "data:image/png;base64,{}",
+                    general_purpose::STANDARD.encode(&image_data)
+                ),
+                hints: None,
+                elapsed_ms: start_time.elapsed().as_millis() as u64,
+            }),
+            Err(e) => {
+                tracing::warn!(
+                    video_path = %path,
+                    chapter = index,
+                    error = %e,
+                    "Chapter thumbnail extraction failed"
+                );
+                None
+            }
+        };
+
+        entries.push(ChapterEntry {
+            index,
+            title: tag(chapter, "title"),
+            start,
+            duration,
+            thumbnail,
+        });
+    }
+
+    tracing::debug!(video_path = %path, chapters = entries.len(), "Built chapter index");
+    Ok(entries)
+}
@@ -0,0 +1,340 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::disk::ensure_free_space;
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, run_ffprobe_json, Error};
+use crate::runner::{sidecar, KillOnDrop, MediaTool};
+use crate::temp::temp_frame_path;
+
+/// Keyframes closer than this to the requested start count as exact
+const KEYFRAME_TOLERANCE: f64 = 0.001;
+
+/// A segment cut out of a file
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ClipResult {
+    output_path: String,
+    /// Where the clip really starts in the source; earlier than requested
+    /// when a plain stream copy had to start at the previous keyframe
+    start: f64,
+    end: f64,
+    /// Whether the first GOP was re-encoded to start exactly at `start`
+    smart_cut: bool,
+    size: u64,
+}
+
+/// Copy `start..end` (seconds) of a file into a new file without re-encoding
+///
+/// A stream copy can only start on a keyframe, so the clip begins at the last
+/// keyframe before `start`. With `smart_cut` the part up to the next keyframe
+/// is re-encoded instead, so the clip starts exactly at `start`; players that
+/// don't handle a change of codec parameters mid-stream may glitch at that
+/// splice. The output defaults to `<name>_clip_<start>-<end>.<ext>` next to
+/// the input.
+#[tauri::command]
+pub async fn extract_clip(
+    path: String,
+    start: f64,
+    end: f64,
+    smart_cut: Option<bool>,
+    output_path: Option<String>,
+) -> Result<ClipResult, String> {
+    extract_clip_async(
+        &path,
+        start,
+        end,
+        smart_cut.unwrap_or(false),
+        output_path.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Clip extraction failed");
+        e.localized()
+    })
+}
+
+async fn extract_clip_async(
+    path: &str,
+    start: f64,
+    end: f64,
+    smart_cut: bool,
+    output_path: Option<&str>,
+) -> Result<ClipResult, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let video_info = get_video_info_with_ffprobe(app_handle, path).await?;
+    let end = end.min(video_info.duration);
+    if !(start >= 0.0 && start < end) {
+        return Err(Error::ParseError(format!(
+            "Invalid clip range {:.3}..{:.3}",
+            start, end
+        )));
+    }
+
+    let output_path = match output_path {
+        Some(output_path) => PathBuf::from(output_path),
+        None => default_output_path(path, start, end),
+    };
+    if output_path.exists() {
+        return Err(Error::FFmpegError(format!(
+            "{} already exists",
+            output_path.display()
+        )));
+    }
+    let estimated_size =
+        fs::metadata(path)?.len() as f64 * (end - start) / video_info.duration.max(f64::EPSILON);
+    ensure_free_space(&output_path, estimated_size as u64)?;
+    let output = output_path.to_string_lossy().to_string();
+
+    let keyframes = keyframe_times(app_handle, path, start, end).await?;
+    let keyframe_start = keyframes
+        .iter()
+        .copied()
+        .rfind(|&time| time <= start + KEYFRAME_TOLERANCE)
+        .unwrap_or(0.0);
+    let next_keyframe = keyframes
+        .iter()
+        .copied()
+        .find(|&time| time > start + KEYFRAME_TOLERANCE && time < end);
+
+    let started = Instant::now();
+    let on_keyframe = (start - keyframe_start).abs() <= KEYFRAME_TOLERANCE;
+    let (clip_start, smart_cut) = match next_keyframe {
+        Some(next_keyframe) if smart_cut && !on_keyframe => {
+            let encoder = smart_cut_encoder(video_info.video_stream.codec_name())?;
+            let pix_fmt = video_info
+                .video_stream_json()
+                .and_then(|stream| stream["pix_fmt"].as_str())
+                .unwrap_or("yuv420p")
+                .to_string();
+            splice_clip(
+                app_handle,
+                path,
+                (start, next_keyframe, end),
+                encoder,
+                &pix_fmt,
+                &output,
+            )
+            .await?;
+            (start, true)
+        }
+        _ => {
+            copy_range(app_handle, path, (start, Some(end)), &output, false).await?;
+            (keyframe_start, false)
+        }
+    };
+
+    let size = fs::metadata(&output_path)?.len();
+    tracing::info!(
+        video_path = %path,
+        output_path = %output,
+        start = clip_start,
+        end,
+        smart_cut,
+        elapsed = ?started.elapsed(),
+        "Extracted clip"
+    );
+
+    Ok(ClipResult {
+        output_path: output,
+        start: clip_start,
+        end,
+        smart_cut,
+        size,
+    })
+}
+
+/// `<dir>/<name>_clip_<start>-<end>.<ext>`, keeping the input container
+fn default_output_path(path: &str, start: f64, end: f64) -> PathBuf {
+    let input = Path::new(path);
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let extension = input
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mkv".to_string());
+    input.with_file_name(format!(
+        "{}_clip_{:.0}-{:.0}.{}",
+        stem, start, end, extension
+    ))
+}
+
+/// Presentation times of the video keyframes between `from` and `to`
+///
+/// Reading starts at the keyframe before `from`, which is included.
+pub async fn keyframe_times(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    from: f64,
+    to: f64,
+) -> Result<Vec<f64>, Error> {
+    let intervals = format!("{:.3}%{:.3}", from, to);
+    let json = run_ffprobe_json(
+        app_handle,
+        path,
+        &[
+            "-select_streams",
+            "v:0",
+            "-read_intervals",
+            &intervals,
+            "-show_entries",
+            "packet=pts_time,flags",
+        ],
+    )
+    .await?;
+
+    let mut keyframes: Vec<f64> = json["packets"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+        .iter()
+        .filter(|packet| packet["flags"].as_str().is_some_and(|f| f.starts_with('K')))
+        .filter_map(|packet| json_f64(&packet["pts_time"]))
+        .collect();
+    keyframes.sort_by(f64::total_cmp);
+    keyframes.dedup();
+    Ok(keyframes)
+}
+
+/// Re-encode `start..next_keyframe`, copy `next_keyframe..end` and join both
+async fn splice_clip(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    (start, next_keyframe, end): (f64, f64, f64),
+    encoder: &str,
+    pix_fmt: &str,
+    output: &str,
+) -> Result<(), Error> {
+    let extension = Path::new(output)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mkv".to_string());
+    let head_path = temp_frame_path("clip_head", &extension)?;
+    let tail_path = temp_frame_path("clip_tail", &extension)?;
+    let list_path = temp_frame_path("clip_concat", "txt")?;
+    let head = head_path.to_string_lossy().to_string();
+    let tail = tail_path.to_string_lossy().to_string();
+
+    run_ffmpeg(
+        app_handle,
+        &[
+            "-y",
+            "-ss",
+            &format!("{:.6}", start),
+            "-i",
+            path,
+            "-t",
+            &format!("{:.6}", next_keyframe - start),
+            "-map",
+            "0:v:0",
+            "-map",
+            "0:a?",
+            "-c:v",
+            encoder,
+            "-crf",
+            "16",
+            "-pix_fmt",
+            pix_fmt,
+            "-c:a",
+            "copy",
+            &head,
+        ],
+    )
+    .await?;
+    copy_range(app_handle, path, (next_keyframe, Some(end)), &tail, true).await?;
+
+    // The concat demuxer reads a list of files; quotes in paths are escaped
+    let mut list = fs::File::create(&list_path)?;
+    for part in [&head, &tail] {
+        writeln!(list, "file '{}'", part.replace('\'', r"'\''"))?;
+    }
+    drop(list);
+
+    run_ffmpeg(
+        app_handle,
+        &[
+            "-n",
+            "-f",
+            "concat",
+            "-safe",
+            "0",
+            "-i",
+            &list_path.to_string_lossy(),
+            "-map",
+            "0",
+            "-c",
+            "copy",
+            output,
+        ],
+    )
+    .await
+}
+
+/// Stream-copy the main video and every audio stream from `start`
+async fn copy_range(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    (start, end): (f64, Option<f64>),
+    output: &str,
+    overwrite: bool,
+) -> Result<(), Error> {
+    let start = format!("{:.6}", start);
+    let end = end.map(|end| format!("{:.6}", end));
+    let mut args = vec![if overwrite { "-y" } else { "-n" }, "-ss", &start];
+    if let Some(end) = &end {
+        args.extend(["-to", end]);
+    }
+    args.extend([
+        "-i",
+        path,
+        "-map",
+        "0:v:0",
+        "-map",
+        "0:a?",
+        "-c",
+        "copy",
+        // Shift timestamps so the clip starts at zero
+        "-avoid_negative_ts",
+        "make_zero",
+        output,
+    ]);
+    run_ffmpeg(app_handle, &args).await
+}
+
+async fn run_ffmpeg(app_handle: &tauri::AppHandle, args: &[&str]) -> Result<(), Error> {
+    let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args(["-hide_banner", "-v", "error"])
+        .args(args)
+        .output_or_kill()
+        .await
+        .map_err(|e| Error::FFmpegError(format!("Failed to execute ffmpeg: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffmpeg failed: {}", stderr)));
+    }
+    Ok(())
+}
+
+/// Encoder producing the same codec as the source for the re-encoded GOP
+fn smart_cut_encoder(codec_name: &str) -> Result<&'static str, Error> {
+    match codec_name {
+        "h264" => Ok("libx264"),
+        "hevc" => Ok("libx265"),
+        "vp9" => Ok("libvpx-vp9"),
+        "av1" => Ok("libsvtav1"),
+        "mpeg4" => Ok("mpeg4"),
+        other => Err(Error::FFmpegError(format!(
+            "Smart cut isn't supported for {} video",
+            other
+        ))),
+    }
+}
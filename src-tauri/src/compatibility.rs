@@ -0,0 +1,463 @@
+use crate::codec::{bit_depth, video_level};
+use crate::container::read_container_info;
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::probe::parse_fraction;
+use crate::sniff::ensure_media_file;
+
+/// A video codec a target can decode, with its limits
+pub struct VideoSupport {
+    pub codec: &'static str,
+    /// Accepted ffprobe profile names (lowercase); empty accepts any
+    pub profiles: &'static [&'static str],
+    /// Highest level, e.g. 4.1 for H.264 High@4.1
+    pub max_level: Option<f64>,
+    pub max_bit_depth: u32,
+}
+
+/// Playback capabilities of a family of devices
+pub struct Target {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub mime_types: &'static [&'static str],
+    /// In order of preference, the first one being the safest choice
+    pub video: &'static [VideoSupport],
+    /// In order of preference, the first one being the safest choice
+    pub audio_codecs: &'static [&'static str],
+    pub max_audio_channels: u32,
+    /// Longest and shortest side, so portrait videos are judged the same way
+    pub max_long_side: u32,
+    pub max_short_side: u32,
+    pub max_frame_rate: f64,
+}
+
+const H264_HIGH: &[&str] = &["constrained baseline", "baseline", "main", "high"];
+
+/// Built-in target profiles
+///
+/// These are conservative: something passing here plays on practically every
+/// device of the family, while a failure may still play on recent models.
+pub const TARGETS: &[Target] = &[
+    Target {
+        id: "web",
+        name: "Web browsers",
+        mime_types: &["video/mp4", "video/webm"],
+        video: &[
+            VideoSupport {
+                codec: "h264",
+                profiles: H264_HIGH,
+                max_level: Some(5.1),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "vp9",
+                profiles: &["profile 0"],
+                max_level: None,
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "av1",
+                profiles: &["main"],
+                max_level: None,
+                max_bit_depth: 10,
+            },
+        ],
+        audio_codecs: &["aac", "opus", "mp3", "vorbis"],
+        max_audio_channels: 2,
+        max_long_side: 3840,
+        max_short_side: 2160,
+        max_frame_rate: 60.0,
+    },
+    Target {
+        id: "ios",
+        name: "iPhone / iPad",
+        mime_types: &["video/mp4", "video/quicktime"],
+        video: &[
+            VideoSupport {
+                codec: "h264",
+                profiles: H264_HIGH,
+                max_level: Some(5.2),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "hevc",
+                profiles: &["main", "main 10"],
+                max_level: Some(5.1),
+                max_bit_depth: 10,
+            },
+        ],
+        audio_codecs: &["aac", "alac", "ac3", "eac3", "mp3"],
+        max_audio_channels: 8,
+        max_long_side: 3840,
+        max_short_side: 2160,
+        max_frame_rate: 60.0,
+    },
+    Target {
+        id: "android",
+        name: "Android phones",
+        mime_types: &["video/mp4", "video/webm", "video/x-matroska", "video/3gpp"],
+        video: &[
+            VideoSupport {
+                codec: "h264",
+                profiles: H264_HIGH,
+                max_level: Some(4.2),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "hevc",
+                profiles: &["main"],
+                max_level: Some(4.1),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "vp9",
+                profiles: &["profile 0"],
+                max_level: None,
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "vp8",
+                profiles: &[],
+                max_level: None,
+                max_bit_depth: 8,
+            },
+        ],
+        audio_codecs: &["aac", "opus", "mp3", "vorbis", "flac"],
+        max_audio_channels: 2,
+        max_long_side: 1920,
+        max_short_side: 1080,
+        max_frame_rate: 60.0,
+    },
+    Target {
+        id: "smart_tv",
+        name: "Smart TVs",
+        mime_types: &["video/mp4", "video/x-matroska", "video/mp2t"],
+        video: &[
+            VideoSupport {
+                codec: "h264",
+                profiles: H264_HIGH,
+                max_level: Some(5.1),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "hevc",
+                profiles: &["main", "main 10"],
+                max_level: Some(5.1),
+                max_bit_depth: 10,
+            },
+            VideoSupport {
+                codec: "vp9",
+                profiles: &["profile 0", "profile 2"],
+                max_level: None,
+                max_bit_depth: 10,
+            },
+        ],
+        audio_codecs: &["aac", "ac3", "eac3", "mp3"],
+        max_audio_channels: 6,
+        max_long_side: 3840,
+        max_short_side: 2160,
+        max_frame_rate: 60.0,
+    },
+    Target {
+        id: "console",
+        name: "Game consoles (PlayStation / Xbox)",
+        mime_types: &["video/mp4", "video/x-matroska"],
+        video: &[
+            VideoSupport {
+                codec: "h264",
+                profiles: H264_HIGH,
+                max_level: Some(5.2),
+                max_bit_depth: 8,
+            },
+            VideoSupport {
+                codec: "hevc",
+                profiles: &["main", "main 10"],
+                max_level: Some(5.1),
+                max_bit_depth: 10,
+            },
+        ],
+        audio_codecs: &["aac", "ac3", "mp3"],
+        max_audio_channels: 6,
+        max_long_side: 3840,
+        max_short_side: 2160,
+        max_frame_rate: 60.0,
+    },
+];
+
+/// What an incompatibility is about, so fixes can be derived from it
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    Container,
+    VideoCodec,
+    VideoProfile,
+    VideoLevel,
+    BitDepth,
+    Resolution,
+    FrameRate,
+    AudioCodec,
+    AudioChannels,
+}
+
+/// A single reason a file won't play on a target
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct CompatibilityIssue {
+    pub kind: IssueKind,
+    /// ffprobe stream index; `None` for container issues
+    pub stream_index: Option<u64>,
+    pub message: String,
+}
+
+/// Compatibility of a file with one target
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TargetCompatibility {
+    pub target: &'static str,
+    pub name: &'static str,
+    pub compatible: bool,
+    pub issues: Vec<CompatibilityIssue>,
+}
+
+/// Check a file against the built-in device profiles
+///
+/// `target` is a profile id ("web", "ios", "android", "smart_tv", "console");
+/// when omitted every profile is checked.
+#[tauri::command]
+pub async fn check_compatibility(
+    path: String,
+    target: Option<String>,
+) -> Result<Vec<TargetCompatibility>, String> {
+    check_compatibility_async(&path, target.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Compatibility check failed");
+            e.localized()
+        })
+}
+
+pub async fn check_compatibility_async(
+    path: &str,
+    target: Option<&str>,
+) -> Result<Vec<TargetCompatibility>, Error> {
+    let targets: Vec<&Target> = match target {
+        Some(id) => vec![find_target(id)?],
+        None => TARGETS.iter().collect(),
+    };
+
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let (mime_type, json) = probe_for_compatibility(app_handle, path).await?;
+
+    let results: Vec<TargetCompatibility> = targets
+        .into_iter()
+        .map(|target| {
+            let issues = check_target(target, mime_type.as_deref(), &json);
+            TargetCompatibility {
+                target: target.id,
+                name: target.name,
+                compatible: issues.is_empty(),
+                issues,
+            }
+        })
+        .collect();
+
+    tracing::debug!(
+        video_path = %path,
+        incompatible = results.iter().filter(|r| !r.compatible).count(),
+        "Checked device compatibility"
+    );
+
+    Ok(results)
+}
+
+/// Read what the checks need: the container MIME type and the ffprobe streams
+pub async fn probe_for_compatibility(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+) -> Result<(Option<String>, serde_json::Value), Error> {
+    let kind = ensure_media_file(path)?;
+    let container = read_container_info(path, kind)?;
+    let json = run_ffprobe_json(app_handle, path, &["-show_format", "-show_streams"]).await?;
+    Ok((container.mime_type().map(str::to_string), json))
+}
+
+/// Look up a built-in target profile by id
+pub fn find_target(id: &str) -> Result<&'static Target, Error> {
+    TARGETS
+        .iter()
+        .find(|target| target.id == id)
+        .ok_or_else(|| {
+            let known: Vec<&str> = TARGETS.iter().map(|target| target.id).collect();
+            Error::ParseError(format!(
+                "Unknown target '{}', expected one of: {}",
+                id,
+                known.join(", ")
+            ))
+        })
+}
+
+/// Compare the container and every video/audio stream with a target
+pub fn check_target(
+    target: &Target,
+    mime_type: Option<&str>,
+    json: &serde_json::Value,
+) -> Vec<CompatibilityIssue> {
+    let mut issues = Vec::new();
+
+    if !mime_type.is_some_and(|mime| target.mime_types.contains(&mime)) {
+        issues.push(CompatibilityIssue {
+            kind: IssueKind::Container,
+            stream_index: None,
+            message: format!(
+                "Container {} is not supported (supported: {})",
+                mime_type.unwrap_or("unknown"),
+                target.mime_types.join(", ")
+            ),
+        });
+    }
+
+    let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    for stream in streams {
+        match stream["codec_type"].as_str() {
+            // Cover art is stored as a video stream but never played
+            Some("video") if stream["disposition"]["attached_pic"].as_u64() != Some(1) => {
+                check_video_stream(target, stream, &mut issues)
+            }
+            Some("audio") => check_audio_stream(target, stream, &mut issues),
+            _ => {}
+        }
+    }
+
+    issues
+}
+
+fn check_video_stream(
+    target: &Target,
+    stream: &serde_json::Value,
+    issues: &mut Vec<CompatibilityIssue>,
+) {
+    let stream_index = stream["index"].as_u64();
+    let mut push = |kind, message| {
+        issues.push(CompatibilityIssue {
+            kind,
+            stream_index,
+            message,
+        })
+    };
+
+    let codec = stream["codec_name"].as_str().unwrap_or("unknown");
+    match target.video.iter().find(|support| support.codec == codec) {
+        None => {
+            let supported: Vec<&str> = target.video.iter().map(|s| s.codec).collect();
+            push(
+                IssueKind::VideoCodec,
+                format!(
+                    "Video codec {} is not supported (supported: {})",
+                    codec,
+                    supported.join(", ")
+                ),
+            );
+        }
+        Some(support) => {
+            let profile = stream["profile"].as_str().map(str::to_ascii_lowercase);
+            if let Some(profile) = &profile {
+                if !support.profiles.is_empty() && !support.profiles.contains(&profile.as_str()) {
+                    push(
+                        IssueKind::VideoProfile,
+                        format!(
+                            "{} profile {} is not supported (supported: {})",
+                            codec,
+                            profile,
+                            support.profiles.join(", ")
+                        ),
+                    );
+                }
+            }
+
+            if let (Some(level), Some(max_level)) = (video_level(stream), support.max_level) {
+                if level > max_level + f64::EPSILON {
+                    push(
+                        IssueKind::VideoLevel,
+                        format!(
+                            "{} level {:.1} exceeds the maximum of {:.1}",
+                            codec, level, max_level
+                        ),
+                    );
+                }
+            }
+
+            let bit_depth = bit_depth(stream);
+            if bit_depth > support.max_bit_depth {
+                push(
+                    IssueKind::BitDepth,
+                    format!(
+                        "{}-bit {} is not supported (maximum {}-bit)",
+                        bit_depth, codec, support.max_bit_depth
+                    ),
+                );
+            }
+        }
+    }
+
+    let width = stream["width"].as_u64().unwrap_or(0) as u32;
+    let height = stream["height"].as_u64().unwrap_or(0) as u32;
+    if width.max(height) > target.max_long_side || width.min(height) > target.max_short_side {
+        push(
+            IssueKind::Resolution,
+            format!(
+                "Resolution {}x{} exceeds {}x{}",
+                width, height, target.max_long_side, target.max_short_side
+            ),
+        );
+    }
+
+    let frame_rate = stream["avg_frame_rate"]
+        .as_str()
+        .and_then(|rate| parse_fraction(rate).ok())
+        .filter(|rate| rate.is_finite() && *rate > 0.0);
+    if let Some(frame_rate) = frame_rate {
+        // Leave room for rounding in 59.94/60 style rates
+        if frame_rate > target.max_frame_rate + 0.5 {
+            push(
+                IssueKind::FrameRate,
+                format!(
+                    "Frame rate {:.2} fps exceeds {} fps",
+                    frame_rate, target.max_frame_rate
+                ),
+            );
+        }
+    }
+}
+
+fn check_audio_stream(
+    target: &Target,
+    stream: &serde_json::Value,
+    issues: &mut Vec<CompatibilityIssue>,
+) {
+    let stream_index = stream["index"].as_u64();
+
+    let codec = stream["codec_name"].as_str().unwrap_or("unknown");
+    if !target.audio_codecs.contains(&codec) {
+        issues.push(CompatibilityIssue {
+            kind: IssueKind::AudioCodec,
+            stream_index,
+            message: format!(
+                "Audio codec {} is not supported (supported: {})",
+                codec,
+                target.audio_codecs.join(", ")
+            ),
+        });
+    }
+
+    let channels = stream["channels"].as_u64().unwrap_or(0) as u32;
+    if channels > target.max_audio_channels {
+        issues.push(CompatibilityIssue {
+            kind: IssueKind::AudioChannels,
+            stream_index,
+            message: format!(
+                "{} audio channels exceed the maximum of {}",
+                channels, target.max_audio_channels
+            ),
+        });
+    }
+}
@@ -0,0 +1,172 @@
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+
+/// Video stream fields that must match for the concat demuxer to copy streams
+const VIDEO_FIELDS: [&str; 8] = [
+    "codec_name",
+    "profile",
+    "width",
+    "height",
+    "pix_fmt",
+    "r_frame_rate",
+    "time_base",
+    "field_order",
+];
+
+/// Audio stream fields that must match for the concat demuxer to copy streams
+const AUDIO_FIELDS: [&str; 6] = [
+    "codec_name",
+    "profile",
+    "sample_rate",
+    "channels",
+    "channel_layout",
+    "sample_fmt",
+];
+
+/// A field whose value isn't the same in every file
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FieldDifference {
+    /// "video", "audio 1", ... or "streams" for stream counts
+    stream: String,
+    field: String,
+    /// One value per file, in the order the files were given
+    values: Vec<Option<String>>,
+}
+
+/// Whether files can be joined losslessly
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ConcatReport {
+    files: Vec<String>,
+    /// True when every compared field matches, so `-c copy` concat will work
+    lossless: bool,
+    differences: Vec<FieldDifference>,
+}
+
+/// Compare the streams of several files to tell whether they can be
+/// concatenated without re-encoding
+#[tauri::command]
+pub async fn check_concat(paths: Vec<String>) -> Result<ConcatReport, String> {
+    check_concat_async(paths).await.map_err(|e| {
+        tracing::error!(error = %e, "Concat check failed");
+        e.localized()
+    })
+}
+
+async fn check_concat_async(paths: Vec<String>) -> Result<ConcatReport, Error> {
+    if paths.len() < 2 {
+        return Err(Error::ParseError(
+            "At least two files are needed to check concatenation".to_string(),
+        ));
+    }
+
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let mut probes = Vec::with_capacity(paths.len());
+    for path in &paths {
+        probes.push(run_ffprobe_json(app_handle, path, &["-show_streams"]).await?);
+    }
+    let streams_of = |codec_type: &str| -> Vec<Vec<&serde_json::Value>> {
+        probes
+            .iter()
+            .map(|json| {
+                json["streams"]
+                    .as_array()
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter(|stream| stream["codec_type"].as_str() == Some(codec_type))
+                    // Cover art isn't part of the concatenated video
+                    .filter(|stream| stream["disposition"]["attached_pic"].as_u64() != Some(1))
+                    .collect()
+            })
+            .collect()
+    };
+    let video = streams_of("video");
+    let audio = streams_of("audio");
+
+    let mut differences = Vec::new();
+    for (codec_type, streams) in [("video", &video), ("audio", &audio)] {
+        let counts: Vec<Option<String>> = streams
+            .iter()
+            .map(|streams| Some(streams.len().to_string()))
+            .collect();
+        push_if_different(
+            &mut differences,
+            "streams",
+            &format!("{}_streams", codec_type),
+            counts,
+        );
+    }
+
+    // Only the main video stream is compared; extra angles are rare
+    compare_streams(&mut differences, "video", &video, 0, &VIDEO_FIELDS);
+    let audio_count = audio.iter().map(Vec::len).max().unwrap_or(0);
+    for position in 0..audio_count {
+        let stream = format!("audio {}", position + 1);
+        compare_streams(&mut differences, &stream, &audio, position, &AUDIO_FIELDS);
+    }
+
+    tracing::debug!(
+        files = paths.len(),
+        differences = differences.len(),
+        "Checked concat compatibility"
+    );
+
+    Ok(ConcatReport {
+        files: paths,
+        lossless: differences.is_empty(),
+        differences,
+    })
+}
+
+/// Compare `fields` of the stream at `position` across every file
+fn compare_streams(
+    differences: &mut Vec<FieldDifference>,
+    stream: &str,
+    streams: &[Vec<&serde_json::Value>],
+    position: usize,
+    fields: &[&str],
+) {
+    let present: Vec<Option<&serde_json::Value>> = streams
+        .iter()
+        .map(|streams| streams.get(position).copied())
+        .collect();
+    // A missing stream already shows up as a stream count difference
+    if present.iter().any(Option::is_none) {
+        return;
+    }
+
+    for field in fields {
+        let values = present
+            .iter()
+            .map(|stream| stream.and_then(|stream| field_value(&stream[*field])))
+            .collect();
+        push_if_different(differences, stream, field, values);
+    }
+}
+
+fn push_if_different(
+    differences: &mut Vec<FieldDifference>,
+    stream: &str,
+    field: &str,
+    values: Vec<Option<String>>,
+) {
+    if values.windows(2).all(|pair| pair[0] == pair[1]) {
+        return;
+    }
+    differences.push(FieldDifference {
+        stream: stream.to_string(),
+        field: field.to_string(),
+        values,
+    });
+}
+
+/// ffprobe prints some fields as strings and others as numbers
+fn field_value(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
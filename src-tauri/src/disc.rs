@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::events::{emit_partial_result, PartialResult};
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::probe::parse_fraction;
+use crate::thumbnail::{default_time_points, generate_thumbnails_with_ffmpeg, Thumbnail};
+
+/// ffprobe options for disc video, where streams (subpictures especially)
+/// may only show up well into the file
+pub const DISC_PROBE_ARGS: [&str; 6] = [
+    "-analyzeduration",
+    "100M",
+    "-probesize",
+    "100M",
+    "-show_format",
+    "-show_streams",
+];
+
+/// An audio track of a disc title
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DiscAudioTrack {
+    codec_name: Option<String>,
+    channels: Option<u64>,
+    language: Option<String>,
+}
+
+/// What a disc title contains, from probing its video files
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct TitleStreams {
+    video_codec: Option<String>,
+    resolution: Option<String>,
+    frame_rate: Option<f64>,
+    audio: Vec<DiscAudioTrack>,
+    /// Language of each subtitle track, where the disc declares one
+    subtitles: Vec<Option<String>>,
+}
+
+impl TitleStreams {
+    pub fn from_probe(json: &serde_json::Value) -> Self {
+        let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        let of_type = |codec_type: &'static str| {
+            streams
+                .iter()
+                .filter(move |stream| stream["codec_type"].as_str() == Some(codec_type))
+        };
+        let language =
+            |stream: &serde_json::Value| stream["tags"]["language"].as_str().map(str::to_string);
+
+        let video = of_type("video").next();
+        Self {
+            video_codec: video.and_then(|video| video["codec_name"].as_str().map(str::to_string)),
+            resolution: video.and_then(|video| {
+                Some(format!(
+                    "{}x{}",
+                    video["width"].as_u64()?,
+                    video["height"].as_u64()?
+                ))
+            }),
+            frame_rate: video
+                .and_then(|video| video["r_frame_rate"].as_str())
+                .and_then(|rate| parse_fraction(rate).ok()),
+            audio: of_type("audio")
+                .map(|stream| DiscAudioTrack {
+                    codec_name: stream["codec_name"].as_str().map(str::to_string),
+                    channels: stream["channels"].as_u64(),
+                    language: language(stream),
+                })
+                .collect(),
+            subtitles: of_type("subtitle").map(language).collect(),
+        }
+    }
+}
+
+/// Container duration in seconds, if ffprobe could work it out
+pub fn probe_duration(json: &serde_json::Value) -> Option<f64> {
+    json["format"]["duration"]
+        .as_str()
+        .and_then(|duration| duration.parse().ok())
+}
+
+/// ffmpeg `concat:` URL reading the inputs back to back, like a player would
+pub fn concat_url(inputs: &[impl AsRef<str>]) -> String {
+    let inputs: Vec<&str> = inputs.iter().map(AsRef::as_ref).collect();
+    format!("concat:{}", inputs.join("|"))
+}
+
+/// Thumbnails spread over a title, emitted as partial results of `job_id`
+pub async fn thumbnail_title(
+    app_handle: &tauri::AppHandle,
+    url: &str,
+    path: &str,
+    job_id: &str,
+) -> Result<Vec<Thumbnail>, Error> {
+    let info = get_video_info_with_ffprobe(app_handle, url).await?;
+    let (event_job_id, event_path) = (job_id.to_string(), path.to_string());
+    generate_thumbnails_with_ffmpeg(
+        app_handle,
+        url,
+        &info,
+        &default_time_points(info.duration),
+        move |index, thumbnail| {
+            emit_partial_result(
+                &event_job_id,
+                &event_path,
+                PartialResult::Thumbnail {
+                    index,
+                    timestamp: thumbnail.timestamp,
+                    data_url: thumbnail.data_url.clone(),
+                    hints: thumbnail.hints,
+                },
+            )
+        },
+    )
+    .await
+}
+
+/// Find an entry of `dir` by name, ignoring case
+///
+/// Discs use upper case names, but copies made on some systems don't.
+pub fn find_child(dir: &Path, name: &str) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map(|entry| entry.path())
+}
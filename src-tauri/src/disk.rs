@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use crate::inspector::Error;
+
+/// Space kept free on top of an operation's own estimate, so a write never
+/// fills the disk completely
+const SAFETY_MARGIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Check that `destination` has room for `estimated_bytes` plus a margin
+///
+/// `destination` may be a file that doesn't exist yet; the nearest existing
+/// ancestor directory is checked. If the free space can't be determined the
+/// check passes, leaving the write itself to report any failure.
+pub fn ensure_free_space(destination: &Path, estimated_bytes: u64) -> Result<(), Error> {
+    let Some(existing) = destination.ancestors().find(|path| path.exists()) else {
+        return Ok(());
+    };
+
+    let available = match fs4::available_space(existing) {
+        Ok(available) => available,
+        Err(e) => {
+            tracing::debug!(
+                path = %existing.display(),
+                error = %e,
+                "Could not determine free disk space"
+            );
+            return Ok(());
+        }
+    };
+
+    let required = estimated_bytes.saturating_add(SAFETY_MARGIN_BYTES);
+    if available < required {
+        tracing::warn!(
+            path = %destination.display(),
+            required,
+            available,
+            "Not enough free disk space"
+        );
+        return Err(Error::InsufficientSpace {
+            required,
+            available,
+        });
+    }
+
+    Ok(())
+}
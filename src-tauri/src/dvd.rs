@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::disc::{
+    concat_url, find_child, probe_duration, thumbnail_title, TitleStreams, DISC_PROBE_ARGS,
+};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::job::new_job_id;
+use crate::thumbnail::Thumbnail;
+use video_inspector_core::Error as CoreError;
+
+/// Folder holding the DVD-Video files
+const VIDEO_TS: &str = "VIDEO_TS";
+
+/// A VOB file, in a folder or inside a disc image
+#[derive(Clone, Debug)]
+pub struct VobFile {
+    /// Path shown in the report
+    pub name: String,
+    /// What ffmpeg reads, a path or a `subfile:` URL
+    pub input: String,
+    pub size: u64,
+}
+
+/// A title set of a DVD, played as its VOB files joined together
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DvdTitle {
+    /// The `nn` of `VTS_nn_*.VOB`
+    title_set: u32,
+    vob_files: Vec<String>,
+    size: u64,
+    /// Seconds; estimated by ffprobe for MPEG program streams
+    duration: Option<f64>,
+    streams: TitleStreams,
+}
+
+/// Titles of a DVD folder and thumbnails of its main title
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DvdReport {
+    job_id: String,
+    video_ts: String,
+    titles: Vec<DvdTitle>,
+    /// Title set of the longest title, which is almost always the feature
+    main_title: Option<u32>,
+    thumbnails: Vec<Thumbnail>,
+}
+
+/// Inspect a ripped DVD: the `VIDEO_TS` folder itself or a folder containing it
+///
+/// Thumbnails of the main title are also emitted as `inspection://partial`
+/// events tagged with `job_id`.
+#[tauri::command]
+pub async fn inspect_dvd(path: String, job_id: Option<String>) -> Result<DvdReport, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    inspect_dvd_async(&path, job_id).await.map_err(|e| {
+        tracing::error!(folder = %path, error = %e, "DVD inspection failed");
+        e.localized()
+    })
+}
+
+async fn inspect_dvd_async(path: &str, job_id: String) -> Result<DvdReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let video_ts = video_ts_dir(Path::new(path))
+        .ok_or_else(|| CoreError::NotMediaFile(format!("{} has no {} folder", path, VIDEO_TS)))?;
+
+    let mut vob_files = Vec::new();
+    for entry in fs::read_dir(&video_ts)? {
+        let file = entry?.path();
+        vob_files.push(VobFile {
+            name: file.to_string_lossy().to_string(),
+            input: file.to_string_lossy().to_string(),
+            size: fs::metadata(&file)?.len(),
+        });
+    }
+    let video_ts = video_ts.to_string_lossy().to_string();
+    inspect_title_sets(app_handle, path, video_ts, vob_files, job_id).await
+}
+
+/// Probe every title set among `files` and thumbnail the main title
+///
+/// `path` is what the user asked to inspect, used for logs and events.
+pub async fn inspect_title_sets(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    video_ts: String,
+    files: Vec<VobFile>,
+    job_id: String,
+) -> Result<DvdReport, Error> {
+    let mut titles = Vec::new();
+    let mut inputs = BTreeMap::new();
+    for (title_set, vob_files) in title_sets(files) {
+        let url = concat_url(&vob_files.iter().map(|file| &file.input).collect::<Vec<_>>());
+        let size = vob_files.iter().map(|file| file.size).sum();
+        let probe = run_ffprobe_json(app_handle, &url, &DISC_PROBE_ARGS)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(folder = %path, title_set, error = %e, "Failed to probe DVD title");
+                serde_json::Value::Null
+            });
+        titles.push(DvdTitle {
+            title_set,
+            vob_files: vob_files.into_iter().map(|file| file.name).collect(),
+            size,
+            duration: probe_duration(&probe),
+            streams: TitleStreams::from_probe(&probe),
+        });
+        inputs.insert(title_set, url);
+    }
+    if titles.is_empty() {
+        return Err(CoreError::NotMediaFile(format!("{} has no title VOB files", path)).into());
+    }
+
+    let main_title = titles
+        .iter()
+        .filter(|title| title.duration.is_some())
+        .max_by(|a, b| {
+            a.duration
+                .unwrap_or(0.0)
+                .total_cmp(&b.duration.unwrap_or(0.0))
+        })
+        .or_else(|| titles.iter().max_by_key(|title| title.size));
+    let main_title_set = main_title.map(|title| title.title_set);
+
+    let thumbnails = match main_title {
+        Some(title) => thumbnail_title(app_handle, &inputs[&title.title_set], path, &job_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(folder = %path, error = %e, "Failed to thumbnail DVD title");
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+
+    tracing::debug!(
+        folder = %path,
+        titles = titles.len(),
+        main_title = ?main_title_set,
+        "Inspected DVD"
+    );
+
+    Ok(DvdReport {
+        job_id,
+        video_ts,
+        titles,
+        main_title: main_title_set,
+        thumbnails,
+    })
+}
+
+/// The `VIDEO_TS` folder at or directly under `path`
+pub fn video_ts_dir(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        return None;
+    }
+    let is_video_ts = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(VIDEO_TS));
+    if is_video_ts {
+        return Some(path.to_path_buf());
+    }
+    find_child(path, VIDEO_TS).filter(|dir| dir.is_dir())
+}
+
+/// Title VOB files grouped by title set, in playback order
+///
+/// Other files are dropped, and so is `VTS_nn_0.VOB`, the title set menu.
+fn title_sets(files: Vec<VobFile>) -> BTreeMap<u32, Vec<VobFile>> {
+    let mut parts: BTreeMap<u32, BTreeMap<u32, VobFile>> = BTreeMap::new();
+    for file in files {
+        let name = Path::new(&file.name)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_uppercase())
+            .unwrap_or_default();
+        if let Some((title_set, part)) = parse_vob_name(&name) {
+            if part > 0 {
+                parts.entry(title_set).or_default().insert(part, file);
+            }
+        }
+    }
+    parts
+        .into_iter()
+        .map(|(title_set, files)| (title_set, files.into_values().collect()))
+        .collect()
+}
+
+/// Title set and part number of a `VTS_nn_p.VOB` name
+fn parse_vob_name(name: &str) -> Option<(u32, u32)> {
+    let (title_set, part) = name
+        .strip_prefix("VTS_")?
+        .strip_suffix(".VOB")?
+        .split_once('_')?;
+    Some((title_set.parse().ok()?, part.parse().ok()?))
+}
@@ -0,0 +1,282 @@
+#[cfg(feature = "error-reporting")]
+use sentry::protocol::{Event as ReportEvent, Exception, Level as ReportLevel, Values};
+#[cfg(feature = "error-reporting")]
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+#[cfg(feature = "error-reporting")]
+use tauri_plugin_shell::ShellExt;
+use tracing::Subscriber;
+#[cfg(feature = "error-reporting")]
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level,
+};
+use tracing_subscriber::Layer;
+#[cfg(feature = "error-reporting")]
+use tracing_subscriber::{layer::Context, registry::LookupSpan};
+
+#[cfg(feature = "error-reporting")]
+use crate::get_app_handle;
+#[cfg(feature = "error-reporting")]
+use crate::inspector::Error;
+#[cfg(feature = "error-reporting")]
+use crate::privacy::PATH_FIELDS;
+use crate::settings;
+
+/// Where reports are sent; builds made without one never report anything
+#[cfg(feature = "error-reporting")]
+const REPORTING_DSN: Option<&str> = option_env!("VIDEO_INSPECTOR_SENTRY_DSN");
+
+/// Replaces anything that looks like a path in reported text
+#[cfg(feature = "error-reporting")]
+const PATH_PLACEHOLDER: &str = "<path>";
+
+/// Span or event field holding the size range of the file being worked on
+#[cfg(feature = "error-reporting")]
+pub const SIZE_BUCKET_FIELD: &str = "size_bucket";
+
+#[cfg(feature = "error-reporting")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The reporting client; dropping it flushes pending reports
+#[cfg(feature = "error-reporting")]
+static GUARD: Mutex<Option<sentry::ClientInitGuard>> = Mutex::new(None);
+
+/// Start or stop error reporting to match the current settings
+///
+/// Reports carry the OS, the ffmpeg version and a coarse file size range,
+/// never file paths, names or hashes.
+#[cfg(feature = "error-reporting")]
+pub fn apply_settings() {
+    let enabled = settings::current().error_reporting;
+    let mut guard = GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    if !enabled {
+        ENABLED.store(false, Ordering::Relaxed);
+        if guard.take().is_some() {
+            tracing::info!("Error reporting stopped");
+        }
+        return;
+    }
+    if guard.is_some() {
+        return;
+    }
+    let Some(dsn) = REPORTING_DSN.filter(|dsn| !dsn.is_empty()) else {
+        tracing::warn!("Error reporting enabled, but this build has no reporting endpoint");
+        return;
+    };
+
+    *guard = Some(sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: Some(env!("CARGO_PKG_VERSION").into()),
+            send_default_pii: false,
+            before_send: Some(Arc::new(scrub_event)),
+            ..Default::default()
+        },
+    )));
+    sentry::configure_scope(|scope| {
+        scope.set_tag("os", std::env::consts::OS);
+        scope.set_tag("arch", std::env::consts::ARCH);
+    });
+    ENABLED.store(true, Ordering::Relaxed);
+
+    tauri::async_runtime::spawn(async {
+        match ffmpeg_version().await {
+            Ok(version) => {
+                sentry::configure_scope(|scope| scope.set_tag("ffmpeg_version", version))
+            }
+            // Not an error, which would itself be reported
+            Err(e) => tracing::warn!(error = %e, "Failed to read the ffmpeg version"),
+        }
+    });
+    tracing::info!("Error reporting started");
+}
+
+/// Version of the bundled ffmpeg, from the first line of `ffmpeg -version`
+#[cfg(feature = "error-reporting")]
+async fn ffmpeg_version() -> Result<String, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let output = app_handle
+        .shell()
+        .sidecar("ffmpeg")?
+        .args(["-version"])
+        .output()
+        .await?;
+
+    // "ffmpeg version 7.1.1 Copyright (c) ..."
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(2)
+        .map(str::to_string)
+        .ok_or_else(|| Error::ParseError("ffmpeg version not found".to_string()))
+}
+
+/// Builds without the `error-reporting` feature only warn when it's turned on
+#[cfg(not(feature = "error-reporting"))]
+pub fn apply_settings() {
+    if settings::current().error_reporting {
+        tracing::warn!("Error reporting enabled, but this build doesn't include it");
+    }
+}
+
+/// Coarse size range reported instead of the exact file size
+pub fn size_bucket(bytes: u64) -> &'static str {
+    const MB: u64 = 1000 * 1000;
+    match bytes {
+        b if b < 10 * MB => "<10MB",
+        b if b < 100 * MB => "10-100MB",
+        b if b < 1000 * MB => "100MB-1GB",
+        b if b < 10_000 * MB => "1-10GB",
+        _ => ">10GB",
+    }
+}
+
+/// Tracing layer turning error events into reports while reporting is on
+///
+/// Only the event message and its `error` field are sent, with paths taken
+/// out. The size range comes from the event or the closest span that has one.
+pub struct ErrorReportLayer;
+
+/// Size range recorded on a span, for errors logged inside it
+#[cfg(feature = "error-reporting")]
+struct SpanSizeBucket(String);
+
+#[cfg(feature = "error-reporting")]
+impl<S> Layer<S> for ErrorReportLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = ReportVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(size_bucket), Some(span)) = (visitor.size_bucket, ctx.span(id)) {
+            span.extensions_mut().insert(SpanSizeBucket(size_bucket));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR || !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let mut visitor = ReportVisitor::default();
+        event.record(&mut visitor);
+        let size_bucket = visitor.size_bucket.clone().or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| {
+                let extensions = span.extensions();
+                extensions
+                    .get::<SpanSizeBucket>()
+                    .map(|bucket| bucket.0.clone())
+            })
+        });
+
+        // Longest first, so a file path is replaced before its folder
+        visitor
+            .paths
+            .sort_by_key(|path| std::cmp::Reverse(path.len()));
+        let scrub = |text: &String| scrub_paths(text, &visitor.paths);
+
+        let mut report = ReportEvent {
+            level: ReportLevel::Error,
+            logger: Some(event.metadata().target().to_string()),
+            message: visitor.message.as_ref().map(scrub),
+            exception: Values {
+                values: visitor
+                    .error
+                    .as_ref()
+                    .map(|error| Exception {
+                        ty: "Error".to_string(),
+                        value: Some(scrub(error)),
+                        ..Default::default()
+                    })
+                    .into_iter()
+                    .collect(),
+            },
+            ..Default::default()
+        };
+        if let Some(size_bucket) = size_bucket {
+            report
+                .tags
+                .insert(SIZE_BUCKET_FIELD.to_string(), size_bucket);
+        }
+        sentry::capture_event(report);
+    }
+}
+
+/// Collects the few fields a report may use
+#[cfg(feature = "error-reporting")]
+#[derive(Default)]
+struct ReportVisitor {
+    message: Option<String>,
+    error: Option<String>,
+    size_bucket: Option<String>,
+    /// Values of path fields, removed from the message and error
+    paths: Vec<String>,
+}
+
+#[cfg(feature = "error-reporting")]
+impl ReportVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        match field.name() {
+            "message" => self.message = Some(value),
+            "error" => self.error = Some(value),
+            SIZE_BUCKET_FIELD => self.size_bucket = Some(value),
+            name if PATH_FIELDS.contains(&name) && !value.is_empty() => self.paths.push(value),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "error-reporting")]
+impl Visit for ReportVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{:?}", value));
+    }
+}
+
+/// Last pass over every report, panics included, before it leaves the machine
+#[cfg(feature = "error-reporting")]
+fn scrub_event(mut event: ReportEvent<'static>) -> Option<ReportEvent<'static>> {
+    // The host name often contains the user name
+    event.server_name = None;
+    event.user = None;
+    event.request = None;
+    event.breadcrumbs.values.clear();
+    event.extra.clear();
+    event.message = event.message.map(|message| scrub_paths(&message, &[]));
+    for exception in &mut event.exception.values {
+        exception.value = exception.value.take().map(|value| scrub_paths(&value, &[]));
+    }
+    Some(event)
+}
+
+/// Replace `known_paths`, then any remaining word containing a path separator
+#[cfg(feature = "error-reporting")]
+fn scrub_paths(text: &str, known_paths: &[String]) -> String {
+    let text = known_paths.iter().fold(text.to_string(), |text, path| {
+        text.replace(path.as_str(), PATH_PLACEHOLDER)
+    });
+    text.split(' ')
+        .map(|word| {
+            if word.contains(['/', '\\']) {
+                PATH_PLACEHOLDER
+            } else {
+                word
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(not(feature = "error-reporting"))]
+impl<S: Subscriber> Layer<S> for ErrorReportLayer {}
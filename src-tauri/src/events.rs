@@ -0,0 +1,263 @@
+use std::sync::OnceLock;
+use tauri::Emitter;
+use tokio::sync::broadcast;
+
+use crate::batch::BatchItem;
+use crate::frame_content::ContentHints;
+use crate::get_app_handle;
+use crate::hash::{HashKind, HashProgress};
+use crate::hooks::HookOutcome;
+use crate::planner::ScanPlan;
+use crate::progress::FfmpegProgress;
+use crate::stages::InspectionProgress;
+
+/// Event carrying partial inspection results as each stage finishes
+pub const PARTIAL_RESULT_EVENT: &str = "inspection://partial";
+
+/// Event reporting hashing progress for large files
+pub const HASH_PROGRESS_EVENT: &str = "inspection://hash-progress";
+
+/// Event reporting progress of long-running ffmpeg operations
+pub const FFMPEG_PROGRESS_EVENT: &str = "inspection://ffmpeg-progress";
+
+/// Event reporting the outcome of a post-inspection hook
+pub const HOOK_FINISHED_EVENT: &str = "inspection://hook-finished";
+
+/// Event carrying the plan chosen for a batch scan before it starts
+pub const SCAN_PLAN_EVENT: &str = "inspection://scan-plan";
+
+/// Event carrying the result of one file of a batch inspection
+pub const BATCH_FILE_EVENT: &str = "inspection://batch-file";
+
+/// Event reporting which stage an inspection is in and how far along it is
+pub const INSPECTION_PROGRESS_EVENT: &str = "inspection://progress";
+
+/// Events buffered per automation client before a slow one starts missing some
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// Copy of every emitted event for automation clients
+static EVENT_BUS: OnceLock<broadcast::Sender<String>> = OnceLock::new();
+
+/// Receive every event emitted from now on, as `{"event", "payload"}` JSON
+pub fn subscribe() -> broadcast::Receiver<String> {
+    event_bus().subscribe()
+}
+
+fn event_bus() -> &'static broadcast::Sender<String> {
+    EVENT_BUS.get_or_init(|| broadcast::channel(EVENT_BUS_CAPACITY).0)
+}
+
+/// Forward an event to automation clients, if any are listening
+fn publish(event: &str, payload: &impl serde::Serialize) {
+    let bus = event_bus();
+    if bus.receiver_count() == 0 {
+        return;
+    }
+    let message = serde_json::json!({ "event": event, "payload": payload });
+    // Sending only fails when every receiver went away in the meantime
+    let _ = bus.send(message.to_string());
+}
+
+/// A piece of an inspection result that became available early
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum PartialResult {
+    /// Basic stream information from ffprobe
+    Probe {
+        resolution: String,
+        frame_rate: String,
+        duration: String,
+        bit_rate: String,
+    },
+    /// File size and hash
+    Hash {
+        file_size: String,
+        file_hash: String,
+        hash_kind: HashKind,
+    },
+    /// A single thumbnail; they may arrive out of order
+    Thumbnail {
+        index: usize,
+        timestamp: f64,
+        data_url: String,
+        hints: Option<ContentHints>,
+    },
+}
+
+#[derive(serde::Serialize, Clone)]
+struct PartialResultPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    #[serde(flatten)]
+    result: PartialResult,
+}
+
+/// Emit a partial result for a running job
+///
+/// Emission failures are only logged; the final result is still returned by
+/// the command, so a missed event just means a less incremental UI.
+pub fn emit_partial_result(job_id: &str, path: &str, result: PartialResult) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = PartialResultPayload {
+        job_id,
+        path,
+        result,
+    };
+    publish(PARTIAL_RESULT_EVENT, &payload);
+    if let Err(e) = app_handle.emit(PARTIAL_RESULT_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit partial result");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct HashProgressPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    #[serde(flatten)]
+    progress: HashProgress,
+}
+
+/// Emit hashing progress for a running job
+pub fn emit_hash_progress(job_id: &str, path: &str, progress: HashProgress) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = HashProgressPayload {
+        job_id,
+        path,
+        progress,
+    };
+    publish(HASH_PROGRESS_EVENT, &payload);
+    if let Err(e) = app_handle.emit(HASH_PROGRESS_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit hash progress");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct FfmpegProgressPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    /// Which operation is running, e.g. "integrity_scan"
+    operation: &'a str,
+    #[serde(flatten)]
+    progress: &'a FfmpegProgress,
+}
+
+/// Emit progress of an ffmpeg operation for a running job
+pub fn emit_ffmpeg_progress(job_id: &str, path: &str, operation: &str, progress: &FfmpegProgress) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = FfmpegProgressPayload {
+        job_id,
+        path,
+        operation,
+        progress,
+    };
+    publish(FFMPEG_PROGRESS_EVENT, &payload);
+    if let Err(e) = app_handle.emit(FFMPEG_PROGRESS_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit ffmpeg progress");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct HookFinishedPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    #[serde(flatten)]
+    outcome: &'a HookOutcome,
+}
+
+/// Emit the outcome of a hook run after an inspection
+pub fn emit_hook_finished(job_id: &str, path: &str, outcome: &HookOutcome) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = HookFinishedPayload {
+        job_id,
+        path,
+        outcome,
+    };
+    publish(HOOK_FINISHED_EVENT, &payload);
+    if let Err(e) = app_handle.emit(HOOK_FINISHED_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit hook outcome");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct ScanPlanPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    #[serde(flatten)]
+    plan: &'a ScanPlan,
+}
+
+/// Emit the plan chosen for a batch scan
+pub fn emit_scan_plan(job_id: &str, path: &str, plan: &ScanPlan) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = ScanPlanPayload { job_id, path, plan };
+    publish(SCAN_PLAN_EVENT, &payload);
+    if let Err(e) = app_handle.emit(SCAN_PLAN_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit scan plan");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct BatchFilePayload<'a> {
+    job_id: &'a str,
+    /// Position of the file in the batch
+    index: usize,
+    #[serde(flatten)]
+    item: &'a BatchItem,
+}
+
+/// Emit the result of one file of a batch inspection
+pub fn emit_batch_file(job_id: &str, index: usize, item: &BatchItem) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = BatchFilePayload {
+        job_id,
+        index,
+        item,
+    };
+    publish(BATCH_FILE_EVENT, &payload);
+    if let Err(e) = app_handle.emit(BATCH_FILE_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit batch file result");
+    }
+}
+
+#[derive(serde::Serialize, Clone)]
+struct InspectionProgressPayload<'a> {
+    job_id: &'a str,
+    path: &'a str,
+    #[serde(flatten)]
+    progress: InspectionProgress,
+}
+
+/// Emit the stage and overall progress of a running inspection
+pub fn emit_inspection_progress(job_id: &str, path: &str, progress: InspectionProgress) {
+    let Some(app_handle) = get_app_handle() else {
+        return;
+    };
+
+    let payload = InspectionProgressPayload {
+        job_id,
+        path,
+        progress,
+    };
+    publish(INSPECTION_PROGRESS_EVENT, &payload);
+    if let Err(e) = app_handle.emit(INSPECTION_PROGRESS_EVENT, payload) {
+        tracing::warn!(job_id = %job_id, error = %e, "Failed to emit inspection progress");
+    }
+}
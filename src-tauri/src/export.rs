@@ -0,0 +1,403 @@
+use base64::{engine::general_purpose, Engine};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::{format_size, get_video_info_with_ffprobe, Error};
+use crate::probe::{parse_fraction, VideoInfo};
+use crate::tags::tag;
+use crate::thumbnail::{default_time_points, generate_thumbnails_with_ffmpeg, Thumbnail};
+
+/// Width of the label column in MediaInfo's text view
+const MEDIAINFO_LABEL_WIDTH: usize = 41;
+
+/// Text formats an inspection can be exported as
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Tables for GitHub issues, wikis and chat tools
+    Markdown,
+    /// MediaInfo-style text in a spoiler, for trackers and forums
+    Bbcode,
+}
+
+/// How thumbnails are included in an export
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailEmbed {
+    #[default]
+    None,
+    /// PNG files next to the export, linked by name
+    Files,
+    /// Inline data URLs; not every renderer shows them
+    Base64,
+}
+
+/// An exported inspection
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ExportResult {
+    /// The report, for copying to the clipboard
+    text: String,
+    export_path: Option<String>,
+    /// Thumbnail files written next to the export
+    thumbnail_paths: Vec<String>,
+}
+
+/// Format-neutral summary of a file that the exporters render
+struct Summary {
+    file_name: String,
+    general: Vec<(&'static str, String)>,
+    streams: Vec<StreamSummary>,
+}
+
+struct StreamSummary {
+    index: u64,
+    /// Video, Audio, Subtitle, ...
+    kind: String,
+    codec: String,
+    details: Vec<(&'static str, String)>,
+    language: Option<String>,
+    title: Option<String>,
+}
+
+/// Inspect a file and render the result as text
+///
+/// With `export_path` the report is also written there. Thumbnails saved as
+/// files need `export_path`; they're named after it with `_thumbN.png`.
+#[tauri::command]
+pub async fn export_inspection(
+    path: String,
+    format: ExportFormat,
+    thumbnails: Option<ThumbnailEmbed>,
+    export_path: Option<String>,
+) -> Result<ExportResult, String> {
+    export_inspection_async(
+        &path,
+        format,
+        thumbnails.unwrap_or_default(),
+        export_path.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Export failed");
+        e.localized()
+    })
+}
+
+async fn export_inspection_async(
+    path: &str,
+    format: ExportFormat,
+    embed: ThumbnailEmbed,
+    export_path: Option<&str>,
+) -> Result<ExportResult, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    if embed == ThumbnailEmbed::Files && export_path.is_none() {
+        return Err(Error::ParseError(
+            "Thumbnail files need an export path to be saved next to".to_string(),
+        ));
+    }
+
+    let info = get_video_info_with_ffprobe(app_handle, path).await?;
+    let thumbnails = if embed == ThumbnailEmbed::None {
+        Vec::new()
+    } else {
+        let time_points = default_time_points(info.duration);
+        generate_thumbnails_with_ffmpeg(app_handle, path, &info, &time_points, |_, _| {}).await?
+    };
+
+    // Links to thumbnail files, or the data URLs themselves
+    let mut thumbnail_paths = Vec::new();
+    let mut images = Vec::with_capacity(thumbnails.len());
+    for (index, thumbnail) in thumbnails.iter().enumerate() {
+        match export_path {
+            Some(export_path) if embed == ThumbnailEmbed::Files => {
+                let file = thumbnail_file(Path::new(export_path), index);
+                fs::write(&file, png_data(thumbnail)?)?;
+                images.push(file_name(&file));
+                thumbnail_paths.push(file.to_string_lossy().to_string());
+            }
+            _ => images.push(thumbnail.data_url.clone()),
+        }
+    }
+
+    let summary = summarize(path, &info);
+    let text = match format {
+        ExportFormat::Markdown => to_markdown(&summary, &images),
+        ExportFormat::Bbcode => to_bbcode(&summary, &images),
+    };
+    if let Some(export_path) = export_path {
+        fs::write(export_path, &text)?;
+    }
+
+    tracing::debug!(video_path = %path, ?format, ?embed, "Exported inspection");
+    Ok(ExportResult {
+        text,
+        export_path: export_path.map(str::to_string),
+        thumbnail_paths,
+    })
+}
+
+fn summarize(path: &str, info: &VideoInfo) -> Summary {
+    let format = &info.probe_json["format"];
+    let mut general = Vec::new();
+    if let Some(title) = tag(format, "title") {
+        general.push(("Title", title));
+    }
+    if let Some(name) = format["format_long_name"]
+        .as_str()
+        .or(format["format_name"].as_str())
+    {
+        general.push(("Format", name.to_string()));
+    }
+    if let Ok(metadata) = fs::metadata(path) {
+        general.push(("File size", format_size(metadata.len())));
+    }
+    general.push(("Duration", timecode(info.duration)));
+    general.push(("Overall bit rate", format_bit_rate(info.bit_rate)));
+
+    let streams = info.probe_json["streams"]
+        .as_array()
+        .map(|streams| streams.iter().map(summarize_stream).collect())
+        .unwrap_or_default();
+    Summary {
+        file_name: file_name(Path::new(path)),
+        general,
+        streams,
+    }
+}
+
+fn summarize_stream(stream: &serde_json::Value) -> StreamSummary {
+    let mut details = Vec::new();
+    match stream["codec_type"].as_str() {
+        Some("video") => {
+            if let (Some(width), Some(height)) =
+                (stream["width"].as_u64(), stream["height"].as_u64())
+            {
+                details.push(("Resolution", format!("{}x{}", width, height)));
+            }
+            if let Some(rate) = stream["avg_frame_rate"]
+                .as_str()
+                .and_then(|rate| parse_fraction(rate).ok())
+                .filter(|rate| rate.is_finite() && *rate > 0.0)
+            {
+                details.push(("Frame rate", format!("{} fps", trim_number(rate))));
+            }
+            if let Some(pix_fmt) = stream["pix_fmt"].as_str() {
+                details.push(("Pixel format", pix_fmt.to_string()));
+            }
+        }
+        Some("audio") => {
+            if let Some(channels) = stream["channels"].as_u64() {
+                details.push(("Channels", channels.to_string()));
+            }
+            if let Some(layout) = stream["channel_layout"].as_str() {
+                details.push(("Channel layout", layout.to_string()));
+            }
+            if let Some(rate) = json_f64(&stream["sample_rate"]) {
+                details.push(("Sample rate", format!("{} Hz", rate)));
+            }
+        }
+        _ => {}
+    }
+    if let Some(profile) = stream["profile"].as_str() {
+        details.push(("Profile", profile.to_string()));
+    }
+    if let Some(bit_rate) = json_f64(&stream["bit_rate"]) {
+        details.push(("Bit rate", format_bit_rate(bit_rate)));
+    }
+
+    let kind = stream["codec_type"]
+        .as_str()
+        .filter(|kind| !kind.is_empty())
+        .unwrap_or("data");
+    StreamSummary {
+        index: stream["index"].as_u64().unwrap_or_default(),
+        kind: kind[..1].to_uppercase() + &kind[1..],
+        codec: stream["codec_long_name"]
+            .as_str()
+            .or(stream["codec_name"].as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        details,
+        language: tag(stream, "language").filter(|language| language != "und"),
+        title: tag(stream, "title"),
+    }
+}
+
+fn to_markdown(summary: &Summary, images: &[String]) -> String {
+    let mut markdown = format!("## {}\n\n", markdown_escape(&summary.file_name));
+    markdown.push_str("| | |\n|---|---|\n");
+    for (label, value) in &summary.general {
+        markdown.push_str(&format!("| **{}** | {} |\n", label, markdown_escape(value)));
+    }
+
+    markdown.push_str("\n### Streams\n\n");
+    markdown.push_str("| # | Type | Codec | Details | Language | Title |\n");
+    markdown.push_str("|---|---|---|---|---|---|\n");
+    for stream in &summary.streams {
+        let details: Vec<String> = stream
+            .details
+            .iter()
+            .map(|(label, value)| format!("{}: {}", label, value))
+            .collect();
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            stream.index,
+            stream.kind,
+            markdown_escape(&stream.codec),
+            markdown_escape(&details.join(", ")),
+            markdown_escape(stream.language.as_deref().unwrap_or("")),
+            markdown_escape(stream.title.as_deref().unwrap_or(""))
+        ));
+    }
+
+    if !images.is_empty() {
+        markdown.push_str("\n### Thumbnails\n\n");
+        for (index, image) in images.iter().enumerate() {
+            // Angle brackets allow spaces in file names
+            markdown.push_str(&format!("![Thumbnail {}](<{}>)\n", index + 1, image));
+        }
+    }
+    markdown
+}
+
+/// Escape characters that would end a table cell or start formatting
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(if c == '\n' { ' ' } else { c });
+    }
+    escaped
+}
+
+/// The MediaInfo text view inside `[spoiler]` and `[code]`, then the
+/// thumbnails as `[img]` tags
+fn to_bbcode(summary: &Summary, images: &[String]) -> String {
+    let mut general = vec![("Complete name", summary.file_name.clone())];
+    general.extend(summary.general.iter().cloned());
+    let mut sections = vec![mediainfo_section("General", &general)];
+    for stream in &summary.streams {
+        let kind = mediainfo_kind(&stream.kind);
+        let of_kind = summary
+            .streams
+            .iter()
+            .filter(|other| mediainfo_kind(&other.kind) == kind)
+            .collect::<Vec<_>>();
+        // Numbered only when there's more than one of the kind, like MediaInfo
+        let heading = match of_kind.iter().position(|other| other.index == stream.index) {
+            Some(position) if of_kind.len() > 1 => format!("{} #{}", kind, position + 1),
+            _ => kind.to_string(),
+        };
+        let mut fields = vec![
+            ("ID", stream.index.to_string()),
+            ("Format", stream.codec.clone()),
+        ];
+        fields.extend(stream.details.iter().cloned());
+        if let Some(title) = &stream.title {
+            fields.push(("Title", title.clone()));
+        }
+        if let Some(language) = &stream.language {
+            fields.push(("Language", language.clone()));
+        }
+        sections.push(mediainfo_section(&heading, &fields));
+    }
+
+    let mut bbcode = format!("[b]{}[/b]\n", bbcode_escape(&summary.file_name));
+    bbcode.push_str(&format!(
+        "[spoiler=MediaInfo][code]\n{}[/code][/spoiler]\n",
+        bbcode_escape(&sections.join("\n"))
+    ));
+    for image in images {
+        bbcode.push_str(&format!("[img]{}[/img]\n", image));
+    }
+    bbcode
+}
+
+/// A MediaInfo section: the heading, then `Label<padding>: value` lines
+fn mediainfo_section(heading: &str, fields: &[(&'static str, String)]) -> String {
+    let mut section = format!("{}\n", heading);
+    for (label, value) in fields {
+        section.push_str(&format!(
+            "{:<width$}: {}\n",
+            label,
+            value,
+            width = MEDIAINFO_LABEL_WIDTH
+        ));
+    }
+    section
+}
+
+/// MediaInfo's name for a stream kind
+fn mediainfo_kind(kind: &str) -> &'static str {
+    match kind {
+        "Video" => "Video",
+        "Audio" => "Audio",
+        "Subtitle" => "Text",
+        "Attachment" => "Attachment",
+        _ => "Other",
+    }
+}
+
+/// Keep brackets in names from being read as tags, by putting a zero width
+/// space after each `[`
+fn bbcode_escape(text: &str) -> String {
+    text.replace('[', "[\u{200B}")
+}
+
+/// `H:MM:SS.mmm`
+fn timecode(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+fn format_bit_rate(bits_per_second: f64) -> String {
+    format!("{:.2} kbps", bits_per_second / 1024.0)
+}
+
+/// Up to three decimals without trailing zeros: 25, 29.97, 23.976
+fn trim_number(value: f64) -> String {
+    format!("{:.3}", value)
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// `<export stem>_thumbN.png` next to the export
+fn thumbnail_file(export_path: &Path, index: usize) -> PathBuf {
+    let stem = export_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    export_path.with_file_name(format!("{}_thumb{}.png", stem, index + 1))
+}
+
+/// Decoded PNG of a thumbnail's data URL
+fn png_data(thumbnail: &Thumbnail) -> Result<Vec<u8>, Error> {
+    let encoded = thumbnail
+        .data_url
+        .split_once(',')
+        .map(|(_, data)| data)
+        .unwrap_or_default();
+    general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| Error::ParseError(format!("Invalid thumbnail data: {}", e)))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,501 @@
+use std::{path::Path, time::Instant};
+
+use crate::disk::ensure_free_space;
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::runner::{sidecar, KillOnDrop, MediaTool};
+
+/// Number of windows sampled across the file for frame statistics
+const SAMPLE_WINDOWS: usize = 5;
+
+/// Length of each sampled window when none is specified, in seconds
+const DEFAULT_WINDOW_SECS: f64 = 10.0;
+
+/// A single decoded frame as reported by `ffprobe -show_frames`
+#[derive(Debug, Clone)]
+pub struct FrameSample {
+    pub pts_time: Option<f64>,
+    pub pict_type: char,
+    pub key_frame: bool,
+    pub size: u64,
+}
+
+/// Count and average size of one picture type
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct FrameTypeCount {
+    count: u64,
+    ratio: f64,
+    average_size: f64,
+}
+
+/// Picture type distribution of the first video stream
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FrameTypeStats {
+    sampled_frames: u64,
+    i_frames: FrameTypeCount,
+    p_frames: FrameTypeCount,
+    b_frames: FrameTypeCount,
+    /// Reference frame count declared by the stream
+    reference_frames: Option<u64>,
+    /// Maximum consecutive B-frames (reorder depth) declared by the stream
+    max_b_frames: Option<u64>,
+    /// Average distance between keyframes in the sampled windows, in frames
+    average_gop_length: Option<f64>,
+}
+
+/// Sample frames of the first video stream and report the I/P/B distribution
+///
+/// `window_secs` controls how long each of the sampled windows is; short files
+/// are read in full.
+#[tauri::command]
+pub async fn analyze_frame_types(
+    path: String,
+    window_secs: Option<f64>,
+) -> Result<FrameTypeStats, String> {
+    analyze_frame_types_async(&path, window_secs.unwrap_or(DEFAULT_WINDOW_SECS))
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Frame type analysis failed");
+            e.localized()
+        })
+}
+
+async fn analyze_frame_types_async(path: &str, window_secs: f64) -> Result<FrameTypeStats, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let stream_json = run_ffprobe_json(
+        app_handle,
+        path,
+        &[
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=refs,has_b_frames:format=duration",
+        ],
+    )
+    .await?;
+    let stream = &stream_json["streams"][0];
+    let duration = stream_json["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse::<f64>().ok())
+        .unwrap_or(0.0);
+
+    let start = Instant::now();
+    let intervals = sample_intervals(duration, SAMPLE_WINDOWS, window_secs);
+    let frames = probe_frames(app_handle, path, intervals.as_deref()).await?;
+
+    tracing::debug!(
+        video_path = %path,
+        frames = frames.len(),
+        elapsed = ?start.elapsed(),
+        "Sampled frames for frame type analysis"
+    );
+
+    Ok(FrameTypeStats {
+        sampled_frames: frames.len() as u64,
+        i_frames: count_type(&frames, 'I'),
+        p_frames: count_type(&frames, 'P'),
+        b_frames: count_type(&frames, 'B'),
+        reference_frames: stream["refs"].as_u64(),
+        max_b_frames: stream["has_b_frames"].as_u64(),
+        average_gop_length: average_gop_length(&frames),
+    })
+}
+
+/// Build an ffprobe `-read_intervals` spec with `windows` windows spread over
+/// the file, or `None` when the file is short enough to read in full
+pub fn sample_intervals(duration: f64, windows: usize, window_secs: f64) -> Option<String> {
+    if duration <= 0.0 || duration <= windows as f64 * window_secs * 1.5 {
+        return None;
+    }
+
+    let spec = (0..windows)
+        .map(|i| {
+            let position = 0.05 + 0.9 * i as f64 / (windows.max(2) - 1) as f64;
+            let start = (duration * position).min(duration - window_secs).max(0.0);
+            format!("{:.3}%+{:.3}", start, window_secs)
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    Some(spec)
+}
+
+/// Run `ffprobe -show_frames` on the first video stream
+pub async fn probe_frames(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    read_intervals: Option<&str>,
+) -> Result<Vec<FrameSample>, Error> {
+    let mut args = vec![
+        "-select_streams",
+        "v:0",
+        "-show_frames",
+        "-show_entries",
+        "frame=pict_type,pkt_size,key_frame,pts_time,best_effort_timestamp_time",
+    ];
+    if let Some(intervals) = read_intervals {
+        args.extend(["-read_intervals", intervals]);
+    }
+
+    let json = run_ffprobe_json(app_handle, path, &args).await?;
+    let frames = json["frames"]
+        .as_array()
+        .ok_or_else(|| Error::ParseError("No frames found in ffprobe output".to_string()))?;
+
+    Ok(frames
+        .iter()
+        .map(|frame| FrameSample {
+            pts_time: json_f64(&frame["pts_time"])
+                .or_else(|| json_f64(&frame["best_effort_timestamp_time"])),
+            pict_type: frame["pict_type"]
+                .as_str()
+                .and_then(|t| t.chars().next())
+                .unwrap_or('?'),
+            key_frame: frame["key_frame"].as_u64() == Some(1),
+            size: json_f64(&frame["pkt_size"]).unwrap_or(0.0) as u64,
+        })
+        .collect())
+}
+
+/// Read a number that ffprobe may print either as a JSON number or a string
+pub fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn count_type(frames: &[FrameSample], pict_type: char) -> FrameTypeCount {
+    let matching: Vec<&FrameSample> = frames
+        .iter()
+        .filter(|frame| frame.pict_type == pict_type)
+        .collect();
+    if matching.is_empty() {
+        return FrameTypeCount::default();
+    }
+
+    let total_size: u64 = matching.iter().map(|frame| frame.size).sum();
+    FrameTypeCount {
+        count: matching.len() as u64,
+        ratio: matching.len() as f64 / frames.len() as f64,
+        average_size: total_size as f64 / matching.len() as f64,
+    }
+}
+
+/// Average number of frames between consecutive keyframes
+///
+/// Only distances within a contiguous run are counted, so gaps between
+/// sampled windows don't inflate the result.
+fn average_gop_length(frames: &[FrameSample]) -> Option<f64> {
+    let keyframe_positions: Vec<usize> = frames
+        .iter()
+        .enumerate()
+        .filter(|(_, frame)| frame.key_frame || frame.pict_type == 'I')
+        .map(|(i, _)| i)
+        .collect();
+
+    let gaps: Vec<usize> = keyframe_positions
+        .windows(2)
+        .filter(|pair| {
+            // Skip pairs spanning a jump between sampled windows
+            let (a, b) = (&frames[pair[0]], &frames[pair[1]]);
+            match (a.pts_time, b.pts_time) {
+                (Some(ta), Some(tb)) => tb >= ta && tb - ta < 60.0,
+                _ => true,
+            }
+        })
+        .map(|pair| pair[1] - pair[0])
+        .collect();
+
+    if gaps.is_empty() {
+        return None;
+    }
+    Some(gaps.iter().sum::<usize>() as f64 / gaps.len() as f64)
+}
+
+/// Default number of points in a frame size timeline
+const DEFAULT_TIMELINE_POINTS: usize = 500;
+
+/// A video packet as reported by `ffprobe -show_packets`
+#[derive(Debug, Clone)]
+struct PacketSample {
+    pts_time: f64,
+    size: u64,
+    key_frame: bool,
+}
+
+/// How timeline points are grouped
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineGranularity {
+    /// Fixed-length time buckets
+    #[default]
+    Frame,
+    /// One point per group of pictures (keyframe to keyframe)
+    Gop,
+}
+
+/// One point of the frame size timeline
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TimelinePoint {
+    /// Start of the bucket / GOP, in seconds
+    time: f64,
+    /// Length covered by this point, in seconds
+    span: f64,
+    frames: u64,
+    total_bytes: u64,
+    /// Largest single frame in the bucket, in bytes
+    peak_frame_bytes: u64,
+    /// Average bit rate over the bucket, in bits per second
+    bit_rate: f64,
+}
+
+/// Downsampled per-frame size series of the first video stream
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FrameSizeTimeline {
+    total_frames: u64,
+    duration: f64,
+    points: Vec<TimelinePoint>,
+}
+
+/// Build a frame size timeline suitable for charting bit rate over time
+///
+/// Packet sizes are read without decoding. With `output_path`, the series is
+/// also written there as CSV.
+#[tauri::command]
+pub async fn frame_size_timeline(
+    path: String,
+    max_points: Option<usize>,
+    granularity: Option<TimelineGranularity>,
+    output_path: Option<String>,
+) -> Result<FrameSizeTimeline, String> {
+    frame_size_timeline_async(
+        &path,
+        max_points.unwrap_or(DEFAULT_TIMELINE_POINTS).max(1),
+        granularity.unwrap_or_default(),
+        output_path.as_deref(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Frame size timeline failed");
+        e.localized()
+    })
+}
+
+async fn frame_size_timeline_async(
+    path: &str,
+    max_points: usize,
+    granularity: TimelineGranularity,
+    output_path: Option<&str>,
+) -> Result<FrameSizeTimeline, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let start = Instant::now();
+    let mut packets = probe_video_packets(app_handle, path).await?;
+    packets.sort_by(|a, b| a.pts_time.total_cmp(&b.pts_time));
+
+    let first = packets.first().map_or(0.0, |p| p.pts_time);
+    let last = packets.last().map_or(0.0, |p| p.pts_time);
+    let duration = (last - first).max(0.0);
+
+    let mut points = match granularity {
+        TimelineGranularity::Frame => bucket_by_time(&packets, first, duration, max_points),
+        TimelineGranularity::Gop => bucket_by_gop(&packets),
+    };
+    // GOP series can still be very long; merge neighbours down to max_points
+    if points.len() > max_points {
+        points = merge_points(points, max_points);
+    }
+
+    let timeline = FrameSizeTimeline {
+        total_frames: packets.len() as u64,
+        duration,
+        points,
+    };
+
+    if let Some(output_path) = output_path {
+        let csv = timeline_to_csv(&timeline);
+        ensure_free_space(Path::new(output_path), csv.len() as u64)?;
+        std::fs::write(output_path, csv)?;
+    }
+
+    tracing::debug!(
+        video_path = %path,
+        frames = timeline.total_frames,
+        points = timeline.points.len(),
+        elapsed = ?start.elapsed(),
+        "Built frame size timeline"
+    );
+
+    Ok(timeline)
+}
+
+/// Read every packet of the first video stream
+async fn probe_video_packets(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+) -> Result<Vec<PacketSample>, Error> {
+    let output = sidecar(app_handle, MediaTool::Ffprobe)?
+        .args([
+            "-v",
+            "quiet",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "packet=pts_time,dts_time,size,flags",
+            "-of",
+            "compact=p=0",
+            path,
+        ])
+        .output_or_kill()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut pts_time = None;
+            let mut dts_time = None;
+            let mut size = None;
+            let mut key_frame = false;
+            for field in line.split('|') {
+                match field.split_once('=') {
+                    Some(("pts_time", value)) => pts_time = value.parse::<f64>().ok(),
+                    Some(("dts_time", value)) => dts_time = value.parse::<f64>().ok(),
+                    Some(("size", value)) => size = value.parse::<u64>().ok(),
+                    Some(("flags", value)) => key_frame = value.contains('K'),
+                    _ => {}
+                }
+            }
+            Some(PacketSample {
+                pts_time: pts_time.or(dts_time)?,
+                size: size?,
+                key_frame,
+            })
+        })
+        .collect())
+}
+
+fn bucket_by_time(
+    packets: &[PacketSample],
+    first: f64,
+    duration: f64,
+    max_points: usize,
+) -> Vec<TimelinePoint> {
+    if packets.is_empty() {
+        return Vec::new();
+    }
+
+    let bucket_count = max_points.min(packets.len());
+    let span = if duration > 0.0 {
+        duration / bucket_count as f64
+    } else {
+        1.0
+    };
+
+    let mut points: Vec<TimelinePoint> = (0..bucket_count)
+        .map(|i| TimelinePoint {
+            time: first + i as f64 * span,
+            span,
+            frames: 0,
+            total_bytes: 0,
+            peak_frame_bytes: 0,
+            bit_rate: 0.0,
+        })
+        .collect();
+
+    for packet in packets {
+        let bucket = (((packet.pts_time - first) / span) as usize).min(bucket_count - 1);
+        let point = &mut points[bucket];
+        point.frames += 1;
+        point.total_bytes += packet.size;
+        point.peak_frame_bytes = point.peak_frame_bytes.max(packet.size);
+    }
+
+    for point in &mut points {
+        point.bit_rate = point.total_bytes as f64 * 8.0 / point.span;
+    }
+
+    points
+}
+
+fn bucket_by_gop(packets: &[PacketSample]) -> Vec<TimelinePoint> {
+    let mut points: Vec<TimelinePoint> = Vec::new();
+
+    for packet in packets {
+        if packet.key_frame || points.is_empty() {
+            points.push(TimelinePoint {
+                time: packet.pts_time,
+                span: 0.0,
+                frames: 0,
+                total_bytes: 0,
+                peak_frame_bytes: 0,
+                bit_rate: 0.0,
+            });
+        }
+        let point = points.last_mut().expect("a point was just pushed");
+        point.frames += 1;
+        point.total_bytes += packet.size;
+        point.peak_frame_bytes = point.peak_frame_bytes.max(packet.size);
+    }
+
+    // Each GOP lasts until the next one starts; the last one ends at the last packet
+    let last_time = packets.last().map_or(0.0, |p| p.pts_time);
+    let starts: Vec<f64> = points.iter().map(|p| p.time).collect();
+    for (i, point) in points.iter_mut().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(last_time);
+        point.span = (end - point.time).max(0.0);
+        if point.span > 0.0 {
+            point.bit_rate = point.total_bytes as f64 * 8.0 / point.span;
+        }
+    }
+
+    points
+}
+
+/// Merge consecutive points so at most `max_points` remain
+fn merge_points(points: Vec<TimelinePoint>, max_points: usize) -> Vec<TimelinePoint> {
+    let chunk_size = points.len().div_ceil(max_points);
+    points
+        .chunks(chunk_size)
+        .map(|chunk| {
+            let span: f64 = chunk.iter().map(|p| p.span).sum();
+            let total_bytes: u64 = chunk.iter().map(|p| p.total_bytes).sum();
+            TimelinePoint {
+                time: chunk[0].time,
+                span,
+                frames: chunk.iter().map(|p| p.frames).sum(),
+                total_bytes,
+                peak_frame_bytes: chunk.iter().map(|p| p.peak_frame_bytes).max().unwrap_or(0),
+                bit_rate: if span > 0.0 {
+                    total_bytes as f64 * 8.0 / span
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect()
+}
+
+fn timeline_to_csv(timeline: &FrameSizeTimeline) -> String {
+    let mut csv = String::from("time,span,frames,total_bytes,peak_frame_bytes,bit_rate\n");
+    for point in &timeline.points {
+        csv.push_str(&format!(
+            "{:.3},{:.3},{},{},{},{:.0}\n",
+            point.time,
+            point.span,
+            point.frames,
+            point.total_bytes,
+            point.peak_frame_bytes,
+            point.bit_rate
+        ));
+    }
+    csv
+}
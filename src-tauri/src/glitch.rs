@@ -0,0 +1,535 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+
+use crate::analyzer::{first_stream, Analyzer};
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::runner::{sidecar, KillOnDrop, MediaTool, MediaToolRunner, SidecarRunner};
+
+/// freezedetect settings: noise tolerance, and the shortest freeze reported
+/// in seconds
+const FREEZE_FILTER: &str = "freezedetect=n=-60dB:d=2,mpdecimate";
+
+/// Runs of at least this many duplicate frames are reported individually;
+/// shorter ones only count towards the total
+const MIN_DUPLICATE_RUN: u64 = 12;
+
+/// silencedetect settings for dropouts: near digital silence, lasting at
+/// least 20 ms
+const DROPOUT_FILTER: &str = "silencedetect=noise=-70dB:d=0.02";
+
+/// Silences longer than this are pauses rather than dropouts
+const MAX_DROPOUT_SECS: f64 = 0.5;
+
+/// Audio packets starting later than the previous one ends by more than
+/// this many seconds leave a gap
+const AUDIO_GAP_TOLERANCE: f64 = 0.01;
+
+/// Decode timestamps advancing by more than this many seconds, and by more
+/// than `GAP_PACKET_DURATIONS` packet durations, skip content
+const TIMESTAMP_GAP_SECS: f64 = 0.5;
+const GAP_PACKET_DURATIONS: f64 = 4.0;
+
+/// Where MPEG-TS timestamps wrap: 2^33 ticks of the 90 kHz clock
+const MPEG_TS_WRAP_SECS: f64 = 8_589_934_592.0 / 90_000.0;
+
+/// Backward jumps this close to `MPEG_TS_WRAP_SECS` are wraparounds
+const WRAP_TOLERANCE_SECS: f64 = 5.0;
+
+/// Discontinuities listed per file; the counts cover all of them
+const MAX_DISCONTINUITIES: usize = 500;
+
+/// A frozen stretch of video
+#[derive(serde::Serialize, Clone, Debug)]
+struct Freeze {
+    start: f64,
+    /// `None` when the video is still frozen at the end
+    end: Option<f64>,
+    duration: Option<f64>,
+}
+
+/// Consecutive frames mpdecimate found identical to the one before them
+#[derive(serde::Serialize, Clone, Debug)]
+struct DuplicateRun {
+    /// Time of the first duplicate
+    start: f64,
+    frames: u64,
+}
+
+/// A short stretch of silence inside the audio
+#[derive(serde::Serialize, Clone, Debug)]
+struct Dropout {
+    start: f64,
+    duration: f64,
+}
+
+/// Audio packets that don't follow on from the one before
+#[derive(serde::Serialize, Clone, Debug)]
+struct AudioGap {
+    stream_index: u64,
+    /// Where the previous packet ends
+    time: f64,
+    /// Seconds missing before the next packet
+    gap: f64,
+}
+
+/// How a stream's timestamps break
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum DiscontinuityKind {
+    /// Timestamps skip ahead
+    Gap,
+    /// Timestamps go back, e.g. where files were joined badly
+    Backwards,
+    /// MPEG-TS timestamps rolled over; players usually cope
+    Wraparound,
+}
+
+/// A jump between consecutive packets of one stream
+#[derive(serde::Serialize, Clone, Debug)]
+struct Discontinuity {
+    stream_index: u64,
+    kind: DiscontinuityKind,
+    /// Timestamp of the packet before the jump, in seconds
+    time: f64,
+    /// Seconds jumped; negative when going back
+    jump: f64,
+}
+
+/// Timestamps of one packet, from `ffprobe -show_packets`
+#[derive(Clone, Copy, Debug)]
+struct PacketTimes {
+    stream_index: u64,
+    pts: Option<f64>,
+    dts: Option<f64>,
+    duration: Option<f64>,
+}
+
+/// Frozen video and runs of duplicate frames, from ffmpeg's freezedetect
+/// and mpdecimate filters over the whole first video stream
+pub struct FreezeAnalyzer;
+
+#[async_trait]
+impl Analyzer for FreezeAnalyzer {
+    fn name(&self) -> &str {
+        "freeze"
+    }
+
+    fn description(&self) -> &str {
+        "Frozen video and long runs of duplicate frames"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let app_handle = get_app_handle()
+            .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+        // mpdecimate only logs its keep/drop decisions at debug level
+        let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-v",
+                "debug",
+                "-i",
+                path,
+                "-map",
+                "0:v:0",
+                "-filter:v",
+                FREEZE_FILTER,
+                "-an",
+                "-sn",
+                "-dn",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output_or_kill()
+            .await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FFmpegError(format!(
+                "ffmpeg freeze detection failed: {}",
+                stderr.lines().last().unwrap_or_default()
+            )));
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let freezes = parse_freezes(&stderr);
+        let duration = json_f64(&probe["format"]["duration"]);
+        let frozen_seconds: f64 = freezes
+            .iter()
+            .filter_map(|freeze| freeze.duration.or(duration.map(|d| d - freeze.start)))
+            .sum();
+        let (runs, kept, dropped) = parse_decimation(&stderr);
+        let frames = (kept + dropped).max(1);
+        tracing::debug!(
+            video_path = %path,
+            freezes = freezes.len(),
+            duplicates = dropped,
+            "Checked for frozen and duplicate frames"
+        );
+
+        Ok(json!({
+            "freezes": freezes,
+            "frozen_seconds": frozen_seconds,
+            "duplicate_frames": dropped,
+            // A steady share around 20% points at a 24 to 30 fps conversion
+            "duplicate_ratio": dropped as f64 / frames as f64,
+            "duplicate_runs": runs,
+        }))
+    }
+}
+
+/// Collect freezedetect's `lavfi.freezedetect.freeze_start: 12.5` style
+/// metadata lines
+fn parse_freezes(stderr: &str) -> Vec<Freeze> {
+    let mut freezes: Vec<Freeze> = Vec::new();
+    for line in stderr.lines() {
+        let Some((_, rest)) = line.split_once("lavfi.freezedetect.") else {
+            continue;
+        };
+        let Some((key, value)) = rest.split_once(':') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<f64>() else {
+            continue;
+        };
+        match key {
+            "freeze_start" => freezes.push(Freeze {
+                start: value,
+                end: None,
+                duration: None,
+            }),
+            "freeze_duration" => {
+                if let Some(freeze) = freezes.last_mut() {
+                    freeze.duration = Some(value);
+                }
+            }
+            "freeze_end" => {
+                if let Some(freeze) = freezes.last_mut() {
+                    freeze.end = Some(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    freezes
+}
+
+/// Turn mpdecimate's `keep pts:... pts_time:1.2` / `drop pts:...` debug lines
+/// into long duplicate runs and kept and dropped frame counts
+fn parse_decimation(stderr: &str) -> (Vec<DuplicateRun>, u64, u64) {
+    let mut runs = Vec::new();
+    let (mut kept, mut dropped) = (0, 0);
+    let mut current: Option<DuplicateRun> = None;
+    for line in stderr.lines().filter(|line| line.contains("mpdecimate")) {
+        let Some((_, decision)) = line.split_once("] ") else {
+            continue;
+        };
+        let time = decision
+            .split_once("pts_time:")
+            .and_then(|(_, rest)| rest.split_whitespace().next())
+            .and_then(|time| time.parse::<f64>().ok());
+        if decision.starts_with("drop ") {
+            dropped += 1;
+            match &mut current {
+                Some(run) => run.frames += 1,
+                None => {
+                    current = Some(DuplicateRun {
+                        start: time.unwrap_or_default(),
+                        frames: 1,
+                    })
+                }
+            }
+        } else if decision.starts_with("keep ") {
+            kept += 1;
+            if let Some(run) = current.take().filter(|run| run.frames >= MIN_DUPLICATE_RUN) {
+                runs.push(run);
+            }
+        }
+    }
+    if let Some(run) = current.filter(|run| run.frames >= MIN_DUPLICATE_RUN) {
+        runs.push(run);
+    }
+    (runs, kept, dropped)
+}
+
+/// Brief silences in the first audio stream and gaps between the packets of
+/// every audio stream
+pub struct AudioDropoutAnalyzer;
+
+#[async_trait]
+impl Analyzer for AudioDropoutAnalyzer {
+    fn name(&self) -> &str {
+        "audio_dropouts"
+    }
+
+    fn description(&self) -> &str {
+        "Short audio dropouts and gaps between audio packets"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "audio").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let app_handle = get_app_handle()
+            .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+        let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+            .args([
+                "-hide_banner",
+                "-nostats",
+                "-i",
+                path,
+                "-map",
+                "0:a:0",
+                "-filter:a",
+                DROPOUT_FILTER,
+                "-vn",
+                "-sn",
+                "-dn",
+                "-f",
+                "null",
+                "-",
+            ])
+            .output_or_kill()
+            .await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::FFmpegError(format!(
+                "ffmpeg dropout detection failed: {}",
+                stderr.lines().last().unwrap_or_default()
+            )));
+        }
+
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(f64::MAX);
+        // Silence running into the start or end of the file is a lead-in or
+        // tail, not a dropout
+        let dropouts: Vec<Dropout> = parse_silences(&String::from_utf8_lossy(&output.stderr))
+            .into_iter()
+            .filter(|dropout| {
+                dropout.duration <= MAX_DROPOUT_SECS
+                    && dropout.start > 0.0
+                    && dropout.start + dropout.duration < duration - AUDIO_GAP_TOLERANCE
+            })
+            .collect();
+
+        let packets = read_packet_times(&SidecarRunner::new(app_handle), path, Some("a")).await?;
+        let gaps = audio_gaps(&packets);
+        tracing::debug!(
+            video_path = %path,
+            dropouts = dropouts.len(),
+            gaps = gaps.len(),
+            "Checked audio for dropouts"
+        );
+
+        Ok(json!({
+            "dropouts": dropouts,
+            "packet_gaps": gaps,
+        }))
+    }
+}
+
+/// Pair silencedetect's `silence_start: 12.3` and
+/// `silence_end: 12.4 | silence_duration: 0.1` lines
+fn parse_silences(stderr: &str) -> Vec<Dropout> {
+    let mut silences = Vec::new();
+    let mut start = None;
+    for line in stderr.lines() {
+        if let Some((_, rest)) = line.split_once("silence_start:") {
+            start = rest.trim().parse::<f64>().ok();
+        } else if let Some((_, rest)) = line.split_once("silence_duration:") {
+            if let (Some(start), Ok(duration)) = (start.take(), rest.trim().parse::<f64>()) {
+                silences.push(Dropout { start, duration });
+            }
+        }
+    }
+    silences
+}
+
+/// Gaps between consecutive packets of each stream, by presentation time
+fn audio_gaps(packets: &[PacketTimes]) -> Vec<AudioGap> {
+    let mut ends: HashMap<u64, f64> = HashMap::new();
+    let mut gaps = Vec::new();
+    for packet in packets {
+        let (Some(pts), Some(duration)) = (packet.pts, packet.duration) else {
+            continue;
+        };
+        if let Some(&end) = ends.get(&packet.stream_index) {
+            if pts - end > AUDIO_GAP_TOLERANCE {
+                gaps.push(AudioGap {
+                    stream_index: packet.stream_index,
+                    time: end,
+                    gap: pts - end,
+                });
+            }
+        }
+        ends.insert(packet.stream_index, pts + duration);
+    }
+    gaps
+}
+
+/// Gaps, backward jumps and wraparounds in the packet timestamps of every
+/// audio and video stream
+pub struct TimestampAnalyzer;
+
+#[async_trait]
+impl Analyzer for TimestampAnalyzer {
+    fn name(&self) -> &str {
+        "timestamps"
+    }
+
+    fn description(&self) -> &str {
+        "Timestamp gaps, backward jumps and wraparounds that make players skip"
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let app_handle = get_app_handle()
+            .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+        // Subtitle and data streams are sparse, so their gaps mean nothing
+        let streams: Vec<u64> = probe["streams"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|stream| matches!(stream["codec_type"].as_str(), Some("audio" | "video")))
+            .filter(|stream| stream["disposition"]["attached_pic"].as_u64() != Some(1))
+            .filter_map(|stream| stream["index"].as_u64())
+            .collect();
+
+        let packets = read_packet_times(&SidecarRunner::new(app_handle), path, None).await?;
+        let packets: Vec<PacketTimes> = packets
+            .into_iter()
+            .filter(|packet| streams.contains(&packet.stream_index))
+            .collect();
+        let mut discontinuities = find_discontinuities(&packets);
+        let count = |kind| {
+            discontinuities
+                .iter()
+                .filter(|discontinuity| discontinuity.kind == kind)
+                .count()
+        };
+        let (gaps, backwards, wraparounds) = (
+            count(DiscontinuityKind::Gap),
+            count(DiscontinuityKind::Backwards),
+            count(DiscontinuityKind::Wraparound),
+        );
+        tracing::debug!(
+            video_path = %path,
+            packets = packets.len(),
+            gaps,
+            backwards,
+            wraparounds,
+            "Checked packet timestamps"
+        );
+        discontinuities.truncate(MAX_DISCONTINUITIES);
+
+        Ok(json!({
+            "packets": packets.len(),
+            "gaps": gaps,
+            "backwards": backwards,
+            "wraparounds": wraparounds,
+            "discontinuities": discontinuities,
+        }))
+    }
+}
+
+/// Compare each packet's decode timestamp, or presentation timestamp when
+/// there is none, with the previous packet of its stream
+///
+/// Presentation timestamps alone can't be used for video since B-frames
+/// reorder them.
+fn find_discontinuities(packets: &[PacketTimes]) -> Vec<Discontinuity> {
+    let mut previous: HashMap<u64, (f64, Option<f64>)> = HashMap::new();
+    let mut discontinuities = Vec::new();
+    for packet in packets {
+        let Some(time) = packet.dts.or(packet.pts) else {
+            continue;
+        };
+        if let Some(&(last, last_duration)) = previous.get(&packet.stream_index) {
+            let jump = time - last;
+            let gap_threshold = last_duration.map_or(TIMESTAMP_GAP_SECS, |d| {
+                (d * GAP_PACKET_DURATIONS).max(TIMESTAMP_GAP_SECS)
+            });
+            let kind = if jump < 0.0 && (jump + MPEG_TS_WRAP_SECS).abs() < WRAP_TOLERANCE_SECS {
+                Some(DiscontinuityKind::Wraparound)
+            } else if jump < 0.0 {
+                Some(DiscontinuityKind::Backwards)
+            } else if jump > gap_threshold {
+                Some(DiscontinuityKind::Gap)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                discontinuities.push(Discontinuity {
+                    stream_index: packet.stream_index,
+                    kind,
+                    time: last,
+                    jump,
+                });
+            }
+        }
+        previous.insert(packet.stream_index, (time, packet.duration));
+    }
+    discontinuities
+}
+
+/// Read packet timestamps without decoding, of the streams matching
+/// `select_streams` (e.g. `a` or `v:0`) or of all streams
+async fn read_packet_times(
+    runner: &dyn MediaToolRunner,
+    path: &str,
+    select_streams: Option<&str>,
+) -> Result<Vec<PacketTimes>, Error> {
+    let mut args = vec!["-v", "quiet"];
+    if let Some(select_streams) = select_streams {
+        args.extend(["-select_streams", select_streams]);
+    }
+    args.extend([
+        "-show_entries",
+        "packet=stream_index,pts_time,dts_time,duration_time",
+        "-of",
+        "compact=p=0",
+        path,
+    ]);
+    let output = runner.run(MediaTool::Ffprobe, &args).await?;
+    if !output.success {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    }
+    Ok(parse_packet_times(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `compact` lines like `stream_index=1|pts_time=0.021|dts_time=...`;
+/// `N/A` values are left out
+fn parse_packet_times(output: &str) -> Vec<PacketTimes> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut packet = PacketTimes {
+                stream_index: 0,
+                pts: None,
+                dts: None,
+                duration: None,
+            };
+            let mut has_index = false;
+            for field in line.split('|') {
+                match field.split_once('=') {
+                    Some(("stream_index", value)) => {
+                        packet.stream_index = value.parse().ok()?;
+                        has_index = true;
+                    }
+                    Some(("pts_time", value)) => packet.pts = value.parse().ok(),
+                    Some(("dts_time", value)) => packet.dts = value.parse().ok(),
+                    Some(("duration_time", value)) => packet.duration = value.parse().ok(),
+                    _ => {}
+                }
+            }
+            has_index.then_some(packet)
+        })
+        .collect()
+}
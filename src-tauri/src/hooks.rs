@@ -0,0 +1,215 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::events::emit_hook_finished;
+use crate::inspector::Error;
+use crate::settings;
+use crate::temp::temp_frame_path;
+
+/// Hooks still running after this long are killed
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often a running hook is polled for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Bytes of hook stderr kept for the outcome event
+const STDERR_TAIL_BYTES: usize = 4096;
+
+/// How a hook receives the inspection result
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HookInput {
+    /// Metadata JSON written to the hook's stdin
+    #[default]
+    Stdin,
+    /// Metadata JSON written to a temp file, passed as `{json}`
+    TempFile,
+}
+
+/// External command run after each successful inspection
+///
+/// `{path}` in `args` is replaced by the inspected file and `{json}` by the
+/// metadata file in `temp_file` mode. Both are also available as the
+/// `VIDEO_INSPECTOR_PATH` and `VIDEO_INSPECTOR_JSON` environment variables.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct Hook {
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub input: HookInput,
+    /// Defaults to 60 seconds
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Result of running one hook, emitted as an `inspection://hook-finished`
+/// event
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct HookOutcome {
+    hook: String,
+    success: bool,
+    /// None when the hook couldn't be started or was killed
+    exit_code: Option<i32>,
+    timed_out: bool,
+    /// End of the hook's stderr, or why it couldn't be started
+    stderr: String,
+    elapsed_ms: u64,
+}
+
+/// Run the configured hooks for an inspection result in the background
+///
+/// Hooks run one after another so that, e.g., a spreadsheet append and a
+/// quarantine move happen in the order they're listed.
+pub fn run_hooks(job_id: &str, path: &str, metadata: &impl serde::Serialize) {
+    let hooks: Vec<Hook> = settings::current()
+        .hooks
+        .into_iter()
+        .filter(|hook| hook.enabled)
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+
+    let json = match serde_json::to_vec(metadata) {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::error!(video_path = %path, error = %e, "Failed to serialize hook input");
+            return;
+        }
+    };
+
+    let job_id = job_id.to_string();
+    let path = path.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        for hook in &hooks {
+            let outcome = run_hook(hook, &path, &json).unwrap_or_else(|e| HookOutcome {
+                hook: hook.name.clone(),
+                success: false,
+                exit_code: None,
+                timed_out: false,
+                stderr: e.to_string(),
+                elapsed_ms: 0,
+            });
+            if outcome.success {
+                tracing::info!(
+                    video_path = %path,
+                    hook = %hook.name,
+                    elapsed_ms = outcome.elapsed_ms,
+                    "Hook finished"
+                );
+            } else {
+                tracing::warn!(
+                    video_path = %path,
+                    hook = %hook.name,
+                    exit_code = ?outcome.exit_code,
+                    timed_out = outcome.timed_out,
+                    stderr = %outcome.stderr,
+                    "Hook failed"
+                );
+            }
+            emit_hook_finished(&job_id, &path, &outcome);
+        }
+    });
+}
+
+fn run_hook(hook: &Hook, path: &str, json: &[u8]) -> Result<HookOutcome, Error> {
+    // Kept until the hook exits; deleted on drop
+    let json_file = match hook.input {
+        HookInput::Stdin => None,
+        HookInput::TempFile => {
+            let json_file = temp_frame_path("hook_metadata", "json")?;
+            fs::write(&json_file, json)?;
+            Some(json_file)
+        }
+    };
+    let json_path = json_file
+        .as_ref()
+        .map(|json_file| json_file.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let args = hook
+        .args
+        .iter()
+        .map(|arg| arg.replace("{path}", path).replace("{json}", &json_path));
+    let mut command = Command::new(&hook.program);
+    command
+        .args(args)
+        .env("VIDEO_INSPECTOR_PATH", path)
+        .env("VIDEO_INSPECTOR_JSON", &json_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .stdin(match hook.input {
+            HookInput::Stdin => Stdio::piped(),
+            HookInput::TempFile => Stdio::null(),
+        });
+
+    let started = Instant::now();
+    let mut child = command.spawn().map_err(|e| {
+        Error::IoError(std::io::Error::new(
+            e.kind(),
+            format!("Failed to start hook '{}': {}", hook.program, e),
+        ))
+    })?;
+
+    // Feed stdin and drain stderr on their own threads, so a hook that
+    // ignores its input or writes a lot can't block us or itself
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = json.to_vec();
+        thread::spawn(move || {
+            // A hook that exits without reading its input isn't an error
+            let _ = stdin.write_all(&json);
+        });
+    }
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut output = Vec::new();
+            let _ = stderr.read_to_end(&mut output);
+            output
+        })
+    });
+
+    let timeout = hook
+        .timeout_secs
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_HOOK_TIMEOUT);
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            timed_out = true;
+            let _ = child.kill();
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stderr = stderr_reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+    let tail = &stderr[stderr.len().saturating_sub(STDERR_TAIL_BYTES)..];
+
+    Ok(HookOutcome {
+        hook: hook.name.clone(),
+        success: status.is_some_and(|status| status.success()),
+        exit_code: status.and_then(|status| status.code()),
+        timed_out,
+        stderr: String::from_utf8_lossy(tail).trim().to_string(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
@@ -1,28 +1,122 @@
-use base64::{engine::general_purpose, Engine};
-use sha2::{Digest, Sha256};
+use fluent_bundle::FluentArgs;
 use std::{
-    fs,
-    sync::{Arc, Mutex},
+    fs, io,
+    sync::atomic::{AtomicUsize, Ordering},
     time::Instant,
 };
-use tauri_plugin_shell::ShellExt;
 use thiserror::Error;
-
+use tracing::{info_span, Instrument};
+use video_inspector_core::Error as CoreError;
+
+use crate::audio::{has_stereo_downmix, AudioStreamInfo};
+use crate::container::{read_container_info, ContainerInfo};
+use crate::duration::DurationSource;
+use crate::error_reporting::size_bucket;
+use crate::events::{emit_hash_progress, emit_partial_result, PartialResult};
+use crate::frame_content::ContentHints;
 use crate::get_app_handle;
+use crate::hash::{calculate_file_hash, calculate_quick_hash, HashKind};
+use crate::hooks::run_hooks;
+use crate::integrity::{scan_integrity_async, IntegrityReport};
+use crate::job::{cancel_flag, new_job_id, run_cancellable};
+use crate::layout::StreamLayout;
+use crate::locale::tr;
+use crate::loudness::{measure_loudness_async, LoudnessReport};
+use crate::metadata_cache::{metadata_key, read_metadata, write_metadata};
+use crate::mp4::{read_tracks, track_for_stream};
+use crate::offsets::{analyze_start_offsets, StartOffsetReport};
+use crate::preset::{InspectionPreset, Pipeline};
+use crate::probe::{
+    self, fill_missing_bit_rates, probe_video_info, ProbeOptions, Rational, VideoInfo,
+};
+use crate::programs::ProgramInfo;
+use crate::report::qc_issues;
+use crate::runner::{validate_input_args, SidecarRunner};
+use crate::savings::{bit_rate_efficiency, BitRateEfficiency, EncodedVideo};
+use crate::scene::{detect_scenes, representative_time_points};
+use crate::scripting::{evaluate_computed_fields, ComputedValue};
+use crate::settings;
+use crate::sidecar::{check_sidecars, SidecarCheck};
+use crate::sniff::{ensure_media_file, FileKind};
+use crate::stages::{InspectionStage, StageTracker};
+use crate::subtitle::{
+    find_external_subtitles, parse_subtitle_streams, ExternalSubtitle, SubtitleStreamInfo,
+};
+use crate::thumbnail::{default_time_points, generate_thumbnails_with_ffmpeg, Thumbnail};
+use crate::video::VideoStreamInfo;
+
+/// Number of thumbnails generated per video
+const THUMBNAIL_COUNT: usize = 4;
 
-#[derive(serde::Serialize, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct VideoMetadata {
+    job_id: String,
     file_path: String,
     resolution: String,
     frame_rate: String,
     duration: String,
+    duration_source: DurationSource, // Derived from streams or packets unless "format"
     bit_rate: String,
+    video_bit_rate: Option<String>,
+    video_bit_rate_measured: bool, // Computed from packet sizes rather than declared
+    video_stream: VideoStreamInfo,
     file_size: String,
-    file_hash: String,
+    // The values above unformatted, for sorting and unit conversion
+    width: u32,            // 0 without a video stream
+    height: u32,           // 0 without a video stream
+    fps: Option<Rational>, // As declared, e.g. 30000/1001
+    duration_secs: f64,
+    bit_rate_bps: f64,
+    video_bit_rate_bps: Option<f64>,
+    file_size_bytes: u64,
+    file_hash: Option<String>, // None when the preset skips hashing
+    hash_kind: Option<HashKind>,
     thumbnails_base64: Vec<String>, // Store base64 encoding of 4 thumbnails
+    thumbnail_timestamps: Vec<f64>, // Timestamp in seconds of each thumbnail
+    thumbnail_hints: Vec<Option<ContentHints>>, // Faces/text per thumbnail, if enabled
+    audio_streams: Vec<AudioStreamInfo>,
+    has_stereo_downmix: Option<bool>, // None when the file has no audio
+    stream_layout: StreamLayout,      // Stream counts, with notes such as "No audio stream"
+    subtitle_streams: Vec<SubtitleStreamInfo>, // Embedded text and bitmap tracks
+    container: ContainerInfo,         // MIME type, MP4 brands, Matroska DocType
+    start_offsets: StartOffsetReport,
+    computed_fields: Vec<ComputedValue>, // User-scripted fields from settings
+    preset: InspectionPreset,            // Steps run beyond the probe, before skip flags
+    qc_issues: Option<Vec<String>>,
+    loudness: Option<LoudnessReport>,
+    integrity: Option<IntegrityReport>,
+    sidecars: Vec<SidecarCheck>, // .nfo/.xml/.xmp next to the file, with mismatches
+    external_subtitles: Vec<ExternalSubtitle>, // .srt/.ass/... named after the file
+    efficiency: Option<BitRateEfficiency>, // Bits per pixel with a starved/bloated verdict
+    programs: Vec<ProgramInfo>,  // Programs of a multi-program transport stream
+    program: Option<u64>,        // The one described above, when there was a choice
+    timings: InspectionTimings,  // Where the time went, to find the slow phase
+    #[serde(default)]
+    from_cache: bool, // Timings and stages are those of the cached inspection
+}
+
+/// How long each phase of an inspection took, in milliseconds
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct InspectionTimings {
+    /// ffprobe alone
+    probe_ms: u64,
+    /// Bit rate measurement, HDR and AV1 details that follow the probe
+    stream_details_ms: u64,
+    /// `None` when the pipeline skips hashing
+    hash_ms: Option<u64>,
+    /// Hash read speed in megabytes per second
+    hash_mb_per_sec: Option<f64>,
+    /// `None` when the pipeline skips thumbnails
+    thumbnails_ms: Option<u64>,
+    /// Each thumbnail in order; cached ones take next to nothing
+    thumbnail_ms: Vec<u64>,
+    /// QC, loudness and integrity steps
+    deep_checks_ms: u64,
+    total_ms: u64,
 }
 
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[error("Failed to execute ffmpeg: {0}")]
     FFmpegError(String),
@@ -32,349 +126,590 @@ pub enum Error {
     IoError(#[from] std::io::Error),
     #[error("Shell error: {0}")]
     ShellError(#[from] tauri_plugin_shell::Error),
+    #[error("Insufficient disk space: {required} bytes required, {available} bytes available")]
+    InsufficientSpace { required: u64, available: u64 },
+    #[error("Cancelled")]
+    Cancelled,
+    #[error("Job {0} is already running")]
+    JobInUse(String),
+    #[error(transparent)]
+    Core(#[from] video_inspector_core::Error),
 }
 
+impl Error {
+    /// Message for the user in the locale selected by the frontend
+    ///
+    /// `Display` stays in English for the logs.
+    pub fn localized(&self) -> String {
+        let mut args = FluentArgs::new();
+        let id = match self {
+            Error::FFmpegError(detail) => {
+                args.set("detail", detail.as_str());
+                "error-ffmpeg"
+            }
+            Error::ParseError(detail) => {
+                args.set("detail", detail.as_str());
+                "error-parse"
+            }
+            Error::IoError(e) => {
+                args.set("detail", e.to_string());
+                "error-io"
+            }
+            Error::ShellError(e) => {
+                args.set("detail", e.to_string());
+                "error-shell"
+            }
+            Error::InsufficientSpace {
+                required,
+                available,
+            } => {
+                args.set("required", format_size(*required));
+                args.set("available", format_size(*available));
+                "error-insufficient-space"
+            }
+            Error::Cancelled => "error-cancelled",
+            Error::JobInUse(job_id) => {
+                args.set("job", job_id.as_str());
+                "error-job-in-use"
+            }
+            Error::Core(CoreError::FFmpegError(detail)) => {
+                args.set("detail", detail.as_str());
+                "error-ffmpeg"
+            }
+            Error::Core(CoreError::ParseError(detail)) => {
+                args.set("detail", detail.as_str());
+                "error-parse"
+            }
+            Error::Core(CoreError::IoError(e)) => {
+                args.set("detail", e.to_string());
+                "error-io"
+            }
+            Error::Core(CoreError::NotMediaFile(detail)) => {
+                args.set("detail", detail.as_str());
+                "error-not-media"
+            }
+        };
+        tr(id, &args)
+    }
+}
+
+/// Inspect a video file
+///
+/// Partial results are emitted as `inspection://partial` events, and the
+/// current stage with an overall percentage as `inspection://progress`
+/// events, tagged with `job_id` while the inspection runs; pass your own
+/// `job_id` to correlate them before the command returns, or to stop the
+/// inspection with `cancel_inspection`; it must not be one still running.
+/// `preset` picks the steps run after the probe and defaults to the one in
+/// settings; `skip_hash` and `skip_thumbnails` drop those steps whatever the
+/// preset, and `quick_hash: true` swaps the full SHA-256 the presets compute
+/// for the sampled quick hash, whose `hash_kind` is `quick`.
+/// `input_args`, such as `["-probesize", "100M"]`, are added to the probe
+/// after the ones from settings, for files that need more probing than usual.
+/// `program` picks which program of a multi-program transport stream the
+/// metadata and thumbnails describe; all of them are listed in `programs`.
+///
+/// Results are cached by path, size and modification time along with the
+/// options and settings, so inspecting an unchanged file again returns at
+/// once without events; `clear_metadata_cache` drops them.
 #[tauri::command]
-pub async fn get_video_metadata(path: String) -> Result<VideoMetadata, String> {
+#[allow(clippy::too_many_arguments)]
+pub async fn get_video_metadata(
+    path: String,
+    scene_detection: Option<bool>,
+    job_id: Option<String>,
+    preset: Option<InspectionPreset>,
+    skip_hash: Option<bool>,
+    skip_thumbnails: Option<bool>,
+    input_args: Option<Vec<String>>,
+    program: Option<u64>,
+    quick_hash: Option<bool>,
+) -> Result<VideoMetadata, String> {
     let start_time = Instant::now();
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    let preset = preset.unwrap_or_else(|| settings::current().inspection_preset);
+    let mut pipeline = preset.pipeline();
+    if let Some(quick_hash) = quick_hash {
+        pipeline.hash = Some(if quick_hash {
+            HashKind::Quick
+        } else {
+            HashKind::Sha256
+        });
+    }
+    if skip_hash.unwrap_or(false) {
+        pipeline.hash = None;
+    }
+    if skip_thumbnails.unwrap_or(false) {
+        pipeline.thumbnails = false;
+    }
+    let size_bucket = fs::metadata(&path).map_or("unknown", |file| size_bucket(file.len()));
+    let stages = StageTracker::new(&job_id, &path, pipeline);
+    let scene_detection = scene_detection.unwrap_or(false);
+    let input_args = input_args.unwrap_or_default();
+
+    // An unchanged file inspected with the same options gives the same result
+    let options_key = format!(
+        "{:?}|{:?}|{}|{:?}|{:?}",
+        preset, pipeline, scene_detection, input_args, program
+    );
+    let cache_key = metadata_key(&path, &options_key);
+    if let Some(mut metadata) = cache_key.as_deref().and_then(read_metadata) {
+        metadata.job_id = job_id.clone();
+        metadata.from_cache = true;
+        tracing::info!(
+            video_path = %path,
+            job_id = %job_id,
+            event = "processing_cached",
+            "Returned cached video metadata"
+        );
+        stages.enter(InspectionStage::Done);
+        run_hooks(&job_id, &path, &metadata);
+        return Ok(metadata);
+    }
 
     tracing::info!(
         video_path = %path,
+        job_id = %job_id,
         event = "processing_start",
         "Starting video metadata extraction"
     );
 
-    let result = extract_video_metadata_async(&path).await;
+    // Root span of the inspection trace, each phase below is a child span
+    let span = info_span!("inspect", job_id = %job_id, ?preset, size_bucket);
+    let inspection = {
+        let (path, job_id, stages) = (path.clone(), job_id.clone(), stages.clone());
+        async move {
+            extract_video_metadata_async(
+                &path,
+                scene_detection,
+                preset,
+                pipeline,
+                &job_id,
+                &ProbeOptions {
+                    input_args,
+                    program,
+                    trace_output: settings::current().trace_ffprobe_output,
+                },
+                &stages,
+            )
+            .await
+        }
+        .instrument(span)
+    };
+    let result = run_cancellable(&job_id, inspection).await;
 
     let total_duration = start_time.elapsed().as_millis() as u64;
 
     match &result {
-        Ok(_) => {
+        Ok(metadata) => {
             tracing::info!(
                 video_path = %path,
                 event = "processing_success",
                 duration_ms = total_duration,
                 "Video metadata extraction completed successfully"
             );
+            stages.enter(InspectionStage::Done);
+            run_hooks(&job_id, &path, metadata);
+            if let Some(key) = &cache_key {
+                write_metadata(key, metadata);
+            }
         }
         Err(e) => {
+            stages.enter(InspectionStage::Failed);
             tracing::error!(
                 video_path = %path,
                 event = "processing_error",
                 error = %e,
+                size_bucket,
                 duration_ms = total_duration,
                 "Video metadata extraction failed"
             );
         }
     }
 
-    result.map_err(|e| e.to_string())
+    result.map_err(|e| e.localized())
 }
 
 /// Extract video metadata using ffmpeg sidecar
-async fn extract_video_metadata_async(path: &str) -> Result<VideoMetadata, Error> {
+///
+/// When `scene_detection` is set, thumbnails are placed in the most
+/// representative scenes instead of at fixed percentages. Steps left out of
+/// `pipeline` are skipped; `preset` is only reported. Each stage emits its
+/// results as soon as they're ready so slow inputs show progress.
+async fn extract_video_metadata_async(
+    path: &str,
+    scene_detection: bool,
+    preset: InspectionPreset,
+    pipeline: Pipeline,
+    job_id: &str,
+    probe_options: &ProbeOptions,
+    stages: &StageTracker,
+) -> Result<VideoMetadata, Error> {
     let app_handle = get_app_handle()
         .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    validate_input_args(&probe_options.input_args)?;
+
+    // Reject archives, documents and the like before spending time in ffprobe
+    let file_kind = ensure_media_file(path)?;
+    let container = read_container_info(path, file_kind).unwrap_or_else(|e| {
+        tracing::warn!(video_path = %path, error = %e, "Failed to read container header");
+        ContainerInfo::default()
+    });
+
+    let start = Instant::now();
+    let mut timings = InspectionTimings::default();
+    stages.enter(InspectionStage::Probe);
 
     // Get metadata using ffprobe (part of ffmpeg)
-    let metadata = get_video_info_with_ffprobe(app_handle, path).await?;
+    let runner = SidecarRunner::new(app_handle);
+    let mut metadata = probe_video_info(&runner, path, probe_options)
+        .instrument(info_span!("probe"))
+        .await?;
+    timings.probe_ms = start.elapsed().as_millis() as u64;
+    stages.enter(InspectionStage::StreamDetails);
+    let phase_start = Instant::now();
+    fill_missing_bit_rates(&runner, path, &mut metadata)
+        .instrument(info_span!("bit_rates"))
+        .await;
+    metadata
+        .video_stream
+        .complete_hdr_metadata(&runner, path)
+        .instrument(info_span!("hdr"))
+        .await;
+
+    // MP4 boxes carry details ffprobe doesn't report
+    let mp4_tracks = if file_kind == FileKind::Media("mp4") {
+        read_tracks(path)
+    } else {
+        Vec::new()
+    };
+    let video_track = metadata
+        .video_stream_json()
+        .and_then(|stream| track_for_stream(&mp4_tracks, stream))
+        .cloned();
+    if let Some(track) = video_track {
+        metadata.video_stream.apply_mp4_track(&track);
+    }
+    metadata
+        .video_stream
+        .complete_av1_info(&runner, path)
+        .instrument(info_span!("av1"))
+        .await;
+    let start_offsets = analyze_start_offsets(&metadata.probe_json, &mp4_tracks);
+    timings.stream_details_ms = phase_start.elapsed().as_millis() as u64;
+
+    let (resolution, frame_rate) = if metadata.layout.has_video() {
+        (
+            format!("{}x{}", metadata.width, metadata.height),
+            format!("{:.2}", metadata.frame_rate),
+        )
+    } else {
+        ("No video stream".to_string(), "No video stream".to_string())
+    };
+    let duration = format!("{:.2}s", metadata.duration);
+    let bit_rate = format!("{:.2} kbps", metadata.bit_rate / 1024.0);
+    emit_partial_result(
+        job_id,
+        path,
+        PartialResult::Probe {
+            resolution: resolution.clone(),
+            frame_rate: frame_rate.clone(),
+            duration: duration.clone(),
+            bit_rate: bit_rate.clone(),
+        },
+    );
 
     // Calculate file size and hash
-    let file_size = get_file_size(path)?;
-    let file_hash = calculate_file_hash(path)?;
+    let file_size_bytes = fs::metadata(path)?.len();
+    let file_size = format_size(file_size_bytes);
+    let file_hash = match pipeline.hash {
+        Some(hash_kind) => {
+            stages.enter(InspectionStage::Hash);
+            let phase_start = Instant::now();
+            let hash_stages = stages.clone();
+            let file_hash = hash_file(path, job_id, hash_kind, move |fraction| {
+                hash_stages.advance(fraction, None)
+            })
+            .instrument(info_span!("hash", ?hash_kind))
+            .await?;
+            emit_partial_result(
+                job_id,
+                path,
+                PartialResult::Hash {
+                    file_size: file_size.clone(),
+                    file_hash: file_hash.clone(),
+                    hash_kind,
+                },
+            );
+            let elapsed = phase_start.elapsed();
+            timings.hash_ms = Some(elapsed.as_millis() as u64);
+            // Quick hashes only read samples of the file, so a rate means nothing
+            if hash_kind == HashKind::Sha256 && elapsed.as_secs_f64() > 0.0 {
+                let bytes = fs::metadata(path).map_or(0, |file| file.len());
+                timings.hash_mb_per_sec = Some(bytes as f64 / 1_000_000.0 / elapsed.as_secs_f64());
+            }
+            Some(file_hash)
+        }
+        None => None,
+    };
+
+    // Audio-only files have nothing to take thumbnails of
+    let thumbnails = if pipeline.thumbnails && metadata.layout.has_video() {
+        stages.enter(InspectionStage::Thumbnails);
+        let phase_start = Instant::now();
+        let thumbnails =
+            thumbnails(app_handle, path, &metadata, scene_detection, job_id, stages).await?;
+        timings.thumbnails_ms = Some(phase_start.elapsed().as_millis() as u64);
+        timings.thumbnail_ms = thumbnails.iter().map(|t| t.elapsed_ms).collect();
+        thumbnails
+    } else {
+        Vec::new()
+    };
+    if pipeline.qc || pipeline.loudness || pipeline.integrity_scan {
+        stages.enter(InspectionStage::DeepChecks);
+    }
+    let phase_start = Instant::now();
+    let deep_results = deep_checks(path, &metadata, pipeline, job_id).await;
+    timings.deep_checks_ms = phase_start.elapsed().as_millis() as u64;
 
-    // Generate 4 thumbnails
-    let thumbnails_base64 = generate_thumbnails_with_ffmpeg(app_handle, path, &metadata).await?;
+    let computed_fields = evaluate_computed_fields(&metadata.probe_json);
+    let sidecars = check_sidecars(path, &metadata);
+    let external_subtitles = find_external_subtitles(path, metadata.duration, metadata.frame_rate);
+    let subtitle_streams = parse_subtitle_streams(&metadata.probe_json);
+    let efficiency = bit_rate_efficiency(&EncodedVideo::from_info(&metadata));
+    timings.total_ms = start.elapsed().as_millis() as u64;
 
     Ok(VideoMetadata {
+        job_id: job_id.to_string(),
         file_path: path.to_string(),
-        resolution: format!("{}x{}", metadata.width, metadata.height),
-        frame_rate: format!("{:.2}", metadata.frame_rate),
-        duration: format!("{:.2}s", metadata.duration),
-        bit_rate: format!("{:.2} kbps", metadata.bit_rate / 1024.0),
+        resolution,
+        frame_rate,
+        duration,
+        duration_source: metadata.duration_source,
+        bit_rate,
+        video_bit_rate: metadata
+            .video_bit_rate
+            .map(|rate| format!("{:.2} kbps", rate / 1024.0)),
+        video_bit_rate_measured: metadata.video_bit_rate_measured,
+        video_stream: metadata.video_stream,
         file_size,
+        width: metadata.width,
+        height: metadata.height,
+        fps: metadata.frame_rate_fraction,
+        duration_secs: metadata.duration,
+        bit_rate_bps: metadata.bit_rate,
+        video_bit_rate_bps: metadata.video_bit_rate,
+        file_size_bytes,
         file_hash,
-        thumbnails_base64,
+        hash_kind: pipeline.hash,
+        thumbnails_base64: thumbnails.iter().map(|t| t.data_url.clone()).collect(),
+        thumbnail_timestamps: thumbnails.iter().map(|t| t.timestamp).collect(),
+        thumbnail_hints: thumbnails.iter().map(|t| t.hints).collect(),
+        has_stereo_downmix: has_stereo_downmix(&metadata.audio_streams),
+        audio_streams: metadata.audio_streams,
+        stream_layout: metadata.layout,
+        subtitle_streams,
+        container,
+        start_offsets,
+        computed_fields,
+        preset,
+        qc_issues: deep_results.qc_issues,
+        loudness: deep_results.loudness,
+        integrity: deep_results.integrity,
+        sidecars,
+        external_subtitles,
+        efficiency,
+        programs: metadata.programs,
+        program: metadata.program,
+        timings,
+        from_cache: false,
     })
 }
 
-#[derive(Debug)]
-struct VideoInfo {
-    width: u32,
-    height: u32,
-    duration: f64,
-    frame_rate: f64,
-    bit_rate: f64,
+/// Results of the optional QC, loudness and integrity steps
+struct DeepResults {
+    qc_issues: Option<Vec<String>>,
+    loudness: Option<LoudnessReport>,
+    integrity: Option<IntegrityReport>,
 }
 
-/// Get video information using ffprobe sidecar
-async fn get_video_info_with_ffprobe(
-    app_handle: &tauri::AppHandle,
+/// Run the QC, loudness and integrity steps the pipeline asks for
+///
+/// These are extras on top of the metadata, so failures are logged and leave
+/// the field empty instead of failing the inspection.
+async fn deep_checks(
     path: &str,
-) -> Result<VideoInfo, Error> {
-    tracing::debug!(video_path = %path, "Getting video info with ffprobe");
+    metadata: &VideoInfo,
+    pipeline: Pipeline,
+    job_id: &str,
+) -> DeepResults {
+    let qc_issues = pipeline.qc.then(|| qc_issues(metadata));
+
+    let loudness = if pipeline.loudness && !metadata.audio_streams.is_empty() {
+        match measure_loudness_async(path, 0)
+            .instrument(info_span!("loudness"))
+            .await
+        {
+            Ok(report) => Some(report),
+            Err(e) => {
+                tracing::warn!(video_path = %path, error = %e, "Failed to measure loudness");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    let shell = app_handle.shell();
+    let integrity = if pipeline.integrity_scan {
+        match scan_integrity_async(path, job_id.to_string())
+            .instrument(info_span!("integrity_scan"))
+            .await
+        {
+            Ok(report) => Some(report),
+            Err(e) => {
+                tracing::warn!(video_path = %path, error = %e, "Integrity scan failed");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    let start = Instant::now();
-    // Use ffprobe to get video metadata in JSON format
-    let output = shell
-        .sidecar("ffprobe")?
-        .args([
-            "-v",
-            "quiet",
-            "-print_format",
-            "json",
-            "-show_format",
-            "-show_streams",
-            "-select_streams",
-            "v:0",
-            path,
-        ])
-        .output()
-        .await
-        .map_err(|e| Error::FFmpegError(format!("Failed to execute ffprobe: {}", e)))?;
-
-    let elapsed = start.elapsed();
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(Error::FFmpegError(format!("ffprobe failed: {}", stderr)));
+    DeepResults {
+        qc_issues,
+        loudness,
+        integrity,
     }
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    tracing::debug!(
-        video_path = %path,
-        ffprobe_output = %stdout,
-        elapsed = ?elapsed,
-        "FFprobe JSON output"
-    );
-
-    // Parse the JSON output
-    let json: serde_json::Value = serde_json::from_str(&stdout)
-        .map_err(|e| Error::ParseError(format!("Failed to parse ffprobe JSON: {}", e)))?;
-
-    // Extract video stream information
-    let streams = json["streams"]
-        .as_array()
-        .ok_or_else(|| Error::ParseError("No streams found in ffprobe output".to_string()))?;
-
-    let video_stream = streams
-        .iter()
-        .find(|stream| stream["codec_type"].as_str() == Some("video"))
-        .ok_or_else(|| Error::ParseError("No video stream found".to_string()))?;
-
-    // Extract metadata
-    let width = video_stream["width"]
-        .as_u64()
-        .ok_or_else(|| Error::ParseError("Width not found".to_string()))? as u32;
-
-    let height = video_stream["height"]
-        .as_u64()
-        .ok_or_else(|| Error::ParseError("Height not found".to_string()))? as u32;
-
-    // Parse frame rate (can be a fraction like "30/1")
-    let frame_rate_str = video_stream["r_frame_rate"]
-        .as_str()
-        .ok_or_else(|| Error::ParseError("Frame rate not found".to_string()))?;
-    let frame_rate = parse_fraction(frame_rate_str)?;
-
-    // Parse duration from format section
-    let format = &json["format"];
-    let duration_str = format["duration"]
-        .as_str()
-        .ok_or_else(|| Error::ParseError("Duration not found".to_string()))?;
-    let duration: f64 = duration_str
-        .parse()
-        .map_err(|_| Error::ParseError("Invalid duration format".to_string()))?;
-
-    // Parse bit rate
-    let bit_rate_str = format["bit_rate"].as_str().unwrap_or("0");
-    let bit_rate: f64 = bit_rate_str.parse().unwrap_or(0.0);
-
-    tracing::debug!(
-        video_path = %path,
-        width = width,
-        height = height,
-        duration = duration,
-        frame_rate = frame_rate,
-        bit_rate = bit_rate,
-        "Successfully extracted video metadata"
-    );
-
-    Ok(VideoInfo {
-        width,
-        height,
-        duration,
-        frame_rate,
-        bit_rate,
-    })
 }
 
-/// Generate 4 thumbnails using ffmpeg sidecar
-async fn generate_thumbnails_with_ffmpeg(
+/// Generate thumbnails, streaming each one as it finishes
+async fn thumbnails(
     app_handle: &tauri::AppHandle,
     path: &str,
-    video_info: &VideoInfo,
-) -> Result<Vec<String>, Error> {
-    tracing::debug!(video_path = %path, "Generating 4 thumbnails with ffmpeg");
-
-    let temp_dir = std::env::temp_dir();
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-
-    // Ensure temp directory exists
-    std::fs::create_dir_all(&temp_dir)?;
-
-    // let shell = app_handle.shell();
-    let thumbnails_base64 = Arc::new(Mutex::new(Vec::new()));
-
-    // Calculate 4 time points evenly distributed across the video duration
-    let duration = video_info.duration;
-    let time_points = [
-        duration * 0.1, // 10% into the video
-        duration * 0.3, // 30% into the video
-        duration * 0.6, // 60% into the video
-        duration * 0.9, // 90% into the video
-    ];
-
-    let start = Instant::now();
-
-    // tauri::async_runtime::spawn(task)
-    let mut tasks = vec![];
-
-    for (i, &time_point) in time_points.iter().enumerate() {
-        let app_handle = app_handle.clone();
-        let path = path.to_string();
-        let temp_dir = temp_dir.clone();
-        let thumbnails_base64 = thumbnails_base64.clone();
-        tasks.push(tauri::async_runtime::spawn(async move {
-            let temp_image_path = temp_dir.join(format!("thumbnail_{}_{}.png", timestamp, i));
-
-            // Generate thumbnail at specific time point - optimized for speed
-            let temp_image_path_string = temp_image_path.to_str().unwrap().to_string();
-
-            let output = tauri::async_runtime::spawn_blocking(move || -> Result<_, Error> {
-                Ok(app_handle
-                    .shell()
-                    .sidecar("ffmpeg")
-                    .map_err(|e| Error::FFmpegError(format!("Failed to execute ffmpeg: {}", e)))?
-                    .args([
-                        "-ss",
-                        &format!("{:.2}", time_point),
-                        "-i",
-                        &path,
-                        "-vframes",
-                        "1",
-                        "-vf",
-                        "scale=480:270:force_original_aspect_ratio=decrease", // Smaller size for thumbnails
-                        "-q:v",
-                        "2",
-                        "-f",
-                        "image2",
-                        "-y",
-                        &temp_image_path_string,
-                    ])
-                    .output())
-            })
+    metadata: &VideoInfo,
+    scene_detection: bool,
+    job_id: &str,
+    stages: &StageTracker,
+) -> Result<Vec<Thumbnail>, Error> {
+    // Pick thumbnail positions, preferring scene boundaries when requested
+    let scene_time_points = if scene_detection {
+        match detect_scenes(&SidecarRunner::new(app_handle), path)
+            .instrument(info_span!("scene_detection"))
             .await
-            .map_err(|e| Error::FFmpegError(format!("Failed to execute ffmpeg: {}", e)))??
-            .await?;
-
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let _ = fs::remove_file(&temp_image_path);
-                return Err(Error::FFmpegError(format!(
-                    "ffmpeg thumbnail generation failed at time {:.2}s: {}",
-                    time_point, stderr
-                )));
+        {
+            Ok(scene_changes) => {
+                representative_time_points(&scene_changes, metadata.duration, THUMBNAIL_COUNT)
             }
-
-            // Read the generated image file and convert to base64
-            let image_data = fs::read(&temp_image_path)?;
-            let thumbnail_base64 = general_purpose::STANDARD.encode(&image_data);
-            {
-                let mut thumbnails_base64 = thumbnails_base64.lock().unwrap();
-                thumbnails_base64.push(format!("data:image/png;base64,{}", thumbnail_base64));
+            Err(e) => {
+                tracing::warn!(
+                    video_path = %path,
+                    error = %e,
+                    "Scene detection failed, using fixed thumbnail positions"
+                );
+                None
             }
-
-            // Clean up temporary file
-            let _ = fs::remove_file(&temp_image_path);
-
-            Ok(())
-        }));
-    }
-    for task in tasks {
-        let _ = task.await;
-    }
-
-    let elapsed = start.elapsed();
-
-    let thumbnails_base64 = thumbnails_base64.lock().unwrap().clone();
-
-    tracing::debug!(
-        video_path = %path,
-        thumbnails_count = thumbnails_base64.len(),
-        "Successfully generated thumbnails in {:?}",
-        elapsed
-    );
-
-    Ok(thumbnails_base64)
+        }
+    } else {
+        None
+    };
+    let time_points = scene_time_points.unwrap_or_else(|| default_time_points(metadata.duration));
+
+    let (event_job_id, event_path) = (job_id.to_string(), path.to_string());
+    let (stages, finished) = (stages.clone(), AtomicUsize::new(0));
+    let count = time_points.len();
+    generate_thumbnails_with_ffmpeg(
+        app_handle,
+        path,
+        metadata,
+        &time_points,
+        move |index, thumbnail| {
+            // Thumbnails finish out of order, so count rather than use the index
+            let finished = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            stages.advance(
+                finished as f64 / count as f64,
+                Some(format!("Thumbnail {} of {}", finished, count)),
+            );
+            emit_partial_result(
+                &event_job_id,
+                &event_path,
+                PartialResult::Thumbnail {
+                    index,
+                    timestamp: thumbnail.timestamp,
+                    data_url: thumbnail.data_url.clone(),
+                    hints: thumbnail.hints,
+                },
+            )
+        },
+    )
+    .instrument(info_span!("thumbnails", count = time_points.len()))
+    .await
 }
 
-/// Parse a fraction string like "30/1" to a float
-fn parse_fraction(fraction_str: &str) -> Result<f64, Error> {
-    let parts: Vec<&str> = fraction_str.split('/').collect();
-    if parts.len() != 2 {
-        return Err(Error::ParseError(format!(
-            "Invalid fraction format: {}",
-            fraction_str
-        )));
-    }
-
-    let numerator: f64 = parts[0]
-        .parse()
-        .map_err(|_| Error::ParseError(format!("Invalid numerator: {}", parts[0])))?;
-    let denominator: f64 = parts[1]
-        .parse()
-        .map_err(|_| Error::ParseError(format!("Invalid denominator: {}", parts[1])))?;
-
-    if denominator == 0.0 {
-        return Err(Error::ParseError(
-            "Division by zero in fraction".to_string(),
-        ));
-    }
+/// Run ffprobe with JSON output and parse the result
+///
+/// `args` are inserted before the input path; `-v quiet -print_format json`
+/// are always added.
+pub async fn run_ffprobe_json(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    args: &[&str],
+) -> Result<serde_json::Value, Error> {
+    Ok(probe::run_ffprobe_json(&SidecarRunner::new(app_handle), path, args).await?)
+}
 
-    Ok(numerator / denominator)
+/// Get video information using ffprobe sidecar
+pub async fn get_video_info_with_ffprobe(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+) -> Result<VideoInfo, Error> {
+    let options = ProbeOptions {
+        trace_output: settings::current().trace_ffprobe_output,
+        ..ProbeOptions::default()
+    };
+    Ok(probe_video_info(&SidecarRunner::new(app_handle), path, &options).await?)
 }
 
-/// Get file size in human readable format
-fn get_file_size(path: &str) -> Result<String, Error> {
-    let metadata = fs::metadata(path)?;
-    let size_bytes = metadata.len();
+/// Hash a file on a blocking thread, emitting `inspection://hash-progress`
+/// events tagged with `job_id`
+///
+/// The file is read in chunks, so memory use stays flat whatever its size,
+/// and a full hash stops at the next chunk when the job is cancelled.
+/// `on_fraction` gets the share of the file hashed so far, 0 to 1.
+pub async fn hash_file(
+    path: &str,
+    job_id: &str,
+    hash_kind: HashKind,
+    mut on_fraction: impl FnMut(f64) + Send + 'static,
+) -> Result<String, Error> {
+    let cancelled = cancel_flag(job_id);
+    let (path, job_id) = (path.to_string(), job_id.to_string());
+    let span = tracing::Span::current();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _entered = span.enter();
+        match hash_kind {
+            HashKind::Sha256 => calculate_file_hash(&path, &cancelled, |progress| {
+                if progress.total_bytes > 0 {
+                    on_fraction(progress.bytes_processed as f64 / progress.total_bytes as f64);
+                }
+                emit_hash_progress(&job_id, &path, progress)
+            }),
+            HashKind::Quick => calculate_quick_hash(&path),
+        }
+    })
+    .await
+    .map_err(|e| Error::IoError(io::Error::other(e.to_string())))?
+    .map_err(Error::from)
+}
 
+/// Format a byte count as B, KB, MB or GB
+pub fn format_size(size_bytes: u64) -> String {
     if size_bytes < 1024 {
-        Ok(format!("{} B", size_bytes))
+        format!("{} B", size_bytes)
     } else if size_bytes < 1024 * 1024 {
-        Ok(format!("{:.2} KB", size_bytes as f64 / 1024.0))
+        format!("{:.2} KB", size_bytes as f64 / 1024.0)
     } else if size_bytes < 1024 * 1024 * 1024 {
-        Ok(format!("{:.2} MB", size_bytes as f64 / (1024.0 * 1024.0)))
+        format!("{:.2} MB", size_bytes as f64 / (1024.0 * 1024.0))
     } else {
-        Ok(format!(
-            "{:.2} GB",
-            size_bytes as f64 / (1024.0 * 1024.0 * 1024.0)
-        ))
+        format!("{:.2} GB", size_bytes as f64 / (1024.0 * 1024.0 * 1024.0))
     }
 }
-
-/// Calculate SHA256 hash of the file
-fn calculate_file_hash(path: &str) -> Result<String, Error> {
-    let file_data = fs::read(path)?;
-    let mut hasher = Sha256::new();
-    hasher.update(&file_data);
-    let result = hasher.finalize();
-    Ok(format!("{:x}", result))
-}
@@ -0,0 +1,88 @@
+use std::time::Instant;
+
+use crate::events::emit_ffmpeg_progress;
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::job::new_job_id;
+use crate::progress::run_ffmpeg_with_progress;
+
+/// Maximum number of decoder error lines kept in the report
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/// Result of decoding the whole file looking for corruption
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct IntegrityReport {
+    job_id: String,
+    /// Total number of errors ffmpeg reported while decoding
+    error_count: usize,
+    /// The first errors, as printed by ffmpeg
+    errors: Vec<String>,
+    clean: bool,
+}
+
+/// Decode every stream of a file and report decoding errors
+///
+/// Progress is emitted as `inspection://ffmpeg-progress` events tied to
+/// `job_id`, since a full decode can take as long as the video itself.
+#[tauri::command]
+pub async fn scan_integrity(
+    path: String,
+    job_id: Option<String>,
+) -> Result<IntegrityReport, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    scan_integrity_async(&path, job_id).await.map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Integrity scan failed");
+        e.localized()
+    })
+}
+
+pub async fn scan_integrity_async(path: &str, job_id: String) -> Result<IntegrityReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let json = run_ffprobe_json(app_handle, path, &["-show_format"]).await?;
+    let duration = json["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse::<f64>().ok());
+
+    let start = Instant::now();
+    let args: Vec<String> = ["-v", "error", "-i", path, "-f", "null", "-"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let run = run_ffmpeg_with_progress(app_handle, &args, duration, |progress| {
+        emit_ffmpeg_progress(&job_id, path, "integrity_scan", progress)
+    })
+    .await?;
+
+    let errors: Vec<String> = run
+        .stderr
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .cloned()
+        .collect();
+
+    // A non-zero exit without any message still means the file is unreadable
+    if errors.is_empty() && !run.success() {
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg exited with {:?} while decoding",
+            run.exit_code
+        )));
+    }
+
+    tracing::debug!(
+        video_path = %path,
+        job_id = %job_id,
+        error_count = errors.len(),
+        elapsed = ?start.elapsed(),
+        "Integrity scan finished"
+    );
+
+    let error_count = errors.len();
+    Ok(IntegrityReport {
+        job_id,
+        error_count,
+        errors: errors.into_iter().take(MAX_REPORTED_ERRORS).collect(),
+        clean: error_count == 0,
+    })
+}
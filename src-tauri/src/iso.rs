@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use crate::disc::{probe_duration, TitleStreams, DISC_PROBE_ARGS};
+use crate::dvd::{inspect_title_sets, DvdReport, VobFile};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::job::new_job_id;
+use video_inspector_core::iso9660::{self, ImageInfo};
+use video_inspector_core::Error as CoreError;
+
+/// What a disc image turned out to hold
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IsoContent {
+    Dvd(DvdReport),
+    /// Main title as picked by libbluray
+    Bluray {
+        duration: Option<f64>,
+        streams: TitleStreams,
+    },
+}
+
+/// Disc image with its file systems and contents
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct IsoReport {
+    image: ImageInfo,
+    content: IsoContent,
+}
+
+/// Inspect a DVD or Blu-ray `.iso` image without mounting it
+///
+/// DVD titles are read straight from the ISO 9660 file system; Blu-ray images
+/// go through ffmpeg's `bluray:` protocol, which needs libbluray.
+#[tauri::command]
+pub async fn inspect_iso(path: String, job_id: Option<String>) -> Result<IsoReport, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    inspect_iso_async(&path, job_id).await.map_err(|e| {
+        tracing::error!(file = %path, error = %e, "Disc image inspection failed");
+        e.localized()
+    })
+}
+
+async fn inspect_iso_async(path: &str, job_id: String) -> Result<IsoReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let image = iso9660::detect(Path::new(path))?;
+    if !image.iso9660 && !image.udf {
+        return Err(CoreError::NotMediaFile(format!("{} is not a disc image", path)).into());
+    }
+
+    let vob_files = if image.iso9660 {
+        dvd_vob_files(path)
+    } else {
+        Vec::new()
+    };
+    let content = if !vob_files.is_empty() {
+        let video_ts = format!("{}/VIDEO_TS", path);
+        IsoContent::Dvd(inspect_title_sets(app_handle, path, video_ts, vob_files, job_id).await?)
+    } else {
+        let probe = run_ffprobe_json(app_handle, &format!("bluray:{}", path), &DISC_PROBE_ARGS)
+            .await
+            .map_err(|e| {
+                Error::FFmpegError(format!(
+                    "Failed to open the image as a Blu-ray (ffmpeg needs libbluray): {}",
+                    e
+                ))
+            })?;
+        IsoContent::Bluray {
+            duration: probe_duration(&probe),
+            streams: TitleStreams::from_probe(&probe),
+        }
+    };
+
+    tracing::debug!(file = %path, udf = image.udf, "Inspected disc image");
+    Ok(IsoReport { image, content })
+}
+
+/// Files of the image's `VIDEO_TS` folder, as ffmpeg `subfile:` inputs
+///
+/// Empty when the image has no such folder, e.g. a Blu-ray with an ISO 9660
+/// bridge.
+fn dvd_vob_files(path: &str) -> Vec<VobFile> {
+    let entries = match iso9660::list_dir(Path::new(path), &["VIDEO_TS"]) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::debug!(file = %path, error = %e, "No DVD structure in image");
+            return Vec::new();
+        }
+    };
+    entries
+        .into_iter()
+        .filter(|entry| !entry.is_dir && entry.size > 0)
+        .map(|entry| VobFile {
+            name: format!("VIDEO_TS/{}", entry.name),
+            // `end` is exclusive; ffmpeg reads the byte range as its own file
+            input: format!(
+                "subfile,,start,{},end,{},,:{}",
+                entry.offset,
+                entry.offset + entry.size,
+                path
+            ),
+            size: entry.size,
+        })
+        .collect()
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    task::{Context, Poll},
+};
+use tauri::async_runtime::JoinHandle;
+use tokio::sync::oneshot;
+
+use crate::inspector::Error;
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The jobs that can be cancelled, by job ID
+static RUNNING_JOBS: OnceLock<Mutex<HashMap<String, RunningJob>>> = OnceLock::new();
+
+struct RunningJob {
+    task: JoinHandle<()>,
+    /// Set on cancel, for blocking work that aborting the task can't stop
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Generate an identifier for a new inspection job
+///
+/// IDs are unique for the lifetime of the process, which is all the frontend
+/// needs to correlate events with the call that started them.
+pub fn new_job_id() -> String {
+    format!("job-{}", NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Stop a running inspection started with `job_id`
+///
+/// Its task is aborted, which kills the ffmpeg and ffprobe processes it
+/// waits on, stops the thumbnail tasks it spawned and removes their temp
+/// files; a hash being computed stops at its next chunk. The inspection
+/// itself fails with a cancellation error. Returns whether a running job
+/// was found.
+#[tauri::command]
+pub async fn cancel_inspection(job_id: String) -> Result<bool, String> {
+    let cancelled = cancel_job(&job_id);
+    tracing::info!(job_id = %job_id, cancelled, "Cancel requested");
+    Ok(cancelled)
+}
+
+/// Run `future` as its own task that `cancel_job` can abort
+///
+/// Aborting drops the future wherever it is waiting, which kills the sidecar
+/// processes it started and deletes its temp paths. Tasks it spawns only stop
+/// with it when held in an `AbortOnDrop`, and blocking work has to check
+/// `cancel_flag`. Fails without running `future` when another job is running
+/// as `job_id`, since a cancel could then only reach one of them.
+pub async fn run_cancellable<T: Send + 'static>(
+    job_id: &str,
+    future: impl Future<Output = Result<T, Error>> + Send + 'static,
+) -> Result<T, Error> {
+    let (sender, receiver) = oneshot::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        // Registered before the task can ask for its cancel flag
+        let mut jobs = running_jobs();
+        if jobs.contains_key(job_id) {
+            return Err(Error::JobInUse(job_id.to_string()));
+        }
+        let task = tauri::async_runtime::spawn(async move {
+            // The receiver is only gone when the caller was dropped as well
+            let _ = sender.send(future.await);
+        });
+        let job = RunningJob {
+            task,
+            cancelled: cancelled.clone(),
+        };
+        jobs.insert(job_id.to_string(), job);
+    }
+
+    let result = receiver.await;
+    {
+        // Once cancelled, the ID may already belong to a new job
+        let mut jobs = running_jobs();
+        if jobs
+            .get(job_id)
+            .is_some_and(|job| Arc::ptr_eq(&job.cancelled, &cancelled))
+        {
+            jobs.remove(job_id);
+        }
+    }
+    // The sender is only dropped unused when the task was aborted or panicked
+    result.unwrap_or(Err(Error::Cancelled))
+}
+
+/// Flag set when the job `job_id` is cancelled
+///
+/// Blocking work checks it between steps, since aborting the job's task
+/// doesn't reach a blocking thread. Jobs that can't be cancelled get a flag
+/// that is never set.
+pub fn cancel_flag(job_id: &str) -> Arc<AtomicBool> {
+    running_jobs()
+        .get(job_id)
+        .map(|job| job.cancelled.clone())
+        .unwrap_or_default()
+}
+
+/// Abort the task of a running job; false when there is none
+fn cancel_job(job_id: &str) -> bool {
+    match running_jobs().remove(job_id) {
+        Some(job) => {
+            job.cancelled.store(true, Ordering::Relaxed);
+            job.task.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+/// A task spawned by a job, aborted when dropped
+///
+/// Spawned tasks outlive the job that spawned them, so a cancelled job would
+/// otherwise leave them running and emitting events. Awaiting it awaits the
+/// task.
+pub struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> AbortOnDrop<T> {
+    pub fn new(task: JoinHandle<T>) -> Self {
+        AbortOnDrop(task)
+    }
+}
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = tauri::Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        // Does nothing when the task already finished
+        self.0.abort();
+    }
+}
+
+fn running_jobs() -> std::sync::MutexGuard<'static, HashMap<String, RunningJob>> {
+    RUNNING_JOBS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn cancelled_job_emits_nothing_more() {
+        let job_id = new_job_id();
+        let events = Arc::new(AtomicU64::new(0));
+
+        let (job_events, flag_job_id) = (events.clone(), job_id.clone());
+        let job = async move {
+            // A spawned task reporting progress, as thumbnails do
+            let task_events = job_events.clone();
+            let _task = AbortOnDrop::new(tauri::async_runtime::spawn(async move {
+                loop {
+                    task_events.fetch_add(1, Ordering::Relaxed);
+                    tokio::time::sleep(Duration::from_millis(2)).await;
+                }
+            }));
+            // Blocking work reporting progress, as hashing does
+            let cancelled = cancel_flag(&flag_job_id);
+            let _ = tauri::async_runtime::spawn_blocking(move || {
+                while !cancelled.load(Ordering::Relaxed) {
+                    job_events.fetch_add(1, Ordering::Relaxed);
+                    std::thread::sleep(Duration::from_millis(2));
+                }
+            })
+            .await;
+            Ok(())
+        };
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            cancel_job(&job_id)
+        };
+        let (result, found) = tokio::join!(run_cancellable(&job_id, job), cancel);
+
+        assert!(found);
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(events.load(Ordering::Relaxed) > 0);
+        // Let the blocking thread finish the step it was in
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let emitted = events.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(events.load(Ordering::Relaxed), emitted);
+        assert!(!cancel_job(&job_id));
+    }
+
+    #[tokio::test]
+    async fn rejects_a_job_id_in_use() {
+        let job_id = new_job_id();
+        let first = run_cancellable(&job_id, std::future::pending::<Result<(), Error>>());
+        let second = async {
+            // Let the first job register
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let result = run_cancellable(&job_id, async { Ok(()) }).await;
+            (result, cancel_job(&job_id))
+        };
+        let (first, (second, found)) = tokio::join!(first, second);
+
+        assert!(matches!(second, Err(Error::JobInUse(id)) if id == job_id));
+        // The first job was still registered, so the cancel reached it
+        assert!(found);
+        assert!(matches!(first, Err(Error::Cancelled)));
+        assert!(!cancel_job(&job_id));
+    }
+}
@@ -1,10 +1,74 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+mod analyzer;
+mod api;
+mod archive;
+mod batch;
+mod benchmark;
+mod bluray;
+mod cache;
+mod camera_card;
+mod chapters;
+mod clip;
+mod compatibility;
+mod concat;
+mod disc;
+mod disk;
+mod dvd;
+mod error_reporting;
+mod events;
+mod export;
+mod frames;
+mod glitch;
+mod hooks;
 mod inspector;
+mod integrity;
+mod iso;
+mod job;
+mod locale;
 mod logging;
+mod loudness;
+mod metadata_cache;
+mod motion_photo;
+mod multipart;
+mod nfo;
+mod overlay;
+mod planner;
+mod poster;
+mod preset;
+mod preview;
+mod privacy;
+mod progress;
+mod quality;
+mod quarantine;
+mod rename;
+mod report;
+mod runner;
+mod savings;
+mod scan_rules;
+mod scripting;
+mod settings;
+mod sidecar;
+mod split;
+mod stability;
+mod stages;
+mod stdio;
+mod subtitle;
+mod telemetry;
+mod temp;
+mod thumbnail;
+mod transcode;
+mod transport_stream;
+mod whisper;
 
 use std::sync::OnceLock;
 use tauri::AppHandle;
 
+// Tauri-free parts of the inspection live in the core crate
+use video_inspector_core::{
+    audio, bitrate, codec, container, duration, frame_content, frame_stats, hash, layout, mp4,
+    offsets, probe, programs, scene, sniff, stereo3d, tags, video,
+};
+
 // Global static APP_HANDLE
 static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
 
@@ -47,12 +111,79 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![inspector::get_video_metadata])
+        .invoke_handler(tauri::generate_handler![
+            analyzer::list_analyzers,
+            analyzer::run_analyzers,
+            archive::extract_archive_entry,
+            archive::list_archive,
+            batch::inspect_videos_batch,
+            benchmark::benchmark_decode,
+            bluray::inspect_bluray,
+            cache::clear_cache,
+            cache::get_cache_stats,
+            camera_card::inspect_camera_card,
+            chapters::chapter_index,
+            clip::extract_clip,
+            compatibility::check_compatibility,
+            concat::check_concat,
+            dvd::inspect_dvd,
+            export::export_inspection,
+            frames::analyze_frame_types,
+            frames::frame_size_timeline,
+            inspector::get_video_metadata,
+            integrity::scan_integrity,
+            iso::inspect_iso,
+            job::cancel_inspection,
+            locale::set_locale,
+            loudness::measure_loudness,
+            metadata_cache::clear_metadata_cache,
+            motion_photo::inspect_motion_photo,
+            multipart::inspect_multipart,
+            nfo::write_nfo,
+            poster::pick_poster_frame,
+            poster::save_poster_frame,
+            poster::embed_poster_frame,
+            preview::preview_transcode,
+            rename::rename_files,
+            report::report_folder,
+            settings::get_settings,
+            settings::update_settings,
+            split::split_file,
+            subtitle::preview_subtitles,
+            transcode::suggest_transcode,
+            transcode::run_transcode,
+            transport_stream::inspect_transport_stream
+        ])
         .setup(|app| {
             // Initialize the global APP_HANDLE
             init_app_handle(app.handle().clone());
+
+            // Settings can be read now; redact logs from here on if asked to
+            privacy::apply_settings();
+
+            // Opt-in crash and error reports
+            error_reporting::apply_settings();
+
+            analyzer::register_builtin_analyzers();
+
+            // Clean up frames left behind by runs that crashed mid-extraction
+            tauri::async_runtime::spawn_blocking(temp::sweep_stale_temp_files);
+
+            // Serve the automation API when the user turned it on
+            api::apply_settings();
+
+            // Headless mode for tools embedding the app as a backend
+            if stdio::requested() {
+                stdio::start(app.handle());
+            }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Send the last spans before the process goes away
+                telemetry::shutdown();
+            }
+        });
 }
@@ -0,0 +1,14 @@
+use video_inspector_core::locale::select_locale;
+
+pub use video_inspector_core::locale::{current_locale, tr};
+
+/// Select the language of user-facing messages returned by commands
+///
+/// Accepts language tags like "zh-CN"; unsupported languages fall back to
+/// English.
+#[tauri::command]
+pub async fn set_locale(locale: String) -> Result<(), String> {
+    let selected = select_locale(&locale);
+    tracing::debug!(requested = %locale, selected, "Locale changed");
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use tracing_subscriber::{
-    fmt::{self, time::LocalTime},
+    fmt::{self, format::JsonFields, time::LocalTime, writer::BoxMakeWriter},
     layer::SubscriberExt,
     util::SubscriberInitExt,
     EnvFilter, Layer,
@@ -8,13 +8,17 @@ use tracing_appender::{non_blocking, rolling};
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
+use crate::privacy::{RedactingFields, RedactingJson};
+use crate::settings::{self, FileLogFormat};
+
 // Global guard to keep the non-blocking writer alive
 static _GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
 
 /// Initialize the logging system for the video inspector application
 ///
 /// This sets up both console and file logging with appropriate formatting and filtering.
-/// File logs are stored in the application data directory with daily rotation.
+/// File logs are stored in the application data directory with daily rotation,
+/// as text or JSON lines depending on the `file_log` setting.
 pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     // Create time formatter for logs
     let timer = LocalTime::new(time::format_description::parse(
@@ -26,8 +30,8 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     let console_env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info,video_inspector=debug"));
 
-    // File: More detailed logging (DEBUG level) - commented out
-    let _file_env_filter = EnvFilter::try_from_default_env()
+    // File: More detailed logging (DEBUG level)
+    let file_env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("debug,video_inspector=debug"));
 
     // Get application data directory for log files
@@ -36,45 +40,91 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     // Ensure log directory exists
     std::fs::create_dir_all(&log_dir)?;
 
-    // Create file appender with daily rotation (commented out for now)
+    // Create file appender with daily rotation
     let file_appender = rolling::daily(&log_dir, "video-inspector.log");
-    let (_non_blocking_appender, guard) = non_blocking(file_appender);
+    let (non_blocking_appender, guard) = non_blocking(file_appender);
 
     // Store the guard globally to keep the non-blocking writer alive
     if _GUARD.set(guard).is_err() {
         tracing::warn!("Failed to set global guard - logging may not work properly");
     }
 
+    // In JSON-RPC stdio mode stdout carries the protocol, so log to stderr
+    let serve_stdio = crate::stdio::requested();
+    let console_writer = if serve_stdio {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     // Create console logging layer
     let console_layer = fmt::layer()
+        .fmt_fields(RedactingFields) // Anonymizes paths in privacy mode
+        .with_writer(console_writer)
         .with_timer(timer.clone())
         .with_target(false) // Less verbose for console
         .with_thread_ids(false)
         .with_thread_names(false)
         .with_file(false)
         .with_line_number(false)
-        .with_ansi(true) // ANSI colors for console
+        .with_ansi(!serve_stdio) // ANSI colors for console
         .with_filter(console_env_filter);
 
-    // Create file logging layer (commented out to disable file logging)
-    // let file_layer = fmt::layer()
-    //     .with_timer(timer)
-    //     .with_target(true) // More verbose for file
-    //     .with_thread_ids(true)
-    //     .with_thread_names(true)
-    //     .with_file(true)
-    //     .with_line_number(true)
-    //     .with_ansi(false) // No ANSI colors for file
-    //     .with_writer(non_blocking_appender)
-    //     .with_filter(file_env_filter);
-
-    // Initialize the global subscriber with console output only (file logging commented out)
+    // Settings are read early since logging starts before the app
+    let early_settings = settings::read_before_startup();
+    crate::privacy::apply(&early_settings);
+
+    // Create file logging layer
+    let file_log = early_settings.file_log;
+    let file_layer = match file_log {
+        FileLogFormat::Off => None,
+        FileLogFormat::Text => Some(
+            fmt::layer()
+                .fmt_fields(RedactingFields)
+                .with_timer(timer)
+                .with_target(true) // More verbose for file
+                .with_thread_ids(true)
+                .with_thread_names(true)
+                .with_file(true)
+                .with_line_number(true)
+                .with_ansi(false) // No ANSI colors for file
+                .with_writer(non_blocking_appender)
+                .with_filter(file_env_filter)
+                .boxed(),
+        ),
+        FileLogFormat::Json => Some(
+            fmt::layer()
+                .event_format(RedactingJson(
+                    fmt::format()
+                        .json()
+                        .with_timer(timer)
+                        .with_current_span(true)
+                        .with_span_list(false)
+                        .with_thread_ids(true)
+                        .with_file(true)
+                        .with_line_number(true),
+                ))
+                .fmt_fields(JsonFields::new())
+                .with_ansi(false)
+                .with_writer(non_blocking_appender)
+                .with_filter(file_env_filter)
+                .boxed(),
+        ),
+    };
+
+    // Export spans to an OpenTelemetry collector when one is configured
+    let otlp_layer = crate::telemetry::otlp_layer();
+    let otlp_enabled = otlp_layer.is_some();
+
+    // Initialize the global subscriber
     tracing_subscriber::registry()
+        .with(file_layer)
         .with(console_layer)
-        // .with(file_layer)  // Commented out to disable file logging
+        .with(otlp_layer)
+        .with(crate::error_reporting::ErrorReportLayer) // Only reports when opted in
         .init();
 
-    tracing::info!("Logging system initialized with console output only");
+    tracing::info!(file_log = ?file_log, otlp = otlp_enabled, "Logging system initialized");
 
     Ok(())
 }
@@ -92,4 +142,3 @@ fn get_log_directory() -> Result<PathBuf, Box<dyn std::error::Error>> {
 
     Ok(log_dir)
 }
-
@@ -0,0 +1,141 @@
+use std::time::Instant;
+use video_inspector_core::loudness::LoudnessTags;
+
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::runner::{sidecar, KillOnDrop, MediaTool};
+
+/// Claimed vs measured loudness differing by more than this is a mismatch
+const MISMATCH_TOLERANCE_LU: f64 = 1.0;
+
+/// Loudness measured with ffmpeg's ebur128 filter
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct LoudnessMeasurement {
+    integrated_lufs: f64,
+    loudness_range_lu: Option<f64>,
+    true_peak_dbfs: Option<f64>,
+}
+
+/// Claimed and measured loudness for one audio stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct LoudnessReport {
+    audio_stream: usize,
+    claimed: Option<LoudnessTags>,
+    claimed_lufs: Option<f64>,
+    measured: LoudnessMeasurement,
+    /// Set when the tags claim a loudness that differs from the measurement
+    mismatch: bool,
+}
+
+/// Measure the loudness of an audio stream and compare it with its tags
+///
+/// `audio_stream` is the position among audio streams (0 = first audio track).
+#[tauri::command]
+pub async fn measure_loudness(
+    path: String,
+    audio_stream: Option<usize>,
+) -> Result<LoudnessReport, String> {
+    measure_loudness_async(&path, audio_stream.unwrap_or(0))
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Loudness measurement failed");
+            e.localized()
+        })
+}
+
+pub async fn measure_loudness_async(
+    path: &str,
+    audio_stream: usize,
+) -> Result<LoudnessReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    // Read the claimed values first; this is cheap compared to the measurement
+    let json = run_ffprobe_json(
+        app_handle,
+        path,
+        &[
+            "-show_format",
+            "-show_streams",
+            "-select_streams",
+            &format!("a:{}", audio_stream),
+        ],
+    )
+    .await?;
+    let stream = json["streams"]
+        .get(0)
+        .ok_or_else(|| Error::ParseError(format!("Audio stream {} not found", audio_stream)))?;
+    let claimed = LoudnessTags::from_tags(&stream["tags"], &json["format"]["tags"]);
+
+    let start = Instant::now();
+    let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args([
+            "-hide_banner",
+            "-nostats",
+            "-i",
+            path,
+            "-map",
+            &format!("0:a:{}", audio_stream),
+            "-filter:a",
+            "ebur128=peak=true",
+            "-f",
+            "null",
+            "-",
+        ])
+        .output_or_kill()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg loudness measurement failed: {}",
+            stderr
+        )));
+    }
+
+    let measured =
+        parse_ebur128_summary(&String::from_utf8_lossy(&output.stderr)).ok_or_else(|| {
+            Error::ParseError("Loudness summary not found in ffmpeg output".to_string())
+        })?;
+
+    let claimed_lufs = claimed.as_ref().and_then(LoudnessTags::claimed_lufs);
+    let mismatch = claimed_lufs
+        .is_some_and(|claimed| (claimed - measured.integrated_lufs).abs() > MISMATCH_TOLERANCE_LU);
+
+    tracing::debug!(
+        video_path = %path,
+        audio_stream,
+        integrated_lufs = measured.integrated_lufs,
+        claimed_lufs = ?claimed_lufs,
+        mismatch,
+        elapsed = ?start.elapsed(),
+        "Measured loudness"
+    );
+
+    Ok(LoudnessReport {
+        audio_stream,
+        claimed,
+        claimed_lufs,
+        measured,
+        mismatch,
+    })
+}
+
+/// Parse the summary block that ebur128 prints when the stream ends
+fn parse_ebur128_summary(stderr: &str) -> Option<LoudnessMeasurement> {
+    let summary = &stderr[stderr.rfind("Summary:")?..];
+
+    let value_after = |label: &str| {
+        summary
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(label))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|value| value.parse::<f64>().ok())
+    };
+
+    Some(LoudnessMeasurement {
+        integrated_lufs: value_after("I:")?,
+        loudness_range_lu: value_after("LRA:"),
+        true_peak_dbfs: value_after("Peak:"),
+    })
+}
@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+
+use crate::cache::{cache_key, file_fingerprint};
+use crate::get_app_handle;
+use crate::inspector::{Error, VideoMetadata};
+use crate::locale::current_locale;
+use crate::settings;
+
+/// Bump when `VideoMetadata` changes shape so stale entries are ignored
+const METADATA_CACHE_VERSION: &str = "metadata-v4";
+
+/// Subdirectory of the app data directory holding cached results
+///
+/// Kept apart from the size-capped thumbnail cache so large thumbnail
+/// batches never evict them.
+const METADATA_DIR_NAME: &str = "metadata";
+
+/// Extension of entry files, so nothing else in the directory is removed
+const ENTRY_EXTENSION: &str = "json";
+
+/// Directory holding cached inspection results
+fn metadata_dir() -> Result<PathBuf, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::IoError(std::io::Error::other(e.to_string())))?
+        .join(METADATA_DIR_NAME);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Cache key of the inspection of `path` as it is now, with `options`
+///
+/// Only the settings an inspection reads are part of the key: the probe's
+/// input arguments, computed field scripts and thumbnail options, along with
+/// the locale the stream notes are written in. `None` when the file can't be
+/// fingerprinted, which leaves it uncached.
+pub fn metadata_key(path: &str, options: &str) -> Option<String> {
+    let fingerprint = match file_fingerprint(path) {
+        Ok(fingerprint) => fingerprint,
+        Err(e) => {
+            tracing::debug!(video_path = %path, error = %e, "Metadata cache disabled");
+            return None;
+        }
+    };
+    let current = settings::current();
+    let settings = serde_json::json!({
+        "input_args": current.input_args,
+        "computed_fields": current.computed_fields,
+        "thumbnail_hints": current.thumbnail_hints,
+        "thumbnail_timestamps": current.thumbnail_timestamps,
+    })
+    .to_string();
+    Some(cache_key(&[
+        METADATA_CACHE_VERSION,
+        &fingerprint,
+        options,
+        &settings,
+        current_locale(),
+    ]))
+}
+
+/// Cached inspection result; entries that no longer parse count as misses
+pub fn read_metadata(key: &str) -> Option<VideoMetadata> {
+    let data = fs::read(entry_path(&metadata_dir().ok()?, key)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Cache an inspection result
+///
+/// A failed write only costs the next inspection its speed, so it's logged.
+pub fn write_metadata(key: &str, metadata: &VideoMetadata) {
+    let result = serde_json::to_vec(metadata)
+        .map_err(|e| Error::ParseError(e.to_string()))
+        .and_then(|data| {
+            let path = entry_path(&metadata_dir()?, key);
+            // Written aside and renamed so concurrent reads never see half
+            let partial = path.with_extension("partial");
+            fs::write(&partial, data)?;
+            fs::rename(&partial, &path)?;
+            Ok(())
+        });
+    if let Err(e) = result {
+        tracing::debug!(error = %e, "Failed to cache video metadata");
+    }
+}
+
+/// Delete cached inspection results, keeping thumbnails and folder report
+/// entries; returns how many were removed
+#[tauri::command]
+pub async fn clear_metadata_cache() -> Result<usize, String> {
+    let removed = clear_entries().map_err(|e| {
+        tracing::error!(error = %e, "Failed to clear metadata cache");
+        e.localized()
+    })?;
+    tracing::info!(removed, "Metadata cache cleared");
+    Ok(removed)
+}
+
+fn clear_entries() -> Result<usize, Error> {
+    let mut removed = 0;
+    for entry in fs::read_dir(metadata_dir()?)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some(ENTRY_EXTENSION) {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.{}", key, ENTRY_EXTENSION))
+}
@@ -0,0 +1,211 @@
+use std::{
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::disc::{probe_duration, TitleStreams};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::tags::tag;
+use video_inspector_core::motion_photo::{
+    apple_content_identifier, find_motion_photo, MotionPhoto,
+};
+
+/// Extensions of the video half of a Live Photo
+const LIVE_PHOTO_VIDEO_EXTENSIONS: [&str; 2] = ["mov", "mp4"];
+
+/// Extensions of the still half of a Live Photo
+const LIVE_PHOTO_IMAGE_EXTENSIONS: [&str; 3] = ["heic", "jpg", "jpeg"];
+
+/// QuickTime tag Apple writes to both halves of a Live Photo
+const CONTENT_IDENTIFIER_TAG: &str = "com.apple.quicktime.content.identifier";
+
+/// What a phone photo or clip holds beyond the still image
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MotionPhotoReport {
+    path: String,
+    /// The video appended to the photo itself, as Google and Samsung cameras
+    /// write it
+    motion_photo: Option<MotionPhoto>,
+    /// Streams of the embedded video
+    embedded_streams: Option<TitleStreams>,
+    /// Seconds
+    embedded_duration: Option<f64>,
+    /// Where the embedded video was saved, when asked to
+    extracted_path: Option<String>,
+    /// The other half of an Apple Live Photo, confirmed by the identifier
+    /// both files carry
+    live_photo: Option<LivePhotoPair>,
+    /// A still or video with the same name whose identifier is missing or
+    /// differs, so it may or may not be the other half
+    same_name_companion: Option<String>,
+}
+
+/// An Apple Live Photo: a HEIC or JPEG still and a short MOV
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct LivePhotoPair {
+    photo_path: String,
+    video_path: String,
+    /// Identifier the video shares with the photo's maker note
+    content_identifier: String,
+    /// Seconds
+    video_duration: Option<f64>,
+}
+
+/// Inspect a motion photo or Live Photo from either of its files
+///
+/// JPEG and HEIC motion photos carry a video after the image, which is found
+/// from their XMP metadata, the Samsung trailer or by scanning, and probed in
+/// place; with `extract_to` it's also saved there. Live Photos are a still
+/// and a video next to each other with the same name, paired from whichever
+/// one is given when Apple's content identifier in both matches; a file that
+/// only shares the name is reported as a same-name companion.
+#[tauri::command]
+pub async fn inspect_motion_photo(
+    path: String,
+    extract_to: Option<String>,
+) -> Result<MotionPhotoReport, String> {
+    inspect_motion_photo_async(&path, extract_to.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Motion photo inspection failed");
+            e.localized()
+        })
+}
+
+async fn inspect_motion_photo_async(
+    path: &str,
+    extract_to: Option<&str>,
+) -> Result<MotionPhotoReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let photo_path = path.to_string();
+    let motion_photo = tauri::async_runtime::spawn_blocking(move || find_motion_photo(&photo_path))
+        .await
+        .map_err(|e| Error::IoError(io::Error::other(e.to_string())))??;
+
+    let mut report = MotionPhotoReport {
+        path: path.to_string(),
+        motion_photo: None,
+        embedded_streams: None,
+        embedded_duration: None,
+        extracted_path: None,
+        live_photo: None,
+        same_name_companion: None,
+    };
+    if let Some(motion_photo) = motion_photo {
+        // ffmpeg reads the byte range directly, so nothing is copied to probe it
+        let url = format!(
+            "subfile,,start,{},end,0,,:{}",
+            motion_photo.video_offset, path
+        );
+        let probe = run_ffprobe_json(app_handle, &url, &["-show_format", "-show_streams"]).await?;
+        report.embedded_streams = Some(TitleStreams::from_probe(&probe));
+        report.embedded_duration = probe_duration(&probe);
+        if let Some(extract_to) = extract_to {
+            extract_video(path, &motion_photo, extract_to)?;
+            report.extracted_path = Some(extract_to.to_string());
+        }
+        report.motion_photo = Some(motion_photo);
+    }
+
+    if let Some((photo_path, video_path)) = live_photo_pair(Path::new(path)) {
+        let (photo_path, video_path) = (
+            photo_path.to_string_lossy().to_string(),
+            video_path.to_string_lossy().to_string(),
+        );
+        let companion = if photo_path == path {
+            video_path.clone()
+        } else {
+            photo_path.clone()
+        };
+
+        let identifier_path = photo_path.clone();
+        let photo_identifier = tauri::async_runtime::spawn_blocking(move || {
+            apple_content_identifier(&identifier_path)
+        })
+        .await
+        .map_err(|e| Error::IoError(io::Error::other(e.to_string())))??;
+        // The companion is a guess, so a video ffprobe can't read only loses it
+        let probe = match run_ffprobe_json(app_handle, &video_path, &["-show_format"]).await {
+            Ok(probe) => Some(probe),
+            Err(e) => {
+                tracing::warn!(
+                    video_path = %video_path,
+                    error = %e,
+                    "Failed to probe Live Photo video"
+                );
+                None
+            }
+        };
+        let video_identifier = probe
+            .as_ref()
+            .and_then(|probe| tag(&probe["format"], CONTENT_IDENTIFIER_TAG));
+
+        match (photo_identifier, video_identifier) {
+            (Some(photo), Some(video)) if photo.eq_ignore_ascii_case(&video) => {
+                report.live_photo = Some(LivePhotoPair {
+                    photo_path,
+                    video_path,
+                    content_identifier: video,
+                    video_duration: probe.as_ref().and_then(probe_duration),
+                });
+            }
+            _ => report.same_name_companion = Some(companion),
+        }
+    }
+
+    tracing::debug!(
+        video_path = %path,
+        motion_photo = ?report.motion_photo.as_ref().map(|photo| &photo.source),
+        live_photo = report.live_photo.is_some(),
+        same_name_companion = report.same_name_companion.is_some(),
+        "Inspected motion photo"
+    );
+    Ok(report)
+}
+
+/// Copy the embedded video out of a motion photo
+fn extract_video(path: &str, motion_photo: &MotionPhoto, output: &str) -> Result<(), Error> {
+    let mut input = File::open(path)?;
+    input.seek(SeekFrom::Start(motion_photo.video_offset))?;
+    let mut output = File::create(output)?;
+    io::copy(&mut input.take(motion_photo.video_length), &mut output)?;
+    Ok(())
+}
+
+/// The still and the video of what may be a Live Photo, from either of them
+///
+/// The other half has the same name with the other kind of extension, in
+/// any case, as iPhones export `IMG_0001.HEIC` with `IMG_0001.MOV`.
+fn live_photo_pair(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let (is_photo, wanted) = if LIVE_PHOTO_IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        (true, LIVE_PHOTO_VIDEO_EXTENSIONS.as_slice())
+    } else if LIVE_PHOTO_VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+        (false, LIVE_PHOTO_IMAGE_EXTENSIONS.as_slice())
+    } else {
+        return None;
+    };
+
+    let stem = path.file_stem()?;
+    let other = fs::read_dir(path.parent()?)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|candidate| {
+            candidate.file_stem() == Some(stem)
+                && candidate
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .is_some_and(|extension| wanted.contains(&extension.to_lowercase().as_str()))
+        })?;
+
+    Some(if is_photo {
+        (path.to_path_buf(), other)
+    } else {
+        (other, path.to_path_buf())
+    })
+}
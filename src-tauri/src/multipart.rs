@@ -0,0 +1,185 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::disc::{concat_url, probe_duration, thumbnail_title, TitleStreams, DISC_PROBE_ARGS};
+use crate::get_app_handle;
+use crate::inspector::{run_ffprobe_json, Error};
+use crate::job::new_job_id;
+use crate::thumbnail::Thumbnail;
+use video_inspector_core::Error as CoreError;
+
+/// One file of a split video
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FilePart {
+    index: u32,
+    path: String,
+    size: u64,
+}
+
+/// A split video inspected as the parts joined together
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct MultipartReport {
+    job_id: String,
+    /// Parts found next to the given file, in order
+    parts: Vec<FilePart>,
+    /// Part numbers absent from the sequence; the video is cut where they'd be
+    missing: Vec<u32>,
+    total_size: u64,
+    /// Seconds
+    duration: Option<f64>,
+    streams: TitleStreams,
+    thumbnails: Vec<Thumbnail>,
+}
+
+/// How the parts of a set are named: `prefix`, part number, `suffix`
+#[derive(Debug, PartialEq)]
+struct PartPattern {
+    prefix: String,
+    suffix: String,
+    /// Menu parts like `VTS_01_0.VOB` that aren't part of the video
+    skip_zero: bool,
+}
+
+/// Inspect a split video from any of its parts: `movie.mkv.001`,
+/// `movie.part2.mkv`, `VTS_01_3.VOB` and the like
+///
+/// Thumbnails are also emitted as `inspection://partial` events tagged with
+/// `job_id`.
+#[tauri::command]
+pub async fn inspect_multipart(
+    path: String,
+    job_id: Option<String>,
+) -> Result<MultipartReport, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    inspect_multipart_async(&path, job_id).await.map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Multi-part inspection failed");
+        e.localized()
+    })
+}
+
+async fn inspect_multipart_async(path: &str, job_id: String) -> Result<MultipartReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let (parts, missing) = find_parts(Path::new(path))?;
+
+    let mut file_parts = Vec::with_capacity(parts.len());
+    for (index, part) in &parts {
+        file_parts.push(FilePart {
+            index: *index,
+            path: part.to_string_lossy().to_string(),
+            size: fs::metadata(part)?.len(),
+        });
+    }
+    let url = concat_url(&file_parts.iter().map(|part| &part.path).collect::<Vec<_>>());
+
+    let probe = run_ffprobe_json(app_handle, &url, &DISC_PROBE_ARGS).await?;
+    let thumbnails = thumbnail_title(app_handle, &url, path, &job_id)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(video_path = %path, error = %e, "Failed to thumbnail split video");
+            Vec::new()
+        });
+
+    tracing::debug!(
+        video_path = %path,
+        parts = file_parts.len(),
+        missing = missing.len(),
+        "Inspected split video"
+    );
+
+    Ok(MultipartReport {
+        job_id,
+        total_size: file_parts.iter().map(|part| part.size).sum(),
+        parts: file_parts,
+        missing,
+        duration: probe_duration(&probe),
+        streams: TitleStreams::from_probe(&probe),
+        thumbnails,
+    })
+}
+
+/// The parts of the set `path` belongs to, by number, and the numbers missing
+/// between the first expected part and the last one found
+fn find_parts(path: &Path) -> Result<(BTreeMap<u32, PathBuf>, Vec<u32>), Error> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (pattern, _) = parse_part_name(&name)
+        .ok_or_else(|| CoreError::NotMediaFile(format!("{} is not part of a split file", name)))?;
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+
+    let mut parts = BTreeMap::new();
+    for entry in fs::read_dir(dir.unwrap_or(Path::new(".")))? {
+        let entry = entry?;
+        let sibling = entry.file_name().to_string_lossy().to_string();
+        if let Some((sibling_pattern, index)) = parse_part_name(&sibling) {
+            if sibling_pattern == pattern && !(pattern.skip_zero && index == 0) {
+                parts.insert(index, entry.path());
+            }
+        }
+    }
+
+    // Sets start at 1, or at 0 for `.000` style numbering
+    let first = match parts.keys().next() {
+        Some(0) => 0,
+        _ => 1,
+    };
+    let last = parts.keys().next_back().copied().unwrap_or(0);
+    let missing = (first..=last)
+        .filter(|index| !parts.contains_key(index))
+        .collect();
+    Ok((parts, missing))
+}
+
+/// Split a part file name into its set pattern and part number
+fn parse_part_name(name: &str) -> Option<(PartPattern, u32)> {
+    let upper = name.to_ascii_uppercase();
+    let pattern = |prefix: &str, suffix: &str, skip_zero| PartPattern {
+        prefix: prefix.to_ascii_uppercase(),
+        suffix: suffix.to_ascii_uppercase(),
+        skip_zero,
+    };
+
+    // VOB sequences: VTS_01_1.VOB, VTS_01_2.VOB, ...
+    if let Some(rest) = upper
+        .strip_prefix("VTS_")
+        .and_then(|rest| rest.strip_suffix(".VOB"))
+    {
+        let (title_set, part) = rest.split_once('_')?;
+        let prefix = format!("VTS_{}_", title_set);
+        return Some((pattern(&prefix, ".VOB", true), part.parse().ok()?));
+    }
+
+    // Numbered extensions: movie.mkv.001, movie.mkv.002, ...
+    if let Some((stem, extension)) = name.rsplit_once('.') {
+        if extension.len() >= 3 && extension.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Some((
+                pattern(&format!("{}.", stem), "", false),
+                extension.parse().ok()?,
+            ));
+        }
+    }
+
+    // Part markers: movie.part1.mkv, movie.mkv.part1, ...
+    let marker = upper.rfind(".PART")?;
+    let digits = upper[marker + 5..]
+        .bytes()
+        .take_while(u8::is_ascii_digit)
+        .count();
+    if digits == 0 {
+        return None;
+    }
+    let number_end = marker + 5 + digits;
+    let suffix = &name[number_end..];
+    if !(suffix.is_empty() || suffix.starts_with('.')) {
+        return None;
+    }
+    Some((
+        pattern(&name[..marker + 5], suffix, false),
+        name[marker + 5..number_end].parse().ok()?,
+    ))
+}
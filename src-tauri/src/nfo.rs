@@ -0,0 +1,163 @@
+use std::{fs, path::Path};
+
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::probe::VideoInfo;
+use crate::tags::tag;
+
+/// ffprobe side data naming a Dolby Vision configuration
+const DOVI_SIDE_DATA: &str = "DOVI configuration record";
+
+/// A written `.nfo` sidecar
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct NfoFile {
+    path: String,
+    xml: String,
+}
+
+/// Write a Kodi/Jellyfin `<movie>` `.nfo` next to a video
+///
+/// The NFO holds the title and runtime, and a `fileinfo/streamdetails`
+/// section describing each video, audio and subtitle stream. An existing
+/// NFO is only replaced with `overwrite`, since it may hold hand-edited
+/// details.
+#[tauri::command]
+pub async fn write_nfo(path: String, overwrite: Option<bool>) -> Result<NfoFile, String> {
+    write_nfo_async(&path, overwrite.unwrap_or(false))
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Writing NFO failed");
+            e.localized()
+        })
+}
+
+async fn write_nfo_async(path: &str, overwrite: bool) -> Result<NfoFile, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let nfo_path = Path::new(path).with_extension("nfo");
+    if nfo_path.exists() && !overwrite {
+        return Err(Error::IoError(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists", nfo_path.display()),
+        )));
+    }
+
+    let info = get_video_info_with_ffprobe(app_handle, path).await?;
+    let xml = movie_nfo(Path::new(path), &info);
+    fs::write(&nfo_path, &xml)?;
+    tracing::debug!(video_path = %path, nfo = %nfo_path.display(), "Wrote NFO");
+    Ok(NfoFile {
+        path: nfo_path.to_string_lossy().to_string(),
+        xml,
+    })
+}
+
+fn movie_nfo(path: &Path, info: &VideoInfo) -> String {
+    let title = tag(&info.probe_json["format"], "title").unwrap_or_else(|| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    xml.push_str("<movie>\n");
+    xml.push_str(&element(1, "title", &title));
+    // Kodi shows the runtime in whole minutes
+    xml.push_str(&element(
+        1,
+        "runtime",
+        &((info.duration / 60.0).round() as u64).to_string(),
+    ));
+    xml.push_str("  <fileinfo>\n    <streamdetails>\n");
+    for stream in info.probe_json["streams"].as_array().into_iter().flatten() {
+        // Cover art is stored as a video stream
+        if stream["disposition"]["attached_pic"].as_u64() == Some(1) {
+            continue;
+        }
+        let language = tag(stream, "language").filter(|language| language != "und");
+        match stream["codec_type"].as_str() {
+            Some("video") => {
+                xml.push_str("      <video>\n");
+                if let Some(codec) = stream["codec_name"].as_str() {
+                    xml.push_str(&element(4, "codec", codec));
+                }
+                let (width, height) = (stream["width"].as_u64(), stream["height"].as_u64());
+                if let (Some(width), Some(height)) = (width, height.filter(|&h| h > 0)) {
+                    xml.push_str(&element(
+                        4,
+                        "aspect",
+                        &format!("{:.2}", width as f64 / height as f64),
+                    ));
+                    xml.push_str(&element(4, "width", &width.to_string()));
+                    xml.push_str(&element(4, "height", &height.to_string()));
+                }
+                xml.push_str(&element(
+                    4,
+                    "durationinseconds",
+                    &(info.duration.round() as u64).to_string(),
+                ));
+                if let Some(hdr_type) = hdr_type(stream) {
+                    xml.push_str(&element(4, "hdrtype", hdr_type));
+                }
+                xml.push_str("      </video>\n");
+            }
+            Some("audio") => {
+                xml.push_str("      <audio>\n");
+                if let Some(codec) = stream["codec_name"].as_str() {
+                    xml.push_str(&element(4, "codec", codec));
+                }
+                if let Some(language) = &language {
+                    xml.push_str(&element(4, "language", language));
+                }
+                if let Some(channels) = stream["channels"].as_u64() {
+                    xml.push_str(&element(4, "channels", &channels.to_string()));
+                }
+                xml.push_str("      </audio>\n");
+            }
+            Some("subtitle") => {
+                xml.push_str("      <subtitle>\n");
+                if let Some(language) = &language {
+                    xml.push_str(&element(4, "language", language));
+                }
+                xml.push_str("      </subtitle>\n");
+            }
+            _ => {}
+        }
+    }
+    xml.push_str("    </streamdetails>\n  </fileinfo>\n</movie>\n");
+    xml
+}
+
+/// Kodi's `hdrtype` for a video stream; `None` for SDR
+fn hdr_type(stream: &serde_json::Value) -> Option<&'static str> {
+    let dolby_vision = stream["side_data_list"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|side_data| side_data["side_data_type"].as_str() == Some(DOVI_SIDE_DATA));
+    if dolby_vision {
+        return Some("dolbyvision");
+    }
+    match stream["color_transfer"].as_str()? {
+        "smpte2084" => Some("hdr10"),
+        "arib-std-b67" => Some("hlg"),
+        _ => None,
+    }
+}
+
+/// `<name>text</name>` on its own line, indented by `depth` levels
+fn element(depth: usize, name: &str, text: &str) -> String {
+    format!(
+        "{}<{}>{}</{}>\n",
+        "  ".repeat(depth),
+        name,
+        xml_escape(text),
+        name
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,332 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::analyzer::{first_stream, Analyzer};
+use crate::frame_content::text_bands;
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::runner::SidecarRunner;
+use crate::temp::temp_frame_path;
+use crate::thumbnail::extract_frame;
+
+/// Frames sampled across the video for overlay detection
+const OVERLAY_SAMPLES: usize = 24;
+
+/// Number of ffmpeg processes run at once while sampling
+const SAMPLE_CONCURRENCY: usize = 4;
+
+/// Lower third of the frame, where subtitles are burned in, at a width that
+/// keeps glyph edges sharp
+const SUBTITLE_AREA_FILTER: &str = "crop=iw:ih/3:0:ih*2/3,scale=640:-2";
+
+/// Share of sampled frames with text in the lower third above which the
+/// video likely has burned-in subtitles; dialogue-free scenes keep real
+/// hardsubbed releases well below 100%
+const HARDSUB_RATIO: f64 = 0.3;
+
+/// Whole frames for logo detection, small enough to keep the per-pixel
+/// statistics cheap
+const LOGO_FILTER: &str = "scale=480:-2";
+
+/// Fewer good samples than this can't tell a logo from a still scene
+const MIN_LOGO_SAMPLES: usize = 8;
+
+/// Width and height of the corner regions searched for logos, as a share
+/// of the frame
+const CORNER_WIDTH: f64 = 0.25;
+const CORNER_HEIGHT: f64 = 0.2;
+
+/// Luma standard deviation across samples below which a pixel is static
+const STATIC_STDDEV: f64 = 6.0;
+
+/// Gradient of the averaged frame above which a static pixel is part of a
+/// drawn shape rather than a flat area such as a letterbox bar
+const LOGO_EDGE: f64 = 40.0;
+
+/// Mean luma a logo pixel must exceed; keeps the static edges of black bars
+/// out
+const LOGO_MIN_LUMA: f64 = 40.0;
+
+/// Share of a corner that must be static edges to report a logo
+const LOGO_COVERAGE: f64 = 0.01;
+
+/// Share of the frame centre that may be static before the footage is too
+/// still to judge
+const MAX_STATIC_CENTRE: f64 = 0.5;
+
+/// Burned-in subtitles guessed from text in the lower third of sampled frames
+pub struct HardsubAnalyzer;
+
+#[async_trait]
+impl Analyzer for HardsubAnalyzer {
+    fn name(&self) -> &str {
+        "hardsub"
+    }
+
+    fn description(&self) -> &str {
+        "Whether the video likely has burned-in subtitles"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let frames = sample_frames(path, duration, OVERLAY_SAMPLES, SUBTITLE_AREA_FILTER).await?;
+        if frames.is_empty() {
+            return Err(Error::FFmpegError(
+                "No frames could be sampled for subtitle detection".to_string(),
+            ));
+        }
+
+        let with_text: Vec<f64> = frames
+            .iter()
+            // The crop is a third of the frame
+            .filter(|(_, image)| !text_bands(image, image.height() * 3).is_empty())
+            .map(|(timestamp, _)| *timestamp)
+            .collect();
+        let ratio = with_text.len() as f64 / frames.len() as f64;
+        tracing::debug!(
+            video_path = %path,
+            sampled = frames.len(),
+            with_text = with_text.len(),
+            "Checked lower third for burned-in subtitles"
+        );
+
+        Ok(json!({
+            "likely": ratio >= HARDSUB_RATIO,
+            "frames_sampled": frames.len(),
+            "frames_with_text": with_text.len(),
+            "ratio": ratio,
+            // Frames worth checking by eye
+            "timestamps": with_text,
+        }))
+    }
+}
+
+/// Static corner overlays, such as channel logos and watermarks, found by
+/// comparing sampled frames
+pub struct WatermarkAnalyzer;
+
+#[async_trait]
+impl Analyzer for WatermarkAnalyzer {
+    fn name(&self) -> &str {
+        "watermark"
+    }
+
+    fn description(&self) -> &str {
+        "Channel logos or watermarks that stay put in a corner"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let frames: Vec<image::GrayImage> =
+            sample_frames(path, duration, OVERLAY_SAMPLES, LOGO_FILTER)
+                .await?
+                .into_iter()
+                .map(|(_, image)| image)
+                .collect();
+        if frames.len() < MIN_LOGO_SAMPLES {
+            return Err(Error::FFmpegError(format!(
+                "Only {} frames could be sampled for logo detection",
+                frames.len()
+            )));
+        }
+
+        let stats = PixelStats::from_frames(&frames);
+        let static_centre = stats.static_share(
+            stats.width / 4,
+            stats.height / 4,
+            stats.width * 3 / 4,
+            stats.height * 3 / 4,
+        );
+        if static_centre > MAX_STATIC_CENTRE {
+            tracing::debug!(
+                video_path = %path,
+                static_centre,
+                "Footage too still for logo detection"
+            );
+            return Ok(json!({ "too_static": true, "logos": [] }));
+        }
+
+        let corner_width = (stats.width as f64 * CORNER_WIDTH) as u32;
+        let corner_height = (stats.height as f64 * CORNER_HEIGHT) as u32;
+        let corners = [
+            ("top_left", 0, 0),
+            ("top_right", stats.width - corner_width, 0),
+            ("bottom_left", 0, stats.height - corner_height),
+            (
+                "bottom_right",
+                stats.width - corner_width,
+                stats.height - corner_height,
+            ),
+        ];
+        let mut logos = Vec::new();
+        for (corner, left, top) in corners {
+            let Some((coverage, [x0, y0, x1, y1])) =
+                stats.logo_pixels(left, top, left + corner_width, top + corner_height)
+            else {
+                continue;
+            };
+            if coverage < LOGO_COVERAGE {
+                continue;
+            }
+            let (width, height) = (stats.width as f64, stats.height as f64);
+            logos.push(json!({
+                "corner": corner,
+                "coverage": coverage,
+                // Bounding box as a share of the frame, for drawing over a thumbnail
+                "x": x0 as f64 / width,
+                "y": y0 as f64 / height,
+                "width": (x1 - x0 + 1) as f64 / width,
+                "height": (y1 - y0 + 1) as f64 / height,
+            }));
+        }
+        tracing::debug!(video_path = %path, logos = logos.len(), "Checked corners for logos");
+        Ok(json!({ "too_static": false, "logos": logos }))
+    }
+}
+
+/// Per-pixel luma mean and standard deviation across frames of one size
+struct PixelStats {
+    width: u32,
+    height: u32,
+    mean: Vec<f64>,
+    stddev: Vec<f64>,
+}
+
+impl PixelStats {
+    /// Frames of a different size than the first, e.g. after a resolution
+    /// change, are left out
+    fn from_frames(frames: &[image::GrayImage]) -> Self {
+        let (width, height) = frames.first().map(|f| f.dimensions()).unwrap_or_default();
+        let frames: Vec<&image::GrayImage> = frames
+            .iter()
+            .filter(|frame| frame.dimensions() == (width, height))
+            .collect();
+        let pixel_count = (width * height) as usize;
+        let mut sum = vec![0.0; pixel_count];
+        let mut sum_sq = vec![0.0; pixel_count];
+        for frame in &frames {
+            for (i, pixel) in frame.pixels().enumerate() {
+                let value = pixel.0[0] as f64;
+                sum[i] += value;
+                sum_sq[i] += value * value;
+            }
+        }
+        let count = frames.len().max(1) as f64;
+        let mean: Vec<f64> = sum.iter().map(|sum| sum / count).collect();
+        let stddev = sum_sq
+            .iter()
+            .zip(&mean)
+            .map(|(sum_sq, mean)| (sum_sq / count - mean * mean).max(0.0).sqrt())
+            .collect();
+        PixelStats {
+            width,
+            height,
+            mean,
+            stddev,
+        }
+    }
+
+    fn is_static(&self, x: u32, y: u32) -> bool {
+        self.stddev[(y * self.width + x) as usize] < STATIC_STDDEV
+    }
+
+    /// Share of static pixels in a region, right and bottom exclusive
+    fn static_share(&self, left: u32, top: u32, right: u32, bottom: u32) -> f64 {
+        let area = ((right - left) * (bottom - top)).max(1) as f64;
+        let count = (top..bottom)
+            .flat_map(|y| (left..right).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_static(x, y))
+            .count();
+        count as f64 / area
+    }
+
+    /// Share of a region made of static, bright edges, and their bounding
+    /// box; `None` when there are none
+    fn logo_pixels(&self, left: u32, top: u32, right: u32, bottom: u32) -> Option<(f64, [u32; 4])> {
+        let mean = |x: u32, y: u32| self.mean[(y * self.width + x) as usize];
+        let mut count = 0;
+        let mut bounds = [u32::MAX, u32::MAX, 0, 0];
+        // Skip the outermost pixels, which have no neighbour to compare with
+        for y in top.max(1)..bottom.min(self.height - 1) {
+            for x in left.max(1)..right.min(self.width - 1) {
+                let gradient = (mean(x + 1, y) - mean(x - 1, y)).abs()
+                    + (mean(x, y + 1) - mean(x, y - 1)).abs();
+                if self.is_static(x, y) && gradient > LOGO_EDGE && mean(x, y) > LOGO_MIN_LUMA {
+                    count += 1;
+                    bounds = [
+                        bounds[0].min(x),
+                        bounds[1].min(y),
+                        bounds[2].max(x),
+                        bounds[3].max(y),
+                    ];
+                }
+            }
+        }
+        let area = ((right - left) * (bottom - top)).max(1) as f64;
+        (count > 0).then(|| (count as f64 / area, bounds))
+    }
+}
+
+/// `count` grayscale frames spread over 5%-95% of the runtime, passed through
+/// `video_filter`; frames that fail to extract are skipped
+pub async fn sample_frames(
+    path: &str,
+    duration: f64,
+    count: usize,
+    video_filter: &str,
+) -> Result<Vec<(f64, image::GrayImage)>, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let time_points: Vec<f64> = (0..count)
+        .map(|i| duration * (0.05 + 0.9 * (i as f64 + 0.5) / count as f64))
+        .collect();
+
+    let mut frames = Vec::with_capacity(time_points.len());
+    for chunk in time_points.chunks(SAMPLE_CONCURRENCY) {
+        let mut tasks = vec![];
+        for &time_point in chunk {
+            let runner = SidecarRunner::new(app_handle);
+            let path = path.to_string();
+            let video_filter = video_filter.to_string();
+            let temp_image_path = temp_frame_path("overlay", "png")?;
+            tasks.push(tauri::async_runtime::spawn(async move {
+                let image_data = extract_frame(
+                    &runner,
+                    &path,
+                    time_point,
+                    &video_filter,
+                    None,
+                    None,
+                    &temp_image_path,
+                )
+                .await?;
+                let image = image::load_from_memory(&image_data)
+                    .map_err(|e| Error::ParseError(format!("Undecodable frame: {}", e)))?;
+                Ok::<_, Error>((time_point, image.to_luma8()))
+            }));
+        }
+
+        for task in tasks {
+            match task.await {
+                Ok(Ok(frame)) => frames.push(frame),
+                Ok(Err(e)) => {
+                    tracing::warn!(video_path = %path, error = %e, "Overlay sample failed");
+                }
+                Err(e) => {
+                    tracing::warn!(video_path = %path, error = %e, "Overlay sample task failed");
+                }
+            }
+        }
+    }
+    Ok(frames)
+}
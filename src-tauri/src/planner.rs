@@ -0,0 +1,158 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Bytes read from the sample file to estimate disk throughput
+const SAMPLE_BYTES: usize = 32 * 1024 * 1024;
+
+/// Reads during the throughput sample stop after this long
+const SAMPLE_TIME_LIMIT: Duration = Duration::from_secs(2);
+
+/// Below this throughput (bytes/s) the disk is likely rotational or remote,
+/// where parallel reads just add seeks
+const SLOW_DISK_BPS: f64 = 200.0 * 1024.0 * 1024.0;
+
+/// Above this throughput (bytes/s) the disk is likely NVMe
+const FAST_DISK_BPS: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Most files processed at once, however many cores are idle
+const MAX_CONCURRENCY: usize = 8;
+
+/// How a batch scan will run, chosen from a quick sample of the machine
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ScanPlan {
+    files: usize,
+    total_bytes: u64,
+    /// Measured sequential read speed, None when no file could be sampled
+    read_throughput_bps: Option<f64>,
+    logical_cpus: usize,
+    /// Cores not busy with other work, from the load average where available
+    idle_cpus: usize,
+    /// Files processed at once
+    concurrency: usize,
+    hash_chunk_size: usize,
+    /// Why these values were chosen, for the job details
+    reason: String,
+}
+
+impl ScanPlan {
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn hash_chunk_size(&self) -> usize {
+        self.hash_chunk_size
+    }
+}
+
+/// Sample disk and CPU and pick concurrency and hash chunk size for `files`
+///
+/// The sample reads the start of the largest file; on a warm page cache it
+/// overestimates the disk, which only makes the plan more parallel.
+pub fn plan_scan(files: &[PathBuf]) -> ScanPlan {
+    let sizes: Vec<u64> = files
+        .iter()
+        .map(|file| file.metadata().map(|m| m.len()).unwrap_or(0))
+        .collect();
+    let total_bytes = sizes.iter().sum();
+    let largest = sizes
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, size)| **size)
+        .map(|(index, _)| &files[index]);
+    let read_throughput_bps = largest.map(PathBuf::as_path).and_then(sample_throughput);
+
+    let logical_cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let busy_cpus = load_average()
+        .map(|load| load.round() as usize)
+        .unwrap_or(0);
+    let idle_cpus = logical_cpus.saturating_sub(busy_cpus).max(1);
+
+    let (concurrency, hash_chunk_size, reason) = match read_throughput_bps {
+        Some(bps) if bps < SLOW_DISK_BPS => (
+            1,
+            1024 * 1024,
+            format!(
+                "{:.0} MB/s reads look like a hard disk or network share; \
+                 scanning one file at a time avoids seeking",
+                bps / 1e6
+            ),
+        ),
+        Some(bps) if bps >= FAST_DISK_BPS => (
+            idle_cpus.min(MAX_CONCURRENCY),
+            8 * 1024 * 1024,
+            format!(
+                "{:.0} MB/s reads look like an SSD; using one worker per idle core \
+                 ({} of {}) and large reads",
+                bps / 1e6,
+                idle_cpus,
+                logical_cpus
+            ),
+        ),
+        Some(bps) => (
+            (idle_cpus / 2).clamp(1, MAX_CONCURRENCY),
+            4 * 1024 * 1024,
+            format!(
+                "{:.0} MB/s reads; using half of the {} idle cores",
+                bps / 1e6,
+                idle_cpus
+            ),
+        ),
+        None => (
+            1,
+            1024 * 1024,
+            "Disk throughput couldn't be sampled; scanning one file at a time".to_string(),
+        ),
+    };
+    let concurrency = concurrency.min(files.len()).max(1);
+
+    let plan = ScanPlan {
+        files: files.len(),
+        total_bytes,
+        read_throughput_bps,
+        logical_cpus,
+        idle_cpus,
+        concurrency,
+        hash_chunk_size,
+        reason,
+    };
+    tracing::info!(plan = ?plan, "Planned batch scan");
+    plan
+}
+
+/// Sequential read speed over the start of a file, in bytes per second
+fn sample_throughput(path: &Path) -> Option<f64> {
+    let mut file = File::open(path).ok()?;
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut read_total = 0usize;
+    let start = Instant::now();
+    while read_total < SAMPLE_BYTES && start.elapsed() < SAMPLE_TIME_LIMIT {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        read_total += read;
+    }
+    // Tiny files say nothing about the disk
+    if read_total < buffer.len() {
+        return None;
+    }
+    Some(read_total as f64 / start.elapsed().as_secs_f64().max(1e-6))
+}
+
+/// One-minute load average, where the platform exposes it cheaply
+#[cfg(target_os = "linux")]
+fn load_average() -> Option<f64> {
+    let loadavg = std::fs::read_to_string("/proc/loadavg").ok()?;
+    loadavg.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn load_average() -> Option<f64> {
+    None
+}
@@ -0,0 +1,347 @@
+use base64::{engine::general_purpose, Engine};
+use std::{fs, path::Path, time::Instant};
+
+use crate::disk::ensure_free_space;
+use crate::frame_stats::LumaStats;
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, run_ffprobe_json, Error};
+use crate::runner::{sidecar, KillOnDrop, MediaTool, SidecarRunner};
+use crate::temp::temp_frame_path;
+use crate::thumbnail::extract_frame;
+
+/// Number of frames sampled across the video when none is specified
+const DEFAULT_CANDIDATE_COUNT: usize = 12;
+
+/// Number of best candidates returned when none is specified
+const DEFAULT_TOP_COUNT: usize = 3;
+
+/// Number of ffmpeg processes run at once while sampling candidates
+const SAMPLE_CONCURRENCY: usize = 4;
+
+/// Filter used for the cheap scoring pass
+const SCORING_FILTER: &str = "scale=320:-2";
+
+/// Filter used for returned candidates and saved posters
+const POSTER_FILTER: &str = "scale='min(1280,iw)':-2";
+
+/// A scored poster frame candidate
+#[derive(serde::Serialize, Clone)]
+pub struct PosterCandidate {
+    timestamp: f64,
+    score: f64,
+    sharpness: f64,
+    brightness: f64,
+    image_base64: String,
+}
+
+/// Sample candidate frames across the video and return the best-scoring ones
+/// at poster resolution, ordered from best to worst
+#[tauri::command]
+pub async fn pick_poster_frame(
+    path: String,
+    candidates: Option<usize>,
+    top: Option<usize>,
+) -> Result<Vec<PosterCandidate>, String> {
+    pick_poster_frame_async(
+        &path,
+        candidates.unwrap_or(DEFAULT_CANDIDATE_COUNT).max(1),
+        top.unwrap_or(DEFAULT_TOP_COUNT).max(1),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Poster frame picking failed");
+        e.localized()
+    })
+}
+
+/// Save the frame at `timestamp` as an image file (format follows the extension)
+#[tauri::command]
+pub async fn save_poster_frame(
+    path: String,
+    timestamp: f64,
+    output_path: String,
+) -> Result<(), String> {
+    save_poster_frame_async(&path, timestamp, &output_path)
+        .await
+        .map_err(|e| e.localized())
+}
+
+/// Write a copy of the video to `output_path` with the frame at `timestamp`
+/// embedded as cover art
+#[tauri::command]
+pub async fn embed_poster_frame(
+    path: String,
+    timestamp: f64,
+    output_path: String,
+) -> Result<(), String> {
+    embed_poster_frame_async(&path, timestamp, &output_path)
+        .await
+        .map_err(|e| e.localized())
+}
+
+async fn pick_poster_frame_async(
+    path: &str,
+    candidate_count: usize,
+    top_count: usize,
+) -> Result<Vec<PosterCandidate>, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let video_info = get_video_info_with_ffprobe(app_handle, path).await?;
+    let runner = SidecarRunner::new(app_handle);
+    let start = Instant::now();
+
+    // Spread candidates over 5%-95% of the runtime to skip intros and credits
+    let time_points: Vec<f64> = (0..candidate_count)
+        .map(|i| {
+            let position = 0.05 + 0.9 * (i as f64 + 0.5) / candidate_count as f64;
+            video_info.duration * position
+        })
+        .collect();
+
+    let mut scored: Vec<(f64, LumaStats)> = Vec::with_capacity(time_points.len());
+    for chunk in time_points.chunks(SAMPLE_CONCURRENCY) {
+        let mut tasks = vec![];
+        for &time_point in chunk {
+            let runner = runner.clone();
+            let path = path.to_string();
+            let temp_image_path = temp_frame_path("poster", "png")?;
+            tasks.push(tauri::async_runtime::spawn(async move {
+                let image_data = extract_frame(
+                    &runner,
+                    &path,
+                    time_point,
+                    SCORING_FILTER,
+                    None,
+                    None,
+                    &temp_image_path,
+                )
+                .await?;
+                Ok::<_, Error>((time_point, LumaStats::from_image_data(&image_data)))
+            }));
+        }
+
+        for task in tasks {
+            match task.await {
+                Ok(Ok((time_point, Some(stats)))) => scored.push((time_point, stats)),
+                Ok(Ok((time_point, None))) => {
+                    tracing::debug!(
+                        video_path = %path,
+                        time_point,
+                        "Could not decode poster candidate"
+                    );
+                }
+                Ok(Err(e)) => {
+                    tracing::warn!(
+                        video_path = %path,
+                        error = %e,
+                        "Poster candidate extraction failed"
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        video_path = %path,
+                        error = %e,
+                        "Poster candidate task failed"
+                    );
+                }
+            }
+        }
+    }
+
+    scored.sort_by(|a, b| b.1.score().total_cmp(&a.1.score()));
+    scored.truncate(top_count);
+
+    // Re-extract the winners at poster resolution
+    let mut candidates = Vec::with_capacity(scored.len());
+    for (time_point, stats) in scored {
+        let temp_image_path = temp_frame_path("poster_full", "png")?;
+        let image_data = extract_frame(
+            &runner,
+            path,
+            time_point,
+            POSTER_FILTER,
+            None,
+            None,
+            &temp_image_path,
+        )
+        .await?;
+        candidates.push(PosterCandidate {
+            timestamp: time_point,
+            score: stats.score(),
+            sharpness: stats.sharpness,
+            brightness: stats.mean,
+            image_base64: format!(
+                "data:image/png;base64,{}",
+                general_purpose::STANDARD.encode(&image_data)
+            ),
+        });
+    }
+
+    tracing::debug!(
+        video_path = %path,
+        candidates = candidates.len(),
+        elapsed = ?start.elapsed(),
+        "Picked poster frame candidates"
+    );
+
+    Ok(candidates)
+}
+
+async fn save_poster_frame_async(
+    path: &str,
+    timestamp: f64,
+    output_path: &str,
+) -> Result<(), Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let image_data = extract_poster_image(app_handle, path, timestamp, output_path).await?;
+    ensure_free_space(Path::new(output_path), image_data.len() as u64)?;
+    fs::write(output_path, image_data)?;
+
+    tracing::info!(
+        video_path = %path,
+        output_path = %output_path,
+        timestamp,
+        "Saved poster frame"
+    );
+
+    Ok(())
+}
+
+async fn embed_poster_frame_async(
+    path: &str,
+    timestamp: f64,
+    output_path: &str,
+) -> Result<(), Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    if Path::new(path) == Path::new(output_path) {
+        return Err(Error::FFmpegError(
+            "Output path must differ from the input file".to_string(),
+        ));
+    }
+
+    // The output is a full copy of the input plus a small image
+    ensure_free_space(Path::new(output_path), fs::metadata(path)?.len())?;
+
+    // Cover art is stored as JPEG for the widest player support
+    let image_path = temp_frame_path("cover", "jpg")?;
+    let image_data = extract_poster_image(app_handle, path, timestamp, "cover.jpg").await?;
+    fs::write(&image_path, image_data)?;
+
+    mux_cover_art(app_handle, path, &image_path, output_path).await?;
+
+    tracing::info!(
+        video_path = %path,
+        output_path = %output_path,
+        timestamp,
+        "Embedded poster frame as cover art"
+    );
+
+    Ok(())
+}
+
+/// Extract a poster-resolution frame encoded to match `output_path`'s extension
+async fn extract_poster_image(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    timestamp: f64,
+    output_path: &str,
+) -> Result<Vec<u8>, Error> {
+    let extension = Path::new(output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("png");
+    let temp_image_path = temp_frame_path("poster_save", extension)?;
+    Ok(extract_frame(
+        &SidecarRunner::new(app_handle),
+        path,
+        timestamp,
+        POSTER_FILTER,
+        None,
+        None,
+        &temp_image_path,
+    )
+    .await?)
+}
+
+/// Copy all streams of `path` into `output_path` and attach the image as cover art
+async fn mux_cover_art(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    image_path: &Path,
+    output_path: &str,
+) -> Result<(), Error> {
+    let image_path = image_path.to_string_lossy().to_string();
+    let is_matroska = Path::new(output_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "mkv" | "mka" | "webm"));
+
+    let args: Vec<String> = if is_matroska {
+        // Matroska stores cover art as a file attachment
+        vec![
+            "-i".into(),
+            path.into(),
+            "-map".into(),
+            "0".into(),
+            "-c".into(),
+            "copy".into(),
+            "-attach".into(),
+            image_path,
+            "-metadata:s:t".into(),
+            "mimetype=image/jpeg".into(),
+            "-metadata:s:t".into(),
+            "filename=cover.jpg".into(),
+            "-y".into(),
+            output_path.into(),
+        ]
+    } else {
+        // MP4/MOV use an extra video stream flagged as an attached picture,
+        // which lands right after every stream copied from the input
+        let stream_count = count_streams(app_handle, path).await?;
+        vec![
+            "-i".into(),
+            path.into(),
+            "-i".into(),
+            image_path,
+            "-map".into(),
+            "0".into(),
+            "-map".into(),
+            "1".into(),
+            "-c".into(),
+            "copy".into(),
+            format!("-disposition:{}", stream_count),
+            "attached_pic".into(),
+            "-y".into(),
+            output_path.into(),
+        ]
+    };
+
+    let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args(args)
+        .output_or_kill()
+        .await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let _ = fs::remove_file(output_path);
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg failed to embed cover art: {}",
+            stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// Count all streams in the input, regardless of type
+async fn count_streams(app_handle: &tauri::AppHandle, path: &str) -> Result<usize, Error> {
+    let json = run_ffprobe_json(app_handle, path, &["-show_entries", "stream=index"]).await?;
+    Ok(json["streams"]
+        .as_array()
+        .map_or(0, |streams| streams.len()))
+}
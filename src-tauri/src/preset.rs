@@ -0,0 +1,57 @@
+use crate::hash::HashKind;
+
+/// Named sets of inspection steps, from cheapest to most thorough
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InspectionPreset {
+    /// Probe only
+    Quick,
+    /// Probe, thumbnails and a full SHA-256
+    #[default]
+    Standard,
+    /// Everything: thumbnails, full SHA-256, QC checks, loudness and a full
+    /// decode looking for corruption
+    Deep,
+}
+
+/// Steps an inspection runs after probing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pipeline {
+    pub thumbnails: bool,
+    /// `None` skips hashing
+    pub hash: Option<HashKind>,
+    pub qc: bool,
+    /// Loudness of the first audio track
+    pub loudness: bool,
+    pub integrity_scan: bool,
+}
+
+impl InspectionPreset {
+    pub fn pipeline(self) -> Pipeline {
+        match self {
+            InspectionPreset::Quick => Pipeline {
+                thumbnails: false,
+                hash: None,
+                qc: false,
+                loudness: false,
+                integrity_scan: false,
+            },
+            // `file_hash` has always been a full SHA-256; the sampled hash
+            // is only used when asked for with `quick_hash`
+            InspectionPreset::Standard => Pipeline {
+                thumbnails: true,
+                hash: Some(HashKind::Sha256),
+                qc: false,
+                loudness: false,
+                integrity_scan: false,
+            },
+            InspectionPreset::Deep => Pipeline {
+                thumbnails: true,
+                hash: Some(HashKind::Sha256),
+                qc: true,
+                loudness: true,
+                integrity_scan: true,
+            },
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fs, time::Instant};
+
+use crate::events::emit_ffmpeg_progress;
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::job::new_job_id;
+use crate::progress::run_ffmpeg_with_progress;
+use crate::temp::temp_frame_path;
+use crate::thumbnail::{default_time_points, generate_thumbnails_with_ffmpeg, Thumbnail};
+
+/// Length of the preview when none is specified, in seconds
+const DEFAULT_PREVIEW_SECONDS: f64 = 10.0;
+
+/// Longest preview allowed, so a typo doesn't start a full conversion
+const MAX_PREVIEW_SECONDS: f64 = 300.0;
+
+/// Encoder settings to try, mirroring the usual ffmpeg options
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct PreviewSettings {
+    /// ffmpeg encoder, e.g. "libx264", "libx265", "libsvtav1"
+    video_codec: String,
+    crf: Option<u32>,
+    preset: Option<String>,
+    /// Target bit rate in ffmpeg notation, e.g. "4M"
+    video_bit_rate: Option<String>,
+    /// Downscale to at most this height, keeping the aspect ratio
+    max_height: Option<u32>,
+    pix_fmt: Option<String>,
+    /// Audio encoder; `None` copies the audio unchanged
+    audio_codec: Option<String>,
+    audio_bit_rate: Option<String>,
+}
+
+/// The result of encoding the start of a file with some settings
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TranscodePreview {
+    job_id: String,
+    /// Duration actually encoded, in seconds
+    seconds: f64,
+    codec_name: String,
+    resolution: String,
+    /// Bits per second of the encoded sample
+    bit_rate: f64,
+    output_size: u64,
+    /// Size the same span takes in the source, estimated from its bit rate
+    source_size: u64,
+    /// Full-length output size extrapolated from the sample
+    projected_size: u64,
+    /// Seconds of video encoded per second of wall time
+    encode_speed: f64,
+    thumbnails: Vec<Thumbnail>,
+}
+
+/// Encode the first `seconds` of a file and describe the result
+///
+/// The encoded sample is deleted afterwards; only its metadata and thumbnails
+/// are returned. Progress is emitted as `inspection://ffmpeg-progress` events.
+#[tauri::command]
+pub async fn preview_transcode(
+    path: String,
+    settings: PreviewSettings,
+    seconds: Option<f64>,
+    job_id: Option<String>,
+) -> Result<TranscodePreview, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    let seconds = seconds
+        .unwrap_or(DEFAULT_PREVIEW_SECONDS)
+        .clamp(1.0, MAX_PREVIEW_SECONDS);
+    preview_transcode_async(&path, &settings, seconds, job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Transcode preview failed");
+            e.localized()
+        })
+}
+
+async fn preview_transcode_async(
+    path: &str,
+    settings: &PreviewSettings,
+    seconds: f64,
+    job_id: String,
+) -> Result<TranscodePreview, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let source = get_video_info_with_ffprobe(app_handle, path).await?;
+    let seconds = seconds.min(source.duration).max(0.0);
+
+    // Matroska accepts practically any codec combination
+    let output_path = temp_frame_path("transcode_preview", "mkv")?;
+    let output = output_path.to_string_lossy().to_string();
+    let args = build_args(path, settings, seconds, &output)?;
+
+    let start = Instant::now();
+    let run = run_ffmpeg_with_progress(app_handle, &args, Some(seconds), |progress| {
+        emit_ffmpeg_progress(&job_id, path, "transcode_preview", progress)
+    })
+    .await?;
+    let elapsed = start.elapsed();
+    if !run.success() {
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg preview encode failed: {}",
+            run.stderr.join("\n")
+        )));
+    }
+
+    let result = get_video_info_with_ffprobe(app_handle, &output).await?;
+    let output_size = fs::metadata(&output_path)?.len();
+    let thumbnails = generate_thumbnails_with_ffmpeg(
+        app_handle,
+        &output,
+        &result,
+        &default_time_points(result.duration),
+        |_, _| {},
+    )
+    .await?;
+
+    let sample_seconds = result.duration.max(f64::EPSILON);
+    let source_size = (source.bit_rate * sample_seconds / 8.0) as u64;
+    let projected_size = (output_size as f64 * source.duration / sample_seconds) as u64;
+
+    tracing::debug!(
+        video_path = %path,
+        job_id = %job_id,
+        output_size,
+        source_size,
+        elapsed = ?elapsed,
+        "Transcode preview finished"
+    );
+
+    Ok(TranscodePreview {
+        job_id,
+        seconds: result.duration,
+        codec_name: result.video_stream.codec_name().to_string(),
+        resolution: format!("{}x{}", result.width, result.height),
+        bit_rate: output_size as f64 * 8.0 / sample_seconds,
+        output_size,
+        source_size,
+        projected_size,
+        encode_speed: result.duration / elapsed.as_secs_f64().max(f64::EPSILON),
+        thumbnails,
+    })
+}
+
+/// ffmpeg arguments encoding the first `seconds` of the main video and audio
+fn build_args(
+    path: &str,
+    settings: &PreviewSettings,
+    seconds: f64,
+    output: &str,
+) -> Result<Vec<String>, Error> {
+    let mut args: Vec<String> = vec![
+        // The temp file already exists, empty
+        "-y".to_string(),
+        "-i".to_string(),
+        path.to_string(),
+        "-t".to_string(),
+        format!("{:.3}", seconds),
+        "-map".to_string(),
+        "0:v:0".to_string(),
+        "-map".to_string(),
+        "0:a:0?".to_string(),
+    ];
+    let mut push = |option: &str, value: &str| -> Result<(), Error> {
+        // Values go straight to ffmpeg, so don't let them pass as options
+        if value.is_empty() || value.starts_with('-') {
+            return Err(Error::ParseError(format!(
+                "Invalid value '{}' for {}",
+                value, option
+            )));
+        }
+        args.extend([option.to_string(), value.to_string()]);
+        Ok(())
+    };
+
+    push("-c:v", &settings.video_codec)?;
+    if let Some(crf) = settings.crf {
+        push("-crf", &crf.to_string())?;
+    }
+    if let Some(preset) = &settings.preset {
+        push("-preset", preset)?;
+    }
+    if let Some(bit_rate) = &settings.video_bit_rate {
+        push("-b:v", bit_rate)?;
+    }
+    if let Some(max_height) = settings.max_height {
+        push("-vf", &format!("scale=-2:'min(ih,{})'", max_height))?;
+    }
+    if let Some(pix_fmt) = &settings.pix_fmt {
+        push("-pix_fmt", pix_fmt)?;
+    }
+    match &settings.audio_codec {
+        Some(audio_codec) => {
+            push("-c:a", audio_codec)?;
+            if let Some(bit_rate) = &settings.audio_bit_rate {
+                push("-b:a", bit_rate)?;
+            }
+        }
+        None => push("-c:a", "copy")?,
+    }
+
+    args.push(output.to_string());
+    Ok(args)
+}
@@ -0,0 +1,230 @@
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    field::{RecordFields, VisitOutput},
+    fmt::{
+        format::{DefaultVisitor, Writer},
+        FmtContext, FormatEvent, FormatFields,
+    },
+    registry::LookupSpan,
+};
+
+use crate::settings::{self, Settings};
+
+/// Log fields holding file or folder paths
+pub const PATH_FIELDS: [&str; 10] = [
+    "video_path",
+    "path",
+    "output_path",
+    "folder",
+    "dir",
+    "file",
+    "source",
+    "manifest_path",
+    "export_path",
+    "sidecar",
+];
+
+/// Log fields holding content hashes
+const HASH_FIELDS: [&str; 3] = ["file_hash", "sha256", "hash"];
+
+/// Log fields dumping raw tool output, which is full of paths and tags
+const DROPPED_FIELDS: [&str; 2] = ["ffprobe_output", "output_preview"];
+
+/// Values remembered for scrubbing free-text fields; the list starts over
+/// when it grows beyond this
+const MAX_KNOWN_VALUES: usize = 1_000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Salt for anonymized IDs, so they can't be matched against known paths
+static SALT: RwLock<String> = RwLock::new(String::new());
+
+/// Values already anonymized, replaced wherever they show up in other fields
+///
+/// Longest first, so a file path is replaced before its folder.
+static KNOWN_VALUES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// Turn log redaction on or off to match the current settings
+pub fn apply_settings() {
+    apply(&settings::current());
+}
+
+/// Turn log redaction on or off to match `settings`
+pub fn apply(settings: &Settings) {
+    *SALT.write().unwrap_or_else(|e| e.into_inner()) =
+        settings.log_privacy_salt.clone().unwrap_or_default();
+    KNOWN_VALUES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+    ENABLED.store(settings.log_privacy, Ordering::Relaxed);
+}
+
+/// Field formatter replacing paths and hashes with stable anonymized IDs
+///
+/// The same file always gets the same ID (for the same salt), so its events
+/// can still be followed through a log. Paths that also appear inside error
+/// messages are replaced there too, on a best-effort basis.
+pub struct RedactingFields;
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = RedactingVisitor(DefaultVisitor::new(writer, true));
+        fields.record(&mut visitor);
+        visitor.0.finish()
+    }
+}
+
+struct RedactingVisitor<'a>(DefaultVisitor<'a>);
+
+impl Visit for RedactingVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match redact(field.name(), value) {
+            Some(redacted) => self.0.record_debug(field, &format_args!("{}", redacted)),
+            None => self.0.record_str(field, value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !ENABLED.load(Ordering::Relaxed) {
+            self.0.record_debug(field, value);
+            return;
+        }
+        let text = format!("{:?}", value);
+        match redact(field.name(), &text) {
+            Some(redacted) => self.0.record_debug(field, &format_args!("{}", redacted)),
+            None => self.0.record_debug(field, value),
+        }
+    }
+}
+
+/// Event formatter redacting the lines written by a JSON event formatter
+///
+/// tracing's JSON formatter records event fields itself, bypassing
+/// [`RedactingFields`], so its output is rewritten instead. This only costs
+/// anything while privacy mode is on.
+pub struct RedactingJson<F>(pub F);
+
+impl<S, N, F> FormatEvent<S, N> for RedactingJson<F>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+    F: FormatEvent<S, N>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return self.0.format_event(ctx, writer, event);
+        }
+
+        let mut line = String::new();
+        self.0.format_event(ctx, Writer::new(&mut line), event)?;
+        match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(mut json) => {
+                redact_json(None, &mut json);
+                writeln!(writer, "{}", json)
+            }
+            // Not JSON after all; drop it rather than leak a path
+            Err(_) => writeln!(writer, "{{\"redacted\":true}}"),
+        }
+    }
+}
+
+/// Redact string values in place, using object keys as field names
+fn redact_json(name: Option<&str>, value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(text) => {
+            if let Some(redacted) = redact(name.unwrap_or_default(), text) {
+                *text = redacted;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                redact_json(name, item);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                redact_json(Some(key), value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Redacted form of a field, or None to log it unchanged
+fn redact(name: &str, value: &str) -> Option<String> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    if DROPPED_FIELDS.contains(&name) {
+        return Some("<redacted>".to_string());
+    }
+    if PATH_FIELDS.contains(&name) {
+        return Some(remember(value, anonymize_path(value)));
+    }
+    if HASH_FIELDS.contains(&name) {
+        return Some(remember(value, format!("hash-{}", short_digest(value))));
+    }
+
+    let known = KNOWN_VALUES.read().unwrap_or_else(|e| e.into_inner());
+    let mut scrubbed = value.to_string();
+    for (original, id) in known.iter() {
+        if scrubbed.contains(original.as_str()) {
+            scrubbed = scrubbed.replace(original.as_str(), id);
+        }
+    }
+    (scrubbed != value).then_some(scrubbed)
+}
+
+/// `file-<digest>.<ext>`; the extension is kept since it helps debugging and
+/// says nothing about the library
+fn anonymize_path(path: &str) -> String {
+    let digest = short_digest(path);
+    match Path::new(path).extension() {
+        Some(extension) => format!("file-{}.{}", digest, extension.to_string_lossy()),
+        None => format!("file-{}", digest),
+    }
+}
+
+fn short_digest(value: &str) -> String {
+    let salt = SALT.read().unwrap_or_else(|e| e.into_inner());
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(value.as_bytes());
+    format!("{:x}", hasher.finalize())[..12].to_string()
+}
+
+/// Record a value so later free-text fields mentioning it are scrubbed
+fn remember(value: &str, id: String) -> String {
+    // Empty or very short values would match all over unrelated text
+    if value.len() < 4 {
+        return id;
+    }
+    let mut known = KNOWN_VALUES.write().unwrap_or_else(|e| e.into_inner());
+    if known.iter().any(|(original, _)| original == value) {
+        return id;
+    }
+    if known.len() >= MAX_KNOWN_VALUES {
+        known.clear();
+    }
+    let position = known.partition_point(|(original, _)| original.len() >= value.len());
+    known.insert(position, (value.to_string(), id.clone()));
+    id
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use tauri_plugin_shell::process::CommandEvent;
+
+use crate::duration::parse_clock;
+use crate::inspector::Error;
+use crate::runner::{sidecar, ChildGuard, MediaTool};
+
+/// Structured progress from ffmpeg's `-progress` key=value stream
+#[derive(serde::Serialize, Clone, Debug, Default)]
+pub struct FfmpegProgress {
+    frame: Option<u64>,
+    fps: Option<f64>,
+    /// Position reached in the output, in seconds
+    out_time: f64,
+    /// Processing speed relative to real time (2.0 = twice as fast)
+    speed: Option<f64>,
+    total_size: Option<u64>,
+    /// 0-100, only known when the input duration was given
+    percent: Option<f64>,
+    /// Set on the last report, once ffmpeg writes `progress=end`
+    finished: bool,
+}
+
+impl FfmpegProgress {
+    pub fn frame(&self) -> Option<u64> {
+        self.frame
+    }
+
+    pub fn out_time(&self) -> f64 {
+        self.out_time
+    }
+}
+
+/// Output of an ffmpeg run that reported progress
+pub struct FfmpegRun {
+    pub exit_code: Option<i32>,
+    pub stderr: Vec<String>,
+}
+
+impl FfmpegRun {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run ffmpeg with `-progress pipe:1` and report each progress block
+///
+/// `args` must not write media output to stdout, since stdout carries the
+/// progress stream. `duration` (seconds) is used to compute a percentage.
+pub async fn run_ffmpeg_with_progress(
+    app_handle: &tauri::AppHandle,
+    args: &[String],
+    duration: Option<f64>,
+    mut on_progress: impl FnMut(&FfmpegProgress),
+) -> Result<FfmpegRun, Error> {
+    let (mut rx, child) = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args(["-hide_banner", "-nostats", "-progress", "pipe:1"])
+        .args(args)
+        .spawn()?;
+    let _child = ChildGuard::new(child);
+
+    let mut parser = ProgressParser::new(duration);
+    let mut stderr = Vec::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                if let Some(progress) = parser.push_line(&String::from_utf8_lossy(&line)) {
+                    on_progress(&progress);
+                }
+            }
+            CommandEvent::Stderr(line) => {
+                stderr.push(String::from_utf8_lossy(&line).trim_end().to_string());
+            }
+            CommandEvent::Terminated(payload) => exit_code = payload.code,
+            CommandEvent::Error(e) => {
+                return Err(Error::FFmpegError(format!("ffmpeg failed: {}", e)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FfmpegRun { exit_code, stderr })
+}
+
+/// Collects key=value lines until the `progress=` line that closes a block
+struct ProgressParser {
+    duration: Option<f64>,
+    fields: HashMap<String, String>,
+}
+
+impl ProgressParser {
+    fn new(duration: Option<f64>) -> Self {
+        Self {
+            duration: duration.filter(|&d| d > 0.0),
+            fields: HashMap::new(),
+        }
+    }
+
+    fn push_line(&mut self, line: &str) -> Option<FfmpegProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        if key != "progress" {
+            self.fields
+                .insert(key.to_string(), value.trim().to_string());
+            return None;
+        }
+
+        let fields = std::mem::take(&mut self.fields);
+        let number = |key: &str| fields.get(key).and_then(|v| v.parse::<f64>().ok());
+
+        // out_time_us is the precise one; out_time_ms is also in microseconds
+        // in every ffmpeg release, so it's only a fallback
+        let out_time = number("out_time_us")
+            .or_else(|| number("out_time_ms"))
+            .map(|us| us / 1_000_000.0)
+            .or_else(|| fields.get("out_time").and_then(|v| parse_clock(v)))
+            .unwrap_or(0.0)
+            .max(0.0);
+
+        Some(FfmpegProgress {
+            frame: fields.get("frame").and_then(|v| v.parse().ok()),
+            fps: number("fps"),
+            out_time,
+            speed: fields
+                .get("speed")
+                .and_then(|v| v.trim_end_matches('x').trim().parse().ok()),
+            total_size: fields.get("total_size").and_then(|v| v.parse().ok()),
+            percent: self
+                .duration
+                .map(|duration| (out_time / duration * 100.0).clamp(0.0, 100.0)),
+            finished: value == "end",
+        })
+    }
+}
@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::analyzer::{first_stream, to_value, Analyzer};
+use crate::bitrate::declared_bit_rate;
+use crate::frame_stats::{banding, blockiness, noise_sigma, LumaStats};
+use crate::frames::json_f64;
+use crate::inspector::Error;
+use crate::overlay::sample_frames;
+use crate::probe::parse_fraction;
+use crate::savings::{estimate_savings, EncodedVideo};
+use crate::settings;
+
+/// Frames sampled for picture quality estimates
+const QUALITY_SAMPLES: usize = 8;
+
+/// Frames at source resolution, capped at 1080p; downscaling would average
+/// the grain away
+const NOISE_FILTER: &str = "scale='min(1920,iw)':-2";
+
+/// Upper bounds of the noise levels, in luma steps of standard deviation
+const NOISE_LEVELS: [(f64, &str); 3] = [(1.5, "clean"), (3.5, "light"), (7.0, "moderate")];
+
+/// Frames at their coded size, so the codec's block grid stays in place
+const ARTIFACT_FILTER: &str = "null";
+
+/// Blockiness above which a frame looks over-compressed
+const BLOCKY: f64 = 1.4;
+
+/// Share of smooth areas with band edges above which a frame is banded
+const BANDED: f64 = 0.15;
+
+/// Noise or film grain level, which makes video expensive to compress
+pub struct NoiseAnalyzer;
+
+#[async_trait]
+impl Analyzer for NoiseAnalyzer {
+    fn name(&self) -> &str {
+        "noise"
+    }
+
+    fn description(&self) -> &str {
+        "Noise and grain level, which drives up the bit rate needed"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let samples = noise_samples(path, duration).await?;
+        let sigma = median(samples.iter().map(|(_, sigma)| *sigma)).unwrap_or_default();
+        let level = NOISE_LEVELS
+            .iter()
+            .find(|(limit, _)| sigma < *limit)
+            .map_or("heavy", |(_, level)| level);
+        tracing::debug!(video_path = %path, sigma, level, "Estimated noise level");
+
+        Ok(json!({
+            "sigma": sigma,
+            "level": level,
+            "bits_per_pixel": bits_per_pixel(probe),
+            "samples": samples
+                .iter()
+                .map(|(timestamp, sigma)| json!({ "timestamp": timestamp, "sigma": sigma }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+}
+
+/// Compression artifacts: blocking at codec block edges and banding in
+/// smooth gradients
+pub struct ArtifactAnalyzer;
+
+#[async_trait]
+impl Analyzer for ArtifactAnalyzer {
+    fn name(&self) -> &str {
+        "artifacts"
+    }
+
+    fn description(&self) -> &str {
+        "Blockiness and banding that point at an over-compressed source"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let frames = sample_frames(path, duration, QUALITY_SAMPLES, ARTIFACT_FILTER).await?;
+        let samples: Vec<(f64, Option<f64>, Option<f64>)> = frames
+            .iter()
+            .filter(|(_, image)| LumaStats::from_luma(image).is_some_and(|stats| !stats.is_dull()))
+            .map(|(timestamp, image)| (*timestamp, blockiness(image), banding(image)))
+            .collect();
+        if samples.is_empty() {
+            return Err(Error::ParseError(
+                "No sampled frame was suitable for artifact detection".to_string(),
+            ));
+        }
+
+        let blockiness = median(samples.iter().filter_map(|(_, blockiness, _)| *blockiness));
+        let banding = median(samples.iter().filter_map(|(_, _, banding)| *banding));
+        // The worst frames, to look at side by side with another release
+        let examples: Vec<serde_json::Value> = samples
+            .iter()
+            .filter(|(_, blockiness, banding)| {
+                blockiness.is_some_and(|b| b >= BLOCKY) || banding.is_some_and(|b| b >= BANDED)
+            })
+            .map(|(timestamp, blockiness, banding)| {
+                json!({ "timestamp": timestamp, "blockiness": blockiness, "banding": banding })
+            })
+            .collect();
+        tracing::debug!(
+            video_path = %path,
+            blockiness = ?blockiness,
+            banding = ?banding,
+            "Estimated compression artifacts"
+        );
+
+        Ok(json!({
+            "blockiness": blockiness,
+            "banding": banding,
+            "blocky": blockiness.is_some_and(|b| b >= BLOCKY),
+            "banded": banding.is_some_and(|b| b >= BANDED),
+            "examples": examples,
+        }))
+    }
+}
+
+/// What re-encoding would save, with the measured noise level factored in
+pub struct SavingsAnalyzer;
+
+#[async_trait]
+impl Analyzer for SavingsAnalyzer {
+    fn name(&self) -> &str {
+        "reencode_savings"
+    }
+
+    fn description(&self) -> &str {
+        "Rough space saved by re-encoding to the codec and quality in settings"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "video").is_some()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let file_size = std::fs::metadata(path)?.len();
+        let video = EncodedVideo::from_probe(probe).ok_or_else(|| {
+            Error::ParseError("Video stream has no usable size, rate or bit rate".to_string())
+        })?;
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        // Without a noise level the estimate is still useful, just rougher
+        let sigma = match noise_samples(path, duration).await {
+            Ok(samples) => median(samples.iter().map(|(_, sigma)| *sigma)),
+            Err(e) => {
+                tracing::debug!(video_path = %path, error = %e, "Savings estimate without noise");
+                None
+            }
+        };
+        let estimate = estimate_savings(
+            &video,
+            file_size,
+            settings::current().reencode_target,
+            sigma,
+        )
+        .ok_or_else(|| Error::ParseError("Video has no usable duration".to_string()))?;
+        to_value(estimate)
+    }
+}
+
+/// Timestamp and noise sigma of sampled frames; fades and black frames are
+/// flat and would read as noise-free, so they're left out
+async fn noise_samples(path: &str, duration: f64) -> Result<Vec<(f64, f64)>, Error> {
+    let frames = sample_frames(path, duration, QUALITY_SAMPLES, NOISE_FILTER).await?;
+    let samples: Vec<(f64, f64)> = frames
+        .iter()
+        .filter(|(_, image)| LumaStats::from_luma(image).is_some_and(|stats| !stats.is_dull()))
+        .filter_map(|(timestamp, image)| Some((*timestamp, noise_sigma(image)?)))
+        .collect();
+    if samples.is_empty() {
+        return Err(Error::ParseError(
+            "No sampled frame was suitable for noise estimation".to_string(),
+        ));
+    }
+    Ok(samples)
+}
+
+fn median(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values.get(values.len() / 2).copied()
+}
+
+/// Video bits spent per pixel per frame, to put the noise level next to
+fn bits_per_pixel(probe: &serde_json::Value) -> Option<f64> {
+    let stream = first_stream(probe, "video")?;
+    let bit_rate = declared_bit_rate(stream)?;
+    let pixels = stream["width"].as_f64()? * stream["height"].as_f64()?;
+    let frame_rate = parse_fraction(stream["avg_frame_rate"].as_str()?).ok()?;
+    (pixels > 0.0 && frame_rate > 0.0).then(|| bit_rate / (pixels * frame_rate))
+}
@@ -0,0 +1,143 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::inspector::Error;
+
+/// Log of quarantined files kept inside the quarantine folder, one JSON
+/// object per line
+const LOG_FILE_NAME: &str = "quarantine.jsonl";
+
+/// What happens to files that fail their checks
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineAction {
+    /// Failing files are only reported
+    #[default]
+    Off,
+    Move,
+    /// Link to the file from the quarantine folder, leaving it in place
+    Symlink,
+}
+
+/// Where files failing checks are put aside for review
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Quarantine {
+    pub action: QuarantineAction,
+    pub folder: Option<PathBuf>,
+}
+
+/// Line of the quarantine log
+#[derive(serde::Serialize)]
+struct LogRecord<'a> {
+    original: &'a Path,
+    quarantined: &'a Path,
+    action: QuarantineAction,
+    reasons: &'a [String],
+    /// RFC 3339, UTC
+    time: String,
+}
+
+impl Quarantine {
+    /// Fail when quarantining is on without a folder, and create the folder
+    pub fn validate(&self) -> Result<(), Error> {
+        match (self.action, &self.folder) {
+            (QuarantineAction::Off, _) => Ok(()),
+            (_, Some(folder)) => Ok(fs::create_dir_all(folder)?),
+            (_, None) => Err(Error::ParseError(
+                "Quarantine needs a folder to put files in".to_string(),
+            )),
+        }
+    }
+
+    /// Move or link a failing file into the quarantine folder and log why
+    ///
+    /// Returns the path in the quarantine folder, or `None` when quarantining
+    /// is off.
+    pub fn apply(&self, path: &Path, reasons: &[String]) -> Result<Option<PathBuf>, Error> {
+        let Some(folder) = self
+            .folder
+            .as_ref()
+            .filter(|_| self.action != QuarantineAction::Off)
+        else {
+            return Ok(None);
+        };
+        fs::create_dir_all(folder)?;
+        let target = free_target(folder, path)?;
+        if self.action == QuarantineAction::Symlink {
+            symlink(&fs::canonicalize(path)?, &target)?;
+        } else {
+            move_file(path, &target)?;
+        }
+
+        let record = LogRecord {
+            original: path,
+            quarantined: &target,
+            action: self.action,
+            reasons,
+            time: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default(),
+        };
+        let mut log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(folder.join(LOG_FILE_NAME))?;
+        let line = serde_json::to_string(&record)
+            .map_err(|e| Error::ParseError(format!("Failed to serialize log record: {}", e)))?;
+        writeln!(log, "{}", line)?;
+
+        tracing::info!(
+            video_path = %path.display(),
+            output_path = %target.display(),
+            action = ?self.action,
+            "Quarantined failing file"
+        );
+        Ok(Some(target))
+    }
+}
+
+/// `folder/<file name>`, numbered like `clip (2).mov` when that's taken
+fn free_target(folder: &Path, path: &Path) -> Result<PathBuf, Error> {
+    let stem = path
+        .file_stem()
+        .ok_or_else(|| Error::ParseError(format!("{} has no file name", path.display())))?
+        .to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut target = folder.join(format!("{}{}", stem, extension));
+    let mut number = 2;
+    while target.symlink_metadata().is_ok() {
+        target = folder.join(format!("{} ({}){}", stem, number, extension));
+        number += 1;
+    }
+    Ok(target)
+}
+
+/// Rename, or copy and delete when the quarantine folder is on another volume
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+/// Creating symlinks needs developer mode or admin rights on Windows
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
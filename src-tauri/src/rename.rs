@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::get_app_handle;
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::probe::VideoInfo;
+
+/// Placeholders `rename_files` templates can use
+const PLACEHOLDERS: [&str; 8] = [
+    "title",
+    "name",
+    "resolution",
+    "width",
+    "height",
+    "codec",
+    "fps",
+    "audio",
+];
+
+/// Characters not allowed in file names on at least one supported OS
+const INVALID_NAME_CHARS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// What happened, or would happen, to one file of a batch rename
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RenameStatus {
+    /// Renamed, or will be on a real run
+    Renamed,
+    /// The template gives the name the file already has
+    Unchanged,
+    /// Another file already has, or would get, the same name
+    Collision {
+        with: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// One file of a batch rename
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RenameEntry {
+    from: String,
+    /// `None` when the file couldn't be inspected
+    to: Option<String>,
+    status: RenameStatus,
+}
+
+/// Result of `rename_files`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct RenameReport {
+    /// Nothing was renamed; the entries show what would happen
+    dry_run: bool,
+    entries: Vec<RenameEntry>,
+}
+
+/// Rename files from their metadata, e.g. `{title} [{resolution} {codec}]`
+///
+/// Placeholders are `{title}` (the title tag, or the file name without it),
+/// `{name}`, `{resolution}`, `{width}`, `{height}`, `{codec}`, `{fps}` and
+/// `{audio}` (first audio codec); the extension is kept. Files whose new
+/// name is taken, by another file on disk or in the batch, are left alone.
+/// With `dry_run`, which defaults to on, nothing is renamed.
+#[tauri::command]
+pub async fn rename_files(
+    paths: Vec<String>,
+    template: String,
+    dry_run: Option<bool>,
+) -> Result<RenameReport, String> {
+    rename_files_async(&paths, &template, dry_run.unwrap_or(true))
+        .await
+        .map_err(|e| {
+            tracing::error!(template = %template, error = %e, "Batch rename failed");
+            e.localized()
+        })
+}
+
+async fn rename_files_async(
+    paths: &[String],
+    template: &str,
+    dry_run: bool,
+) -> Result<RenameReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let parts = parse_template(template)?;
+
+    let mut planned: Vec<(String, Result<PathBuf, Error>)> = Vec::with_capacity(paths.len());
+    for path in paths {
+        let target = match get_video_info_with_ffprobe(app_handle, path).await {
+            Ok(info) => target_path(Path::new(path), &parts, &info),
+            Err(e) => Err(e),
+        };
+        planned.push((path.clone(), target));
+    }
+
+    // Targets are compared case-insensitively, as on Windows and macOS volumes
+    let mut claimed: HashMap<String, usize> = HashMap::new();
+    for (index, (_, target)) in planned.iter().enumerate() {
+        if let Ok(target) = target {
+            claimed
+                .entry(target.to_string_lossy().to_lowercase())
+                .or_insert(index);
+        }
+    }
+
+    let mut entries = Vec::with_capacity(planned.len());
+    for (index, (from, target)) in planned.into_iter().enumerate() {
+        let target = match target {
+            Ok(target) => target,
+            Err(e) => {
+                entries.push(RenameEntry {
+                    from,
+                    to: None,
+                    status: RenameStatus::Failed {
+                        error: e.to_string(),
+                    },
+                });
+                continue;
+            }
+        };
+        let to = target.to_string_lossy().to_string();
+        let status = if Path::new(&from) == target {
+            RenameStatus::Unchanged
+        } else if let Some(&first) = claimed
+            .get(&to.to_lowercase())
+            .filter(|&&first| first != index)
+        {
+            RenameStatus::Collision {
+                with: paths[first].clone(),
+            }
+        } else if target.symlink_metadata().is_ok() && !same_file_ignoring_case(&from, &to) {
+            RenameStatus::Collision { with: to.clone() }
+        } else if dry_run {
+            RenameStatus::Renamed
+        } else {
+            match fs::rename(&from, &target) {
+                Ok(()) => RenameStatus::Renamed,
+                Err(e) => RenameStatus::Failed {
+                    error: e.to_string(),
+                },
+            }
+        };
+        entries.push(RenameEntry {
+            from,
+            to: Some(to),
+            status,
+        });
+    }
+
+    tracing::info!(
+        files = entries.len(),
+        renamed = entries
+            .iter()
+            .filter(|entry| matches!(entry.status, RenameStatus::Renamed))
+            .count(),
+        dry_run,
+        "Batch rename finished"
+    );
+    Ok(RenameReport { dry_run, entries })
+}
+
+/// Piece of a parsed rename template
+enum TemplatePart {
+    Text(String),
+    Placeholder(String),
+}
+
+/// Split a template into text and placeholders, failing on unknown or
+/// unclosed placeholders
+fn parse_template(template: &str) -> Result<Vec<TemplatePart>, Error> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        if open > 0 {
+            parts.push(TemplatePart::Text(rest[..open].to_string()));
+        }
+        let close = rest[open..].find('}').ok_or_else(|| {
+            Error::ParseError(format!("Unclosed placeholder in template '{}'", template))
+        })?;
+        let name = &rest[open + 1..open + close];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(Error::ParseError(format!(
+                "Unknown placeholder {{{}}}, expected one of {}",
+                name,
+                PLACEHOLDERS.join(", ")
+            )));
+        }
+        parts.push(TemplatePart::Placeholder(name.to_string()));
+        rest = &rest[open + close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(TemplatePart::Text(rest.to_string()));
+    }
+    Ok(parts)
+}
+
+/// New path of a file in its own folder, keeping the extension
+fn target_path(path: &Path, parts: &[TemplatePart], info: &VideoInfo) -> Result<PathBuf, Error> {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut name = String::new();
+    for part in parts {
+        match part {
+            TemplatePart::Text(text) => name.push_str(text),
+            TemplatePart::Placeholder(placeholder) => {
+                name.push_str(&placeholder_value(placeholder, &stem, info))
+            }
+        }
+    }
+    let name = sanitize_name(&name);
+    if name.is_empty() {
+        return Err(Error::ParseError(format!(
+            "Template gives {} an empty name",
+            path.display()
+        )));
+    }
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}", name, extension.to_string_lossy()),
+        None => name,
+    };
+    Ok(path.with_file_name(name))
+}
+
+fn placeholder_value(placeholder: &str, stem: &str, info: &VideoInfo) -> String {
+    match placeholder {
+        "title" => format_tag(info, "title").unwrap_or_else(|| stem.to_string()),
+        "name" => stem.to_string(),
+        "resolution" => format!("{}x{}", info.width, info.height),
+        "width" => info.width.to_string(),
+        "height" => info.height.to_string(),
+        "codec" => info.video_stream.codec_name().to_string(),
+        // 23.976, 25, 29.97
+        "fps" => format!("{:.3}", info.frame_rate)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string(),
+        "audio" => info
+            .audio_streams
+            .first()
+            .map(|stream| stream.codec_name().to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Container tag by name; Matroska files usually have upper case tag names
+fn format_tag(info: &VideoInfo, name: &str) -> Option<String> {
+    info.probe_json["format"]["tags"]
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| value.as_str())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Replace characters that aren't allowed in file names and trim the dots
+/// and spaces Windows drops
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_control() || INVALID_NAME_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .trim()
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// A rename that only changes case finds its own file at the target
+fn same_file_ignoring_case(from: &str, to: &str) -> bool {
+    from.to_lowercase() == to.to_lowercase()
+}
@@ -0,0 +1,682 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+
+use crate::cache::{self, cache_key, file_fingerprint};
+use crate::events::{emit_hash_progress, emit_scan_plan};
+use crate::get_app_handle;
+use crate::hash::{calculate_file_hash_with_chunk_size, calculate_quick_hash};
+use crate::inspector::{get_video_info_with_ffprobe, Error};
+use crate::job::{cancel_flag, new_job_id};
+use crate::planner::{plan_scan, ScanPlan};
+use crate::probe::VideoInfo;
+use crate::quarantine::Quarantine;
+use crate::savings::{estimate_savings, EncodedVideo, ReencodeTarget};
+use crate::scan_rules::CompiledScanRules;
+use crate::scripting::{evaluate_computed_fields, ComputedValue};
+use crate::settings;
+use crate::sniff::{sniff_file, FileKind};
+use crate::stability::wait_until_stable;
+
+/// Bump when the summary fields change to invalidate cached entries
+const REPORT_CACHE_VERSION: &str = "folder-report-v3";
+
+/// Sniffed kinds that never contain video
+const AUDIO_ONLY_KINDS: [&str; 3] = ["wav", "flac", "mp3"];
+
+/// Bits per pixel differing from the folder median by more than this factor
+/// make a file an outlier
+const OUTLIER_FACTOR: f64 = 3.0;
+
+/// Which hashes a folder report adds to its entries
+#[derive(Clone, Copy, Debug)]
+struct FolderHashes {
+    sha256: bool,
+    quick: bool,
+}
+
+/// Summary of one file in a folder report
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct FolderEntry {
+    path: String,
+    codec_name: Option<String>,
+    resolution: Option<String>,
+    frame_rate: Option<f64>,
+    /// Seconds
+    duration: Option<f64>,
+    /// Bits per second
+    bit_rate: Option<f64>,
+    file_size: u64,
+    audio_streams: usize,
+    /// QC problems; empty when the file passed
+    issues: Vec<String>,
+    /// User-scripted fields, in settings order
+    computed_fields: Vec<ComputedValue>,
+    /// Only computed when hashing was requested
+    sha256: Option<String>,
+    /// Quick hash of the size and samples of the file, when requested; never
+    /// comparable with `sha256`
+    #[serde(default)]
+    quick_hash: Option<String>,
+    /// Where the file was moved or linked to when it failed QC
+    #[serde(default)]
+    quarantined: Option<String>,
+    /// Bytes re-encoding to the report's target would save, without a noise
+    /// measurement
+    #[serde(default)]
+    estimated_savings: Option<u64>,
+}
+
+/// How often a value occurs among the files of a folder
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct DistributionEntry {
+    value: String,
+    count: usize,
+}
+
+/// A file that stands out from the rest of the folder
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct Outlier {
+    path: String,
+    reason: String,
+}
+
+/// Aggregated inspection of every video under a folder
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct FolderReport {
+    job_id: String,
+    folder: String,
+    file_count: usize,
+    total_runtime: f64,
+    total_size: u64,
+    /// Encoding the savings estimates are for
+    reencode_target: ReencodeTarget,
+    /// Sum of the per-file savings estimates, in bytes
+    estimated_savings: u64,
+    codecs: Vec<DistributionEntry>,
+    resolutions: Vec<DistributionEntry>,
+    frame_rates: Vec<DistributionEntry>,
+    outliers: Vec<Outlier>,
+    /// Paths of the files with QC issues
+    failing: Vec<String>,
+    entries: Vec<FolderEntry>,
+    /// Where the report was exported, when requested
+    export_path: Option<String>,
+    /// How the scan was run, also emitted as an `inspection://scan-plan` event
+    plan: ScanPlan,
+}
+
+/// Inspect every video under a folder (recursively) and aggregate the results
+///
+/// Per-file summaries are cached, so re-running on a large library only
+/// probes new or modified files. Files are picked with the scan rules from
+/// settings. With `hash` each file's SHA-256 is added, and with `quick_hash`
+/// a quick hash that only reads samples of the file, so large libraries can
+/// be scanned for duplicates in seconds.
+/// Concurrency and hash read size are planned from a sample of disk and CPU
+/// speed. When `export_path` ends in `.html` or `.csv` the report is also
+/// written there.
+#[tauri::command]
+pub async fn report_folder(
+    path: String,
+    export_path: Option<String>,
+    hash: Option<bool>,
+    job_id: Option<String>,
+    quick_hash: Option<bool>,
+) -> Result<FolderReport, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    let hashes = FolderHashes {
+        sha256: hash.unwrap_or(false),
+        quick: quick_hash.unwrap_or(false),
+    };
+    report_folder_async(&path, export_path.as_deref(), hashes, job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(folder = %path, error = %e, "Folder report failed");
+            e.localized()
+        })
+}
+
+async fn report_folder_async(
+    folder: &str,
+    export_path: Option<&str>,
+    hashes: FolderHashes,
+    job_id: String,
+) -> Result<FolderReport, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let start = Instant::now();
+    // Entries computed with other field scripts must not be reused
+    let settings = settings::current();
+    let computed_fields = settings.computed_fields;
+    let fields_key = serde_json::to_string(&computed_fields).unwrap_or_default();
+    let reencode_target = settings.reencode_target;
+    let field_names: Vec<String> = computed_fields
+        .into_iter()
+        .map(|field| field.name)
+        .collect();
+
+    let rules = settings.scan_rules.compile()?;
+    let mut files = Vec::new();
+    collect_files(Path::new(folder), Path::new(folder), &rules, &mut files)?;
+    files.sort();
+    // Only video containers are probed; documents, music etc. are skipped
+    files.retain(|file| {
+        matches!(
+            sniff_file(&file.to_string_lossy()),
+            Ok(FileKind::Media(kind)) if !AUDIO_ONLY_KINDS.contains(&kind)
+        )
+    });
+
+    let plan = plan_scan(&files);
+    emit_scan_plan(&job_id, folder, &plan);
+
+    let semaphore = Arc::new(Semaphore::new(plan.concurrency()));
+    let fields_key = Arc::new(fields_key);
+    let hash_chunk_size = hashes.sha256.then_some(plan.hash_chunk_size());
+    let stability_delay = Duration::from_secs(settings.scan_rules.stability_delay_secs);
+    let quarantine = Arc::new(settings.quarantine);
+    let tasks: Vec<_> = files
+        .into_iter()
+        .map(|file| {
+            let semaphore = semaphore.clone();
+            let fields_key = fields_key.clone();
+            let job_id = job_id.clone();
+            let quarantine = quarantine.clone();
+            tauri::async_runtime::spawn(async move {
+                // Permits only fail once the semaphore is closed, which it never is
+                let _permit = semaphore.acquire_owned().await;
+                if let Err(e) = wait_until_stable(&file, stability_delay).await {
+                    return unreadable_entry(&file, e);
+                }
+                let path = file.to_string_lossy();
+                let mut entry = load_entry(
+                    app_handle,
+                    &path,
+                    &fields_key,
+                    reencode_target,
+                    hash_chunk_size,
+                    hashes.quick,
+                    &job_id,
+                )
+                .await;
+                if !entry.issues.is_empty() {
+                    entry.quarantined = quarantine_file(quarantine, file, &entry.issues).await;
+                }
+                entry
+            })
+        })
+        .collect();
+
+    let mut entries = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let entry = task
+            .await
+            .map_err(|e| Error::FFmpegError(format!("Scan task failed: {}", e)))?;
+        entries.push(entry);
+    }
+
+    let mut report = FolderReport {
+        job_id,
+        folder: folder.to_string(),
+        file_count: entries.len(),
+        total_runtime: entries.iter().filter_map(|e| e.duration).sum(),
+        total_size: entries.iter().map(|e| e.file_size).sum(),
+        reencode_target,
+        estimated_savings: entries.iter().filter_map(|e| e.estimated_savings).sum(),
+        codecs: distribution(entries.iter().map(|e| e.codec_name.clone())),
+        resolutions: distribution(entries.iter().map(|e| e.resolution.clone())),
+        frame_rates: distribution(
+            entries
+                .iter()
+                .map(|e| e.frame_rate.map(|rate| format!("{:.3}", rate))),
+        ),
+        outliers: find_outliers(&entries),
+        failing: entries
+            .iter()
+            .filter(|e| !e.issues.is_empty())
+            .map(|e| e.path.clone())
+            .collect(),
+        entries,
+        export_path: None,
+        plan,
+    };
+
+    if let Some(export_path) = export_path {
+        let contents = match Path::new(export_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("html") | Some("htm") => to_html(&report, &field_names),
+            Some("csv") => to_csv(&report, &field_names),
+            _ => {
+                return Err(Error::ParseError(format!(
+                    "Unsupported export format for {}, expected .html or .csv",
+                    export_path
+                )))
+            }
+        };
+        fs::write(export_path, contents)?;
+        report.export_path = Some(export_path.to_string());
+    }
+
+    tracing::info!(
+        folder = %folder,
+        files = report.file_count,
+        failing = report.failing.len(),
+        elapsed = ?start.elapsed(),
+        "Folder report finished"
+    );
+
+    Ok(report)
+}
+
+/// Every regular file below `dir` that the scan rules let through
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    rules: &CompiledScanRules,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            if rules.allows_dir(relative, &entry.metadata()?) {
+                collect_files(root, &path, rules, files)?;
+            }
+        } else if file_type.is_file() && rules.allows_file(relative, &entry.metadata()?) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Summary of a file from the cache, probing and hashing it as needed
+///
+/// Files are hashed when `hash_chunk_size` is set and the cached entry has no
+/// hash yet; likewise for the quick hash with `quick_hash`.
+async fn load_entry(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    fields_key: &str,
+    reencode_target: ReencodeTarget,
+    hash_chunk_size: Option<usize>,
+    quick_hash: bool,
+    job_id: &str,
+) -> FolderEntry {
+    // Savings depend on the target, so entries for another one are stale
+    let target_key = serde_json::to_string(&reencode_target).unwrap_or_default();
+    let entry_key = file_fingerprint(path).ok().map(|fingerprint| {
+        cache_key(&[REPORT_CACHE_VERSION, &fingerprint, fields_key, &target_key])
+    });
+    let cached = entry_key
+        .as_deref()
+        .and_then(cache::read)
+        .and_then(|data| serde_json::from_slice::<FolderEntry>(&data).ok());
+
+    let mut changed = cached.is_none();
+    let mut entry = match cached {
+        Some(entry) => entry,
+        None => inspect_entry(app_handle, path, reencode_target).await,
+    };
+
+    if let (Some(chunk_size), None) = (hash_chunk_size, &entry.sha256) {
+        let (hash_path, hash_job_id) = (path.to_string(), job_id.to_string());
+        let cancelled = cancel_flag(job_id);
+        let hashed = tauri::async_runtime::spawn_blocking(move || {
+            calculate_file_hash_with_chunk_size(&hash_path, chunk_size, &cancelled, |progress| {
+                emit_hash_progress(&hash_job_id, &hash_path, progress)
+            })
+        })
+        .await;
+        match hashed {
+            Ok(Ok(sha256)) => {
+                entry.sha256 = Some(sha256);
+                changed = true;
+            }
+            Ok(Err(e)) => tracing::warn!(video_path = %path, error = %e, "Failed to hash file"),
+            Err(e) => tracing::warn!(video_path = %path, error = %e, "Hash task failed"),
+        }
+    }
+    if quick_hash && entry.quick_hash.is_none() {
+        let hash_path = path.to_string();
+        let hashed =
+            tauri::async_runtime::spawn_blocking(move || calculate_quick_hash(&hash_path)).await;
+        match hashed {
+            Ok(Ok(hash)) => {
+                entry.quick_hash = Some(hash);
+                changed = true;
+            }
+            Ok(Err(e)) => {
+                tracing::warn!(video_path = %path, error = %e, "Failed to quick hash file")
+            }
+            Err(e) => tracing::warn!(video_path = %path, error = %e, "Hash task failed"),
+        }
+    }
+
+    if let (true, Some(key), Ok(data)) = (changed, &entry_key, serde_json::to_vec(&entry)) {
+        if let Err(e) = cache::write(key, &data) {
+            tracing::debug!(error = %e, "Failed to cache folder entry");
+        }
+    }
+    entry
+}
+
+/// Entry of a file that couldn't be inspected, with the reason as its issue
+fn unreadable_entry(path: &Path, error: Error) -> FolderEntry {
+    let mut entry = empty_entry(&path.to_string_lossy());
+    entry.issues.push(error.to_string());
+    entry
+}
+
+/// Put a file that failed QC aside as configured, returning where it went
+async fn quarantine_file(
+    quarantine: Arc<Quarantine>,
+    path: PathBuf,
+    issues: &[String],
+) -> Option<String> {
+    let issues = issues.to_vec();
+    let quarantined =
+        tauri::async_runtime::spawn_blocking(move || match quarantine.apply(&path, &issues) {
+            Ok(target) => target,
+            Err(e) => {
+                tracing::warn!(
+                    video_path = %path.display(),
+                    error = %e,
+                    "Failed to quarantine file"
+                );
+                None
+            }
+        })
+        .await;
+    match quarantined {
+        Ok(target) => target.map(|target| target.to_string_lossy().to_string()),
+        Err(e) => {
+            tracing::warn!(error = %e, "Quarantine task failed");
+            None
+        }
+    }
+}
+
+/// Probe a file, run the basic QC checks on it and estimate re-encode savings
+async fn inspect_entry(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    reencode_target: ReencodeTarget,
+) -> FolderEntry {
+    let mut entry = empty_entry(path);
+    let info = match get_video_info_with_ffprobe(app_handle, path).await {
+        Ok(info) => info,
+        Err(e) => {
+            entry.issues.push(format!("Probe failed: {}", e));
+            return entry;
+        }
+    };
+
+    // Audio-only files keep the video columns empty; QC notes the missing video
+    if info.layout.has_video() {
+        entry.codec_name = Some(info.video_stream.codec_name().to_string());
+        entry.resolution = Some(format!("{}x{}", info.width, info.height));
+        entry.frame_rate = Some(info.frame_rate);
+    }
+    entry.duration = Some(info.duration);
+    entry.bit_rate = Some(info.bit_rate);
+    entry.audio_streams = info.audio_streams.len();
+    entry.computed_fields = evaluate_computed_fields(&info.probe_json);
+    entry.issues = qc_issues(&info);
+    // Sampling frames for noise would make large scans far slower
+    entry.estimated_savings = estimate_savings(
+        &EncodedVideo::from_info(&info),
+        entry.file_size,
+        reencode_target,
+        None,
+    )
+    .map(|estimate| estimate.savings_bytes());
+
+    entry
+}
+
+/// Entry with only the path and size filled in
+fn empty_entry(path: &str) -> FolderEntry {
+    FolderEntry {
+        path: path.to_string(),
+        codec_name: None,
+        resolution: None,
+        frame_rate: None,
+        duration: None,
+        bit_rate: None,
+        file_size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        audio_streams: 0,
+        issues: Vec::new(),
+        computed_fields: Vec::new(),
+        sha256: None,
+        quick_hash: None,
+        quarantined: None,
+        estimated_savings: None,
+    }
+}
+
+/// Basic QC problems of a probed file; empty when it passes
+pub fn qc_issues(info: &VideoInfo) -> Vec<String> {
+    let mut issues = Vec::new();
+    if info.duration <= 0.0 {
+        issues.push("Duration is zero or unknown".to_string());
+    }
+    if info.audio_streams.is_empty() {
+        issues.push("No audio stream".to_string());
+    }
+    if !info.layout.has_video() {
+        issues.push("No video stream".to_string());
+    } else if !info.width.is_multiple_of(2) || !info.height.is_multiple_of(2) {
+        issues.push(format!(
+            "Odd dimensions {}x{} break 4:2:0 encoders",
+            info.width, info.height
+        ));
+    }
+    issues
+}
+
+/// Count values, most frequent first
+fn distribution(values: impl Iterator<Item = Option<String>>) -> Vec<DistributionEntry> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for value in values.flatten() {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    let mut distribution: Vec<DistributionEntry> = counts
+        .into_iter()
+        .map(|(value, count)| DistributionEntry { value, count })
+        .collect();
+    distribution.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    distribution
+}
+
+/// Files whose bits per pixel are far from the folder median
+fn find_outliers(entries: &[FolderEntry]) -> Vec<Outlier> {
+    let bits_per_pixel = |entry: &FolderEntry| -> Option<f64> {
+        let (width, height) = entry.resolution.as_deref()?.split_once('x')?;
+        let pixels = width.parse::<f64>().ok()? * height.parse::<f64>().ok()?;
+        let rate = entry.bit_rate? / (pixels * entry.frame_rate?);
+        rate.is_finite().then_some(rate)
+    };
+
+    let mut values: Vec<f64> = entries.iter().filter_map(bits_per_pixel).collect();
+    if values.len() < 3 {
+        return Vec::new();
+    }
+    values.sort_by(f64::total_cmp);
+    let median = values[values.len() / 2];
+    if median <= 0.0 {
+        return Vec::new();
+    }
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let value = bits_per_pixel(entry)?;
+            let ratio = value / median;
+            if ratio > OUTLIER_FACTOR {
+                Some(format!("{:.1}x the median bit rate per pixel", ratio))
+            } else if ratio < 1.0 / OUTLIER_FACTOR {
+                Some(format!(
+                    "1/{:.1} of the median bit rate per pixel",
+                    1.0 / ratio
+                ))
+            } else {
+                None
+            }
+            .map(|reason| Outlier {
+                path: entry.path.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+fn to_csv(report: &FolderReport, field_names: &[String]) -> String {
+    let mut header =
+        "path,codec,resolution,frame_rate,duration,bit_rate,file_size,audio_streams,issues,sha256,\
+         quick_hash"
+            .to_string();
+    for name in field_names {
+        header.push(',');
+        header.push_str(&csv_field(name));
+    }
+    let mut csv = header + "\n";
+    for entry in &report.entries {
+        let mut fields = vec![
+            entry.path.clone(),
+            entry.codec_name.clone().unwrap_or_default(),
+            entry.resolution.clone().unwrap_or_default(),
+            optional_number(entry.frame_rate),
+            optional_number(entry.duration),
+            optional_number(entry.bit_rate),
+            entry.file_size.to_string(),
+            entry.audio_streams.to_string(),
+            entry.issues.join("; "),
+            entry.sha256.clone().unwrap_or_default(),
+            entry.quick_hash.clone().unwrap_or_default(),
+        ];
+        fields.extend(computed_columns(entry, field_names));
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn to_html(report: &FolderReport, field_names: &[String]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title></head><body>\n",
+        html_escape(&report.folder)
+    );
+    html.push_str(&format!(
+        "<h1>{}</h1>\n<p>{} files, {:.0} s total runtime, {} bytes, {} failing QC</p>\n",
+        html_escape(&report.folder),
+        report.file_count,
+        report.total_runtime,
+        report.total_size,
+        report.failing.len()
+    ));
+
+    for (title, distribution) in [
+        ("Codecs", &report.codecs),
+        ("Resolutions", &report.resolutions),
+        ("Frame rates", &report.frame_rates),
+    ] {
+        html.push_str(&format!("<h2>{}</h2>\n<ul>\n", title));
+        for entry in distribution {
+            html.push_str(&format!(
+                "<li>{}: {}</li>\n",
+                html_escape(&entry.value),
+                entry.count
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    if !report.outliers.is_empty() {
+        html.push_str("<h2>Outliers</h2>\n<ul>\n");
+        for outlier in &report.outliers {
+            html.push_str(&format!(
+                "<li>{}: {}</li>\n",
+                html_escape(&outlier.path),
+                html_escape(&outlier.reason)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str(
+        "<h2>Files</h2>\n<table border=\"1\">\n<tr><th>Path</th><th>Codec</th>\
+         <th>Resolution</th><th>FPS</th><th>Duration</th><th>Bit rate</th>\
+         <th>Size</th><th>Issues</th>",
+    );
+    for name in field_names {
+        html.push_str(&format!("<th>{}</th>", html_escape(name)));
+    }
+    html.push_str("</tr>\n");
+    for entry in &report.entries {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{}</td><td>{}</td>",
+            html_escape(&entry.path),
+            html_escape(entry.codec_name.as_deref().unwrap_or("")),
+            html_escape(entry.resolution.as_deref().unwrap_or("")),
+            optional_number(entry.frame_rate),
+            optional_number(entry.duration),
+            optional_number(entry.bit_rate),
+            entry.file_size,
+            html_escape(&entry.issues.join("; "))
+        ));
+        for value in computed_columns(entry, field_names) {
+            html.push_str(&format!("<td>{}</td>", html_escape(&value)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body></html>\n");
+    html
+}
+
+/// Computed field values lined up with the export columns
+fn computed_columns(entry: &FolderEntry, field_names: &[String]) -> Vec<String> {
+    field_names
+        .iter()
+        .map(|name| {
+            entry
+                .computed_fields
+                .iter()
+                .find(|value| value.name() == name)
+                .map(ComputedValue::display_value)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn optional_number(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.3}", v)).unwrap_or_default()
+}
+
+/// Quote a CSV field when it contains separators, quotes or newlines
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
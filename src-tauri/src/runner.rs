@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+use std::process::{Output, Stdio};
+use tauri_plugin_shell::{
+    process::{Command, CommandChild},
+    ShellExt,
+};
+use video_inspector_core::Error as CoreError;
+
+use crate::inspector::Error;
+use crate::settings;
+
+pub use video_inspector_core::runner::{MediaTool, MediaToolRunner, ToolOutput};
+
+/// Input options that may be added to every ffprobe and ffmpeg run, each
+/// followed by one value; none of them can add outputs, files or filters
+const ALLOWED_INPUT_OPTIONS: [&str; 10] = [
+    "-analyzeduration",
+    "-probesize",
+    "-fpsprobesize",
+    "-fflags",
+    "-err_detect",
+    "-f",
+    "-max_ts_probe",
+    "-skip_initial_bytes",
+    "-ignore_editlist",
+    "-thread_queue_size",
+];
+
+/// Runs the bundled sidecars for the probing and thumbnailing in the core
+/// crate
+#[derive(Clone)]
+pub struct SidecarRunner(tauri::AppHandle);
+
+impl SidecarRunner {
+    pub fn new(app_handle: &tauri::AppHandle) -> Self {
+        SidecarRunner(app_handle.clone())
+    }
+}
+
+#[async_trait]
+impl MediaToolRunner for SidecarRunner {
+    async fn run(&self, tool: MediaTool, args: &[&str]) -> Result<ToolOutput, CoreError> {
+        let failed =
+            |e: Error| CoreError::FFmpegError(format!("Failed to execute {}: {}", tool.name(), e));
+        let output = sidecar(&self.0, tool)
+            .map_err(failed)?
+            .args(args)
+            .output_or_kill()
+            .await
+            .map_err(failed)?;
+        Ok(ToolOutput {
+            success: output.status.success(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
+
+/// Sidecar command for `tool`, starting with the input arguments from
+/// settings
+///
+/// Arguments before the first `-i` apply to the first input, which is the
+/// inspected file in every invocation, and ffprobe takes options anywhere.
+/// Arguments passed later repeat an option to override it.
+pub fn sidecar(app_handle: &tauri::AppHandle, tool: MediaTool) -> Result<Command, Error> {
+    Ok(app_handle
+        .shell()
+        .sidecar(tool.name())?
+        .args(settings::current().input_args))
+}
+
+/// Runs a sidecar command so that cancelling an inspection stops it
+#[async_trait]
+pub trait KillOnDrop {
+    /// Like `Command::output`, but the process is killed when the future is
+    /// dropped instead of running on unattended
+    async fn output_or_kill(self) -> Result<Output, Error>;
+}
+
+#[async_trait]
+impl KillOnDrop for Command {
+    async fn output_or_kill(self) -> Result<Output, Error> {
+        let mut command = tokio::process::Command::from(std::process::Command::from(self));
+        Ok(command
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await?)
+    }
+}
+
+/// A spawned sidecar process, killed when the guard is dropped
+///
+/// Keeps the process from outliving an inspection that was cancelled or
+/// returned early; dropping it after the process exited does nothing.
+pub struct ChildGuard(Option<CommandChild>);
+
+impl ChildGuard {
+    pub fn new(child: CommandChild) -> Self {
+        ChildGuard(Some(child))
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if let Some(child) = self.0.take() {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Check user supplied ffprobe/ffmpeg arguments, e.g. `-probesize 50M`
+///
+/// Only `-option value` pairs of the input options in
+/// `ALLOWED_INPUT_OPTIONS` are accepted, so they can't write files or
+/// change what is run.
+pub fn validate_input_args(args: &[String]) -> Result<(), Error> {
+    if !args.len().is_multiple_of(2) {
+        return Err(Error::ParseError(
+            "Extra arguments must be option and value pairs".to_string(),
+        ));
+    }
+    for pair in args.chunks(2) {
+        let (option, value) = (&pair[0], &pair[1]);
+        if !ALLOWED_INPUT_OPTIONS.contains(&option.as_str()) {
+            return Err(Error::ParseError(format!(
+                "Unsupported extra argument {}, expected one of {}",
+                option,
+                ALLOWED_INPUT_OPTIONS.join(", ")
+            )));
+        }
+        // `-fflags -genpts` style flag removals are the only dash values
+        let flag_removal = option == "-fflags" && value.starts_with('-');
+        if value.is_empty() || (value.starts_with('-') && !flag_removal) {
+            return Err(Error::ParseError(format!("Missing value for {}", option)));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use crate::analyzer::first_stream;
+use crate::bitrate::declared_bit_rate;
+use crate::frames::json_f64;
+use crate::probe::{parse_fraction, VideoInfo};
+
+/// Pixels of a 1080p frame, the size the reference bits per pixel are for
+const REFERENCE_PIXELS: f64 = 1920.0 * 1080.0;
+
+/// Larger frames need fewer bits per pixel for the same quality; the scale
+/// is kept in this range so tiny or huge frames don't run away
+const RESOLUTION_SCALE: std::ops::RangeInclusive<f64> = 0.7..=1.6;
+
+/// Extra bits grain costs per luma step of noise, and the cap on the factor
+const NOISE_COST: f64 = 0.15;
+const MAX_NOISE_FACTOR: f64 = 2.5;
+
+/// H.264-equivalent bits per pixel at 1080p below which video is starved
+/// and above which it is bloated
+const STARVED_BITS_PER_PIXEL: f64 = 0.04;
+const BLOATED_BITS_PER_PIXEL: f64 = 0.25;
+
+/// Codec a file would be re-encoded to
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetCodec {
+    #[default]
+    X265,
+    Av1,
+}
+
+/// Quality a file would be re-encoded at
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetQuality {
+    /// Visually transparent for most content
+    High,
+    #[default]
+    Medium,
+    /// Fine for phones and previews
+    Low,
+}
+
+impl TargetQuality {
+    /// H.264 bits per pixel per frame at 1080p for this quality
+    fn reference_bits_per_pixel(self) -> f64 {
+        match self {
+            TargetQuality::High => 0.12,
+            TargetQuality::Medium => 0.08,
+            TargetQuality::Low => 0.05,
+        }
+    }
+}
+
+/// Encoding re-encode savings are estimated for
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ReencodeTarget {
+    pub codec: TargetCodec,
+    pub quality: TargetQuality,
+}
+
+/// What an estimate is based on, from inspection results or a raw probe
+pub struct EncodedVideo<'a> {
+    codec_name: &'a str,
+    width: f64,
+    height: f64,
+    frame_rate: f64,
+    /// Seconds
+    duration: f64,
+    /// Bits per second of the video stream
+    bit_rate: f64,
+}
+
+impl<'a> EncodedVideo<'a> {
+    pub fn from_info(info: &'a VideoInfo) -> Self {
+        EncodedVideo {
+            codec_name: info.video_stream.codec_name(),
+            width: info.width as f64,
+            height: info.height as f64,
+            frame_rate: info.frame_rate,
+            duration: info.duration,
+            bit_rate: info.video_bit_rate.unwrap_or(info.bit_rate),
+        }
+    }
+
+    /// `None` without a video stream or a known bit rate
+    pub fn from_probe(probe: &'a serde_json::Value) -> Option<Self> {
+        let stream = first_stream(probe, "video")?;
+        Some(EncodedVideo {
+            codec_name: stream["codec_name"].as_str().unwrap_or("unknown"),
+            width: stream["width"].as_f64()?,
+            height: stream["height"].as_f64()?,
+            frame_rate: parse_fraction(stream["avg_frame_rate"].as_str()?).ok()?,
+            duration: json_f64(&probe["format"]["duration"])?,
+            // Falls back to the overall rate, which includes audio
+            bit_rate: declared_bit_rate(stream)
+                .or_else(|| json_f64(&probe["format"]["bit_rate"]))?,
+        })
+    }
+}
+
+/// Rough size saved by re-encoding the video stream; audio and other
+/// streams are assumed to be copied
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SavingsEstimate {
+    target: ReencodeTarget,
+    /// Bits per second of the video stream now
+    current_bit_rate: f64,
+    /// Bits per second the target encoding is expected to need
+    estimated_bit_rate: f64,
+    savings_bytes: u64,
+    /// Savings as a share of the file size
+    savings_ratio: f64,
+    /// Whether a noise measurement went into the estimate
+    noise_considered: bool,
+}
+
+impl SavingsEstimate {
+    pub fn savings_bytes(&self) -> u64 {
+        self.savings_bytes
+    }
+}
+
+/// How a video's bit rate compares with what its resolution, frame rate and
+/// codec usually need
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetVerdict {
+    /// Likely visibly compressed
+    Starved,
+    Typical,
+    /// More bits than the picture can use; a candidate for re-encoding
+    Bloated,
+}
+
+/// Objective quality-budget indicators of the video stream
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct BitRateEfficiency {
+    /// Bits per pixel per frame, as encoded
+    bits_per_pixel: f64,
+    /// Kilobits per second per megapixel of frame size
+    kbps_per_megapixel: f64,
+    /// Bits per pixel normalized to H.264 at 1080p, which the verdict uses
+    normalized_bits_per_pixel: f64,
+    verdict: BudgetVerdict,
+    /// One line explaining the verdict
+    comment: String,
+}
+
+/// Bits per pixel and bit rate per megapixel with a starved/typical/bloated
+/// verdict; `None` without a usable frame size or rate
+pub fn bit_rate_efficiency(video: &EncodedVideo) -> Option<BitRateEfficiency> {
+    let pixels = video.width * video.height;
+    if pixels <= 0.0 || video.frame_rate <= 0.0 || video.bit_rate <= 0.0 {
+        return None;
+    }
+    let bits_per_pixel = video.bit_rate / (pixels * video.frame_rate);
+    let normalized_bits_per_pixel =
+        bits_per_pixel * source_efficiency(video.codec_name) / resolution_scale(pixels);
+    let (verdict, comment) = if normalized_bits_per_pixel < STARVED_BITS_PER_PIXEL {
+        (
+            BudgetVerdict::Starved,
+            format!(
+                "Below {} H.264-equivalent bits per pixel, expect visible compression",
+                STARVED_BITS_PER_PIXEL
+            ),
+        )
+    } else if normalized_bits_per_pixel > BLOATED_BITS_PER_PIXEL {
+        (
+            BudgetVerdict::Bloated,
+            format!(
+                "Above {} H.264-equivalent bits per pixel, re-encoding would save space",
+                BLOATED_BITS_PER_PIXEL
+            ),
+        )
+    } else {
+        (
+            BudgetVerdict::Typical,
+            format!("Typical bit rate for {}", video.codec_name),
+        )
+    };
+    Some(BitRateEfficiency {
+        bits_per_pixel,
+        kbps_per_megapixel: video.bit_rate / 1000.0 / (pixels / 1_000_000.0),
+        normalized_bits_per_pixel,
+        verdict,
+        comment,
+    })
+}
+
+/// Estimate what re-encoding to `target` would save
+///
+/// The target bit rate comes from a bits-per-pixel budget for the quality,
+/// scaled for resolution, codec efficiency and grain, and never exceeds what
+/// the source already spends in the target codec's terms: re-encoding can't
+/// add quality that isn't there. `None` when the video has no usable frame
+/// size, rate or duration.
+pub fn estimate_savings(
+    video: &EncodedVideo,
+    file_size: u64,
+    target: ReencodeTarget,
+    noise_sigma: Option<f64>,
+) -> Option<SavingsEstimate> {
+    let pixels = video.width * video.height;
+    if pixels <= 0.0 || video.frame_rate <= 0.0 || video.duration <= 0.0 {
+        return None;
+    }
+
+    let noise_factor = noise_sigma.map_or(1.0, |sigma| {
+        (1.0 + NOISE_COST * sigma).min(MAX_NOISE_FACTOR)
+    });
+    let target_efficiency = codec_efficiency(target.codec);
+    let budget =
+        target.quality.reference_bits_per_pixel() * resolution_scale(pixels) * noise_factor
+            / target_efficiency
+            * pixels
+            * video.frame_rate;
+    let source_equivalent =
+        video.bit_rate * source_efficiency(video.codec_name) / target_efficiency;
+    let estimated_bit_rate = budget.min(source_equivalent);
+
+    let savings_bytes =
+        ((video.bit_rate - estimated_bit_rate).max(0.0) * video.duration / 8.0) as u64;
+    Some(SavingsEstimate {
+        target,
+        current_bit_rate: video.bit_rate,
+        estimated_bit_rate,
+        savings_bytes,
+        savings_ratio: if file_size > 0 {
+            savings_bytes as f64 / file_size as f64
+        } else {
+            0.0
+        },
+        noise_considered: noise_sigma.is_some(),
+    })
+}
+
+/// Bits per pixel needed relative to 1080p for the same quality
+fn resolution_scale(pixels: f64) -> f64 {
+    (REFERENCE_PIXELS / pixels)
+        .powf(0.25)
+        .clamp(*RESOLUTION_SCALE.start(), *RESOLUTION_SCALE.end())
+}
+
+/// Compression efficiency of a target codec relative to H.264
+fn codec_efficiency(codec: TargetCodec) -> f64 {
+    match codec {
+        TargetCodec::X265 => 1.6,
+        TargetCodec::Av1 => 2.0,
+    }
+}
+
+/// Compression efficiency of a source codec relative to H.264, by ffmpeg
+/// codec name; unknown codecs count as H.264
+fn source_efficiency(codec_name: &str) -> f64 {
+    match codec_name {
+        // Intra-only mezzanine and lossless codecs
+        "prores" | "dnxhd" | "ffv1" | "huffyuv" | "rawvideo" | "mjpeg" | "v210" => 0.25,
+        "mpeg1video" | "mpeg2video" => 0.6,
+        "mpeg4" | "msmpeg4v3" | "wmv3" | "vc1" | "theora" => 0.8,
+        "vp8" => 0.9,
+        "vp9" => 1.5,
+        "hevc" => 1.6,
+        "av1" => 2.0,
+        _ => 1.0,
+    }
+}
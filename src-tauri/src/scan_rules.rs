@@ -0,0 +1,135 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::{fs::Metadata, path::Path};
+
+use crate::inspector::Error;
+
+/// Names of OS metadata files and folders that are never media
+const SYSTEM_NAMES: [&str; 6] = [
+    "Thumbs.db",
+    "desktop.ini",
+    "$RECYCLE.BIN",
+    "System Volume Information",
+    "lost+found",
+    "Icon\r",
+];
+
+/// Which files folder scans pick up
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ScanRules {
+    /// Globs matched against paths relative to the scanned folder, e.g.
+    /// `**/*.mkv`; empty includes every file
+    pub include: Vec<String>,
+    /// Globs of files and folders to leave out, e.g. `**/Render Cache`
+    pub exclude: Vec<String>,
+    /// Smaller files are skipped, in bytes
+    pub min_file_size: u64,
+    /// Skip dotfiles, OS metadata like `Thumbs.db` and files marked hidden or
+    /// system
+    pub ignore_hidden: bool,
+    /// Seconds a file's size must stay unchanged before it is inspected, so
+    /// files still being copied are waited for; 0 inspects them right away
+    pub stability_delay_secs: u64,
+}
+
+impl Default for ScanRules {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            // Downloads and renders in progress
+            exclude: ["**/*.part", "**/*.crdownload", "**/*.tmp"]
+                .map(str::to_string)
+                .to_vec(),
+            min_file_size: 0,
+            ignore_hidden: true,
+            stability_delay_secs: 5,
+        }
+    }
+}
+
+/// `ScanRules` with the globs compiled
+pub struct CompiledScanRules {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    min_file_size: u64,
+    ignore_hidden: bool,
+}
+
+impl ScanRules {
+    /// Compile the globs, failing on the first invalid one
+    pub fn compile(&self) -> Result<CompiledScanRules, Error> {
+        Ok(CompiledScanRules {
+            include: if self.include.is_empty() {
+                None
+            } else {
+                Some(glob_set(&self.include)?)
+            },
+            exclude: glob_set(&self.exclude)?,
+            min_file_size: self.min_file_size,
+            ignore_hidden: self.ignore_hidden,
+        })
+    }
+}
+
+impl CompiledScanRules {
+    /// Whether to look inside a folder; `relative` is its path below the
+    /// scanned folder
+    pub fn allows_dir(&self, relative: &Path, metadata: &Metadata) -> bool {
+        !self.excludes(relative, metadata)
+    }
+
+    /// Whether a file is picked up; `relative` is its path below the scanned
+    /// folder
+    pub fn allows_file(&self, relative: &Path, metadata: &Metadata) -> bool {
+        metadata.len() >= self.min_file_size
+            && !self.excludes(relative, metadata)
+            && self
+                .include
+                .as_ref()
+                .is_none_or(|include| include.is_match(relative))
+    }
+
+    fn excludes(&self, relative: &Path, metadata: &Metadata) -> bool {
+        (self.ignore_hidden && is_hidden(relative, metadata)) || self.exclude.is_match(relative)
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet, Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| Error::ParseError(format!("Invalid glob '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| Error::ParseError(format!("Invalid glob set: {}", e)))
+}
+
+/// Dotfiles, OS metadata, and on Windows anything with the hidden or system
+/// attribute
+fn is_hidden(path: &Path, metadata: &Metadata) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if name.starts_with('.')
+        || SYSTEM_NAMES
+            .iter()
+            .any(|system| name.eq_ignore_ascii_case(system))
+    {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+        if metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0 {
+            return true;
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = metadata;
+    false
+}
@@ -0,0 +1,129 @@
+use rhai::{Dynamic, Engine, Scope};
+
+use crate::analyzer::first_stream;
+use crate::probe::parse_fraction;
+use crate::settings;
+
+/// Script operations allowed per field, so a runaway loop can't stall an
+/// inspection
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// A user-defined field computed from the ffprobe JSON by a rhai script
+///
+/// Scripts see `format`, `streams`, and `video` / `audio` (the first stream
+/// of that type, or `()`). ffprobe reports most numbers as strings; `num(x)`
+/// converts them and `fraction("30000/1001")` evaluates rates. A script
+/// returning a bool is a pass/fail rule.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ComputedField {
+    pub name: String,
+    pub script: String,
+}
+
+/// Result of a computed field for one file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ComputedValue {
+    name: String,
+    value: Option<serde_json::Value>,
+    /// Compile or runtime error of the script
+    error: Option<String>,
+}
+
+impl ComputedValue {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Value as shown in exports; rules read "pass" or "fail"
+    pub fn display_value(&self) -> String {
+        if let Some(error) = &self.error {
+            return format!("error: {}", error);
+        }
+        match &self.value {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::Bool(true)) => "pass".to_string(),
+            Some(serde_json::Value::Bool(false)) => "fail".to_string(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        }
+    }
+}
+
+/// Evaluate the computed fields from settings against a probe
+pub fn evaluate_computed_fields(probe: &serde_json::Value) -> Vec<ComputedValue> {
+    let fields = settings::current().computed_fields;
+    if fields.is_empty() {
+        return Vec::new();
+    }
+
+    let engine = engine();
+    fields
+        .into_iter()
+        .map(|field| {
+            let (value, error) = match evaluate(&engine, &field.script, probe) {
+                Ok(value) => (Some(value), None),
+                Err(e) => {
+                    tracing::debug!(field = %field.name, error = %e, "Computed field failed");
+                    (None, Some(e))
+                }
+            };
+            ComputedValue {
+                name: field.name,
+                value,
+                error,
+            }
+        })
+        .collect()
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.register_fn("num", num);
+    engine.register_fn("fraction", |text: &str| {
+        parse_fraction(text).unwrap_or(f64::NAN)
+    });
+    engine
+}
+
+fn evaluate(
+    engine: &Engine,
+    script: &str,
+    probe: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    let ast = engine.compile(script).map_err(|e| e.to_string())?;
+
+    let null = serde_json::Value::Null;
+    let mut scope = Scope::new();
+    for (name, value) in [
+        ("format", &probe["format"]),
+        ("streams", &probe["streams"]),
+        ("video", first_stream(probe, "video").unwrap_or(&null)),
+        ("audio", first_stream(probe, "audio").unwrap_or(&null)),
+    ] {
+        let value = rhai::serde::to_dynamic(value).map_err(|e| e.to_string())?;
+        scope.push_constant_dynamic(name, value);
+    }
+
+    let result: Dynamic = engine
+        .eval_ast_with_scope(&mut scope, &ast)
+        .map_err(|e| e.to_string())?;
+    rhai::serde::from_dynamic(&result).map_err(|e| e.to_string())
+}
+
+/// Number from an ffprobe value, which may be a string; NaN when it isn't one
+fn num(value: Dynamic) -> f64 {
+    if let Ok(float) = value.as_float() {
+        float
+    } else if let Ok(int) = value.as_int() {
+        int as f64
+    } else if value.is_string() {
+        value
+            .into_string()
+            .ok()
+            .and_then(|text| text.trim().parse().ok())
+            .unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
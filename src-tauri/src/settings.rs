@@ -0,0 +1,273 @@
+use rand::{distr::Alphanumeric, Rng};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{OnceLock, RwLock},
+};
+use tauri::Manager;
+
+use crate::analyzer::ExternalAnalyzer;
+use crate::get_app_handle;
+use crate::hooks::Hook;
+use crate::inspector::Error;
+use crate::preset::InspectionPreset;
+use crate::quarantine::Quarantine;
+use crate::runner::validate_input_args;
+use crate::savings::ReencodeTarget;
+use crate::scan_rules::ScanRules;
+use crate::scripting::ComputedField;
+use crate::thumbnail::TimestampOverlay;
+use crate::whisper::WhisperConfig;
+
+/// Name of the settings file inside the app config directory
+const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// Bundle identifier, which names the app config directory
+const APP_IDENTIFIER: &str = "com.arc.video-inspector";
+
+/// Default cache size cap (1 GiB)
+const DEFAULT_CACHE_MAX_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Default localhost port of the automation API
+const DEFAULT_API_PORT: u16 = 17_321;
+
+/// Default number of files a batch inspection works on at once
+const DEFAULT_BATCH_CONCURRENCY: usize = 2;
+
+/// Length of generated automation API tokens
+const API_TOKEN_LEN: usize = 32;
+
+/// Format of the log file; changes apply on the next start
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileLogFormat {
+    /// Console logging only
+    #[default]
+    Off,
+    Text,
+    /// One JSON object per line, for jq or log pipelines
+    Json,
+}
+
+/// User settings persisted as JSON in the app config directory
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Where cached thumbnails are stored; `None` uses the app cache
+    /// directory
+    pub cache_dir: Option<PathBuf>,
+    /// Least recently used cache entries are evicted beyond this size
+    pub cache_max_bytes: u64,
+    /// Serve the automation API on localhost
+    pub api_enabled: bool,
+    pub api_port: u16,
+    /// Bearer token required by the automation API, generated when enabling
+    /// it without one
+    pub api_token: Option<String>,
+    /// Commands run after each successful inspection
+    pub hooks: Vec<Hook>,
+    /// Command line analyzers offered next to the built-in ones
+    pub external_analyzers: Vec<ExternalAnalyzer>,
+    /// Scripted fields added to inspection results and folder reports
+    pub computed_fields: Vec<ComputedField>,
+    /// Log the complete ffprobe output of every inspection; only a summary
+    /// is logged otherwise
+    pub trace_ffprobe_output: bool,
+    /// Replace file paths and hashes in logs with anonymized IDs
+    pub log_privacy: bool,
+    /// Salt for the anonymized IDs, generated when enabling log privacy
+    pub log_privacy_salt: Option<String>,
+    /// Write a daily rotated log file in the app data directory
+    pub file_log: FileLogFormat,
+    /// Send crash and error reports with the OS, ffmpeg version and file size
+    /// range; never paths
+    pub error_reporting: bool,
+    /// Steps run by `get_video_metadata` when the call doesn't pick a preset
+    pub inspection_preset: InspectionPreset,
+    /// Which files folder scans pick up
+    pub scan_rules: ScanRules,
+    /// Where folder scans put files failing QC
+    pub quarantine: Quarantine,
+    /// Local whisper.cpp for the speech analyzers; they fail until it's set
+    pub whisper: Option<WhisperConfig>,
+    /// Flag faces and text overlays in thumbnails, to help pick a poster frame
+    pub thumbnail_hints: bool,
+    /// Burn the timestamp into generated thumbnails
+    pub thumbnail_timestamps: Option<TimestampOverlay>,
+    /// Encoding that re-encode savings are estimated for
+    pub reencode_target: ReencodeTarget,
+    /// Files `inspect_videos_batch` works on at once when the call doesn't
+    /// say
+    pub batch_concurrency: usize,
+    /// Input options passed to every ffprobe and ffmpeg run, e.g.
+    /// `["-probesize", "50M"]` for broken transport streams
+    pub input_args: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            cache_max_bytes: DEFAULT_CACHE_MAX_BYTES,
+            api_enabled: false,
+            api_port: DEFAULT_API_PORT,
+            api_token: None,
+            hooks: Vec::new(),
+            external_analyzers: Vec::new(),
+            computed_fields: Vec::new(),
+            trace_ffprobe_output: false,
+            log_privacy: false,
+            log_privacy_salt: None,
+            file_log: FileLogFormat::Off,
+            error_reporting: false,
+            inspection_preset: InspectionPreset::default(),
+            scan_rules: ScanRules::default(),
+            quarantine: Quarantine::default(),
+            whisper: None,
+            thumbnail_hints: false,
+            thumbnail_timestamps: None,
+            reencode_target: ReencodeTarget::default(),
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            input_args: Vec::new(),
+        }
+    }
+}
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+
+/// Current settings, loaded from disk on first use
+pub fn current() -> Settings {
+    let lock = SETTINGS.get_or_init(|| RwLock::new(load()));
+    lock.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Read settings before the app starts, for the logging setup
+///
+/// Mirrors `app_config_dir()`, which needs an app handle. Nothing is logged
+/// since logging isn't up yet; any problem just yields the defaults.
+pub fn read_before_startup() -> Settings {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join(SETTINGS_FILE_NAME))
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Return the current settings
+#[tauri::command]
+pub async fn get_settings() -> Result<Settings, String> {
+    Ok(current())
+}
+
+/// Replace the settings and persist them
+#[tauri::command]
+pub async fn update_settings(settings: Settings) -> Result<Settings, String> {
+    update_settings_async(settings).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to update settings");
+        e.localized()
+    })
+}
+
+async fn update_settings_async(mut settings: Settings) -> Result<Settings, Error> {
+    if settings.api_enabled && settings.api_token.as_deref().is_none_or(str::is_empty) {
+        settings.api_token = Some(generate_token());
+    }
+    if settings.log_privacy
+        && settings
+            .log_privacy_salt
+            .as_deref()
+            .is_none_or(str::is_empty)
+    {
+        settings.log_privacy_salt = Some(generate_token());
+    }
+    // Report bad globs now rather than on the next scan
+    settings.scan_rules.compile()?;
+    settings.quarantine.validate()?;
+    validate_input_args(&settings.input_args)?;
+    if let Some(cache_dir) = &settings.cache_dir {
+        // Fail now rather than on the first cache write
+        fs::create_dir_all(cache_dir)?;
+    }
+
+    save(&settings)?;
+    let lock = SETTINGS.get_or_init(|| RwLock::new(Settings::default()));
+    *lock.write().unwrap_or_else(|e| e.into_inner()) = settings.clone();
+
+    // Keep secrets out of the logs
+    let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "<redacted>".to_string());
+    let logged = Settings {
+        api_token: redacted(&settings.api_token),
+        log_privacy_salt: redacted(&settings.log_privacy_salt),
+        ..settings.clone()
+    };
+    tracing::info!(settings = ?logged, "Settings updated");
+
+    // A lower cap takes effect immediately
+    crate::cache::enforce_size_limit();
+    crate::api::apply_settings();
+    crate::privacy::apply_settings();
+    crate::error_reporting::apply_settings();
+
+    Ok(settings)
+}
+
+/// Random alphanumeric token for the automation API and log anonymization
+fn generate_token() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(API_TOKEN_LEN)
+        .map(char::from)
+        .collect()
+}
+
+fn settings_path() -> Result<PathBuf, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| Error::IoError(std::io::Error::other(e.to_string())))?;
+    Ok(config_dir.join(SETTINGS_FILE_NAME))
+}
+
+/// Read settings from disk, falling back to defaults when missing or invalid
+fn load() -> Settings {
+    let path = match settings_path() {
+        Ok(path) => path,
+        Err(e) => {
+            tracing::warn!(error = %e, "Settings path unavailable, using defaults");
+            return Settings::default();
+        }
+    };
+
+    match fs::read(&path) {
+        Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|e| {
+            tracing::warn!(
+                path = %path.display(),
+                error = %e,
+                "Invalid settings file, using defaults"
+            );
+            Settings::default()
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+        Err(e) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %e,
+                "Failed to read settings, using defaults"
+            );
+            Settings::default()
+        }
+    }
+}
+
+fn save(settings: &Settings) -> Result<(), Error> {
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let data = serde_json::to_vec_pretty(settings)
+        .map_err(|e| Error::ParseError(format!("Failed to serialize settings: {}", e)))?;
+    fs::write(path, data)?;
+    Ok(())
+}
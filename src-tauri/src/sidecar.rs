@@ -0,0 +1,294 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::inspector::Error;
+use crate::probe::{parse_fraction, VideoInfo};
+
+/// Sidecars larger than this aren't metadata files and are skipped
+const MAX_SIDECAR_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Claimed durations within this many seconds, or this fraction of the
+/// measured one, are taken to agree
+const DURATION_TOLERANCE_SECS: f64 = 2.0;
+const DURATION_TOLERANCE_RATIO: f64 = 0.01;
+
+/// Metadata sidecar formats read next to a video
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarKind {
+    /// Kodi/Jellyfin `.nfo`
+    Nfo,
+    /// Camera clip metadata, e.g. Sony `C0001M01.XML`
+    Xml,
+    /// Adobe XMP
+    Xmp,
+}
+
+/// What a sidecar says about the video
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct SidecarClaims {
+    /// Seconds
+    duration: Option<f64>,
+    width: Option<u64>,
+    height: Option<u64>,
+    codec: Option<String>,
+    /// The duration is only given in whole minutes, like an NFO `runtime`
+    #[serde(skip)]
+    duration_in_minutes: bool,
+}
+
+/// A sidecar found next to a video, and where it disagrees with the file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct SidecarCheck {
+    path: String,
+    kind: SidecarKind,
+    claims: SidecarClaims,
+    /// Claims that don't match the measured values, or why the sidecar
+    /// couldn't be read
+    warnings: Vec<String>,
+}
+
+/// Read the `.nfo`, `.xml` and `.xmp` sidecars of a video and compare what
+/// they claim with the measured values
+///
+/// Sidecars are matched by file name, ignoring case: `clip.nfo`,
+/// `clip.xmp`, `clip.xml` and Sony's `clipM01.xml`.
+pub fn check_sidecars(path: &str, info: &VideoInfo) -> Vec<SidecarCheck> {
+    let mut checks = Vec::new();
+    for (sidecar, kind) in find_sidecars(Path::new(path)) {
+        let (claims, mut warnings) = match read_claims(&sidecar) {
+            Ok(claims) => (claims, Vec::new()),
+            Err(e) => (
+                SidecarClaims::default(),
+                vec![format!("Couldn't read sidecar: {}", e)],
+            ),
+        };
+        warnings.extend(discrepancies(&claims, info));
+        if !warnings.is_empty() {
+            tracing::debug!(
+                video_path = %path,
+                sidecar = %sidecar.display(),
+                warnings = warnings.len(),
+                "Sidecar disagrees with the file"
+            );
+        }
+        checks.push(SidecarCheck {
+            path: sidecar.to_string_lossy().to_string(),
+            kind,
+            claims,
+            warnings,
+        });
+    }
+    checks
+}
+
+fn find_sidecars(path: &Path) -> Vec<(PathBuf, SidecarKind)> {
+    let (Some(dir), Some(stem)) = (path.parent(), path.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_lowercase();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut sidecars: Vec<(PathBuf, SidecarKind)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            let kind = match name.strip_prefix(&stem)? {
+                ".nfo" => SidecarKind::Nfo,
+                ".xmp" => SidecarKind::Xmp,
+                ".xml" | "m01.xml" => SidecarKind::Xml,
+                _ => return None,
+            };
+            let is_small = entry
+                .metadata()
+                .is_ok_and(|metadata| metadata.is_file() && metadata.len() <= MAX_SIDECAR_BYTES);
+            is_small.then(|| (entry.path(), kind))
+        })
+        .collect();
+    sidecars.sort_by(|a, b| a.0.cmp(&b.0));
+    sidecars
+}
+
+/// Pick the claims out of a sidecar by element and attribute names, which
+/// covers NFO, XMP and camera XML without a schema for each
+fn read_claims(path: &Path) -> Result<SidecarClaims, Error> {
+    let text = fs::read_to_string(path)?;
+    let mut reader = Reader::from_str(&text);
+    reader.config_mut().trim_text(true);
+
+    let mut claims = SidecarClaims::default();
+    let mut minutes: Option<f64> = None;
+    // Sony clips give the duration in frames and the frame rate separately
+    let (mut duration_value, mut duration_scale, mut frame_rate) = (None, None, None);
+    let mut stack: Vec<String> = Vec::new();
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| Error::ParseError(format!("Invalid XML: {}", e)))?;
+        match event {
+            Event::Start(element) => {
+                read_attributes(
+                    &element,
+                    &mut claims,
+                    &mut duration_value,
+                    &mut duration_scale,
+                    &mut frame_rate,
+                );
+                stack.push(local_name(&element));
+            }
+            Event::Empty(element) => read_attributes(
+                &element,
+                &mut claims,
+                &mut duration_value,
+                &mut duration_scale,
+                &mut frame_rate,
+            ),
+            Event::End(_) => {
+                stack.pop();
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|e| Error::ParseError(format!("Invalid XML text: {}", e)))?;
+                let text = text.trim();
+                let parent = stack.iter().rev().nth(1).map(String::as_str);
+                let in_video = stack.iter().any(|name| name == "video");
+                match stack.last().map(String::as_str) {
+                    // Sometimes written as "120 min"
+                    Some("runtime") => {
+                        minutes = text.split_whitespace().next().and_then(|m| m.parse().ok())
+                    }
+                    Some("durationinseconds") => claims.duration = text.parse().ok(),
+                    Some("width") if in_video => claims.width = text.parse().ok(),
+                    Some("height") if in_video => claims.height = text.parse().ok(),
+                    Some("codec") if in_video => claims.codec = Some(text.to_lowercase()),
+                    // XMP written as elements rather than attributes
+                    Some("value") if parent == Some("duration") => {
+                        duration_value = text.parse().ok()
+                    }
+                    Some("scale") if parent == Some("duration") => {
+                        duration_scale = parse_fraction(text).ok()
+                    }
+                    Some("w") if parent == Some("videoframesize") => {
+                        claims.width = text.parse().ok()
+                    }
+                    Some("h") if parent == Some("videoframesize") => {
+                        claims.height = text.parse().ok()
+                    }
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if claims.duration.is_none() {
+        claims.duration = match (duration_value, duration_scale, frame_rate) {
+            (Some(value), Some(scale), _) => Some(value * scale),
+            (Some(frames), None, Some(rate)) if rate > 0.0 => Some(frames / rate),
+            (Some(seconds), None, None) => Some(seconds),
+            _ => minutes.map(|minutes| {
+                claims.duration_in_minutes = true;
+                minutes * 60.0
+            }),
+        };
+    }
+    Ok(claims)
+}
+
+/// Claims carried in attributes: XMP `xmpDM:duration` and
+/// `xmpDM:videoFrameSize`, Sony `Duration`, `VideoLayout` and `VideoFrame`
+fn read_attributes(
+    element: &BytesStart,
+    claims: &mut SidecarClaims,
+    duration_value: &mut Option<f64>,
+    duration_scale: &mut Option<f64>,
+    frame_rate: &mut Option<f64>,
+) {
+    let name = local_name(element);
+    for attribute in element.attributes().filter_map(Result::ok) {
+        let key = String::from_utf8_lossy(attribute.key.local_name().into_inner()).to_lowercase();
+        let Ok(value) = attribute.unescape_value() else {
+            continue;
+        };
+        match (name.as_str(), key.as_str()) {
+            ("duration", "value") => *duration_value = value.parse().ok(),
+            ("duration", "scale") => *duration_scale = parse_fraction(&value).ok(),
+            ("videoframesize", "w") => claims.width = value.parse().ok(),
+            ("videoframesize", "h") => claims.height = value.parse().ok(),
+            ("videolayout", "pixel") => claims.width = value.parse().ok(),
+            ("videolayout", "numofverticalline") => claims.height = value.parse().ok(),
+            // "29.97p", "50i"
+            ("videoframe", "capturefps") => {
+                *frame_rate = value.trim_end_matches(['p', 'i', 'P', 'I']).parse().ok()
+            }
+            _ => {}
+        }
+    }
+}
+
+fn discrepancies(claims: &SidecarClaims, info: &VideoInfo) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(claimed) = claims.duration.filter(|_| info.duration > 0.0) {
+        let tolerance = if claims.duration_in_minutes {
+            60.0
+        } else {
+            DURATION_TOLERANCE_SECS.max(info.duration * DURATION_TOLERANCE_RATIO)
+        };
+        if (claimed - info.duration).abs() > tolerance {
+            warnings.push(format!(
+                "Runtime is {:.0} s but the file is {:.0} s",
+                claimed, info.duration
+            ));
+        }
+    }
+    let resolution_differs = claims.width.is_some_and(|width| width != info.width as u64)
+        || claims
+            .height
+            .is_some_and(|height| height != info.height as u64);
+    if resolution_differs {
+        warnings.push(format!(
+            "Resolution is {}x{} but the file is {}x{}",
+            claims
+                .width
+                .map(|w| w.to_string())
+                .unwrap_or("?".to_string()),
+            claims
+                .height
+                .map(|h| h.to_string())
+                .unwrap_or("?".to_string()),
+            info.width,
+            info.height
+        ));
+    }
+    if let Some(codec) = &claims.codec {
+        let measured = info.video_stream.codec_name();
+        if normalize_codec(codec) != normalize_codec(measured) {
+            warnings.push(format!("Codec is {} but the file is {}", codec, measured));
+        }
+    }
+    warnings
+}
+
+/// Common spellings of the same codec, as written by media managers
+fn normalize_codec(codec: &str) -> String {
+    match codec.to_lowercase().as_str() {
+        "avc" | "avc1" | "x264" | "h.264" => "h264".to_string(),
+        "h265" | "h.265" | "x265" | "hvc1" | "hev1" => "hevc".to_string(),
+        "xvid" | "divx" | "dx50" => "mpeg4".to_string(),
+        "mpeg2" => "mpeg2video".to_string(),
+        "vc-1" | "wvc1" => "vc1".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn local_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.local_name().into_inner()).to_lowercase()
+}
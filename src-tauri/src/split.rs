@@ -0,0 +1,246 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::disk::ensure_free_space;
+use crate::events::emit_ffmpeg_progress;
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::hash::HashKind;
+use crate::inspector::{hash_file, run_ffprobe_json, Error};
+use crate::job::new_job_id;
+use crate::progress::run_ffmpeg_with_progress;
+use crate::temp::temp_frame_path;
+
+/// Most parts a file can be split into
+const MAX_PARTS: usize = 1000;
+
+/// Where to cut a file
+///
+/// Stream copy can only cut on keyframes, so each part starts at the first
+/// keyframe at or after the requested position.
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SplitMode {
+    /// Parts of (nearly) equal duration
+    EqualParts { parts: usize },
+    /// One part per chapter
+    Chapters,
+    /// A new part every `seconds`
+    Interval { seconds: f64 },
+}
+
+/// One file written by a split
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SplitSegment {
+    path: String,
+    /// Position of the part in the source, in seconds
+    start: f64,
+    end: f64,
+    size: u64,
+    sha256: String,
+}
+
+/// Files written by a split, also saved as `<name>_manifest.json`
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SplitManifest {
+    job_id: String,
+    source: String,
+    manifest_path: String,
+    segments: Vec<SplitSegment>,
+}
+
+/// Split a file into parts with `-f segment -c copy`
+///
+/// Parts are written as `<name>_partNNN.<ext>` into `output_dir`, defaulting
+/// to the input's directory. Progress is emitted as
+/// `inspection://ffmpeg-progress` events, then hashing progress as
+/// `inspection://hash-progress` events.
+#[tauri::command]
+pub async fn split_file(
+    path: String,
+    mode: SplitMode,
+    output_dir: Option<String>,
+    job_id: Option<String>,
+) -> Result<SplitManifest, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    split_file_async(&path, &mode, output_dir.as_deref(), job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Split failed");
+            e.localized()
+        })
+}
+
+async fn split_file_async(
+    path: &str,
+    mode: &SplitMode,
+    output_dir: Option<&str>,
+    job_id: String,
+) -> Result<SplitManifest, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let input = Path::new(path);
+    let output_dir = match output_dir {
+        Some(output_dir) => PathBuf::from(output_dir),
+        None => input.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let extension = input
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mkv".to_string());
+    let pattern = output_dir.join(format!("{}_part%03d.{}", stem, extension));
+    let manifest_path = output_dir.join(format!("{}_manifest.json", stem));
+
+    let first_part = output_dir.join(format!("{}_part000.{}", stem, extension));
+    if first_part.exists() || manifest_path.exists() {
+        return Err(Error::FFmpegError(format!(
+            "{} already contains parts of {}",
+            output_dir.display(),
+            stem
+        )));
+    }
+    // The parts together are as large as the input
+    ensure_free_space(&output_dir, fs::metadata(path)?.len())?;
+
+    let json = run_ffprobe_json(app_handle, path, &["-show_format", "-show_chapters"]).await?;
+    let duration = json_f64(&json["format"]["duration"]);
+
+    let split_args = match mode {
+        SplitMode::EqualParts { parts } => {
+            let parts = (*parts).clamp(1, MAX_PARTS);
+            let duration = duration.ok_or_else(|| {
+                Error::ParseError("Duration unknown, can't split into equal parts".to_string())
+            })?;
+            let times: Vec<f64> = (1..parts)
+                .map(|i| duration * i as f64 / parts as f64)
+                .collect();
+            segment_times_args(&times)
+        }
+        SplitMode::Chapters => {
+            let times: Vec<f64> = json["chapters"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or(&[])
+                .iter()
+                .filter_map(|chapter| json_f64(&chapter["start_time"]))
+                .filter(|&start| start > 0.0)
+                .collect();
+            if times.is_empty() {
+                return Err(Error::ParseError(format!("{} has no chapters", path)));
+            }
+            segment_times_args(&times)
+        }
+        SplitMode::Interval { seconds } => {
+            if !seconds.is_finite() || *seconds <= 0.0 {
+                return Err(Error::ParseError(format!(
+                    "Invalid split interval {}",
+                    seconds
+                )));
+            }
+            vec!["-segment_time".to_string(), format!("{:.3}", seconds)]
+        }
+    };
+
+    // ffmpeg lists every part it writes, with its start and end time
+    let list_path = temp_frame_path("segment_list", "csv")?;
+    let mut args: Vec<String> = [
+        "-n",
+        "-i",
+        path,
+        "-map",
+        "0:v?",
+        "-map",
+        "0:a?",
+        "-c",
+        "copy",
+        "-f",
+        "segment",
+        "-reset_timestamps",
+        "1",
+        "-segment_list_type",
+        "csv",
+        "-segment_list",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    args.push(list_path.to_string_lossy().to_string());
+    args.extend(split_args);
+    args.push(pattern.to_string_lossy().to_string());
+
+    let start = Instant::now();
+    let run = run_ffmpeg_with_progress(app_handle, &args, duration, |progress| {
+        emit_ffmpeg_progress(&job_id, path, "split", progress)
+    })
+    .await?;
+    if !run.success() {
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg segment split failed: {}",
+            run.stderr.join("\n")
+        )));
+    }
+
+    let list = fs::read_to_string(&list_path)?;
+    let mut segments = Vec::new();
+    for (file_name, part_start, part_end) in parse_segment_list(&list) {
+        let part_path = output_dir.join(file_name).to_string_lossy().to_string();
+        let sha256 = hash_file(&part_path, &job_id, HashKind::Sha256, |_| {}).await?;
+        segments.push(SplitSegment {
+            size: fs::metadata(&part_path)?.len(),
+            path: part_path,
+            start: part_start,
+            end: part_end,
+            sha256,
+        });
+    }
+
+    let manifest = SplitManifest {
+        job_id,
+        source: path.to_string(),
+        manifest_path: manifest_path.to_string_lossy().to_string(),
+        segments,
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| Error::ParseError(format!("Failed to serialize manifest: {}", e)))?;
+    fs::write(&manifest_path, manifest_json)?;
+
+    tracing::info!(
+        video_path = %path,
+        job_id = %manifest.job_id,
+        parts = manifest.segments.len(),
+        elapsed = ?start.elapsed(),
+        "Split finished"
+    );
+
+    Ok(manifest)
+}
+
+fn segment_times_args(times: &[f64]) -> Vec<String> {
+    let times = times
+        .iter()
+        .map(|time| format!("{:.3}", time))
+        .collect::<Vec<_>>()
+        .join(",");
+    vec!["-segment_times".to_string(), times]
+}
+
+/// Parse the segment muxer's CSV list of `file,start,end` lines
+fn parse_segment_list(list: &str) -> Vec<(&str, f64, f64)> {
+    list.lines()
+        .filter_map(|line| {
+            // File names may contain commas, the times never do
+            let (rest, end) = line.rsplit_once(',')?;
+            let (file_name, start) = rest.rsplit_once(',')?;
+            let file_name = file_name.trim_matches('"');
+            Some((file_name, start.parse().ok()?, end.parse().ok()?))
+        })
+        .collect()
+}
@@ -0,0 +1,77 @@
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::inspector::Error;
+
+/// How often a file that is still changing is checked again
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Files still changing after this long are given up on
+const MAX_WAIT: Duration = Duration::from_secs(30 * 60);
+
+/// Wait until a file's size hasn't changed for `period` and no other process
+/// holds it open, so half-copied files aren't reported as corrupt
+///
+/// Files last modified more than `period` ago pass straight away.
+pub async fn wait_until_stable(path: &Path, period: Duration) -> Result<(), Error> {
+    if period.is_zero() {
+        return Ok(());
+    }
+    let start = Instant::now();
+    let mut last_size = None;
+    let mut unchanged_since = Instant::now();
+    loop {
+        let metadata = fs::metadata(path)?;
+        if last_size != Some(metadata.len()) {
+            last_size = Some(metadata.len());
+            unchanged_since = Instant::now();
+        }
+        let since_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .unwrap_or_default();
+        if unchanged_since.elapsed().max(since_modified) >= period && can_open_exclusively(path) {
+            if start.elapsed() >= POLL_INTERVAL {
+                tracing::debug!(
+                    video_path = %path.display(),
+                    waited = ?start.elapsed(),
+                    "File settled"
+                );
+            }
+            return Ok(());
+        }
+        if start.elapsed() >= MAX_WAIT {
+            return Err(Error::IoError(io::Error::other(format!(
+                "{} is still being written",
+                path.display()
+            ))));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Whether the file can be opened without sharing, which fails while a copy
+/// to it is still running
+#[cfg(windows)]
+fn can_open_exclusively(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+    fs::OpenOptions::new()
+        .read(true)
+        .share_mode(0)
+        .open(path)
+        .is_ok()
+}
+
+/// Whether an exclusive lock can be taken; only writers that lock the file
+/// themselves are noticed, the size check catches the rest
+#[cfg(not(windows))]
+fn can_open_exclusively(path: &Path) -> bool {
+    use fs4::fs_std::FileExt;
+    fs::File::open(path)
+        .and_then(|file| FileExt::try_lock_exclusive(&file))
+        .unwrap_or(false)
+}
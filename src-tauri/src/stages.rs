@@ -0,0 +1,140 @@
+use std::sync::{Arc, Mutex};
+
+use crate::events::emit_inspection_progress;
+use crate::preset::Pipeline;
+
+/// Relative time each stage usually takes, used to turn stages into an
+/// overall percentage; skipped stages weigh nothing
+const PROBE_WEIGHT: f64 = 10.0;
+const STREAM_DETAILS_WEIGHT: f64 = 10.0;
+const HASH_WEIGHT: f64 = 30.0;
+const THUMBNAILS_WEIGHT: f64 = 30.0;
+const DEEP_CHECKS_WEIGHT: f64 = 20.0;
+
+/// Phase of an inspection, in the order they run
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum InspectionStage {
+    Probe,
+    /// Bit rate measurement, HDR and AV1 details
+    StreamDetails,
+    Hash,
+    Thumbnails,
+    /// QC, loudness and integrity steps
+    DeepChecks,
+    Done,
+    Failed,
+}
+
+impl InspectionStage {
+    fn is_final(self) -> bool {
+        matches!(self, InspectionStage::Done | InspectionStage::Failed)
+    }
+}
+
+/// One step of an inspection's progress
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct InspectionProgress {
+    pub stage: InspectionStage,
+    /// Progress within the stage, 0 to 1, when the stage can tell
+    pub stage_progress: Option<f64>,
+    /// Progress of the whole inspection, 0 to 100
+    pub percent: f64,
+    /// e.g. "Thumbnail 2 of 4"
+    pub detail: Option<String>,
+}
+
+/// Moves an inspection through its stages and emits `inspection://progress`
+/// at every step
+///
+/// Stages only move forward: entering an earlier stage, or any stage after
+/// `Done` or `Failed`, is ignored. Clones share the state, so callbacks on
+/// other threads can report progress within a stage.
+#[derive(Clone)]
+pub struct StageTracker {
+    job_id: Arc<str>,
+    path: Arc<str>,
+    weights: [(InspectionStage, f64); 5],
+    stage: Arc<Mutex<Option<InspectionStage>>>,
+}
+
+impl StageTracker {
+    pub fn new(job_id: &str, path: &str, pipeline: Pipeline) -> Self {
+        let deep_checks = pipeline.qc || pipeline.loudness || pipeline.integrity_scan;
+        let weight = |enabled: bool, weight: f64| if enabled { weight } else { 0.0 };
+        StageTracker {
+            job_id: job_id.into(),
+            path: path.into(),
+            weights: [
+                (InspectionStage::Probe, PROBE_WEIGHT),
+                (InspectionStage::StreamDetails, STREAM_DETAILS_WEIGHT),
+                (
+                    InspectionStage::Hash,
+                    weight(pipeline.hash.is_some(), HASH_WEIGHT),
+                ),
+                (
+                    InspectionStage::Thumbnails,
+                    weight(pipeline.thumbnails, THUMBNAILS_WEIGHT),
+                ),
+                (
+                    InspectionStage::DeepChecks,
+                    weight(deep_checks, DEEP_CHECKS_WEIGHT),
+                ),
+            ],
+            stage: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Start `stage`, or finish the inspection with `Done` or `Failed`
+    pub fn enter(&self, stage: InspectionStage) {
+        {
+            let mut current = self.stage.lock().unwrap_or_else(|e| e.into_inner());
+            if current.is_some_and(|current| current.is_final() || stage <= current) {
+                tracing::debug!(job_id = %self.job_id, ?stage, "Ignored stage change");
+                return;
+            }
+            *current = Some(stage);
+        }
+        let stage_progress = (!stage.is_final()).then_some(0.0);
+        self.emit(stage, stage_progress, None);
+    }
+
+    /// Report progress within the current stage; `fraction` is 0 to 1
+    pub fn advance(&self, fraction: f64, detail: Option<String>) {
+        let current = *self.stage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(stage) = current.filter(|stage| !stage.is_final()) {
+            self.emit(stage, Some(fraction.clamp(0.0, 1.0)), detail);
+        }
+    }
+
+    fn emit(&self, stage: InspectionStage, stage_progress: Option<f64>, detail: Option<String>) {
+        let total: f64 = self.weights.iter().map(|(_, weight)| weight).sum();
+        let percent = match stage {
+            InspectionStage::Done | InspectionStage::Failed => 100.0,
+            _ => {
+                let done: f64 = self
+                    .weights
+                    .iter()
+                    .filter(|(earlier, _)| *earlier < stage)
+                    .map(|(_, weight)| weight)
+                    .sum();
+                let current = self
+                    .weights
+                    .iter()
+                    .find(|(candidate, _)| *candidate == stage)
+                    .map_or(0.0, |(_, weight)| *weight);
+                (done + current * stage_progress.unwrap_or(0.0)) / total * 100.0
+            }
+        };
+        emit_inspection_progress(
+            &self.job_id,
+            &self.path,
+            InspectionProgress {
+                stage,
+                stage_progress,
+                percent,
+                detail,
+            },
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use serde_json::json;
+use std::{
+    io::{self, BufRead, Write},
+    sync::{Arc, Mutex},
+};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+
+use crate::api::{dispatch, API_COMMANDS};
+use crate::events;
+
+/// Command line flag switching the app into headless JSON-RPC mode
+pub const SERVE_STDIO_FLAG: &str = "--serve-stdio";
+
+/// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Start of the implementation-defined range, used when a command fails
+const COMMAND_FAILED: i64 = -32000;
+
+/// Whether the app was started with `--serve-stdio`
+pub fn requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == SERVE_STDIO_FLAG)
+}
+
+/// Serve JSON-RPC 2.0 requests, one per line on stdin, answering on stdout
+///
+/// Windows are hidden so other tools can use the app as a backend without
+/// opening network ports. Progress and partial results are sent as `event`
+/// notifications. The app exits once stdin is closed and pending requests
+/// have been answered.
+pub fn start(app_handle: &AppHandle) {
+    for window in app_handle.webview_windows().values() {
+        if let Err(e) = window.hide() {
+            tracing::warn!(error = %e, "Failed to hide window in stdio mode");
+        }
+    }
+
+    let stdout = Arc::new(Mutex::new(io::stdout()));
+
+    let events_out = stdout.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut events = events::subscribe();
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let params: serde_json::Value =
+                        serde_json::from_str(&event).unwrap_or_default();
+                    write_message(
+                        &events_out,
+                        &json!({ "jsonrpc": "2.0", "method": "event", "params": params }),
+                    );
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "stdio client too slow, events dropped");
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        // Every request task holds a sender; recv() returns once all are done
+        let (pending_tx, mut pending_rx) = mpsc::channel::<()>(1);
+
+        for line in io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to read stdin");
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let stdout = stdout.clone();
+            let pending = pending_tx.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Some(response) = handle_request(&line).await {
+                    write_message(&stdout, &response);
+                }
+                drop(pending);
+            });
+        }
+
+        drop(pending_tx);
+        pending_rx.blocking_recv();
+        tracing::info!("stdin closed, exiting");
+        app_handle.exit(0);
+    });
+
+    tracing::info!("Serving JSON-RPC on stdio");
+}
+
+/// Handle one request line; notifications (requests without an id) get no
+/// response
+async fn handle_request(line: &str) -> Option<serde_json::Value> {
+    let request: serde_json::Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                serde_json::Value::Null,
+                PARSE_ERROR,
+                e.to_string(),
+            ))
+        }
+    };
+
+    let id = request.get("id").cloned();
+    let response_id = id.clone().unwrap_or_default();
+    let (Some("2.0"), Some(method)) = (request["jsonrpc"].as_str(), request["method"].as_str())
+    else {
+        return Some(error_response(
+            response_id,
+            INVALID_REQUEST,
+            "Expected a JSON-RPC 2.0 request with a method".to_string(),
+        ));
+    };
+
+    let params = request.get("params").cloned().unwrap_or_default();
+    let outcome = if !API_COMMANDS.contains(&method) {
+        Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method)))
+    } else if !(params.is_object() || params.is_null()) {
+        Err((INVALID_PARAMS, "params must be an object".to_string()))
+    } else {
+        dispatch(method, params)
+            .await
+            .map_err(|e| (COMMAND_FAILED, e))
+    };
+
+    // Notifications are executed but never answered
+    id.as_ref()?;
+    Some(match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": response_id, "result": result }),
+        Err((code, message)) => error_response(response_id, code, message),
+    })
+}
+
+fn error_response(id: serde_json::Value, code: i64, message: String) -> serde_json::Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Write one message per line; the lock keeps concurrent writers from
+/// interleaving
+fn write_message(stdout: &Mutex<io::Stdout>, message: &serde_json::Value) {
+    let mut stdout = stdout.lock().unwrap_or_else(|e| e.into_inner());
+    let result = writeln!(stdout, "{}", message).and_then(|_| stdout.flush());
+    if let Err(e) = result {
+        tracing::error!(error = %e, "Failed to write to stdout");
+    }
+}
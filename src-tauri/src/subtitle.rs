@@ -0,0 +1,535 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tauri_plugin_shell::process::CommandEvent;
+
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::runner::{sidecar, ChildGuard, KillOnDrop, MediaTool};
+use crate::tags::tag;
+
+/// Number of cues returned when none is specified
+const DEFAULT_CUE_COUNT: usize = 20;
+
+/// Subtitle codecs stored as images, which can't be previewed as text
+const BITMAP_SUBTITLE_CODECS: [&str; 4] =
+    ["hdmv_pgs_subtitle", "dvd_subtitle", "dvb_subtitle", "xsub"];
+
+/// Subtitle files looked for next to a video
+const EXTERNAL_SUBTITLE_EXTENSIONS: [&str; 5] = ["srt", "ass", "ssa", "vtt", "sub"];
+
+/// Larger files aren't text subtitles and are skipped
+const MAX_EXTERNAL_SUBTITLE_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Cues may end this many seconds after the video without a warning
+const SUBTITLE_OVERRUN_TOLERANCE_SECS: f64 = 1.0;
+
+/// Subtitle track embedded in the container
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct SubtitleStreamInfo {
+    index: u64,
+    codec_name: String,
+    /// Short name users know the format by, e.g. "srt", "ass", "pgs"
+    format: String,
+    /// Stored as images, so it can't be previewed or searched as text
+    bitmap: bool,
+    /// Language tag as stored, e.g. "eng"; `None` when untagged or "und"
+    language: Option<String>,
+    title: Option<String>,
+    forced: bool,
+    default: bool,
+    hearing_impaired: bool,
+}
+
+/// Collect every subtitle stream from the ffprobe JSON
+pub fn parse_subtitle_streams(json: &serde_json::Value) -> Vec<SubtitleStreamInfo> {
+    json["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|stream| stream["codec_type"].as_str() == Some("subtitle"))
+        .map(|stream| {
+            let codec_name = stream["codec_name"].as_str().unwrap_or("unknown");
+            let disposition = |name: &str| stream["disposition"][name].as_u64() == Some(1);
+            SubtitleStreamInfo {
+                index: stream["index"].as_u64().unwrap_or(0),
+                codec_name: codec_name.to_string(),
+                format: subtitle_format(codec_name).to_string(),
+                bitmap: BITMAP_SUBTITLE_CODECS.contains(&codec_name),
+                language: tag(stream, "language").filter(|language| language != "und"),
+                title: tag(stream, "title"),
+                forced: disposition("forced"),
+                default: disposition("default"),
+                hearing_impaired: disposition("hearing_impaired"),
+            }
+        })
+        .collect()
+}
+
+/// Common name of a subtitle codec; ffmpeg's name when there's none
+fn subtitle_format(codec_name: &str) -> &str {
+    match codec_name {
+        "subrip" => "srt",
+        "ssa" => "ass",
+        "hdmv_pgs_subtitle" => "pgs",
+        "dvd_subtitle" => "vobsub",
+        "dvb_subtitle" => "dvb",
+        "mov_text" => "tx3g",
+        "webvtt" => "vtt",
+        "eia_608" => "cea-608",
+        other => other,
+    }
+}
+
+/// A single subtitle cue
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SubtitleCue {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// The first cues of a text subtitle stream
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SubtitlePreview {
+    subtitle_stream: usize,
+    codec_name: String,
+    language: Option<String>,
+    cues: Vec<SubtitleCue>,
+}
+
+/// Return the first cues of a text subtitle stream without extracting the
+/// whole track
+///
+/// `subtitle_stream` is the position among subtitle streams (0 = first track).
+#[tauri::command]
+pub async fn preview_subtitles(
+    path: String,
+    subtitle_stream: Option<usize>,
+    cues: Option<usize>,
+) -> Result<SubtitlePreview, String> {
+    preview_subtitles_async(
+        &path,
+        subtitle_stream.unwrap_or(0),
+        cues.unwrap_or(DEFAULT_CUE_COUNT).max(1),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Subtitle preview failed");
+        e.localized()
+    })
+}
+
+async fn preview_subtitles_async(
+    path: &str,
+    subtitle_stream: usize,
+    cue_count: usize,
+) -> Result<SubtitlePreview, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let probe = sidecar(app_handle, MediaTool::Ffprobe)?
+        .args([
+            "-v",
+            "quiet",
+            "-print_format",
+            "json",
+            "-show_streams",
+            "-select_streams",
+            &format!("s:{}", subtitle_stream),
+            path,
+        ])
+        .output_or_kill()
+        .await?;
+    let json: serde_json::Value = serde_json::from_slice(&probe.stdout)
+        .map_err(|e| Error::ParseError(format!("Failed to parse ffprobe JSON: {}", e)))?;
+    let stream = json["streams"].get(0).ok_or_else(|| {
+        Error::ParseError(format!("Subtitle stream {} not found", subtitle_stream))
+    })?;
+
+    let codec_name = stream["codec_name"]
+        .as_str()
+        .unwrap_or("unknown")
+        .to_string();
+    if BITMAP_SUBTITLE_CODECS.contains(&codec_name.as_str()) {
+        return Err(Error::ParseError(format!(
+            "Subtitle stream {} is image-based ({}) and can't be previewed as text",
+            subtitle_stream, codec_name
+        )));
+    }
+    let language = stream["tags"]["language"].as_str().map(str::to_string);
+
+    // Convert the track to SRT on stdout and stop as soon as enough cues arrived
+    let (mut rx, child) = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args([
+            "-v",
+            "error",
+            "-i",
+            path,
+            "-map",
+            &format!("0:s:{}", subtitle_stream),
+            "-c:s",
+            "srt",
+            "-f",
+            "srt",
+            "-",
+        ])
+        .spawn()?;
+    // Also stops ffmpeg once enough cues arrived; the rest isn't needed
+    let _child = ChildGuard::new(child);
+
+    let mut parser = SrtParser::default();
+    let mut stderr = String::new();
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                parser.push_line(&String::from_utf8_lossy(&line));
+                if parser.cues.len() >= cue_count {
+                    break;
+                }
+            }
+            CommandEvent::Stderr(line) => {
+                stderr.push_str(&String::from_utf8_lossy(&line));
+                stderr.push('\n');
+            }
+            CommandEvent::Terminated(payload) => exit_code = payload.code,
+            _ => {}
+        }
+    }
+
+    let mut cues = parser.finish();
+    if cues.is_empty() && exit_code.is_some_and(|code| code != 0) {
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg subtitle extraction failed: {}",
+            stderr
+        )));
+    }
+    cues.truncate(cue_count);
+
+    Ok(SubtitlePreview {
+        subtitle_stream,
+        codec_name,
+        language,
+        cues,
+    })
+}
+
+/// Incremental SRT parser fed one line at a time
+#[derive(Default)]
+struct SrtParser {
+    cues: Vec<SubtitleCue>,
+    current: Option<SubtitleCue>,
+}
+
+impl SrtParser {
+    fn push_line(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if line.trim().is_empty() {
+            self.flush();
+            return;
+        }
+
+        if let Some((start, end)) = parse_srt_timing(line) {
+            self.flush();
+            self.current = Some(SubtitleCue {
+                start,
+                end,
+                text: String::new(),
+            });
+            return;
+        }
+
+        if let Some(cue) = self.current.as_mut() {
+            if !cue.text.is_empty() {
+                cue.text.push('\n');
+            }
+            cue.text.push_str(line);
+        }
+        // Lines outside a cue are sequence numbers
+    }
+
+    fn flush(&mut self) {
+        if let Some(cue) = self.current.take() {
+            if !cue.text.is_empty() {
+                self.cues.push(cue);
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<SubtitleCue> {
+        self.flush();
+        self.cues
+    }
+}
+
+/// Parse an SRT timing line like `00:01:02,500 --> 00:01:04,000`
+fn parse_srt_timing(line: &str) -> Option<(f64, f64)> {
+    let (start, end) = line.split_once("-->")?;
+    Some((
+        parse_srt_timestamp(start.trim())?,
+        parse_srt_timestamp(end.split_whitespace().next()?)?,
+    ))
+}
+
+/// Parse an SRT timestamp like `01:02:03,456` into seconds
+pub fn parse_srt_timestamp(timestamp: &str) -> Option<f64> {
+    let (hms, millis) = timestamp.split_once([',', '.'])?;
+    let mut parts = hms.split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let millis: f64 = millis.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds + millis / 1000.0)
+}
+
+/// A subtitle file next to a video, checked against it
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct ExternalSubtitle {
+    path: String,
+    /// srt, ass, ssa, vtt, microdvd or vobsub
+    format: String,
+    /// From the file name, e.g. `en` in `movie.en.srt`
+    language: Option<String>,
+    /// utf-8, utf-8-bom, utf-16le, utf-16be, or `None` when the text isn't
+    /// valid in any of them, as with legacy code pages
+    encoding: Option<String>,
+    cue_count: usize,
+    /// Seconds
+    first_cue_start: Option<f64>,
+    last_cue_end: Option<f64>,
+    /// Problems players are likely to show
+    warnings: Vec<String>,
+}
+
+/// Find subtitle files named after a video, like `movie.srt` or
+/// `movie.en.forced.ass`, and check their encoding and timing
+///
+/// MicroDVD `.sub` frame numbers are converted with `frame_rate`; VobSub
+/// `.sub` files are timed from their `.idx`.
+pub fn find_external_subtitles(
+    path: &str,
+    duration: f64,
+    frame_rate: f64,
+) -> Vec<ExternalSubtitle> {
+    let path = Path::new(path);
+    let (Some(dir), Some(stem)) = (path.parent(), path.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_lowercase();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(PathBuf, String, Option<String>)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            let rest = name.strip_prefix(&stem)?.strip_prefix('.')?;
+            let (tags, extension) = rest.rsplit_once('.').unwrap_or(("", rest));
+            if !EXTERNAL_SUBTITLE_EXTENSIONS.contains(&extension) {
+                return None;
+            }
+            let is_small = entry.metadata().is_ok_and(|metadata| {
+                metadata.is_file() && metadata.len() <= MAX_EXTERNAL_SUBTITLE_BYTES
+            });
+            // Two or three letter codes; "forced", "sdh" and the like aren't languages
+            let language = tags
+                .split('.')
+                .find(|tag| {
+                    (2..=3).contains(&tag.len())
+                        && tag.chars().all(|c| c.is_ascii_alphabetic())
+                        && !matches!(*tag, "sdh" | "cc")
+                })
+                .map(str::to_string);
+            is_small.then(|| (entry.path(), extension.to_string(), language))
+        })
+        .collect();
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|(file, extension, language)| {
+            check_external_subtitle(&file, &extension, language, duration, frame_rate)
+        })
+        .collect()
+}
+
+fn check_external_subtitle(
+    path: &Path,
+    extension: &str,
+    language: Option<String>,
+    duration: f64,
+    frame_rate: f64,
+) -> ExternalSubtitle {
+    let mut subtitle = ExternalSubtitle {
+        path: path.to_string_lossy().to_string(),
+        format: extension.to_string(),
+        language,
+        encoding: None,
+        cue_count: 0,
+        first_cue_start: None,
+        last_cue_end: None,
+        warnings: Vec::new(),
+    };
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) => {
+            subtitle.warnings.push(format!("Couldn't read file: {}", e));
+            return subtitle;
+        }
+    };
+
+    let timings = if extension == "sub" && data.starts_with(&[0x00, 0x00, 0x01, 0xBA]) {
+        // MPEG program stream: VobSub images, timed by the .idx next to it
+        subtitle.format = "vobsub".to_string();
+        match fs::read_to_string(path.with_extension("idx")) {
+            Ok(idx) => vobsub_timings(&idx),
+            Err(_) => {
+                subtitle
+                    .warnings
+                    .push("VobSub .idx file is missing".to_string());
+                Vec::new()
+            }
+        }
+    } else {
+        let (text, encoding) = decode_subtitle_text(&data);
+        match &encoding {
+            Some(encoding) if encoding.starts_with("utf-16") => subtitle
+                .warnings
+                .push("UTF-16 text isn't supported by every player; UTF-8 is safer".to_string()),
+            Some(_) => {}
+            None => subtitle
+                .warnings
+                .push("Not UTF-8 or UTF-16; players may show garbled characters".to_string()),
+        }
+        subtitle.encoding = encoding;
+        match extension {
+            "ass" | "ssa" => ass_timings(&text),
+            "sub" => {
+                subtitle.format = "microdvd".to_string();
+                microdvd_timings(&text, frame_rate)
+            }
+            // WebVTT cue timings only differ in allowing the hours to be left out
+            _ => text.lines().filter_map(parse_srt_timing_or_short).collect(),
+        }
+    };
+
+    subtitle.cue_count = timings.len();
+    subtitle.first_cue_start = timings.iter().map(|(start, _)| *start).reduce(f64::min);
+    subtitle.last_cue_end = timings.iter().map(|(_, end)| *end).reduce(f64::max);
+    if timings.is_empty() {
+        subtitle.warnings.push("No cues found".to_string());
+    }
+    let inverted = timings.iter().filter(|(start, end)| end < start).count();
+    if inverted > 0 {
+        subtitle
+            .warnings
+            .push(format!("{} cues end before they start", inverted));
+    }
+    if let Some(last_end) = subtitle
+        .last_cue_end
+        .filter(|end| duration > 0.0 && *end > duration + SUBTITLE_OVERRUN_TOLERANCE_SECS)
+    {
+        subtitle.warnings.push(format!(
+            "Subtitles run to {:.1} s but the video ends at {:.1} s",
+            last_end, duration
+        ));
+    }
+    subtitle
+}
+
+/// Decode subtitle text from its byte order mark, or as UTF-8; the encoding
+/// is `None` when the bytes aren't valid UTF-8
+fn decode_subtitle_text(data: &[u8]) -> (String, Option<String>) {
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    if let Some(rest) = data.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        (
+            String::from_utf8_lossy(rest).to_string(),
+            Some("utf-8-bom".to_string()),
+        )
+    } else if let Some(rest) = data.strip_prefix(&[0xFF, 0xFE]) {
+        (
+            utf16(rest, u16::from_le_bytes),
+            Some("utf-16le".to_string()),
+        )
+    } else if let Some(rest) = data.strip_prefix(&[0xFE, 0xFF]) {
+        (
+            utf16(rest, u16::from_be_bytes),
+            Some("utf-16be".to_string()),
+        )
+    } else {
+        match std::str::from_utf8(data) {
+            Ok(text) => (text.to_string(), Some("utf-8".to_string())),
+            // Timings are ASCII, so they still parse
+            Err(_) => (String::from_utf8_lossy(data).to_string(), None),
+        }
+    }
+}
+
+/// SRT timing, or WebVTT timing with the hours left out
+fn parse_srt_timing_or_short(line: &str) -> Option<(f64, f64)> {
+    parse_srt_timing(line).or_else(|| {
+        let (start, end) = line.split_once("-->")?;
+        Some((
+            parse_srt_timestamp(&format!("00:{}", start.trim()))?,
+            parse_srt_timestamp(&format!("00:{}", end.split_whitespace().next()?))?,
+        ))
+    })
+}
+
+/// Start and end of each `Dialogue:` line, whose times are `H:MM:SS.cc`
+fn ass_timings(text: &str) -> Vec<(f64, f64)> {
+    let time = |value: &str| -> Option<f64> {
+        let mut parts = value.trim().split(':');
+        let hours: f64 = parts.next()?.parse().ok()?;
+        let minutes: f64 = parts.next()?.parse().ok()?;
+        let seconds: f64 = parts.next()?.parse().ok()?;
+        Some(hours * 3600.0 + minutes * 60.0 + seconds)
+    };
+    text.lines()
+        .filter_map(|line| line.trim_start().strip_prefix("Dialogue:"))
+        .filter_map(|fields| {
+            let mut fields = fields.split(',');
+            let _layer = fields.next()?;
+            Some((time(fields.next()?)?, time(fields.next()?)?))
+        })
+        .collect()
+}
+
+/// MicroDVD lines look like `{240}{312}Text`, counted in frames
+fn microdvd_timings(text: &str, frame_rate: f64) -> Vec<(f64, f64)> {
+    if frame_rate <= 0.0 {
+        return Vec::new();
+    }
+    text.lines()
+        .filter_map(|line| {
+            let (start, rest) = line.trim().strip_prefix('{')?.split_once('}')?;
+            let (end, _) = rest.strip_prefix('{')?.split_once('}')?;
+            let start: f64 = start.parse().ok()?;
+            let end: f64 = end.parse().ok()?;
+            Some((start / frame_rate, end / frame_rate))
+        })
+        .collect()
+}
+
+/// Start of each VobSub image from `timestamp: 00:01:02:345, filepos: ...`
+/// lines; images have no end time, so the start stands in for it
+fn vobsub_timings(idx: &str) -> Vec<(f64, f64)> {
+    idx.lines()
+        .filter_map(|line| {
+            let timestamp = line.strip_prefix("timestamp:")?.split(',').next()?.trim();
+            let (hms, millis) = timestamp.rsplit_once(':')?;
+            let start = parse_srt_timestamp(&format!("{}.{}", hms, millis))?;
+            Some((start, start))
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "telemetry")]
+use opentelemetry::trace::TracerProvider;
+#[cfg(feature = "telemetry")]
+use opentelemetry_sdk::{trace::SdkTracerProvider, Resource};
+#[cfg(feature = "telemetry")]
+use std::sync::OnceLock;
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+/// Standard OpenTelemetry variable naming the collector, e.g. `http://localhost:4318`
+#[cfg(feature = "telemetry")]
+const OTLP_ENDPOINT_VAR: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Service name spans are reported under
+#[cfg(feature = "telemetry")]
+const SERVICE_NAME: &str = "video-inspector";
+
+/// Kept so buffered spans can be flushed on exit
+#[cfg(feature = "telemetry")]
+static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+/// Layer exporting spans over OTLP/HTTP, when a collector endpoint is configured
+///
+/// Meant for development: run a local collector (e.g. Jaeger) and start the app
+/// with `OTEL_EXPORTER_OTLP_ENDPOINT` set to see per-phase timings of each
+/// inspection as a trace.
+#[cfg(feature = "telemetry")]
+pub fn otlp_layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    std::env::var(OTLP_ENDPOINT_VAR)
+        .ok()
+        .filter(|endpoint| !endpoint.trim().is_empty())?;
+
+    // The exporter reads the endpoint (and headers, timeouts...) from the environment
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            // Logging isn't up yet
+            eprintln!("Failed to create OTLP span exporter: {}", e);
+            return None;
+        }
+    };
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+    let tracer = provider.tracer(SERVICE_NAME);
+    let _ = PROVIDER.set(provider);
+
+    Some(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Flush spans the batch exporter hasn't sent yet
+#[cfg(feature = "telemetry")]
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!(error = %e, "Failed to flush OTLP spans");
+        }
+    }
+}
+
+/// Builds without the `telemetry` feature never export spans
+#[cfg(not(feature = "telemetry"))]
+pub fn otlp_layer<S>() -> Option<impl Layer<S>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    None::<tracing_subscriber::layer::Identity>
+}
+
+#[cfg(not(feature = "telemetry"))]
+pub fn shutdown() {}
@@ -0,0 +1,94 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+use tauri::Manager;
+use tempfile::TempPath;
+
+use crate::get_app_handle;
+use crate::inspector::Error;
+
+/// Name of the temp subdirectory inside the app cache directory
+const TEMP_DIR_NAME: &str = "tmp";
+
+/// Temp files older than this are considered leftovers from a crashed run
+///
+/// Files are only swept when they're this old so that a second running
+/// instance doesn't lose frames it's still extracting.
+const STALE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// App-specific directory for intermediate files like extracted frames
+pub fn temp_dir() -> Result<PathBuf, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let cache_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| Error::IoError(std::io::Error::other(e.to_string())))?;
+
+    let dir = cache_dir.join(TEMP_DIR_NAME);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Create a uniquely named, empty temp file for an extracted frame or sample
+///
+/// The file is deleted when the returned path is dropped.
+pub fn temp_frame_path(label: &str, extension: &str) -> Result<TempPath, Error> {
+    let file = tempfile::Builder::new()
+        .prefix(&format!("{}_", label))
+        .suffix(&format!(".{}", extension))
+        .tempfile_in(temp_dir()?)?;
+    Ok(file.into_temp_path())
+}
+
+/// Remove stale files left in the temp directory by earlier runs
+///
+/// Called once at startup; failures are logged and otherwise ignored.
+pub fn sweep_stale_temp_files() {
+    let dir = match temp_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::warn!(error = %e, "Temp directory unavailable, skipping cleanup");
+            return;
+        }
+    };
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!(dir = %dir.display(), error = %e, "Failed to read temp directory");
+            return;
+        }
+    };
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let is_stale = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age >= STALE_AGE);
+        if !metadata.is_file() || !is_stale {
+            continue;
+        }
+
+        match fs::remove_file(entry.path()) {
+            Ok(()) => removed += 1,
+            Err(e) => tracing::debug!(
+                file = %entry.path().display(),
+                error = %e,
+                "Failed to remove stale temp file"
+            ),
+        }
+    }
+
+    if removed > 0 {
+        tracing::info!(dir = %dir.display(), removed, "Removed stale temp files");
+    }
+}
@@ -0,0 +1,142 @@
+use std::{sync::Arc, time::Instant};
+use tracing::Instrument;
+
+use crate::cache::{self, cache_key, file_fingerprint};
+use crate::inspector::Error;
+use crate::job::AbortOnDrop;
+use crate::probe::VideoInfo;
+use crate::runner::SidecarRunner;
+use crate::settings;
+use crate::temp::temp_frame_path;
+
+pub use video_inspector_core::thumbnail::{
+    default_time_points, extract_frame, Thumbnail, TimestampOverlay,
+};
+use video_inspector_core::thumbnail::{render_thumbnail, FrameFilter};
+
+/// Bump when thumbnail selection or encoding changes to invalidate the cache
+const THUMBNAIL_CACHE_VERSION: &str = "thumbnail-v1";
+
+/// Generate one thumbnail per time point using ffmpeg sidecar
+///
+/// `on_thumbnail` is called with the thumbnail's position as soon as each one
+/// is ready, which may be out of order; the returned list is sorted.
+pub async fn generate_thumbnails_with_ffmpeg(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    video_info: &VideoInfo,
+    time_points: &[f64],
+    on_thumbnail: impl Fn(usize, &Thumbnail) + Send + Sync + 'static,
+) -> Result<Vec<Thumbnail>, Error> {
+    tracing::debug!(
+        video_path = %path,
+        "Generating {} thumbnails with ffmpeg",
+        time_points.len()
+    );
+
+    let duration = video_info.duration;
+    let thumbnail_filter = Arc::new(FrameFilter::for_thumbnails(
+        video_info,
+        settings::current().thumbnail_timestamps,
+    ));
+    let runner = SidecarRunner::new(app_handle);
+    let detect_hints = settings::current().thumbnail_hints;
+
+    let start = Instant::now();
+
+    let on_thumbnail = Arc::new(on_thumbnail);
+    let mut tasks = vec![];
+
+    // Without a fingerprint the file can't be matched to cache entries
+    let fingerprint = match file_fingerprint(path) {
+        Ok(fingerprint) => Some(fingerprint),
+        Err(e) => {
+            tracing::debug!(video_path = %path, error = %e, "Thumbnail cache disabled");
+            None
+        }
+    };
+
+    for (i, &time_point) in time_points.iter().enumerate() {
+        let runner = runner.clone();
+        let path = path.to_string();
+        let on_thumbnail = on_thumbnail.clone();
+        let thumbnail_filter = thumbnail_filter.clone();
+        let entry_key = fingerprint.as_ref().map(|fingerprint| {
+            cache_key(&[
+                THUMBNAIL_CACHE_VERSION,
+                fingerprint,
+                &format!("{:.3}", time_point),
+                &thumbnail_filter.at(time_point),
+                &thumbnail_filter
+                    .video_stream()
+                    .map_or(String::new(), |index| format!("stream {}", index)),
+                if detect_hints { "hints" } else { "" },
+            ])
+        });
+        // Created here so the spawned task stays a child of the caller's span
+        let span = tracing::info_span!("thumbnail", index = i, time_point);
+        // Guarded so a cancelled inspection stops its thumbnails as well
+        tasks.push(AbortOnDrop::new(tauri::async_runtime::spawn(
+            async move {
+                let task_start = Instant::now();
+                let cached = entry_key
+                    .as_deref()
+                    .and_then(cache::read)
+                    .and_then(|data| serde_json::from_slice::<Thumbnail>(&data).ok());
+
+                let mut thumbnail = match cached {
+                    Some(thumbnail) => thumbnail,
+                    None => {
+                        let temp_image_path = temp_frame_path("thumbnail", "png")?;
+                        let thumbnail = render_thumbnail(
+                            &runner,
+                            &path,
+                            time_point,
+                            duration,
+                            &thumbnail_filter,
+                            detect_hints,
+                            &temp_image_path,
+                        )
+                        .await?;
+                        if let (Some(key), Ok(data)) = (&entry_key, serde_json::to_vec(&thumbnail))
+                        {
+                            if let Err(e) = cache::write(key, &data) {
+                                tracing::debug!(error = %e, "Failed to cache thumbnail");
+                            }
+                        }
+                        thumbnail
+                    }
+                };
+                thumbnail.elapsed_ms = task_start.elapsed().as_millis() as u64;
+                on_thumbnail(i, &thumbnail);
+                Ok::<_, Error>(thumbnail)
+            }
+            .instrument(span),
+        )));
+    }
+
+    // Await in order so thumbnails stay sorted by timestamp
+    let mut thumbnails = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        match task.await {
+            Ok(Ok(thumbnail)) => thumbnails.push(thumbnail),
+            Ok(Err(e)) => {
+                tracing::warn!(video_path = %path, error = %e, "Thumbnail generation failed");
+            }
+            Err(e) => {
+                tracing::warn!(video_path = %path, error = %e, "Thumbnail task failed");
+            }
+        }
+    }
+
+    let elapsed = start.elapsed();
+
+    tracing::debug!(
+        video_path = %path,
+        thumbnails_count = thumbnails.len(),
+        "Successfully generated thumbnails in {:?}",
+        elapsed
+    );
+
+    Ok(thumbnails)
+}
@@ -0,0 +1,426 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use crate::codec::bit_depth;
+use crate::compatibility::{
+    check_target, find_target, probe_for_compatibility, CompatibilityIssue, IssueKind, Target,
+};
+use crate::disk::ensure_free_space;
+use crate::events::emit_ffmpeg_progress;
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::job::new_job_id;
+use crate::progress::run_ffmpeg_with_progress;
+
+/// Number of trailing ffmpeg stderr lines included in a failure message
+const ERROR_CONTEXT_LINES: usize = 5;
+
+/// Highest H.264 level needed for 1080p, preferred when the output fits in it
+const H264_1080P_LEVEL: f64 = 4.1;
+
+/// An ffmpeg command line that makes a file play on a target
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TranscodeSuggestion {
+    target: &'static str,
+    issues: Vec<CompatibilityIssue>,
+    output_path: String,
+    /// ffmpeg arguments, without the program name; empty when nothing needs fixing
+    args: Vec<String>,
+    /// `args` as a shell command line, ready to paste into a terminal
+    command: Option<String>,
+    /// Side effects of the conversion worth knowing before running it
+    notes: Vec<String>,
+}
+
+/// Outcome of running a suggested conversion
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct TranscodeResult {
+    job_id: String,
+    output_path: String,
+    args: Vec<String>,
+}
+
+/// Build the ffmpeg command that fixes every incompatibility with `target`
+///
+/// Streams that are already fine are copied; the output defaults to
+/// `<name>.<target>.<ext>` next to the input.
+#[tauri::command]
+pub async fn suggest_transcode(
+    path: String,
+    target: String,
+    output_path: Option<String>,
+) -> Result<TranscodeSuggestion, String> {
+    suggest_transcode_async(&path, &target, output_path.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Transcode suggestion failed");
+            e.localized()
+        })
+}
+
+/// Run the command from `suggest_transcode`
+///
+/// Progress is emitted as `inspection://ffmpeg-progress` events tied to
+/// `job_id`. An existing output file is never overwritten.
+#[tauri::command]
+pub async fn run_transcode(
+    path: String,
+    target: String,
+    output_path: Option<String>,
+    job_id: Option<String>,
+) -> Result<TranscodeResult, String> {
+    let job_id = job_id.unwrap_or_else(new_job_id);
+    run_transcode_async(&path, &target, output_path.as_deref(), job_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(video_path = %path, error = %e, "Transcode failed");
+            e.localized()
+        })
+}
+
+async fn suggest_transcode_async(
+    path: &str,
+    target_id: &str,
+    output_path: Option<&str>,
+) -> Result<TranscodeSuggestion, Error> {
+    let target = find_target(target_id)?;
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+
+    let (mime_type, json) = probe_for_compatibility(app_handle, path).await?;
+    let issues = check_target(target, mime_type.as_deref(), &json);
+
+    let output_path = match output_path {
+        Some(output_path) => PathBuf::from(output_path),
+        None => default_output_path(path, target),
+    };
+    let output_path = output_path.to_string_lossy().to_string();
+
+    let (args, notes) = if issues.is_empty() {
+        (Vec::new(), Vec::new())
+    } else {
+        build_args(target, &issues, &json, path, &output_path)
+    };
+    let command = (!args.is_empty()).then(|| {
+        std::iter::once("ffmpeg")
+            .chain(args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+
+    Ok(TranscodeSuggestion {
+        target: target.id,
+        issues,
+        output_path,
+        args,
+        command,
+        notes,
+    })
+}
+
+async fn run_transcode_async(
+    path: &str,
+    target_id: &str,
+    output_path: Option<&str>,
+    job_id: String,
+) -> Result<TranscodeResult, Error> {
+    let suggestion = suggest_transcode_async(path, target_id, output_path).await?;
+    if suggestion.args.is_empty() {
+        return Err(Error::FFmpegError(format!(
+            "{} already plays on target '{}'",
+            path, target_id
+        )));
+    }
+
+    let output_path = Path::new(&suggestion.output_path);
+    if output_path.exists() {
+        return Err(Error::FFmpegError(format!(
+            "{} already exists",
+            suggestion.output_path
+        )));
+    }
+    // Re-encoding rarely makes the file much bigger than the input
+    ensure_free_space(output_path, fs::metadata(path)?.len())?;
+
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let (_, json) = probe_for_compatibility(app_handle, path).await?;
+    let duration = json["format"]["duration"]
+        .as_str()
+        .and_then(|d| d.parse::<f64>().ok());
+
+    let start = Instant::now();
+    let run = run_ffmpeg_with_progress(app_handle, &suggestion.args, duration, |progress| {
+        emit_ffmpeg_progress(&job_id, path, "transcode", progress)
+    })
+    .await?;
+
+    if !run.success() {
+        // Don't leave a truncated file behind
+        let _ = fs::remove_file(output_path);
+        let tail = run.stderr.len().saturating_sub(ERROR_CONTEXT_LINES);
+        let context = run.stderr[tail..].join("\n");
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg transcode failed: {}",
+            context
+        )));
+    }
+
+    tracing::info!(
+        video_path = %path,
+        output_path = %suggestion.output_path,
+        target = target_id,
+        elapsed = ?start.elapsed(),
+        "Transcode finished"
+    );
+
+    Ok(TranscodeResult {
+        job_id,
+        output_path: suggestion.output_path,
+        args: suggestion.args,
+    })
+}
+
+/// `<dir>/<name>.<target>.<ext>` using the target's preferred container
+fn default_output_path(path: &str, target: &Target) -> PathBuf {
+    let input = Path::new(path);
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "output".to_string());
+    let extension = container_extension(target.mime_types[0]);
+    input.with_file_name(format!("{}.{}.{}", stem, target.id, extension))
+}
+
+/// Assemble ffmpeg arguments re-encoding only the streams with issues
+///
+/// Returns the arguments and notes about side effects of the conversion.
+fn build_args(
+    target: &Target,
+    issues: &[CompatibilityIssue],
+    json: &serde_json::Value,
+    path: &str,
+    output_path: &str,
+) -> (Vec<String>, Vec<String>) {
+    let mut args: Vec<String> = ["-n", "-i", path, "-map", "0:v:0?", "-map", "0:a?"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let mut notes = Vec::new();
+
+    let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+    let has_issue = |stream_index: Option<u64>, kinds: &[IssueKind]| {
+        issues
+            .iter()
+            .any(|issue| issue.stream_index == stream_index && kinds.contains(&issue.kind))
+    };
+
+    let video = streams.iter().find(|stream| {
+        stream["codec_type"].as_str() == Some("video")
+            && stream["disposition"]["attached_pic"].as_u64() != Some(1)
+    });
+    match video {
+        Some(stream)
+            if has_issue(
+                stream["index"].as_u64(),
+                &[
+                    IssueKind::VideoCodec,
+                    IssueKind::VideoProfile,
+                    IssueKind::VideoLevel,
+                    IssueKind::BitDepth,
+                    IssueKind::Resolution,
+                    IssueKind::FrameRate,
+                ],
+            ) =>
+        {
+            args.extend(video_args(target, stream, issues, &mut notes));
+        }
+        _ => args.extend(["-c:v".to_string(), "copy".to_string()]),
+    }
+
+    let audio_streams = streams
+        .iter()
+        .filter(|stream| stream["codec_type"].as_str() == Some("audio"));
+    for (audio_index, stream) in audio_streams.enumerate() {
+        let stream_index = stream["index"].as_u64();
+        if !has_issue(
+            stream_index,
+            &[IssueKind::AudioCodec, IssueKind::AudioChannels],
+        ) {
+            args.extend([format!("-c:a:{}", audio_index), "copy".to_string()]);
+            continue;
+        }
+
+        let codec = target.audio_codecs[0];
+        let channels = (stream["channels"].as_u64().unwrap_or(2) as u32)
+            .min(target.max_audio_channels)
+            .max(1);
+        args.extend([
+            format!("-c:a:{}", audio_index),
+            audio_encoder(codec).to_string(),
+        ]);
+        if is_lossy_audio(codec) {
+            let bit_rate = if channels <= 2 { "192k" } else { "384k" };
+            args.extend([format!("-b:a:{}", audio_index), bit_rate.to_string()]);
+        }
+        if has_issue(stream_index, &[IssueKind::AudioChannels]) {
+            args.extend([format!("-ac:a:{}", audio_index), channels.to_string()]);
+            notes.push(format!(
+                "Audio track {} is downmixed to {} channels",
+                audio_index + 1,
+                channels
+            ));
+        }
+    }
+
+    if streams
+        .iter()
+        .any(|stream| matches!(stream["codec_type"].as_str(), Some("subtitle" | "data")))
+    {
+        notes.push("Subtitle and data streams are not carried over".to_string());
+    }
+
+    if matches!(target.mime_types[0], "video/mp4" | "video/quicktime") {
+        // Put the index first so playback can start before the download ends
+        args.extend(["-movflags".to_string(), "+faststart".to_string()]);
+    }
+    args.push(output_path.to_string());
+
+    (args, notes)
+}
+
+/// Re-encode the video with the target's preferred codec
+fn video_args(
+    target: &Target,
+    stream: &serde_json::Value,
+    issues: &[CompatibilityIssue],
+    notes: &mut Vec<String>,
+) -> Vec<String> {
+    let support = &target.video[0];
+    let width = stream["width"].as_u64().unwrap_or(0) as u32;
+    let height = stream["height"].as_u64().unwrap_or(0) as u32;
+    let has_issue = |kind: IssueKind| issues.iter().any(|issue| issue.kind == kind);
+
+    let mut args: Vec<String> = Vec::new();
+    let mut short_side = width.min(height);
+    if has_issue(IssueKind::Resolution) {
+        let (max_width, max_height) = if width >= height {
+            (target.max_long_side, target.max_short_side)
+        } else {
+            (target.max_short_side, target.max_long_side)
+        };
+        short_side = short_side.min(target.max_short_side);
+        args.extend([
+            "-vf".to_string(),
+            format!(
+                "scale=w={}:h={}:force_original_aspect_ratio=decrease:force_divisible_by=2",
+                max_width, max_height
+            ),
+        ]);
+    }
+    if has_issue(IssueKind::FrameRate) {
+        args.extend(["-r".to_string(), target.max_frame_rate.to_string()]);
+    }
+
+    let ten_bit = support.max_bit_depth >= 10 && bit_depth(stream) >= 10;
+    let pix_fmt = if ten_bit { "yuv420p10le" } else { "yuv420p" };
+    args.extend(["-pix_fmt".to_string(), pix_fmt.to_string()]);
+
+    match support.codec {
+        "h264" => {
+            let level = match support.max_level {
+                Some(max) if short_side <= 1080 => max.min(H264_1080P_LEVEL),
+                Some(max) => max,
+                None => H264_1080P_LEVEL,
+            };
+            args.extend(
+                [
+                    "-c:v",
+                    "libx264",
+                    "-preset",
+                    "medium",
+                    "-crf",
+                    "20",
+                    "-profile:v",
+                    "high",
+                    "-level:v",
+                ]
+                .iter()
+                .map(|arg| arg.to_string()),
+            );
+            args.push(format!("{:.1}", level));
+        }
+        "hevc" => args.extend(
+            // hvc1 is the tag Apple players require
+            [
+                "-c:v", "libx265", "-preset", "medium", "-crf", "22", "-tag:v", "hvc1",
+            ]
+            .iter()
+            .map(|arg| arg.to_string()),
+        ),
+        "vp9" => args.extend(
+            ["-c:v", "libvpx-vp9", "-crf", "32", "-b:v", "0"]
+                .iter()
+                .map(|arg| arg.to_string()),
+        ),
+        "av1" => args.extend(
+            ["-c:v", "libsvtav1", "-crf", "32"]
+                .iter()
+                .map(|arg| arg.to_string()),
+        ),
+        other => args.extend(["-c:v".to_string(), other.to_string()]),
+    }
+
+    let transfer = stream["color_transfer"].as_str();
+    if !ten_bit && matches!(transfer, Some("smpte2084" | "arib-std-b67")) {
+        notes.push(
+            "HDR video is converted to 8-bit without tone mapping, colors will look washed out"
+                .to_string(),
+        );
+    }
+
+    args
+}
+
+/// ffmpeg encoder producing an audio codec
+fn audio_encoder(codec: &str) -> &str {
+    match codec {
+        "opus" => "libopus",
+        "mp3" => "libmp3lame",
+        "vorbis" => "libvorbis",
+        other => other,
+    }
+}
+
+fn is_lossy_audio(codec: &str) -> bool {
+    !matches!(codec, "flac" | "alac")
+}
+
+/// File extension for a container MIME type
+fn container_extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "video/webm" => "webm",
+        "video/quicktime" => "mov",
+        "video/x-matroska" => "mkv",
+        "video/mp2t" => "ts",
+        "video/3gpp" => "3gp",
+        _ => "mp4",
+    }
+}
+
+/// Quote an argument for POSIX shells when it contains special characters
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,39 @@
+use std::{io, path::PathBuf, time::Instant};
+
+use crate::inspector::Error;
+use video_inspector_core::mpegts::{scan_file, TransportStreamInfo};
+
+/// Inspect the transport stream layer of a `.ts` or `.m2ts` capture
+///
+/// Lists the programs of the PAT with their PMT streams and DVB service
+/// names, and counts packets, continuity counter errors and transport errors
+/// per PID, none of which ffprobe reports. Broadcast extras are picked out
+/// too: DVB subtitle services, teletext pages and SCTE-35 splice markers. The
+/// whole file is read.
+#[tauri::command]
+pub async fn inspect_transport_stream(path: String) -> Result<TransportStreamInfo, String> {
+    inspect_transport_stream_async(&path).await.map_err(|e| {
+        tracing::error!(video_path = %path, error = %e, "Transport stream inspection failed");
+        e.localized()
+    })
+}
+
+pub async fn inspect_transport_stream_async(path: &str) -> Result<TransportStreamInfo, Error> {
+    let start = Instant::now();
+    // Reading a capture of several GB takes a while; keep it off the async workers
+    let file = PathBuf::from(path);
+    let info = tauri::async_runtime::spawn_blocking(move || scan_file(&file))
+        .await
+        .map_err(|e| Error::IoError(io::Error::other(e.to_string())))??;
+
+    tracing::debug!(
+        video_path = %path,
+        programs = info.programs.len(),
+        packets = info.packets,
+        continuity_errors = info.continuity_errors,
+        scte35_markers = info.scte35_markers.len(),
+        elapsed = ?start.elapsed(),
+        "Inspected transport stream"
+    );
+    Ok(info)
+}
@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::{
+    path::{Path, PathBuf},
+    process::{Output, Stdio},
+    time::Duration,
+};
+use tempfile::TempPath;
+use tokio::process::Command;
+
+use crate::analyzer::{first_stream, Analyzer};
+use crate::frames::json_f64;
+use crate::get_app_handle;
+use crate::inspector::Error;
+use crate::runner::{sidecar, KillOnDrop, MediaTool};
+use crate::settings;
+use crate::temp::temp_frame_path;
+
+/// whisper.cpp runs still going after this long are killed
+const WHISPER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Seconds of audio sampled to identify a track's language
+const LANGUAGE_SAMPLE_SECS: f64 = 30.0;
+
+/// Where language samples start, as a fraction of the duration, to skip
+/// intros that are often music only
+const LANGUAGE_SAMPLE_POSITION: f64 = 0.1;
+
+/// Seconds transcribed from the start of the audio
+const TRANSCRIPT_SECS: f64 = 60.0;
+
+/// A local whisper.cpp install used for speech analysis
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct WhisperConfig {
+    /// The `whisper-cli` (formerly `main`) binary
+    pub program: String,
+    /// A ggml model such as `ggml-tiny.bin`; small models are plenty for
+    /// language identification
+    pub model: PathBuf,
+}
+
+/// Suggested languages of audio tracks without a language tag, from
+/// whisper.cpp's language detection
+pub struct AudioLanguageAnalyzer;
+
+#[async_trait]
+impl Analyzer for AudioLanguageAnalyzer {
+    fn name(&self) -> &str {
+        "audio_language"
+    }
+
+    fn description(&self) -> &str {
+        "Suggested language of untagged audio tracks, using a local whisper.cpp"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        !untagged_audio_streams(probe).is_empty()
+    }
+
+    async fn run(&self, path: &str, probe: &serde_json::Value) -> Result<serde_json::Value, Error> {
+        let config = whisper_config()?;
+        let duration = json_f64(&probe["format"]["duration"]).unwrap_or(0.0);
+        let start = (duration * LANGUAGE_SAMPLE_POSITION)
+            .min(duration - LANGUAGE_SAMPLE_SECS)
+            .max(0.0);
+
+        let mut suggestions = Vec::new();
+        for (audio_position, stream_index) in untagged_audio_streams(probe) {
+            let sample =
+                extract_speech_sample(path, audio_position, start, LANGUAGE_SAMPLE_SECS).await?;
+            let output =
+                run_whisper(&config, &sample, &["-l", "auto", "--detect-language"]).await?;
+            let detected = parse_detected_language(&String::from_utf8_lossy(&output.stderr));
+            tracing::debug!(
+                video_path = %path,
+                stream = stream_index,
+                language = ?detected.as_ref().map(|(language, _)| language),
+                "Identified audio language"
+            );
+            suggestions.push(json!({
+                "stream_index": stream_index,
+                "language": detected.as_ref().map(|(language, _)| language),
+                "probability": detected.map(|(_, probability)| probability),
+                "sample_start": start,
+            }));
+        }
+        Ok(json!(suggestions))
+    }
+}
+
+/// Transcript of the first minute of the first audio stream, to confirm
+/// what an unlabeled file contains
+pub struct TranscriptAnalyzer;
+
+#[async_trait]
+impl Analyzer for TranscriptAnalyzer {
+    fn name(&self) -> &str {
+        "transcript"
+    }
+
+    fn description(&self) -> &str {
+        "Transcript of the first minute of speech, using a local whisper.cpp"
+    }
+
+    fn applies_to(&self, probe: &serde_json::Value) -> bool {
+        first_stream(probe, "audio").is_some()
+    }
+
+    async fn run(
+        &self,
+        path: &str,
+        _probe: &serde_json::Value,
+    ) -> Result<serde_json::Value, Error> {
+        let config = whisper_config()?;
+        let sample = extract_speech_sample(path, 0, 0.0, TRANSCRIPT_SECS).await?;
+        // Plain text without timestamps on stdout
+        let output = run_whisper(&config, &sample, &["-l", "auto", "-nt"]).await?;
+        let detected = parse_detected_language(&String::from_utf8_lossy(&output.stderr));
+        let text = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        tracing::debug!(video_path = %path, chars = text.len(), "Transcribed audio sample");
+        Ok(json!({
+            "language": detected.as_ref().map(|(language, _)| language),
+            "probability": detected.map(|(_, probability)| probability),
+            "seconds": TRANSCRIPT_SECS,
+            "text": text,
+        }))
+    }
+}
+
+/// Position among audio streams and stream index of each audio stream whose
+/// language is missing or `und`
+fn untagged_audio_streams(probe: &serde_json::Value) -> Vec<(usize, u64)> {
+    probe["streams"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|stream| stream["codec_type"].as_str() == Some("audio"))
+        .enumerate()
+        .filter(|(_, stream)| {
+            stream["tags"]["language"]
+                .as_str()
+                .is_none_or(|language| language.is_empty() || language == "und")
+        })
+        .map(|(position, stream)| (position, stream["index"].as_u64().unwrap_or_default()))
+        .collect()
+}
+
+/// The configured whisper.cpp, or an error explaining how to set it up
+pub fn whisper_config() -> Result<WhisperConfig, Error> {
+    settings::current().whisper.ok_or_else(|| {
+        Error::FFmpegError(
+            "whisper.cpp isn't configured; set its program and model in settings".to_string(),
+        )
+    })
+}
+
+/// Extract part of an audio stream as 16 kHz mono WAV, the only input
+/// whisper.cpp reads
+pub async fn extract_speech_sample(
+    path: &str,
+    audio_position: usize,
+    start: f64,
+    seconds: f64,
+) -> Result<TempPath, Error> {
+    let app_handle = get_app_handle()
+        .ok_or_else(|| Error::FFmpegError("App handle not available".to_string()))?;
+    let sample = temp_frame_path("speech", "wav")?;
+    let output = sidecar(app_handle, MediaTool::Ffmpeg)?
+        .args([
+            "-v",
+            "error",
+            "-ss",
+            &format!("{:.3}", start),
+            "-t",
+            &format!("{:.3}", seconds),
+            "-i",
+            path,
+            "-map",
+            &format!("0:a:{}", audio_position),
+            "-ac",
+            "1",
+            "-ar",
+            "16000",
+            "-c:a",
+            "pcm_s16le",
+            "-y",
+            &sample.to_string_lossy(),
+        ])
+        .output_or_kill()
+        .await?;
+    if !output.status.success() {
+        return Err(Error::FFmpegError(format!(
+            "ffmpeg audio sample extraction failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(sample)
+}
+
+/// Run whisper.cpp on a WAV file with extra arguments
+pub async fn run_whisper(
+    config: &WhisperConfig,
+    wav: &Path,
+    args: &[&str],
+) -> Result<Output, Error> {
+    let child = Command::new(&config.program)
+        .arg("-m")
+        .arg(&config.model)
+        .arg("-f")
+        .arg(wav)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            Error::IoError(std::io::Error::new(
+                e.kind(),
+                format!("Failed to start whisper.cpp '{}': {}", config.program, e),
+            ))
+        })?;
+    let output = tokio::time::timeout(WHISPER_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            Error::FFmpegError(format!("whisper.cpp timed out after {:?}", WHISPER_TIMEOUT))
+        })??;
+    if !output.status.success() {
+        return Err(Error::FFmpegError(format!(
+            "whisper.cpp failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output)
+}
+
+/// Parse `auto-detected language: en (p = 0.973)` from whisper.cpp's log
+pub fn parse_detected_language(stderr: &str) -> Option<(String, f64)> {
+    let (_, rest) = stderr
+        .lines()
+        .rev()
+        .find_map(|line| line.split_once("auto-detected language:"))?;
+    let language = rest.split_whitespace().next()?.to_string();
+    let probability = rest
+        .split_once("p =")
+        .and_then(|(_, p)| p.trim().trim_end_matches(')').parse().ok())
+        .unwrap_or(0.0);
+    Some((language, probability))
+}
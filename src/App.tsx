@@ -2,7 +2,8 @@ import { useState, useEffect, useCallback, useRef } from 'react';
 import './App.css';
 import { invoke } from '@tauri-apps/api/core';
 import { getCurrentWebview } from '@tauri-apps/api/webview';
-import type { VideoMetadata } from './types';
+import { listen } from '@tauri-apps/api/event';
+import type { HashProgressEvent, PartialMetadata, PartialResultEvent, VideoMetadata } from './types';
 import Video from './components/Video/Video';
 import { open } from '@tauri-apps/plugin-dialog';
 import { useTranslation } from 'react-i18next';
@@ -15,6 +16,54 @@ function App() {
   const [files, setFiles] = useState<string[]>([]);
   const [metadataMap, setMetadataMap] = useState<Record<string, VideoMetadata>>({});
   const [errorMap, setErrorMap] = useState<Record<string, string>>({});
+  const [partialMap, setPartialMap] = useState<Record<string, PartialMetadata>>({});
+
+  // Collect partial results streamed while a file is being inspected
+  useEffect(() => {
+    const unlisten = listen<PartialResultEvent>('inspection://partial', event => {
+      const { path, ...result } = event.payload;
+      setPartialMap(prevMap => {
+        const partial: PartialMetadata = { ...(prevMap[path] ?? { thumbnails: {} }) };
+        if (result.stage === 'probe') {
+          partial.resolution = result.resolution;
+          partial.frame_rate = result.frame_rate;
+          partial.duration = result.duration;
+          partial.bit_rate = result.bit_rate;
+        } else if (result.stage === 'hash') {
+          partial.file_size = result.file_size;
+          partial.file_hash = result.file_hash;
+        } else {
+          partial.thumbnails = { ...partial.thumbnails, [result.index]: result.data_url };
+        }
+        return { ...prevMap, [path]: partial };
+      });
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Hashing large files takes a while, so show how far along it is
+  useEffect(() => {
+    const unlisten = listen<HashProgressEvent>('inspection://hash-progress', event => {
+      const progress = event.payload;
+      setPartialMap(prevMap => ({
+        ...prevMap,
+        [progress.path]: { ...(prevMap[progress.path] ?? { thumbnails: {} }), hash_progress: progress },
+      }));
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const clearPartial = useCallback((file: string) => {
+    setPartialMap(prevMap => {
+      const newMap = { ...prevMap };
+      delete newMap[file];
+      return newMap;
+    });
+  }, []);
 
   const addFile = useCallback((file: string) => {
     setFiles(prevFiles => [...prevFiles, file]);
@@ -33,7 +82,8 @@ function App() {
       delete newMap[file];
       return newMap;
     });
-  }, []);
+    clearPartial(file);
+  }, [clearPartial]);
 
   const processFile = useCallback(
     async (file: string) => {
@@ -55,9 +105,11 @@ function App() {
           ...prevMap,
           [file]: (error as string) || t('errors.unknownError'),
         }));
+      } finally {
+        clearPartial(file);
       }
     },
-    [t]
+    [clearPartial, t]
   );
 
   const handleFileDrop = useCallback(
@@ -192,6 +244,7 @@ function App() {
                     <Video
                       metadata={metadataMap[file]}
                       error={errorMap[file]}
+                      partial={partialMap[file]}
                       onDelete={() => handleDeleteVideo(file)}
                       onRetry={handleRetryVideo}
                       path={file}
@@ -1,17 +1,28 @@
-import type { VideoMetadata } from '@/types';
+import type { PartialMetadata, VideoMetadata } from '@/types';
 import { useTranslation } from 'react-i18next';
 import { IconX, IconAlertTriangle, IconRefresh, IconExclamationCircle } from '@tabler/icons-react';
 
+// Format seconds as h:mm:ss / m:ss for thumbnail labels
+function formatTimestamp(seconds: number): string {
+  const total = Math.floor(seconds);
+  const h = Math.floor(total / 3600);
+  const m = Math.floor((total % 3600) / 60);
+  const s = String(total % 60).padStart(2, '0');
+  return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+}
+
 export default function Video({
   path,
   metadata,
   error,
+  partial,
   onDelete,
   onRetry,
 }: {
   path: string;
   metadata: VideoMetadata | null;
   error: string | null;
+  partial?: PartialMetadata; // Results streamed in before the inspection finishes
   onDelete?: () => void;
   onRetry?: (filePath: string) => void; // If retry functionality is needed, pass the file path
 }) {
@@ -59,9 +70,15 @@ export default function Video({
           {/* Thumbnails area placeholder - 4 thumbnails in a row */}
           <div className="w-full p-2">
             <div className="grid grid-cols-4 gap-2">
-              {[...Array(4)].map((_, index) => (
-                <div key={index} className="bg-gray-300 rounded" style={{ aspectRatio: '16/9' }} />
-              ))}
+              {[...Array(4)].map((_, index) =>
+                partial?.thumbnails[index] ? (
+                  <div key={index} className="flex items-center justify-center">
+                    <img src={partial.thumbnails[index]} className="object-contain rounded-lg" />
+                  </div>
+                ) : (
+                  <div key={index} className="bg-gray-300 rounded" style={{ aspectRatio: '16/9' }} />
+                )
+              )}
             </div>
           </div>
 
@@ -70,6 +87,37 @@ export default function Video({
             {/* Filename placeholder */}
             <div className="h-5 bg-gray-200 rounded w-3/4 mb-3" />
 
+            {/* Probe data arrives before hash and thumbnails */}
+            {partial?.resolution && (
+              <p className="text-gray-600 text-sm mb-3">
+                {partial.resolution} · {partial.frame_rate} {t('metadata.fps')} · {partial.duration} ·{' '}
+                {partial.bit_rate}
+                {partial.file_size && ` · ${partial.file_size}`}
+              </p>
+            )}
+
+            {/* Hashing progress until the hash result arrives */}
+            {partial?.hash_progress && !partial.file_hash && partial.hash_progress.total_bytes > 0 && (
+              <div className="mb-3">
+                <div className="h-1.5 bg-gray-200 rounded overflow-hidden">
+                  <div
+                    className="h-full bg-blue-500"
+                    style={{
+                      width: `${(partial.hash_progress.bytes_processed / partial.hash_progress.total_bytes) * 100}%`,
+                    }}
+                  />
+                </div>
+                <p className="text-gray-500 text-xs mt-1">
+                  {t('metadata.hashing', {
+                    percent: Math.floor(
+                      (partial.hash_progress.bytes_processed / partial.hash_progress.total_bytes) * 100
+                    ),
+                    speed: (partial.hash_progress.throughput_bps / (1024 * 1024)).toFixed(1),
+                  })}
+                </p>
+              </div>
+            )}
+
             {/* Video information placeholder - 2 columns grid */}
             <div className="grid grid-cols-2 gap-x-4 gap-y-2">
               <div className="h-4 bg-gray-200 rounded" />
@@ -129,11 +177,16 @@ export default function Video({
                 <div className="w-full  p-2">
                   <div className="grid grid-cols-4 gap-2">
                     {metadata.thumbnails_base64.map((thumbnail, index) => (
-                      <div key={index} className="flex items-center justify-center">
+                      <div key={index} className="flex flex-col items-center justify-center">
                         <img
                           src={thumbnail}
                           className="object-contain rounded-lg border-2 border-gray-200 hover:border-blue-400 hover:scale-105 transition-all duration-200 shadow-sm hover:shadow-md cursor-pointer justify-self-center"
                         />
+                        {metadata.thumbnail_timestamps?.[index] !== undefined && (
+                          <span className="mt-1 text-xs text-gray-500 font-mono">
+                            {formatTimestamp(metadata.thumbnail_timestamps[index])}
+                          </span>
+                        )}
                       </div>
                     ))}
                   </div>
@@ -165,16 +218,145 @@ export default function Video({
                       <span className="text-gray-600">{metadata.bit_rate}</span>
                     </div>
                     <div className="flex justify-between">
-                      <span className="font-medium">{t('metadata.fileSize')}:</span>
-                      <span className="text-gray-600">{metadata.file_size}</span>
+                      <span className="font-medium">{t('metadata.codec')}:</span>
+                      <span className="text-gray-600">
+                        {metadata.video_stream.codec_name}
+                        {metadata.video_stream.codec_tag && ` (${metadata.video_stream.codec_tag})`}
+                        {metadata.video_stream.has_alpha && ` · ${t('metadata.hasTransparency')}`}
+                      </span>
                     </div>
                     <div className="flex justify-between">
-                      <span className="font-medium">{t('metadata.fileHash')}:</span>
-                      <span className="text-gray-600 font-mono text-xs truncate ml-2" title={metadata.file_hash}>
-                        {metadata.file_hash.substring(0, 16)}...
-                      </span>
+                      <span className="font-medium">{t('metadata.fileSize')}:</span>
+                      <span className="text-gray-600">{metadata.file_size}</span>
                     </div>
+                    {metadata.file_hash && (
+                      <div className="flex justify-between">
+                        <span className="font-medium">{t('metadata.fileHash')}:</span>
+                        <span className="text-gray-600 font-mono text-xs truncate ml-2" title={metadata.file_hash}>
+                          {metadata.file_hash.substring(0, 16)}...
+                          {metadata.hash_kind === 'quick' && ` (${t('metadata.quickHash')})`}
+                        </span>
+                      </div>
+                    )}
+                    {metadata.video_stream.hdr && (
+                      <div className="flex justify-between col-span-2">
+                        <span className="font-medium">{t('metadata.hdr')}:</span>
+                        <span className="text-gray-600 text-xs ml-2">
+                          {metadata.video_stream.hdr.color_transfer ?? '?'}
+                          {metadata.video_stream.hdr.mastering_display &&
+                            ` · ${t('metadata.masteringDisplay')} ${metadata.video_stream.hdr.mastering_display.min_luminance}-${metadata.video_stream.hdr.mastering_display.max_luminance} cd/m²`}
+                          {metadata.video_stream.hdr.content_light_level &&
+                            ` · MaxCLL ${metadata.video_stream.hdr.content_light_level.max_cll} / MaxFALL ${metadata.video_stream.hdr.content_light_level.max_fall}`}
+                        </span>
+                      </div>
+                    )}
+                    {metadata.video_stream.dolby_vision && (
+                      <div className="flex justify-between col-span-2">
+                        <span className="font-medium">{t('metadata.dolbyVision')}:</span>
+                        <span className="text-gray-600 text-xs ml-2">
+                          {t('metadata.dvProfile', {
+                            profile: metadata.video_stream.dolby_vision.profile_name,
+                            level: metadata.video_stream.dolby_vision.level,
+                          })}{' '}
+                          · {metadata.video_stream.dolby_vision.fallback}
+                        </span>
+                      </div>
+                    )}
+                    {metadata.video_stream.av1 && (
+                      <div className="flex justify-between col-span-2">
+                        <span className="font-medium">AV1:</span>
+                        <span className="text-gray-600 text-xs ml-2">
+                          {t('metadata.av1Summary', {
+                            profile: metadata.video_stream.av1.seq_profile,
+                            level: metadata.video_stream.av1.level,
+                            tier: metadata.video_stream.av1.tier,
+                          })}
+                          {metadata.video_stream.av1.film_grain && ` · ${t('metadata.filmGrain')}`}
+                          {metadata.video_stream.av1.screen_content_tools &&
+                            metadata.video_stream.av1.screen_content_tools !== 'off' &&
+                            ` · ${t('metadata.screenContentTools')}`}
+                        </span>
+                      </div>
+                    )}
+                    {metadata.container.mime_type && (
+                      <div className="flex justify-between col-span-2">
+                        <span className="font-medium">{t('metadata.container')}:</span>
+                        <span className="text-gray-600 font-mono text-xs ml-2">
+                          {metadata.container.mime_type}
+                          {metadata.container.major_brand &&
+                            ` · ${metadata.container.major_brand.trim()} (${metadata.container.compatible_brands
+                              .map(brand => brand.trim())
+                              .join(', ')})`}
+                          {metadata.container.doctype &&
+                            ` · ${metadata.container.doctype} v${metadata.container.doctype_version ?? '?'}`}
+                        </span>
+                      </div>
+                    )}
                   </div>
+
+                  {/* Audio tracks */}
+                  <div className="mt-3 text-gray-700 text-sm">
+                    <span className="font-medium">{t('metadata.audioTracks')}:</span>
+                    {metadata.audio_streams.length === 0 ? (
+                      <span className="ml-2 text-gray-600">{t('metadata.noAudio')}</span>
+                    ) : (
+                      <ul className="mt-1 space-y-0.5">
+                        {metadata.audio_streams.map(stream => (
+                          <li key={stream.index} className="text-gray-600">
+                            #{stream.index} {stream.codec_name}
+                            {stream.codec_tag && ` (${stream.codec_tag})`} · {stream.layout_description}
+                            {stream.gapless && stream.gapless.status !== 'complete' && (
+                              <span className="ml-2 text-amber-600 text-xs">
+                                {t(`metadata.gapless.${stream.gapless.status}`)}
+                              </span>
+                            )}
+                          </li>
+                        ))}
+                      </ul>
+                    )}
+                    {metadata.has_stereo_downmix === false && (
+                      <p className="mt-1 text-amber-600 text-xs">{t('metadata.noStereoDownmix')}</p>
+                    )}
+                  </div>
+
+                  {/* User-scripted fields from settings */}
+                  {metadata.computed_fields.length > 0 && (
+                    <div className="mt-3 text-sm">
+                      <span className="font-medium text-gray-700">{t('metadata.computedFields')}:</span>
+                      <ul className="mt-1 space-y-0.5">
+                        {metadata.computed_fields.map(field => (
+                          <li key={field.name} className="text-gray-600">
+                            {field.name}:{' '}
+                            {field.error ? (
+                              <span className="text-red-600 text-xs">{field.error}</span>
+                            ) : typeof field.value === 'boolean' ? (
+                              <span className={field.value ? 'text-green-600' : 'text-red-600'}>
+                                {t(field.value ? 'metadata.rulePass' : 'metadata.ruleFail')}
+                              </span>
+                            ) : (
+                              <span className="font-mono text-xs">
+                                {typeof field.value === 'string' ? field.value : JSON.stringify(field.value)}
+                              </span>
+                            )}
+                          </li>
+                        ))}
+                      </ul>
+                    </div>
+                  )}
+
+                  {/* Edit lists and start offsets that may cause sync or trimming issues */}
+                  {metadata.start_offsets.issues.length > 0 && (
+                    <div className="mt-3 text-sm">
+                      <span className="font-medium text-gray-700">{t('metadata.startOffsets')}:</span>
+                      <ul className="mt-1 space-y-0.5">
+                        {metadata.start_offsets.issues.map((issue, index) => (
+                          <li key={index} className="text-amber-600 text-xs">
+                            {issue}
+                          </li>
+                        ))}
+                      </ul>
+                    </div>
+                  )}
                 </div>
               </div>
             )}
@@ -1,6 +1,7 @@
 import i18n from 'i18next';
 import { initReactI18next } from 'react-i18next';
 import LanguageDetector from 'i18next-browser-languagedetector';
+import { invoke } from '@tauri-apps/api/core';
 
 // Import translation files
 import enTranslations from './locales/en.json';
@@ -15,6 +16,13 @@ const resources = {
   },
 };
 
+// Keep error messages from the backend in the same language as the UI
+i18n.on('languageChanged', language => {
+  invoke('set_locale', { locale: language }).catch(error => {
+    console.error('Failed to set backend locale:', error);
+  });
+});
+
 i18n
   .use(LanguageDetector)
   .use(initReactI18next)
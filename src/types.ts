@@ -1,11 +1,989 @@
+export interface LoudnessTags {
+  replaygain_track_gain_db: number | null;
+  replaygain_track_peak: number | null;
+  replaygain_album_gain_db: number | null;
+  replaygain_album_peak: number | null;
+  r128_track_gain_db: number | null;
+  itunnorm_gain_db: number | null;
+}
+
+export type GaplessStatus = 'complete' | 'delay_only' | 'inconsistent' | 'missing';
+
+export interface GaplessInfo {
+  source: 'iTunSMPB' | 'opus_pre_skip' | 'initial_padding' | null;
+  encoder_delay: number | null; // Samples
+  padding: number | null; // Samples
+  valid_samples: number | null;
+  status: GaplessStatus;
+}
+
+export interface AudioStreamInfo {
+  index: number;
+  codec_name: string;
+  codec_tag: string | null; // FourCC, e.g. 'mp4a'
+  channels: number;
+  channel_layout: string;
+  layout_description: string;
+  sample_rate: number | null; // Hz
+  bit_rate: number | null;
+  bit_rate_measured: boolean;
+  language: string | null; // e.g. 'eng'; null when untagged or 'und'
+  loudness_tags: LoudnessTags | null;
+  gapless: GaplessInfo | null; // null for codecs without encoder delay
+}
+
+export interface SubtitleStreamInfo {
+  index: number;
+  codec_name: string;
+  format: string; // e.g. 'srt', 'ass', 'pgs'
+  bitmap: boolean; // Image-based, can't be previewed as text
+  language: string | null; // e.g. 'eng'; null when untagged or 'und'
+  title: string | null;
+  forced: boolean;
+  default: boolean;
+  hearing_impaired: boolean;
+}
+
+export interface ContainerInfo {
+  mime_type: string | null;
+  major_brand: string | null; // MP4/MOV ftyp brand
+  minor_version: number | null;
+  compatible_brands: string[];
+  doctype: string | null; // Matroska DocType
+  doctype_version: number | null;
+  doctype_read_version: number | null;
+}
+
+export interface MasteringDisplay {
+  red: [number, number]; // CIE 1931 xy
+  green: [number, number];
+  blue: [number, number];
+  white_point: [number, number];
+  min_luminance: number; // cd/m²
+  max_luminance: number;
+}
+
+export interface ContentLightLevel {
+  max_cll: number; // cd/m²
+  max_fall: number;
+}
+
+export interface HdrInfo {
+  color_transfer: string | null;
+  color_primaries: string | null;
+  color_space: string | null;
+  mastering_display: MasteringDisplay | null;
+  content_light_level: ContentLightLevel | null;
+}
+
+export interface DolbyVisionInfo {
+  version: string;
+  profile: number;
+  level: number;
+  profile_name: string; // e.g. '5', '7', '8.1'
+  rpu_present: boolean;
+  enhancement_layer_present: boolean;
+  base_layer_present: boolean;
+  base_layer_compatibility_id: number;
+  fallback: string; // What non-Dolby Vision displays get
+  source: string; // MP4 box type or 'side_data'
+}
+
+export interface Av1Info {
+  seq_profile: number; // 0 = Main, 1 = High, 2 = Professional
+  seq_level_idx: number;
+  level: string; // e.g. '5.1'
+  tier: 'Main' | 'High';
+  bit_depth: number;
+  monochrome: boolean;
+  chroma_subsampling: string;
+  film_grain: boolean | null; // null when the sequence header was unavailable
+  screen_content_tools: 'off' | 'on' | 'per_frame' | null;
+}
+
+export interface VideoStreamInfo {
+  index: number;
+  codec_name: string;
+  codec_tag: string | null; // FourCC, e.g. 'avc1', 'hvc1', 'XVID'
+  profile: string | null;
+  level: string | null; // e.g. '4.1', H.264 and HEVC only
+  pix_fmt: string | null;
+  bit_depth: number | null; // e.g. 8 or 10
+  chroma_subsampling: string | null; // e.g. '4:2:0'; null for RGB
+  summary: string; // e.g. 'h264 High@4.1 10-bit 4:2:0'
+  has_alpha: boolean; // Alpha channel, e.g. ProRes 4444 or VP9 with alpha
+  hdr: HdrInfo | null; // null for SDR
+  dolby_vision: DolbyVisionInfo | null;
+  av1: Av1Info | null;
+  stereo_3d: Stereo3d | null; // null for 2D
+}
+
+export interface Stereo3d {
+  layout: 'side_by_side' | 'top_bottom' | 'mvc' | 'other';
+  half: boolean; // Each view squeezed to half width/height
+  source: 'metadata' | 'aspect';
+}
+
+export interface EditListEntry {
+  segment_duration: number; // Seconds
+  media_time: number | null; // Seconds; null for an empty edit
+  rate: number;
+}
+
+export interface StreamOffsets {
+  index: number;
+  codec_type: string;
+  start_time: number | null;
+  initial_padding: number | null; // Encoder delay in samples
+  edit_list: EditListEntry[];
+}
+
+export interface StartOffsetReport {
+  streams: StreamOffsets[];
+  issues: string[];
+}
+
+export type InspectionPreset = 'quick' | 'standard' | 'deep';
+
+export type HashKind = 'sha256' | 'quick';
+
+export type DurationSource = 'format' | 'stream' | 'tag' | 'frame_count' | 'packets';
+
 export interface VideoMetadata {
+  job_id: string;
   file_path: string;
   resolution: string;
   frame_rate: string;
   duration: string;
+  duration_source: DurationSource; // Anything but 'format' is derived for legacy containers
   bit_rate: string;
+  video_bit_rate: string | null;
+  video_bit_rate_measured: boolean;
+  video_stream: VideoStreamInfo;
   file_size: string;
-  file_hash: string;
+  // The values above unformatted, for sorting, unit conversion and localizing
+  width: number; // 0 without a video stream
+  height: number;
+  fps: Rational | null; // As declared, e.g. 30000/1001
+  duration_secs: number;
+  bit_rate_bps: number;
+  video_bit_rate_bps: number | null;
+  file_size_bytes: number;
+  file_hash: string | null; // null when the preset skips hashing
+  hash_kind: HashKind | null;
   thumbnails_base64: string[];
+  thumbnail_timestamps: number[];
+  thumbnail_hints: (ContentHints | null)[]; // null unless enabled in settings
+  audio_streams: AudioStreamInfo[];
+  has_stereo_downmix: boolean | null;
+  stream_layout: StreamLayout;
+  subtitle_streams: SubtitleStreamInfo[];
+  container: ContainerInfo;
+  start_offsets: StartOffsetReport;
+  computed_fields: ComputedValue[];
+  preset: InspectionPreset; // Steps run beyond the probe, before skip flags
+  qc_issues: string[] | null;
+  loudness: LoudnessReport | null;
+  integrity: IntegrityReport | null;
+  sidecars: SidecarCheck[]; // .nfo/.xml/.xmp next to the file
+  external_subtitles: ExternalSubtitle[]; // .srt/.ass/... named after the file
+  efficiency: BitRateEfficiency | null;
+  programs: ProgramInfo[]; // Only for transport streams with several programs
+  program: number | null; // Program number the metadata describes, when there was a choice
+  timings: InspectionTimings;
+  from_cache: boolean; // Timings are those of the cached inspection
   error?: string;
 }
+
+export interface Rational {
+  numerator: number;
+  denominator: number;
+}
+
+// Audio-only and video-only files are reported here instead of failing
+export interface StreamLayout {
+  video_streams: number; // Not counting attached pictures
+  audio_streams: number;
+  subtitle_streams: number;
+  attached_pictures: number; // Cover art stored as video streams
+  other_streams: number; // Data, attachment and unknown streams
+  notes: string[]; // e.g. "No audio stream"
+}
+
+// Pass program_number as `program` to get_video_metadata to inspect that program
+export interface ProgramInfo {
+  program_number: number;
+  pmt_pid: number | null;
+  service_name: string | null; // Channel name, e.g. "BBC ONE HD"
+  service_provider: string | null;
+  stream_indexes: number[];
+  video_stream_index: number | null; // null for radio services
+  video_summary: string | null; // e.g. "1920x1080 h264"
+}
+
+export interface PosterCandidate {
+  timestamp: number;
+  score: number;
+  sharpness: number;
+  brightness: number;
+  image_base64: string;
+}
+
+export interface LoudnessReport {
+  audio_stream: number;
+  claimed: LoudnessTags | null;
+  claimed_lufs: number | null;
+  measured: {
+    integrated_lufs: number;
+    loudness_range_lu: number | null;
+    true_peak_dbfs: number | null;
+  };
+  mismatch: boolean;
+}
+
+export interface SubtitleCue {
+  start: number;
+  end: number;
+  text: string;
+}
+
+export interface SubtitlePreview {
+  subtitle_stream: number;
+  codec_name: string;
+  language: string | null;
+  cues: SubtitleCue[];
+}
+
+export interface FrameTypeCount {
+  count: number;
+  ratio: number;
+  average_size: number;
+}
+
+export interface FrameTypeStats {
+  sampled_frames: number;
+  i_frames: FrameTypeCount;
+  p_frames: FrameTypeCount;
+  b_frames: FrameTypeCount;
+  reference_frames: number | null;
+  max_b_frames: number | null;
+  average_gop_length: number | null;
+}
+
+export type TimelineGranularity = 'frame' | 'gop';
+
+export interface TimelinePoint {
+  time: number;
+  span: number;
+  frames: number;
+  total_bytes: number;
+  peak_frame_bytes: number;
+  bit_rate: number;
+}
+
+export interface FrameSizeTimeline {
+  total_frames: number;
+  duration: number;
+  points: TimelinePoint[];
+}
+
+export type PartialResultEvent = { job_id: string; path: string } & (
+  | { stage: 'probe'; resolution: string; frame_rate: string; duration: string; bit_rate: string }
+  | { stage: 'hash'; file_size: string; file_hash: string; hash_kind: HashKind }
+  | {
+      stage: 'thumbnail';
+      index: number;
+      timestamp: number;
+      data_url: string;
+      hints: ContentHints | null;
+    }
+);
+
+// Payload of inspection://hash-progress events
+export interface HashProgressEvent {
+  job_id: string;
+  path: string;
+  bytes_processed: number;
+  total_bytes: number;
+  throughput_bps: number; // Bytes per second
+}
+
+// Partial results accumulated from inspection://partial events
+export interface PartialMetadata {
+  resolution?: string;
+  frame_rate?: string;
+  duration?: string;
+  bit_rate?: string;
+  file_size?: string;
+  file_hash?: string;
+  hash_progress?: HashProgressEvent; // Latest progress while hashing
+  thumbnails: Record<number, string>;
+}
+
+export type InspectionStage =
+  | 'probe'
+  | 'stream_details'
+  | 'hash'
+  | 'thumbnails'
+  | 'deep_checks'
+  | 'done'
+  | 'failed';
+
+// Payload of inspection://progress events
+export interface InspectionProgressEvent {
+  job_id: string;
+  path: string;
+  stage: InspectionStage;
+  stage_progress: number | null; // 0 to 1 within the stage, when known
+  percent: number; // 0 to 100 for the whole inspection
+  detail: string | null; // e.g. 'Thumbnail 2 of 4'
+}
+
+// Payload of inspection://ffmpeg-progress events
+export interface FfmpegProgressEvent {
+  job_id: string;
+  path: string;
+  operation: string; // e.g. 'integrity_scan'
+  frame: number | null;
+  fps: number | null;
+  out_time: number; // Seconds
+  speed: number | null; // Relative to real time
+  total_size: number | null;
+  percent: number | null;
+  finished: boolean;
+}
+
+export interface IntegrityReport {
+  job_id: string;
+  error_count: number;
+  errors: string[];
+  clean: boolean;
+}
+
+export type CompatibilityTarget = 'web' | 'ios' | 'android' | 'smart_tv' | 'console';
+
+export type CompatibilityIssueKind =
+  | 'container'
+  | 'video_codec'
+  | 'video_profile'
+  | 'video_level'
+  | 'bit_depth'
+  | 'resolution'
+  | 'frame_rate'
+  | 'audio_codec'
+  | 'audio_channels';
+
+export interface CompatibilityIssue {
+  kind: CompatibilityIssueKind;
+  stream_index: number | null; // null for container issues
+  message: string;
+}
+
+export interface TargetCompatibility {
+  target: CompatibilityTarget;
+  name: string;
+  compatible: boolean;
+  issues: CompatibilityIssue[];
+}
+
+export interface TranscodeSuggestion {
+  target: CompatibilityTarget;
+  issues: CompatibilityIssue[];
+  output_path: string;
+  args: string[]; // Empty when the file already plays on the target
+  command: string | null; // Shell-quoted command line
+  notes: string[];
+}
+
+export interface TranscodeResult {
+  job_id: string;
+  output_path: string;
+  args: string[];
+}
+
+export interface PreviewSettings {
+  video_codec: string; // ffmpeg encoder, e.g. 'libx264'
+  crf?: number;
+  preset?: string;
+  video_bit_rate?: string; // ffmpeg notation, e.g. '4M'
+  max_height?: number;
+  pix_fmt?: string;
+  audio_codec?: string; // Omit to copy the audio
+  audio_bit_rate?: string;
+}
+
+export interface ContentHints {
+  faces: number; // Skin-toned regions shaped like a face
+  text_overlay: boolean; // Captions, titles or credits
+}
+
+export interface PreviewThumbnail {
+  timestamp: number;
+  data_url: string;
+  hints: ContentHints | null;
+}
+
+export interface TranscodePreview {
+  job_id: string;
+  seconds: number;
+  codec_name: string;
+  resolution: string;
+  bit_rate: number; // Bits per second
+  output_size: number; // Bytes
+  source_size: number; // Bytes, estimated for the same span
+  projected_size: number; // Bytes, extrapolated to the full duration
+  encode_speed: number; // 2.0 = twice real time
+  thumbnails: PreviewThumbnail[];
+}
+
+export interface ClipResult {
+  output_path: string;
+  start: number; // Actual start in the source, may precede the requested one
+  end: number;
+  smart_cut: boolean; // First GOP re-encoded to start exactly
+  size: number; // Bytes
+}
+
+export type SplitMode =
+  | { kind: 'equal_parts'; parts: number }
+  | { kind: 'chapters' }
+  | { kind: 'interval'; seconds: number };
+
+export interface SplitSegment {
+  path: string;
+  start: number; // Seconds in the source
+  end: number;
+  size: number; // Bytes
+  sha256: string;
+}
+
+export interface SplitManifest {
+  job_id: string;
+  source: string;
+  manifest_path: string;
+  segments: SplitSegment[];
+}
+
+export interface FieldDifference {
+  stream: string; // 'video', 'audio 1', ... or 'streams' for stream counts
+  field: string;
+  values: (string | null)[]; // One per file, in the order given
+}
+
+export interface ConcatReport {
+  files: string[];
+  lossless: boolean; // Every field matches, so '-c copy' concat works
+  differences: FieldDifference[];
+}
+
+export interface FolderEntry {
+  path: string;
+  codec_name: string | null;
+  resolution: string | null;
+  frame_rate: number | null;
+  duration: number | null; // Seconds
+  bit_rate: number | null; // Bits per second
+  file_size: number; // Bytes
+  audio_streams: number;
+  issues: string[]; // Empty when the file passed QC
+  computed_fields: ComputedValue[];
+  sha256: string | null; // Only when hashing was requested
+  quick_hash: string | null; // Size plus sampled chunks, only when requested
+  quarantined: string | null; // Where the file was moved or linked after failing QC
+  estimated_savings: number | null; // Bytes saved re-encoding to the report's target
+}
+
+export interface DistributionEntry {
+  value: string;
+  count: number;
+}
+
+export interface Outlier {
+  path: string;
+  reason: string;
+}
+
+// Payload of inspection://scan-plan events (plus job_id and path)
+export interface ScanPlan {
+  files: number;
+  total_bytes: number;
+  read_throughput_bps: number | null;
+  logical_cpus: number;
+  idle_cpus: number;
+  concurrency: number; // Files processed at once
+  hash_chunk_size: number; // Bytes per read when hashing
+  reason: string; // Why these values were chosen
+}
+
+export interface FolderReport {
+  job_id: string;
+  folder: string;
+  file_count: number;
+  total_runtime: number; // Seconds
+  total_size: number; // Bytes
+  reencode_target: ReencodeTarget;
+  estimated_savings: number; // Bytes, summed over the entries
+  codecs: DistributionEntry[];
+  resolutions: DistributionEntry[];
+  frame_rates: DistributionEntry[];
+  outliers: Outlier[];
+  failing: string[]; // Paths of files with QC issues
+  entries: FolderEntry[];
+  export_path: string | null;
+  plan: ScanPlan;
+}
+
+// '{path}' and '{json}' in args are replaced by the file and metadata JSON path
+export interface Hook {
+  name: string;
+  enabled: boolean;
+  program: string;
+  args: string[];
+  input: 'stdin' | 'temp_file';
+  timeout_secs: number | null; // null = 60 seconds
+}
+
+// Payload of inspection://hook-finished events
+export interface HookOutcome {
+  job_id: string;
+  path: string;
+  hook: string;
+  success: boolean;
+  exit_code: number | null;
+  timed_out: boolean;
+  stderr: string; // Tail of the hook's stderr
+  elapsed_ms: number;
+}
+
+// Receives {path, probe} JSON on stdin and prints one JSON value
+export interface ExternalAnalyzer {
+  name: string;
+  description: string;
+  program: string;
+  args: string[];
+}
+
+export interface AnalyzerInfo {
+  name: string;
+  description: string;
+}
+
+export interface AnalyzerResult {
+  name: string;
+  skipped: boolean; // Nothing to analyze, e.g. no audio for 'loudness'
+  value: unknown | null; // Shape depends on the analyzer
+  error: string | null;
+  elapsed_ms: number;
+}
+
+// rhai script over the ffprobe JSON; sees format, streams, video and audio
+export interface ComputedField {
+  name: string;
+  script: string;
+}
+
+export interface ComputedValue {
+  name: string;
+  value: unknown | null; // A boolean makes the field a pass/fail rule
+  error: string | null;
+}
+
+export interface BenchmarkRun {
+  decoder: string; // 'software' or the hwaccel API, e.g. 'videotoolbox'
+  frames: number;
+  decoded: number; // Seconds of video decoded
+  elapsed: number; // Wall clock seconds
+  fps: number;
+  realtime_factor: number; // Below 1.0 playback will stutter
+}
+
+export interface DecodeBenchmark {
+  job_id: string;
+  codec_name: string;
+  resolution: string;
+  frame_rate: number;
+  software: BenchmarkRun;
+  hardware: BenchmarkRun | null;
+  hardware_error: string | null;
+}
+
+export interface DiscAudioTrack {
+  codec_name: string | null;
+  channels: number | null;
+  language: string | null;
+}
+
+export interface TitleStreams {
+  video_codec: string | null;
+  resolution: string | null;
+  frame_rate: number | null;
+  audio: DiscAudioTrack[];
+  subtitles: (string | null)[]; // Language per subtitle track, when declared
+}
+
+export interface DvdTitle {
+  title_set: number; // nn of VTS_nn_*.VOB
+  vob_files: string[];
+  size: number;
+  duration: number | null; // Seconds
+  streams: TitleStreams;
+}
+
+export interface DvdReport {
+  job_id: string;
+  video_ts: string;
+  titles: DvdTitle[];
+  main_title: number | null; // Title set of the longest title
+  thumbnails: PreviewThumbnail[];
+}
+
+export interface BlurayPlaylist {
+  name: string; // e.g. 00800.mpls
+  duration: number; // Seconds
+  segments: string[]; // .m2ts files in playback order
+  chapters: number[]; // Chapter starts in seconds
+}
+
+export interface BlurayReport {
+  bdmv: string;
+  playlists: BlurayPlaylist[]; // Longest first, short playlists left out
+  main_playlist: string;
+  streams: TitleStreams; // Of the main title's first segment
+}
+
+export interface IsoImageInfo {
+  iso9660: boolean;
+  udf: boolean;
+  volume_id: string | null;
+}
+
+export type IsoContent =
+  | ({ kind: 'dvd' } & DvdReport)
+  | { kind: 'bluray'; duration: number | null; streams: TitleStreams };
+
+export interface IsoReport {
+  image: IsoImageInfo;
+  content: IsoContent;
+}
+
+export interface FilePart {
+  index: number;
+  path: string;
+  size: number;
+}
+
+export interface MultipartReport {
+  job_id: string;
+  parts: FilePart[];
+  missing: number[];
+  total_size: number;
+  duration: number | null;
+  streams: TitleStreams;
+  thumbnails: PreviewThumbnail[];
+}
+
+export interface ArchiveEntry {
+  name: string;
+  size: number;
+  extractable: boolean;
+}
+
+export interface ArchiveListing {
+  path: string;
+  format: 'zip' | 'rar' | '7z';
+  entries: ArchiveEntry[];
+  other_files: number;
+}
+
+export type CardLayout = 'dcim' | 'xdcam' | 'avchd';
+
+export interface CardClip {
+  name: string;
+  files: string[];
+  size: number;
+  duration: number | null;
+  streams: TitleStreams;
+}
+
+export interface CardReel {
+  name: string;
+  layout: CardLayout;
+  clips: CardClip[];
+}
+
+export interface CardReport {
+  root: string;
+  reels: CardReel[];
+  clip_count: number;
+  total_size: number;
+  total_duration: number;
+}
+
+export interface ScanRules {
+  include: string[]; // Globs relative to the scanned folder; empty = everything
+  exclude: string[];
+  min_file_size: number; // Bytes
+  ignore_hidden: boolean; // Dotfiles, Thumbs.db, hidden/system attributes
+  stability_delay_secs: number; // Wait for files being copied; 0 disables
+}
+
+export type RenameStatus =
+  | { kind: 'renamed' } // Or will be, on a real run
+  | { kind: 'unchanged' }
+  | { kind: 'collision'; with: string }
+  | { kind: 'failed'; error: string };
+
+export interface RenameEntry {
+  from: string;
+  to: string | null; // null when the file couldn't be inspected
+  status: RenameStatus;
+}
+
+export interface RenameReport {
+  dry_run: boolean; // Nothing was renamed
+  entries: RenameEntry[];
+}
+
+export type ExportFormat = 'markdown' | 'bbcode';
+
+export type ThumbnailEmbed = 'none' | 'files' | 'base64';
+
+export interface ExportResult {
+  text: string; // For copying to the clipboard
+  export_path: string | null;
+  thumbnail_paths: string[]; // PNGs written next to the export
+}
+
+export interface NfoFile {
+  path: string;
+  xml: string;
+}
+
+export type SidecarKind = 'nfo' | 'xml' | 'xmp';
+
+export interface SidecarClaims {
+  duration: number | null; // Seconds
+  width: number | null;
+  height: number | null;
+  codec: string | null;
+}
+
+export interface SidecarCheck {
+  path: string;
+  kind: SidecarKind;
+  claims: SidecarClaims;
+  warnings: string[]; // Mismatches with the file, or why it couldn't be read
+}
+
+export interface ExternalSubtitle {
+  path: string;
+  format: string; // srt, ass, ssa, vtt, microdvd or vobsub
+  language: string | null; // From the file name, e.g. movie.en.srt
+  encoding: string | null; // null when not UTF-8 or UTF-16
+  cue_count: number;
+  first_cue_start: number | null; // Seconds
+  last_cue_end: number | null;
+  warnings: string[];
+}
+
+export interface WhisperConfig {
+  program: string; // whisper-cli binary
+  model: string; // ggml model, e.g. ggml-tiny.bin
+}
+
+export interface ChapterEntry {
+  index: number;
+  title: string | null;
+  start: number; // Seconds
+  duration: number; // Seconds until the next chapter or the end
+  thumbnail: PreviewThumbnail | null; // Taken just after the chapter start
+}
+
+export interface TimestampOverlay {
+  frame_number: boolean; // Also burn in the frame number
+  font_file: string | null; // For ffmpeg builds without fontconfig
+}
+
+export interface ReencodeTarget {
+  codec: 'x265' | 'av1';
+  quality: 'high' | 'medium' | 'low';
+}
+
+export interface SavingsEstimate {
+  target: ReencodeTarget;
+  current_bit_rate: number; // Video bits per second
+  estimated_bit_rate: number; // Video bits per second after re-encoding
+  savings_bytes: number;
+  savings_ratio: number; // Share of the file size
+  noise_considered: boolean;
+}
+
+// Milliseconds per inspection phase
+export interface InspectionTimings {
+  probe_ms: number; // ffprobe alone
+  stream_details_ms: number; // Bit rates, HDR and AV1 details after the probe
+  hash_ms: number | null; // null when hashing was skipped
+  hash_mb_per_sec: number | null; // Only for full SHA-256 hashes
+  thumbnails_ms: number | null; // null when thumbnails were skipped
+  thumbnail_ms: number[]; // Each thumbnail in order, near 0 when cached
+  deep_checks_ms: number; // QC, loudness and integrity steps
+  total_ms: number;
+}
+
+export interface BitRateEfficiency {
+  bits_per_pixel: number; // Per frame, as encoded
+  kbps_per_megapixel: number;
+  normalized_bits_per_pixel: number; // H.264 at 1080p terms, used for the verdict
+  verdict: 'starved' | 'typical' | 'bloated';
+  comment: string;
+}
+
+export interface BatchItem {
+  path: string;
+  file_job_id: string; // This file's own inspection job
+  metadata: VideoMetadata | null;
+  error: string | null;
+}
+
+// Payload of inspection://batch-file events
+export interface BatchFileEvent extends BatchItem {
+  job_id: string; // The batch
+  index: number; // Position of the file in the batch
+}
+
+export interface BatchResult {
+  job_id: string;
+  succeeded: number;
+  failed: number;
+  items: BatchItem[]; // In the order the paths were given
+}
+
+export interface DvbSubtitle {
+  language: string;
+  subtitling_type: number; // 0x10-0x14 normal, 0x20-0x24 for the hard of hearing
+  hard_of_hearing: boolean;
+  composition_page: number;
+  ancillary_page: number;
+}
+
+export interface TeletextPage {
+  language: string;
+  page_type: 'initial' | 'subtitles' | 'additional_info' | 'schedule' | 'hearing_impaired_subtitles' | 'reserved';
+  page: number; // As shown on a TV, e.g. 888
+}
+
+export interface TsStream {
+  pid: number;
+  stream_type: number;
+  kind: string; // e.g. "H.264", from the stream type
+  language: string | null;
+  dvb_subtitles: DvbSubtitle[];
+  teletext_pages: TeletextPage[];
+}
+
+// SCTE-35 splice message, marking ad breaks and program boundaries
+export interface Scte35Marker {
+  pid: number;
+  command: string; // "splice_insert", "time_signal", ...
+  pts_time: number | null; // Seconds on the PTS clock, null for immediate splices
+  event_id: number | null;
+  out_of_network: boolean | null; // true when a splice_insert leaves the network
+  cancelled: boolean;
+  break_duration: number | null; // Seconds
+  encrypted: boolean;
+}
+
+export interface TsProgram {
+  program_number: number;
+  pmt_pid: number;
+  pcr_pid: number | null; // null until the PMT was seen
+  service_name: string | null; // From the DVB SDT
+  provider_name: string | null;
+  service_type: number | null;
+  streams: TsStream[];
+}
+
+export interface PidStats {
+  pid: number;
+  role: string | null; // "PAT", "PMT", a stream kind... null for PIDs no table mentions
+  packets: number;
+  continuity_errors: number; // Skipped counters, i.e. lost packets
+  transport_errors: number;
+  scrambled: boolean;
+}
+
+export interface TransportStreamInfo {
+  packet_size: number; // 188, or 192 for M2TS
+  packets: number;
+  transport_stream_id: number | null;
+  programs: TsProgram[];
+  pids: PidStats[];
+  continuity_errors: number;
+  transport_errors: number;
+  sync_losses: number;
+  scte35_markers: Scte35Marker[]; // In stream order, without back to back repeats
+}
+
+export interface MotionPhoto {
+  image_format: 'jpeg' | 'heic';
+  source: 'xmp_container' | 'micro_video' | 'samsung' | 'scan'; // How the video was found
+  video_offset: number; // Bytes into the photo
+  video_length: number;
+  presentation_timestamp_us: number | null; // Where the still sits in the video
+}
+
+export interface LivePhotoPair {
+  photo_path: string;
+  video_path: string;
+  content_identifier: string; // Matches in the photo's maker note and the video
+  video_duration: number | null; // Seconds
+}
+
+export interface MotionPhotoReport {
+  path: string;
+  motion_photo: MotionPhoto | null; // Video appended to the photo itself
+  embedded_streams: TitleStreams | null;
+  embedded_duration: number | null; // Seconds
+  extracted_path: string | null; // Set when extract_to was given
+  live_photo: LivePhotoPair | null; // Only when the content identifiers match
+  same_name_companion: string | null; // Same name, identifier missing or different
+}
+
+export type QuarantineAction = 'off' | 'move' | 'symlink';
+
+export interface Quarantine {
+  action: QuarantineAction;
+  folder: string | null; // Also holds quarantine.jsonl with the reasons
+}
+
+export interface Settings {
+  cache_dir: string | null; // null = app cache directory
+  cache_max_bytes: number;
+  api_enabled: boolean; // Localhost automation API
+  api_port: number;
+  api_token: string | null; // Generated when enabling the API without one
+  hooks: Hook[]; // Run after each successful inspection
+  external_analyzers: ExternalAnalyzer[];
+  computed_fields: ComputedField[];
+  trace_ffprobe_output: boolean; // Log full ffprobe output instead of a summary
+  log_privacy: boolean; // Anonymize file paths and hashes in logs
+  log_privacy_salt: string | null; // Generated when enabling log privacy
+  file_log: 'off' | 'text' | 'json'; // Applies on the next start
+  error_reporting: boolean; // Opt-in crash and error reports, never include paths
+  inspection_preset: InspectionPreset; // Used when a call doesn't pick one
+  scan_rules: ScanRules; // Which files folder scans pick up
+  quarantine: Quarantine; // Where folder scans put files failing QC
+  whisper: WhisperConfig | null; // Local whisper.cpp for the speech analyzers
+  thumbnail_hints: boolean; // Flag faces and text in thumbnails
+  thumbnail_timestamps: TimestampOverlay | null; // Burned into thumbnails when set
+  reencode_target: ReencodeTarget; // What re-encode savings are estimated for
+  batch_concurrency: number; // Files inspect_videos_batch works on at once by default
+  input_args: string[]; // ffprobe/ffmpeg input options, e.g. ['-probesize', '50M']
+}
+
+export interface CacheStats {
+  location: string;
+  entries: number;
+  total_bytes: number;
+  max_bytes: number;
+}